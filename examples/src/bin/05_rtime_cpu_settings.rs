@@ -2,8 +2,9 @@
 //! Run with: `cargo run --bin 05_rtime_cpu_settings`
 //!
 //! Demonstrates sending an ADS read request to the TwinCAT Real-Time system (Port 200)
-//! and manually parsing the raw little-endian bytes into a Rust struct.
+//! and decoding the raw little-endian bytes into a Rust struct with `#[derive(AdsType)]`.
 
+use tcads::core::ads::{AdsDecode, AdsType};
 use tcads::core::io::blocking::AmsStream;
 use tcads::core::protocol::{
     AdsReadRequest, AdsReadResponse, GetLocalNetIdRequest, GetLocalNetIdResponse,
@@ -33,7 +34,7 @@ fn main() -> Result<()> {
     let target = AmsAddr::new(local_net_id, 200);
     let index_group = ADSSRVID_READDEVICEINFO;
     let index_offset = RTIME_CPU_SETTINGS;
-    let length = RTimeCpuSettings::LENGTH as u32;
+    let length = RTimeCpuSettings::SIZE as u32;
 
     stream.write_frame(
         &AdsReadRequest::new(target, source, 2, index_group, index_offset, length).into_frame(),
@@ -49,7 +50,7 @@ fn main() -> Result<()> {
     }
 
     // Parse the bytes into our strongly typed struct!
-    let cpu_settings = RTimeCpuSettings::try_from(read_response.data())?;
+    let cpu_settings = RTimeCpuSettings::decode(read_response.data())?;
 
     println!("Real-Time CPU Settings:");
     println!("{:#?}", cpu_settings);
@@ -58,7 +59,8 @@ fn main() -> Result<()> {
 }
 
 /// Represents the TwinCAT RTimeCpuSettings structure (32 bytes)
-#[derive(Debug, Clone)]
+#[derive(AdsType, Debug, Clone)]
+#[ads(crate = "tcads::core")]
 pub struct RTimeCpuSettings {
     pub win_cpus: u32,
     pub non_win_cpus: u32,
@@ -68,28 +70,3 @@ pub struct RTimeCpuSettings {
     pub cpu_family: u32,
     pub cpu_freq: u32,
 }
-
-impl RTimeCpuSettings {
-    /// Length of the RTimeCpuSettings structure in bytes.
-    pub const LENGTH: usize = 32;
-}
-
-impl TryFrom<&[u8]> for RTimeCpuSettings {
-    type Error = BoxError;
-    /// Parses the raw 32-byte response from the TwinCAT router
-    fn try_from(data: &[u8]) -> std::result::Result<Self, Self::Error> {
-        if data.len() < 32 {
-            return Err(Box::from("Not enough bytes to parse RTimeCpuSettings"));
-        }
-
-        Ok(Self {
-            win_cpus: u32::from_le_bytes(data[0..4].try_into()?),
-            non_win_cpus: u32::from_le_bytes(data[4..8].try_into()?),
-            affinity_mask: u64::from_le_bytes(data[8..16].try_into()?),
-            rt_cpus: u32::from_le_bytes(data[16..20].try_into()?),
-            cpu_type: u32::from_le_bytes(data[20..24].try_into()?),
-            cpu_family: u32::from_le_bytes(data[24..28].try_into()?),
-            cpu_freq: u32::from_le_bytes(data[28..32].try_into()?),
-        })
-    }
-}
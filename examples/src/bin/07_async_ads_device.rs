@@ -0,0 +1,51 @@
+//! Example 7: Async AdsDevice Usage
+//! Run with: `cargo run --bin 07_async_ads_device`
+//!
+//! This example mirrors Example 6 using the `tokio` flavour of `AdsDevice`.
+//! Instead of background threads, the connection is driven by a reader and
+//! a writer task on the runtime, so no thread is burned per connection.
+//!
+//! PREREQUISITE:
+//! Open `twincat/TcAdsExamples/TcAdsExamples.sln` in TwinCAT XAE,
+//! activate the configuration on your local machine, and put the PLC into RUN mode.
+
+use tcads::client::AmsAddr;
+use tcads::client::devices::tokio::AdsDevice;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    println!("Connecting to the local router...");
+
+    // 1. Connect to the local AMS router (`127.0.0.1:48898`).
+    // This performs the PortConnect handshake and spawns the reader/writer tasks.
+    let device = AdsDevice::connect(None).await?;
+
+    // 2. Ask the router for its Local Net ID
+    let local_net_id = device.get_local_net_id().await?;
+    println!("Local Net ID: {}", local_net_id);
+
+    // 3. Define our target (The standard TwinCAT PLC runtime on Port 851)
+    let plc_target = AmsAddr::new(local_net_id, 851);
+
+    // 4. Issue two requests concurrently over the same connection.
+    // Responses are matched back to their callers by Invoke ID.
+    let (info, state) = tokio::join!(
+        device.read_device_info(plc_target),
+        device.read_state(plc_target)
+    );
+
+    let (version, name) = info?;
+    let (ads_state, device_state) = state?;
+    println!("--------------------------------");
+    println!("Target PLC Name: {}", name);
+    println!("Target PLC Version: {}", version);
+    println!("Current PLC State: {:?}", ads_state);
+    println!("Internal Device State: {}", device_state);
+
+    // 5. Disconnect cleanly
+    device.shutdown()?;
+
+    Ok(())
+}
@@ -1,13 +1,33 @@
+use crate::devices::batch::DEFAULT_MAX_ITEMS;
+use crate::devices::batch::blocking::Batch;
+use crate::devices::connection::{
+    ConnectOptions, ConnectionEvent, ConnectionState, Heartbeat, Reconnect,
+};
+use crate::devices::pending::PendingResponse;
+use crate::devices::request::RequestOptions;
+use crate::devices::subscription::blocking::Subscription;
+use crate::devices::subscription::{NotificationSource, SubscriptionOptions};
+use crate::devices::symbol_handle::blocking::SymbolHandle;
+use crate::symbols::{AdsValue, SymbolTable, ValueError};
 use crate::tasks::blocking::{
     AdsNotificationDispatcher, AmsRequestDispatchKey, AmsRequestDispatcher, AmsRequestWriter,
-    AmsResponseReader, RouterNotificationDispatcher,
+    AmsResponseReader, ConnectionEventDispatcher, ConnectionStateDispatcher,
+    ConnectionStateReceiver, InvokeIdAllocator, NotificationSender, RouterNotificationDispatcher,
 };
-use std::net::ToSocketAddrs;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::ops::Range;
 use std::sync::atomic::{AtomicU32, Ordering};
-use std::sync::mpsc::Receiver;
-use std::sync::{Arc, RwLock};
-use std::time::Duration;
-use tcads_core::io::blocking::AmsStream;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, RwLock, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tcads_core::ads::index_group;
+#[cfg(feature = "tls")]
+use tcads_core::io::TlsConfig;
+#[cfg(feature = "tls")]
+use tcads_core::io::blocking::TlsStream;
+use tcads_core::io::blocking::{AmsReader, AmsStream, AmsWriter};
 use tcads_core::protocol::{
     AdsAddDeviceNotificationRequest, AdsAddDeviceNotificationResponse,
     AdsDeleteDeviceNotificationRequest, AdsDeleteDeviceNotificationResponse,
@@ -16,12 +36,24 @@ use tcads_core::protocol::{
     AdsReadWriteRequestOwned, AdsReadWriteResponse, AdsWriteControlRequestOwned,
     AdsWriteControlResponse, AdsWriteRequestOwned, AdsWriteResponse, GetLocalNetIdRequest,
     GetLocalNetIdResponse, PortCloseRequest, PortConnectRequest, PortConnectResponse,
+    SumAddNotificationItem, SumDeleteNotificationItem, SumRequest,
+};
+use tcads_core::symbol::{
+    AdsDatatypeEntry, AdsSymbolEntry, AdsSymbolUploadInfo, encode_symbol_name,
 };
 use tcads_core::{
-    AdsDeviceVersion, AdsReturnCode, AdsState, AdsTransMode, AmsAddr, AmsFrame, AmsNetId,
-    DeviceState, IndexGroup, IndexOffset, InvokeId, NotificationHandle, RouterState,
+    AdsDecode, AdsDeviceVersion, AdsEncode, AdsReturnCode, AdsState, AdsTransMode, AdsType,
+    AmsAddr, AmsCommand, AmsFrame, AmsNetId, DeviceState, IndexGroup, IndexOffset, InvokeId,
+    NotificationHandle, RouterState,
 };
 
+/// A freshly dialled connection, before it is split between the reader and writer threads.
+enum Dialled {
+    Tcp(AmsStream),
+    #[cfg(feature = "tls")]
+    Tls(AmsStream<TlsStream>),
+}
+
 /// Shared state for an [`AdsDevice`] connection.
 ///
 /// Held behind an [`Arc`] so all [`AdsDevice`] clones share the same connection.
@@ -36,13 +68,19 @@ use tcads_core::{
 /// which drops [`AmsRequestDispatcher`] and its `write_tx`. The writer thread
 /// exits when `write_tx` is dropped, the TCP stream closes, and the reader
 /// thread exits on the next read returning EO
+///
+/// The thread watching the connection only holds a [`Weak`] reference, so it never
+/// keeps a device alive, and a reconnecting device stops re-dialling once dropped.
 pub struct AdsDeviceInner {
     pub ams_requests: Arc<AmsRequestDispatcher>,
     pub ads_notifs: Arc<AdsNotificationDispatcher>,
     pub router_notifs: Arc<RouterNotificationDispatcher>,
+    pub connection_events: Arc<ConnectionEventDispatcher>,
+    pub connection_state: Arc<ConnectionStateDispatcher>,
     pub source: RwLock<AmsAddr>,
-    pub invoke_id: AtomicU32,
+    pub invoke_ids: InvokeIdAllocator,
     pub timeout: Option<Duration>,
+    pub(crate) reconnect: Option<Reconnect<NotificationSender>>,
 }
 
 /// A blocking ADS device client.
@@ -71,12 +109,44 @@ pub struct AdsDeviceInner {
 /// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 ///
+/// With the `tls` feature, Secure ADS routers are reached over TLS with `connect_tls` and
+/// `connect_tls_with_source`. Pre-shared key connections also need the `tls-psk` feature.
+///
+/// # Reconnecting
+///
+/// A device connected by [`connect_with_options`](AdsDevice::connect_with_options) with a
+/// [`ReconnectPolicy`](crate::devices::connection::ReconnectPolicy) re-dials whenever the
+/// connection is lost, e.g. because the PLC rebooted. Once reconnected it registers every
+/// active notification and acquires every [`SymbolHandle`] again, so notification
+/// receivers and symbol handles keep working. Calls made while the connection is down
+/// fail with [`Error::Disconnected`](crate::Error::Disconnected). Progress is reported
+/// through [`subscribe_connection`](AdsDevice::subscribe_connection).
+///
+/// # Monitoring
+///
+/// [`watch_state`](AdsDevice::watch_state) observes the
+/// [`ConnectionState`]. With a [`Heartbeat`] configured, the device also probes the
+/// link periodically, reports it as [`Degraded`](ConnectionState::Degraded) while
+/// probes time out, and records their round-trip time in
+/// [`last_rtt`](AdsDevice::last_rtt).
+///
 /// # Thread Safety
 ///
 /// `AdsDevice` is `Send + Sync`. Multiple threads can issue ADS commands concurrently.
 /// Responses are matched to their callers by Invoke ID with no global lock on the
 /// connection.
 ///
+/// A single thread can also keep many requests in flight: the `submit_*` methods, such
+/// as [`submit_read`](AdsDevice::submit_read), send a request and return a
+/// [`PendingResponse`] to collect its response from later.
+///
+/// # Timeouts
+///
+/// A call gives up after the timeout of the device, or as set per handle with
+/// [`with_options`](AdsDevice::with_options). Its pending entry is purged, and a
+/// response arriving later is only counted in
+/// [`late_responses`](AdsDevice::late_responses).
+///
 /// # Shutdown
 ///
 /// Call [`shutdown`](AdsDevice::shutdown) for a clean disconnect. Dropping the last
@@ -86,6 +156,7 @@ pub struct AdsDeviceInner {
 #[derive(Clone)]
 pub struct AdsDevice {
     inner: Arc<AdsDeviceInner>,
+    options: RequestOptions,
 }
 
 impl AdsDevice {
@@ -151,6 +222,100 @@ impl AdsDevice {
         Self::new(stream, source, timeout)
     }
 
+    /// Connects to a Secure ADS router at `addr`, usually on port
+    /// [`TLS_PORT`](tcads_core::ams::AmsTransport::TLS_PORT).
+    ///
+    /// Performs the TLS handshake described by `config`, then a
+    /// [`PortConnect`](PortConnectRequest) handshake to obtain a dynamically assigned
+    /// source address.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use tcads_client::devices::blocking::AdsDevice;
+    /// use tcads_core::io::TlsConfig;
+    ///
+    /// let config = TlsConfig::shared_ca(std::fs::read("ca.pem")?)?
+    ///     .with_client_cert(std::fs::read("client.pem")?, std::fs::read("client.key")?)?;
+    /// let device = AdsDevice::connect_tls("192.168.1.100:8016", &config, None)?;
+    ///
+    /// println!("Source: {}", device.source()?);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "tls")]
+    pub fn connect_tls(
+        addr: impl ToSocketAddrs,
+        config: &TlsConfig,
+        timeout: Option<Duration>,
+    ) -> crate::Result<Self> {
+        let stream = AmsStream::connect_tls(addr, config)?;
+        let device = Self::new_tls(stream, AmsAddr::default(), timeout)?;
+        let source = device.port_connect()?;
+        *device.inner.source.write()? = source;
+        Ok(device)
+    }
+
+    /// Connects to a Secure ADS router at `addr` using an explicitly provided
+    /// source address, skipping the [`PortConnect`](PortConnectRequest) handshake.
+    ///
+    /// The TLS counterpart of [`connect_with_source`](Self::connect_with_source).
+    #[cfg(feature = "tls")]
+    pub fn connect_tls_with_source(
+        addr: impl ToSocketAddrs,
+        source: AmsAddr,
+        config: &TlsConfig,
+        timeout: Option<Duration>,
+    ) -> crate::Result<Self> {
+        let stream = AmsStream::connect_tls(addr, config)?;
+        Self::new_tls(stream, source, timeout)
+    }
+
+    /// Connects to an AMS router at `addr` as described by `options`.
+    ///
+    /// This is the only constructor that can produce a reconnecting device, see
+    /// [`ConnectOptions::with_reconnect`]. The first connection attempt is made
+    /// immediately and its failure is returned; the reconnect policy only applies once
+    /// connected.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use tcads_client::devices::blocking::AdsDevice;
+    /// use tcads_client::devices::connection::{ConnectOptions, ReconnectPolicy};
+    ///
+    /// let options = ConnectOptions::new().with_reconnect(ReconnectPolicy::default());
+    /// let device = AdsDevice::connect_with_options("192.168.1.100:48898", options)?;
+    ///
+    /// for event in device.subscribe_connection()? {
+    ///     println!("{event:?}");
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn connect_with_options(
+        addr: impl ToSocketAddrs,
+        options: ConnectOptions,
+    ) -> crate::Result<Self> {
+        let addrs: Vec<SocketAddr> = addr.to_socket_addrs()?.collect();
+        let (dialled, source) = Self::dial(&addrs, &options)?;
+        let timeout = options.timeout;
+        let heartbeat = options.heartbeat;
+        let reconnect = options
+            .reconnect
+            .map(|policy| Reconnect::new(addrs, options, policy));
+
+        Ok(match dialled {
+            Dialled::Tcp(stream) => {
+                let (reader, writer) = stream.try_split()?;
+                Self::spawn(reader, writer, source, timeout, reconnect, heartbeat)
+            }
+            #[cfg(feature = "tls")]
+            Dialled::Tls(stream) => {
+                let (reader, writer) = stream.try_split()?;
+                Self::spawn(reader, writer, source, timeout, reconnect, heartbeat)
+            }
+        })
+    }
+
     /// Creates an [`AdsDevice`] from an existing [`AmsStream`].
     ///
     /// Unlike [`connect`](Self::connect) and [`connect_to`](Self::connect_to), this
@@ -178,28 +343,349 @@ impl AdsDevice {
         timeout: Option<Duration>,
     ) -> crate::Result<Self> {
         let (reader, writer) = stream.try_split()?;
-        let (write_tx, _) = AmsRequestWriter::spawn(writer);
+        Ok(Self::spawn(reader, writer, source, timeout, None, None))
+    }
 
-        let ams_requests = Arc::new(AmsRequestDispatcher::new(write_tx));
-        let ads_notifs = Arc::new(AdsNotificationDispatcher::new());
-        let router_notifs = Arc::new(RouterNotificationDispatcher::new());
+    /// Creates an [`AdsDevice`] from an existing Secure ADS [`AmsStream`].
+    ///
+    /// Like [`new`](Self::new), this does **not** perform a [`PortConnect`] handshake.
+    #[cfg(feature = "tls")]
+    pub fn new_tls(
+        stream: AmsStream<TlsStream>,
+        source: AmsAddr,
+        timeout: Option<Duration>,
+    ) -> crate::Result<Self> {
+        let (reader, writer) = stream.try_split()?;
+        Ok(Self::spawn(reader, writer, source, timeout, None, None))
+    }
 
-        AmsResponseReader::spawn(
-            reader,
-            Arc::clone(&ams_requests),
-            Arc::clone(&ads_notifs),
-            Arc::clone(&router_notifs),
-        );
+    /// Spawns the reader and writer threads over the halves of a connection, the thread
+    /// watching it, and the heartbeat thread if one is configured.
+    fn spawn<R, W>(
+        reader: AmsReader<R>,
+        writer: AmsWriter<W>,
+        source: AmsAddr,
+        timeout: Option<Duration>,
+        reconnect: Option<Reconnect<NotificationSender>>,
+        heartbeat: Option<Heartbeat>,
+    ) -> Self
+    where
+        R: Read + Send + 'static,
+        W: Write + Send + 'static,
+    {
+        let (write_tx, _) = AmsRequestWriter::spawn(writer);
 
-        Ok(Self {
+        let device = Self {
+            options: RequestOptions::default(),
             inner: Arc::new(AdsDeviceInner {
-                ams_requests,
-                ads_notifs,
-                router_notifs,
+                ams_requests: Arc::new(AmsRequestDispatcher::new(write_tx)),
+                ads_notifs: Arc::new(AdsNotificationDispatcher::new()),
+                router_notifs: Arc::new(RouterNotificationDispatcher::new()),
+                connection_events: Arc::new(ConnectionEventDispatcher::new()),
+                connection_state: Arc::new(ConnectionStateDispatcher::default()),
                 source: RwLock::new(source),
-                invoke_id: AtomicU32::new(1),
+                invoke_ids: InvokeIdAllocator::new(),
                 timeout,
+                reconnect,
             }),
+        };
+        let reader = device.spawn_reader(reader);
+        let inner = Arc::downgrade(&device.inner);
+        thread::spawn(move || Self::supervise(inner, reader));
+        if let Some(heartbeat) = heartbeat {
+            let inner = Arc::downgrade(&device.inner);
+            thread::spawn(move || Self::heartbeat(inner, heartbeat));
+        }
+
+        device
+    }
+
+    fn spawn_reader<R>(&self, reader: AmsReader<R>) -> JoinHandle<crate::Result<()>>
+    where
+        R: Read + Send + 'static,
+    {
+        AmsResponseReader::spawn(
+            reader,
+            Arc::clone(&self.inner.ams_requests),
+            Arc::clone(&self.inner.ads_notifs),
+            Arc::clone(&self.inner.router_notifs),
+        )
+    }
+
+    /// Replaces a lost connection with the halves of a new one.
+    fn attach<R, W>(
+        &self,
+        reader: AmsReader<R>,
+        writer: AmsWriter<W>,
+    ) -> crate::Result<JoinHandle<crate::Result<()>>>
+    where
+        R: Read + Send + 'static,
+        W: Write + Send + 'static,
+    {
+        let (write_tx, _) = AmsRequestWriter::spawn(writer);
+        self.inner.ams_requests.reopen(write_tx)?;
+        Ok(self.spawn_reader(reader))
+    }
+
+    /// Dials one of `addrs` and obtains the source address for the new connection.
+    ///
+    /// The timeout in `options` applies to connecting and to each read of the handshake.
+    fn dial(addrs: &[SocketAddr], options: &ConnectOptions) -> crate::Result<(Dialled, AmsAddr)> {
+        #[cfg(feature = "tls")]
+        if let Some(config) = &options.tls {
+            let mut stream = match options.timeout {
+                Some(timeout) => Self::connect_any(addrs, |addr| {
+                    AmsStream::connect_tls_timeout(addr, config, timeout)
+                })?,
+                None => AmsStream::connect_tls(addrs, config)?,
+            };
+            stream
+                .get_ref()
+                .socket()
+                .set_read_timeout(options.timeout)?;
+            let source = Self::handshake(&mut stream, options.source)?;
+            stream.get_ref().socket().set_read_timeout(None)?;
+            return Ok((Dialled::Tls(stream), source));
+        }
+
+        let mut stream = match options.timeout {
+            Some(timeout) => {
+                Self::connect_any(addrs, |addr| AmsStream::connect_timeout(addr, timeout))?
+            }
+            None => AmsStream::connect(addrs)?,
+        };
+        stream.set_read_timeout(options.timeout)?;
+        let source = Self::handshake(&mut stream, options.source)?;
+        stream.set_read_timeout(None)?;
+        Ok((Dialled::Tcp(stream), source))
+    }
+
+    /// Tries `connect` on each of `addrs` in turn, returning the first success or the
+    /// last error.
+    fn connect_any<T>(
+        addrs: &[SocketAddr],
+        connect: impl Fn(&SocketAddr) -> io::Result<T>,
+    ) -> io::Result<T> {
+        let mut last = io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "could not resolve to any addresses",
+        );
+        for addr in addrs {
+            match connect(addr) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last = e,
+            }
+        }
+        Err(last)
+    }
+
+    /// Performs the [`PortConnect`](PortConnectRequest) handshake on a fresh stream,
+    /// unless a static `source` is given.
+    fn handshake<S: Read + Write>(
+        stream: &mut AmsStream<S>,
+        source: Option<AmsAddr>,
+    ) -> crate::Result<AmsAddr> {
+        if let Some(source) = source {
+            return Ok(source);
+        }
+
+        stream.write_frame(&PortConnectRequest::default().into_frame())?;
+        loop {
+            let frame = stream.read_frame()?;
+            if frame.header().command() == AmsCommand::PortConnect {
+                return Ok(*PortConnectResponse::try_from(frame)?.addr());
+            }
+        }
+    }
+
+    /// Waits for the reader thread of each connection to exit, then reports the loss and
+    /// reconnects if the device is configured to.
+    fn supervise(inner: Weak<AdsDeviceInner>, mut reader: JoinHandle<crate::Result<()>>) {
+        loop {
+            let _ = reader.join();
+            let Some(device) = Self::upgrade(&inner) else {
+                return;
+            };
+            let _ = device
+                .inner
+                .connection_state
+                .set(ConnectionState::Disconnected);
+            let _ = device
+                .inner
+                .connection_events
+                .broadcast(ConnectionEvent::Disconnected);
+            drop(device);
+
+            match Self::reconnect(&inner) {
+                Some(next) => reader = next,
+                None => {
+                    if let Some(device) = Self::upgrade(&inner) {
+                        let _ = device.inner.connection_state.set(ConnectionState::Closed);
+                        let events = &device.inner.connection_events;
+                        let _ = events.broadcast(ConnectionEvent::Closed);
+                        let _ = events.clear();
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Re-dials with backoff until a new connection is up. Returns [`None`] if the device
+    /// does not reconnect, was shut down or dropped, or ran out of attempts.
+    fn reconnect(inner: &Weak<AdsDeviceInner>) -> Option<JoinHandle<crate::Result<()>>> {
+        for attempt in 1.. {
+            let delay = {
+                let device = Self::upgrade(inner)?;
+                let reconnect = device.inner.reconnect.as_ref()?;
+                if reconnect.is_closing() || !reconnect.policy.allows(attempt) {
+                    return None;
+                }
+                reconnect.policy.delay(attempt)
+            };
+            thread::sleep(delay);
+
+            let device = Self::upgrade(inner)?;
+            if device.inner.reconnect.as_ref()?.is_closing() {
+                return None;
+            }
+            let state = &device.inner.connection_state;
+            let _ = state.set(ConnectionState::Connecting);
+            let _ = device
+                .inner
+                .connection_events
+                .broadcast(ConnectionEvent::Reconnecting { attempt });
+            match device.redial() {
+                Ok(reader) => return Some(reader),
+                Err(_) => {
+                    let _ = state.set(ConnectionState::Disconnected);
+                }
+            }
+        }
+        None
+    }
+
+    /// Opens a new connection and restores notifications and symbol handles on it.
+    fn redial(&self) -> crate::Result<JoinHandle<crate::Result<()>>> {
+        let Some(reconnect) = &self.inner.reconnect else {
+            return Err(crate::Error::Disconnected);
+        };
+        let (dialled, source) = Self::dial(&reconnect.addrs, &reconnect.options)?;
+
+        *self.inner.source.write()? = source;
+        let reader = match dialled {
+            Dialled::Tcp(stream) => {
+                let (reader, writer) = stream.try_split()?;
+                self.attach(reader, writer)?
+            }
+            #[cfg(feature = "tls")]
+            Dialled::Tls(stream) => {
+                let (reader, writer) = stream.try_split()?;
+                self.attach(reader, writer)?
+            }
+        };
+
+        self.restore(reconnect)?;
+        self.inner
+            .connection_state
+            .set(ConnectionState::Connected)?;
+        self.inner
+            .connection_events
+            .broadcast(ConnectionEvent::Reconnected { source })?;
+
+        Ok(reader)
+    }
+
+    /// Registers the tracked notifications and acquires the tracked symbol handles again.
+    ///
+    /// Items the target rejects are reported as lost; items that fail because the new
+    /// connection is already gone are left for the next attempt.
+    fn restore(&self, reconnect: &Reconnect<NotificationSender>) -> crate::Result<()> {
+        let events = &self.inner.connection_events;
+
+        for (target, notifications) in reconnect.notifications()? {
+            for (key, item, sender) in notifications {
+                match self.register_notification(target, &item, sender) {
+                    Ok(handle) => reconnect.update_notification(key, handle)?,
+                    Err(error @ crate::Error::AdsReturnCode(_)) => {
+                        reconnect.forget_notification(key)?;
+                        events
+                            .broadcast(ConnectionEvent::NotificationLost { handle: key, error })?;
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+
+        for (target, handles) in reconnect.handles()? {
+            let names: Vec<_> = handles.iter().map(|(name, _)| name.as_str()).collect();
+            let results = self.handles_by_name(target, &names);
+            for ((name, cell), result) in handles.into_iter().zip(results) {
+                match result {
+                    Ok(handle) => cell.store(handle, Ordering::Release),
+                    Err(error @ crate::Error::AdsReturnCode(_)) => {
+                        events.broadcast(ConnectionEvent::HandleLost { name, error })?;
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Probes the link while it is up, degrading the connection state once too many probes
+    /// in a row time out and recovering it once one is answered again.
+    fn heartbeat(inner: Weak<AdsDeviceInner>, heartbeat: Heartbeat) {
+        let mut timeouts = 0;
+        loop {
+            thread::sleep(heartbeat.interval);
+            let Some(device) = Self::upgrade(&inner) else {
+                return;
+            };
+            let state = &device.inner.connection_state;
+            match state.state() {
+                Ok(ConnectionState::Closed) | Err(_) => return,
+                Ok(current) if !current.is_up() => {
+                    timeouts = 0;
+                    continue;
+                }
+                Ok(_) => {}
+            }
+
+            match device.probe(heartbeat.target, heartbeat.timeout) {
+                Ok(rtt) => {
+                    timeouts = 0;
+                    let _ = state.set_rtt(rtt);
+                    let _ = state.transition(ConnectionState::Degraded, ConnectionState::Connected);
+                }
+                Err(crate::Error::Timeout) => {
+                    timeouts += 1;
+                    if timeouts >= heartbeat.max_timeouts {
+                        let _ =
+                            state.transition(ConnectionState::Connected, ConnectionState::Degraded);
+                    }
+                }
+                Err(_) => {}
+            }
+        }
+    }
+
+    /// Reads the state of `target` and returns the round-trip time. An error code from
+    /// the target still counts as an answer.
+    fn probe(&self, target: AmsAddr, timeout: Duration) -> crate::Result<Duration> {
+        let device = self.with_options(RequestOptions::new().with_timeout(timeout));
+        let start = Instant::now();
+        match device.read_state(target) {
+            Ok(_) | Err(crate::Error::AdsReturnCode(_)) => Ok(start.elapsed()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn upgrade(inner: &Weak<AdsDeviceInner>) -> Option<Self> {
+        inner.upgrade().map(|inner| Self {
+            inner,
+            options: RequestOptions::default(),
         })
     }
 
@@ -211,8 +697,11 @@ impl AdsDevice {
     /// to hit EOF and exit, clearing all pending callers and notification subscribers.
     ///
     /// If the send fails (already disconnected) this returns `Ok(())` meaning the
-    /// connection is already gone.
+    /// connection is already gone. A reconnecting device stops re-dialling.
     pub fn shutdown(&self) -> crate::Result<()> {
+        if let Some(reconnect) = &self.inner.reconnect {
+            reconnect.close();
+        }
         let frame = PortCloseRequest::new(self.source()?.port()).into_frame();
         let _ = self.inner.ams_requests.send_only(frame);
         Ok(())
@@ -223,14 +712,115 @@ impl AdsDevice {
         Ok(*self.inner.source.read()?)
     }
 
+    /// Returns `true` once the connection has been lost or shut down.
+    ///
+    /// A reconnecting device reports `false` again once it has reconnected.
+    pub fn is_closed(&self) -> bool {
+        self.inner.ams_requests.is_closed()
+    }
+
+    /// Returns a handle to the same connection whose calls are limited by `options`.
+    ///
+    /// The options replace those of this handle and carry over to the [`Batch`]es and
+    /// [`SymbolHandle`]s created from the returned one. Calls that time out or are
+    /// cancelled fail with [`Error::Timeout`](crate::Error::Timeout) or
+    /// [`Error::Cancelled`](crate::Error::Cancelled), and their pending entries are purged.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::thread;
+    /// use tcads_client::devices::blocking::AdsDevice;
+    /// use tcads_client::devices::request::{CancellationToken, RequestOptions};
+    ///
+    /// let device = AdsDevice::connect(None)?;
+    /// let target = "192.168.1.100.1.1:851".parse()?;
+    ///
+    /// let token = CancellationToken::new();
+    /// let upload = {
+    ///     let device = device.with_options(RequestOptions::new().with_cancellation(token.clone()));
+    ///     thread::spawn(move || device.upload_symbols(target))
+    /// };
+    /// token.cancel();
+    /// if let Err(e) = upload.join().unwrap() {
+    ///     println!("upload stopped: {e}");
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn with_options(&self, options: RequestOptions) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            options,
+        }
+    }
+
+    /// Reserves `count` consecutive invoke IDs for frames built outside the device.
+    ///
+    /// The device never assigns a reserved ID to its own requests, so frames dispatched
+    /// directly through [`AdsDeviceInner::ams_requests`] under these IDs cannot collide
+    /// with them. Dispatching under an ID that is still in flight fails with
+    /// [`Error::InvokeIdInUse`](crate::Error::InvokeIdInUse).
+    pub fn reserve_invoke_ids(&self, count: u32) -> crate::Result<Range<InvokeId>> {
+        self.inner
+            .invoke_ids
+            .reserve(count, |ids| self.in_flight(ids))
+    }
+
+    /// Returns a range obtained from [`reserve_invoke_ids`](Self::reserve_invoke_ids).
+    pub fn release_invoke_ids(&self, range: &Range<InvokeId>) -> crate::Result<()> {
+        self.inner.invoke_ids.release(range)
+    }
+
+    /// Returns how many ADS responses arrived after their caller had given up, e.g.
+    /// because it timed out or was cancelled.
+    pub fn late_responses(&self) -> u64 {
+        self.inner.ams_requests.late_responses()
+    }
+
+    /// Returns the current [`ConnectionState`].
+    pub fn connection_state(&self) -> crate::Result<ConnectionState> {
+        self.inner.connection_state.state()
+    }
+
+    /// Watches the [`ConnectionState`].
+    ///
+    /// The receiver starts at the current state. Once the device is
+    /// [closed](ConnectionState::Closed) no further changes follow, and
+    /// [`changed`](ConnectionStateReceiver::changed) returns [`Err`] after all clones are
+    /// dropped.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use tcads_client::devices::blocking::AdsDevice;
+    /// use tcads_client::devices::connection::{ConnectOptions, Heartbeat};
+    ///
+    /// let target = "192.168.1.100.1.1:851".parse()?;
+    /// let options = ConnectOptions::new().with_heartbeat(Heartbeat::new(target));
+    /// let device = AdsDevice::connect_with_options("192.168.1.100:48898", options)?;
+    ///
+    /// let mut state = device.watch_state()?;
+    /// while let Ok(current) = state.changed() {
+    ///     println!("{current:?}, rtt {:?}", device.last_rtt()?);
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn watch_state(&self) -> crate::Result<ConnectionStateReceiver> {
+        self.inner.connection_state.watch()
+    }
+
+    /// Returns the round-trip time of the last answered [`Heartbeat`] probe, or [`None`]
+    /// if no heartbeat is configured or none was answered yet.
+    pub fn last_rtt(&self) -> crate::Result<Option<Duration>> {
+        self.inner.connection_state.rtt()
+    }
+
     /// Queries the router's local AMS Net ID.
     pub fn get_local_net_id(&self) -> crate::Result<AmsNetId> {
         let frame = GetLocalNetIdRequest::into_frame();
-        let rx = self
-            .inner
-            .ams_requests
-            .dispatch(AmsRequestDispatchKey::GetLocalNetId, frame)?;
-        let resp = GetLocalNetIdResponse::try_from(self.wait(rx)?)?;
+        let resp = GetLocalNetIdResponse::try_from(
+            self.request(AmsRequestDispatchKey::GetLocalNetId, frame)?,
+        )?;
 
         Ok(resp.net_id())
     }
@@ -244,9 +834,17 @@ impl AdsDevice {
         self.inner.router_notifs.subscribe()
     }
 
+    /// Subscribes to [connection events](ConnectionEvent).
+    ///
+    /// The receiver returns [`Err`] after [`ConnectionEvent::Closed`]. Unlike
+    /// [`subscribe_router`](Self::subscribe_router), it stays open across reconnects.
+    pub fn subscribe_connection(&self) -> crate::Result<Receiver<ConnectionEvent>> {
+        self.inner.connection_events.subscribe()
+    }
+
     /// Reads the device name and version from `target`.
     pub fn read_device_info(&self, target: AmsAddr) -> crate::Result<(AdsDeviceVersion, String)> {
-        let invoke_id = self.next_invoke_id();
+        let invoke_id = self.next_invoke_id()?;
 
         let frame = AdsReadDeviceInfoRequest::new(target, self.source()?, invoke_id).into_frame();
         let resp = AdsReadDeviceInfoResponse::try_from(self.send_and_wait(frame, invoke_id)?)?;
@@ -258,14 +856,28 @@ impl AdsDevice {
 
     /// Reads the ADS and device state of `target`.
     pub fn read_state(&self, target: AmsAddr) -> crate::Result<(AdsState, DeviceState)> {
-        let invoke_id = self.next_invoke_id();
+        self.submit_read_state(target)?.wait()
+    }
+
+    /// Submits a [`read_state`](Self::read_state) without waiting for the response.
+    pub fn submit_read_state(
+        &self,
+        target: AmsAddr,
+    ) -> crate::Result<PendingResponse<(AdsState, DeviceState)>> {
+        let invoke_id = self.next_invoke_id()?;
 
         let frame = AdsReadStateRequest::new(target, self.source()?, invoke_id).into_frame();
-        let resp = AdsReadStateResponse::try_from(self.send_and_wait(frame, invoke_id)?)?;
+        self.submit(
+            AmsRequestDispatchKey::AdsCommand(invoke_id),
+            frame,
+            |frame| {
+                let resp = AdsReadStateResponse::try_from(frame)?;
 
-        Self::check_result(resp.result())?;
+                Self::check_result(resp.result())?;
 
-        Ok((resp.ads_state(), resp.device_state()))
+                Ok((resp.ads_state(), resp.device_state()))
+            },
+        )
     }
 
     /// Changes the ADS and device state of `target`.
@@ -276,7 +888,7 @@ impl AdsDevice {
         device_state: DeviceState,
         data: impl Into<Vec<u8>>,
     ) -> crate::Result<()> {
-        let invoke_id = self.next_invoke_id();
+        let invoke_id = self.next_invoke_id()?;
 
         let frame = AdsWriteControlRequestOwned::with_data(
             target,
@@ -302,23 +914,38 @@ impl AdsDevice {
         index_offset: IndexOffset,
         length: u32,
     ) -> crate::Result<Vec<u8>> {
-        let invoke_id = self.next_invoke_id();
-
-        let frame = AdsReadRequest::new(
-            target,
-            self.source()?,
-            invoke_id,
-            index_group,
-            index_offset,
-            length,
-        )
-        .into_frame();
-        let frame = self.send_and_wait(frame, invoke_id)?;
-        let resp = AdsReadResponse::try_from_frame(&frame)?;
-
-        Self::check_result(resp.result())?;
+        self.submit_read(target, index_group, index_offset, length)?
+            .wait()
+    }
 
-        Ok(resp.data().to_vec())
+    /// Submits a [`read`](Self::read) without waiting for the response.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use tcads_client::devices::blocking::AdsDevice;
+    ///
+    /// let device = AdsDevice::connect(None)?;
+    /// let target = "192.168.1.100.1.1:851".parse()?;
+    ///
+    /// let mut pending = device.submit_read(target, 0x4020, 0, 4)?;
+    /// let data = loop {
+    ///     if let Some(data) = pending.try_get()? {
+    ///         break data;
+    ///     }
+    ///     // Do other work in the meantime.
+    /// };
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn submit_read(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        length: u32,
+    ) -> crate::Result<PendingResponse<Vec<u8>>> {
+        let (key, frame) = self.read_request(target, index_group, index_offset, length)?;
+        self.submit(key, frame, Self::read_data)
     }
 
     /// Writes `data` to `target` at a specified `index_group` and `index_offset`.
@@ -329,7 +956,19 @@ impl AdsDevice {
         index_offset: IndexOffset,
         data: impl Into<Vec<u8>>,
     ) -> crate::Result<()> {
-        let invoke_id = self.next_invoke_id();
+        self.submit_write(target, index_group, index_offset, data)?
+            .wait()
+    }
+
+    /// Submits a [`write`](Self::write) without waiting for the response.
+    pub fn submit_write(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        data: impl Into<Vec<u8>>,
+    ) -> crate::Result<PendingResponse<()>> {
+        let invoke_id = self.next_invoke_id()?;
         let frame = AdsWriteRequestOwned::new(
             target,
             self.source()?,
@@ -339,11 +978,79 @@ impl AdsDevice {
             data,
         )
         .into_frame();
-        let resp = AdsWriteResponse::try_from(self.send_and_wait(frame, invoke_id)?)?;
+        self.submit(
+            AmsRequestDispatchKey::AdsCommand(invoke_id),
+            frame,
+            |frame| {
+                let resp = AdsWriteResponse::try_from(frame)?;
 
-        Self::check_result(resp.result())?;
+                Self::check_result(resp.result())
+            },
+        )
+    }
 
-        Ok(())
+    /// Reads a value of type `T` from `target` at a specified `index_group` and `index_offset`.
+    ///
+    /// Reads exactly [`T::SIZE`](AdsType::SIZE) bytes and decodes them.
+    pub fn read_value<T: AdsType>(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+    ) -> crate::Result<T> {
+        let data = self.read(target, index_group, index_offset, T::SIZE as u32)?;
+
+        Ok(T::decode(&data)?)
+    }
+
+    /// Submits a [`read_value`](Self::read_value) without waiting for the response.
+    ///
+    /// The value is decoded once the response is collected.
+    pub fn submit_read_value<T: AdsType + 'static>(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+    ) -> crate::Result<PendingResponse<T>> {
+        let (key, frame) = self.read_request(target, index_group, index_offset, T::SIZE as u32)?;
+        self.submit(key, frame, |frame| Ok(T::decode(&Self::read_data(frame)?)?))
+    }
+
+    /// Reads `count` consecutive values of type `T`, such as an array whose length is only
+    /// known at runtime.
+    pub fn read_values<T: AdsType>(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        count: usize,
+    ) -> crate::Result<Vec<T>> {
+        let data = self.read(target, index_group, index_offset, (T::SIZE * count) as u32)?;
+
+        Ok(Vec::<T>::decode(&data)?)
+    }
+
+    /// Encodes `value` and writes it to `target` at a specified `index_group` and
+    /// `index_offset`.
+    pub fn write_value<T: AdsEncode + ?Sized>(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        value: &T,
+    ) -> crate::Result<()> {
+        self.write(target, index_group, index_offset, value.to_ads_bytes())
+    }
+
+    /// Submits a [`write_value`](Self::write_value) without waiting for the response.
+    pub fn submit_write_value<T: AdsEncode + ?Sized>(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        value: &T,
+    ) -> crate::Result<PendingResponse<()>> {
+        self.submit_write(target, index_group, index_offset, value.to_ads_bytes())
     }
 
     /// Sends a combined read/write to `target` in a single round trip.
@@ -357,7 +1064,20 @@ impl AdsDevice {
         read_length: u32,
         write_data: impl Into<Vec<u8>>,
     ) -> crate::Result<Vec<u8>> {
-        let invoke_id = self.next_invoke_id();
+        self.submit_read_write(target, index_group, index_offset, read_length, write_data)?
+            .wait()
+    }
+
+    /// Submits a [`read_write`](Self::read_write) without waiting for the response.
+    pub fn submit_read_write(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        read_length: u32,
+        write_data: impl Into<Vec<u8>>,
+    ) -> crate::Result<PendingResponse<Vec<u8>>> {
+        let invoke_id = self.next_invoke_id()?;
         let frame = AdsReadWriteRequestOwned::new(
             target,
             self.source()?,
@@ -368,12 +1088,17 @@ impl AdsDevice {
             write_data,
         )
         .into_frame();
-        let frame = self.send_and_wait(frame, invoke_id)?;
-        let resp = AdsReadWriteResponse::try_from_frame(&frame)?;
+        self.submit(
+            AmsRequestDispatchKey::AdsCommand(invoke_id),
+            frame,
+            |frame| {
+                let resp = AdsReadWriteResponse::try_from_frame(&frame)?;
 
-        Self::check_result(resp.result())?;
+                Self::check_result(resp.result())?;
 
-        Ok(resp.data().to_vec())
+                Ok(resp.data().to_vec())
+            },
+        )
     }
 
     /// Registers a device notification on `target`.
@@ -384,6 +1109,11 @@ impl AdsDevice {
     /// The receiver yields [`Err`] after [`delete_notification`](Self::delete_notification)
     /// is called, or when the router transitions to [`RouterState::Stop`] or [`RouterState::Removed`].
     ///
+    /// On a reconnecting device the notification is registered again after every
+    /// reconnect and the receiver stays open. The returned handle is then assigned by the
+    /// device, stays the same across reconnects, and differs from the
+    /// [handle of the samples](AdsNotificationSampleOwned::handle).
+    ///
     /// # Note
     ///
     /// The target device may fire an initial sample upon registration.
@@ -398,31 +1128,21 @@ impl AdsDevice {
         max_delay: u32,
         cycle_time: u32,
     ) -> crate::Result<(Receiver<AdsNotificationSampleOwned>, NotificationHandle)> {
-        let invoke_id = self.next_invoke_id();
-
-        let rx = self.inner.ads_notifs.pre_register(invoke_id)?;
-
-        let frame = AdsAddDeviceNotificationRequest::new(
-            target,
-            self.source()?,
-            invoke_id,
+        let item = SumAddNotificationItem::new(
             index_group,
             index_offset,
             length,
             trans_mode,
             max_delay,
             cycle_time,
-        )
-        .into_frame();
-        let resp =
-            AdsAddDeviceNotificationResponse::try_from(self.send_and_wait(frame, invoke_id)?)?;
-
-        Self::check_result(resp.result())?;
-
-        let handle = resp.handle();
-        self.inner.ads_notifs.promote(invoke_id, handle)?;
+        );
+        let (tx, rx) = mpsc::channel();
+        let handle = self.register_notification(target, &item, tx.clone().into())?;
 
-        Ok((rx, handle))
+        Ok((
+            rx,
+            self.track_notification(target, item, tx.into(), handle)?,
+        ))
     }
 
     /// Deletes a device notification on `target`.
@@ -434,7 +1154,8 @@ impl AdsDevice {
         target: AmsAddr,
         handle: NotificationHandle,
     ) -> crate::Result<()> {
-        let invoke_id = self.next_invoke_id();
+        let handle = self.untrack_notification(handle)?;
+        let invoke_id = self.next_invoke_id()?;
 
         let frame =
             AdsDeleteDeviceNotificationRequest::new(target, self.source()?, invoke_id, handle)
@@ -447,34 +1168,581 @@ impl AdsDevice {
         self.inner.ads_notifs.remove(handle)
     }
 
+    /// Registers many device notifications on `target` with
+    /// [`SUMUP_ADDDEVNOTE`](index_group::SUMUP_ADDDEVNOTE) sum commands.
+    ///
+    /// Behaves like calling [`add_notification`](Self::add_notification) for every item, but
+    /// takes one round trip per [`DEFAULT_MAX_ITEMS`] items. Returns one result per item, in
+    /// order; a rejected item does not affect the others.
+    pub fn add_notifications(
+        &self,
+        target: AmsAddr,
+        items: &[SumAddNotificationItem],
+    ) -> Vec<crate::Result<(Receiver<AdsNotificationSampleOwned>, NotificationHandle)>> {
+        let mut results = Vec::with_capacity(items.len());
+        for chunk in items.chunks(DEFAULT_MAX_ITEMS) {
+            results.extend(self.add_notifications_chunk(target, chunk));
+        }
+        results
+    }
+
+    /// Deletes many device notifications on `target` with
+    /// [`SUMUP_DELDEVNOTE`](index_group::SUMUP_DELDEVNOTE) sum commands.
+    ///
+    /// Returns one result per handle, in order.
+    pub fn delete_notifications(
+        &self,
+        target: AmsAddr,
+        handles: &[NotificationHandle],
+    ) -> Vec<crate::Result<()>> {
+        let handles: Vec<_> = match handles
+            .iter()
+            .map(|&handle| self.untrack_notification(handle))
+            .collect::<crate::Result<_>>()
+        {
+            Ok(handles) => handles,
+            Err(e) => return handles.iter().map(|_| Err(e.clone())).collect(),
+        };
+
+        let mut results = Vec::with_capacity(handles.len());
+        for chunk in handles.chunks(DEFAULT_MAX_ITEMS) {
+            let items: Vec<_> = chunk
+                .iter()
+                .copied()
+                .map(SumDeleteNotificationItem::new)
+                .collect();
+            let request = SumDeleteNotificationItem::encode_request(&items);
+            let response = self.send_sum(target, request).and_then(|data| {
+                Ok(SumDeleteNotificationItem::parse_response(
+                    items.len(),
+                    &data,
+                )?)
+            });
+
+            match response {
+                Ok(codes) => results.extend(chunk.iter().zip(codes).map(|(handle, code)| {
+                    code?;
+                    self.inner.ads_notifs.remove(*handle)
+                })),
+                Err(e) => results.extend(chunk.iter().map(|_| Err(e.clone()))),
+            }
+        }
+        results
+    }
+
+    /// Subscribes to `source` on `target` and decodes every sample into `T`.
+    ///
+    /// A [symbol](NotificationSource::Symbol) is resolved to its address and size first.
+    /// The returned [`Subscription`] yields every value together with the timestamp the
+    /// PLC stamped it with, and deletes the notification when dropped.
+    ///
+    /// Otherwise behaves like [`add_notification`](Self::add_notification), including on
+    /// a reconnecting device.
+    pub fn subscribe<T: AdsDecode>(
+        &self,
+        target: AmsAddr,
+        source: impl Into<NotificationSource>,
+        options: SubscriptionOptions,
+    ) -> crate::Result<Subscription<T>> {
+        let (index_group, index_offset, length) = match source.into() {
+            NotificationSource::Symbol(name) => {
+                let entry = self.read_symbol_info(target, &name)?;
+                (entry.index_group(), entry.index_offset(), entry.size())
+            }
+            NotificationSource::Address {
+                index_group,
+                index_offset,
+                length,
+            } => (index_group, index_offset, length),
+        };
+        let item = SumAddNotificationItem::new(
+            index_group,
+            index_offset,
+            length,
+            options.trans_mode,
+            options.max_delay_ms(),
+            options.cycle_time_ms(),
+        );
+        let (tx, rx) = mpsc::channel();
+        let handle = self.register_notification(target, &item, tx.clone().into())?;
+        let handle = self.track_notification(target, item, tx.into(), handle)?;
+
+        Ok(Subscription::new(self.clone(), target, handle, rx))
+    }
+
+    /// Starts a [`Batch`] of reads, writes and read/writes on `target`.
+    ///
+    /// The queued operations are sent as ADS sum commands, so many values can be
+    /// accessed in a handful of round trips.
+    pub fn batch(&self, target: AmsAddr) -> Batch {
+        Batch::new(self.clone(), target)
+    }
+
+    /// Reads the symbol information of `name` from `target`.
+    pub fn read_symbol_info(&self, target: AmsAddr, name: &str) -> crate::Result<AdsSymbolEntry> {
+        let data = self.read_write(
+            target,
+            index_group::SYM_INFOBYNAMEEX,
+            0,
+            u16::MAX as u32,
+            encode_symbol_name(name)?,
+        )?;
+
+        Ok(AdsSymbolEntry::try_from(data.as_slice())?)
+    }
+
+    /// Acquires a [`SymbolHandle`] for the variable `name` on `target`.
+    ///
+    /// Reads the symbol information first, so that the handle knows the symbol size,
+    /// then requests the handle. Both round trips happen up front; every read or write
+    /// through the handle is a single round trip afterwards.
+    pub fn symbol_handle(&self, target: AmsAddr, name: &str) -> crate::Result<SymbolHandle> {
+        let entry = self.read_symbol_info(target, name)?;
+        let data = self.read_write(
+            target,
+            index_group::SYM_HNDBYNAME,
+            0,
+            size_of::<u32>() as u32,
+            encode_symbol_name(name)?,
+        )?;
+        let handle = Arc::new(AtomicU32::new(u32::decode(&data)?));
+        let key = match &self.inner.reconnect {
+            Some(reconnect) => Some(reconnect.track_handle(target, name, Arc::clone(&handle))?),
+            None => None,
+        };
+
+        Ok(SymbolHandle::new(self.clone(), target, handle, key, entry))
+    }
+
+    /// Releases a raw symbol handle on `target`.
+    ///
+    /// Only needed for handles obtained without [`symbol_handle`](Self::symbol_handle);
+    /// a [`SymbolHandle`] releases itself.
+    pub fn release_handle(&self, target: AmsAddr, handle: u32) -> crate::Result<()> {
+        self.write(target, index_group::SYM_RELEASEHND, 0, handle.to_le_bytes())
+    }
+
+    /// Acquires raw symbol handles for many `names` on `target` in one
+    /// [`SUMUP_READWRITE`](index_group::SUMUP_READWRITE) sum command per chunk.
+    ///
+    /// Returns one result per name, in order. The handles must be released with
+    /// [`release_handles`](Self::release_handles) or [`release_handle`](Self::release_handle).
+    pub fn handles_by_name(
+        &self,
+        target: AmsAddr,
+        names: &[impl AsRef<str>],
+    ) -> Vec<crate::Result<u32>> {
+        let encoded: Vec<_> = names
+            .iter()
+            .map(|name| encode_symbol_name(name.as_ref()))
+            .collect();
+        let batch = encoded
+            .iter()
+            .flatten()
+            .fold(self.batch(target), |batch, name| {
+                batch.read_write(
+                    index_group::SYM_HNDBYNAME,
+                    0,
+                    size_of::<u32>() as u32,
+                    name.as_slice(),
+                )
+            });
+        let mut handles = batch.execute().into_iter();
+
+        encoded
+            .into_iter()
+            .map(|name| {
+                name?;
+                let data = handles.next().unwrap_or(Err(crate::Error::Disconnected))?;
+                Ok(u32::decode(&data)?)
+            })
+            .collect()
+    }
+
+    /// Releases many raw symbol handles on `target` in one
+    /// [`SUMUP_WRITE`](index_group::SUMUP_WRITE) sum command per chunk.
+    ///
+    /// Returns one result per handle, in order.
+    pub fn release_handles(&self, target: AmsAddr, handles: &[u32]) -> Vec<crate::Result<()>> {
+        handles
+            .iter()
+            .fold(self.batch(target), |batch, handle| {
+                batch.write(index_group::SYM_RELEASEHND, 0, handle.to_le_bytes())
+            })
+            .execute()
+            .into_iter()
+            .map(|result| result.map(|_| ()))
+            .collect()
+    }
+
+    /// Stops re-acquiring the symbol handle tracked under `key` after reconnects.
+    pub(crate) fn untrack_handle(&self, key: Option<u64>) -> crate::Result<()> {
+        match (&self.inner.reconnect, key) {
+            (Some(reconnect), Some(key)) => reconnect.forget_handle(key),
+            _ => Ok(()),
+        }
+    }
+
+    /// Queues the deletion of a device notification without waiting for the response.
+    pub(crate) fn delete_notification_nowait(
+        &self,
+        target: AmsAddr,
+        handle: NotificationHandle,
+    ) -> crate::Result<()> {
+        let handle = self.untrack_notification(handle)?;
+        self.inner.ads_notifs.remove(handle)?;
+        let invoke_id = self.next_invoke_id()?;
+        let frame =
+            AdsDeleteDeviceNotificationRequest::new(target, self.source()?, invoke_id, handle)
+                .into_frame();
+
+        self.inner
+            .ams_requests
+            .dispatch_detached(AmsRequestDispatchKey::AdsCommand(invoke_id), frame)
+    }
+
+    /// Queues a handle release without waiting for the response.
+    pub(crate) fn release_handle_nowait(&self, target: AmsAddr, handle: u32) -> crate::Result<()> {
+        let invoke_id = self.next_invoke_id()?;
+        let frame = AdsWriteRequestOwned::new(
+            target,
+            self.source()?,
+            invoke_id,
+            index_group::SYM_RELEASEHND,
+            0,
+            handle.to_le_bytes(),
+        )
+        .into_frame();
+
+        self.inner
+            .ams_requests
+            .dispatch_detached(AmsRequestDispatchKey::AdsCommand(invoke_id), frame)
+    }
+
+    /// Reads the symbol and data type table sizes of `target`.
+    pub fn read_symbol_upload_info(&self, target: AmsAddr) -> crate::Result<AdsSymbolUploadInfo> {
+        let data = self.read(
+            target,
+            index_group::SYM_UPLOADINFO2,
+            0,
+            AdsSymbolUploadInfo::LENGTH as u32,
+        )?;
+
+        Ok(AdsSymbolUploadInfo::try_from_slice(&data)?)
+    }
+
+    /// Uploads and parses the symbol table of `target`.
+    pub fn upload_symbols(&self, target: AmsAddr) -> crate::Result<Vec<AdsSymbolEntry>> {
+        let info = self.read_symbol_upload_info(target)?;
+        self.upload_symbols_with_length(target, info.symbol_length())
+    }
+
+    /// Uploads and parses the data type table of `target`.
+    pub fn upload_datatypes(&self, target: AmsAddr) -> crate::Result<Vec<AdsDatatypeEntry>> {
+        let info = self.read_symbol_upload_info(target)?;
+        self.upload_datatypes_with_length(target, info.datatype_length())
+    }
+
+    /// Uploads the symbol and data type tables of `target` into a browsable [`SymbolTable`].
+    pub fn upload_symbol_table(&self, target: AmsAddr) -> crate::Result<SymbolTable> {
+        let info = self.read_symbol_upload_info(target)?;
+        let symbols = self.upload_symbols_with_length(target, info.symbol_length())?;
+        let datatypes = self.upload_datatypes_with_length(target, info.datatype_length())?;
+
+        Ok(SymbolTable::new(symbols, datatypes))
+    }
+
+    /// Reads the symbol `name` and decodes it into an [`AdsValue`] using the data types in
+    /// `table`.
+    pub fn read_symbol_value(
+        &self,
+        target: AmsAddr,
+        table: &SymbolTable,
+        name: &str,
+    ) -> crate::Result<AdsValue> {
+        let symbol = table
+            .symbol(name)
+            .ok_or_else(|| ValueError::UnknownSymbol(name.to_string()))?;
+        let data = self.read(
+            target,
+            symbol.index_group(),
+            symbol.index_offset(),
+            symbol.size(),
+        )?;
+
+        Ok(table.decode_symbol(symbol, &data)?)
+    }
+
+    /// Encodes `value` using the data types in `table` and writes it to the symbol `name`.
+    pub fn write_symbol_value(
+        &self,
+        target: AmsAddr,
+        table: &SymbolTable,
+        name: &str,
+        value: &AdsValue,
+    ) -> crate::Result<()> {
+        let symbol = table
+            .symbol(name)
+            .ok_or_else(|| ValueError::UnknownSymbol(name.to_string()))?;
+        let data = table.encode_symbol(symbol, value)?;
+
+        self.write(target, symbol.index_group(), symbol.index_offset(), data)
+    }
+
+    fn upload_symbols_with_length(
+        &self,
+        target: AmsAddr,
+        length: u32,
+    ) -> crate::Result<Vec<AdsSymbolEntry>> {
+        let data = self.read(target, index_group::SYM_UPLOAD, 0, length)?;
+        Ok(AdsSymbolEntry::parse_table(&data)?)
+    }
+
+    fn upload_datatypes_with_length(
+        &self,
+        target: AmsAddr,
+        length: u32,
+    ) -> crate::Result<Vec<AdsDatatypeEntry>> {
+        let data = self.read(target, index_group::SYM_DT_UPLOAD, 0, length)?;
+        Ok(AdsDatatypeEntry::parse_table(&data)?)
+    }
+
+    fn add_notifications_chunk(
+        &self,
+        target: AmsAddr,
+        items: &[SumAddNotificationItem],
+    ) -> Vec<crate::Result<(Receiver<AdsNotificationSampleOwned>, NotificationHandle)>> {
+        // Every item is pre-registered under its own invoke ID from a reserved block, so
+        // samples can be routed per item as soon as its handle is known.
+        let first = match self.next_invoke_ids(items.len()) {
+            Ok(first) => first,
+            Err(e) => return items.iter().map(|_| Err(e.clone())).collect(),
+        };
+        let invoke_ids: Vec<InvokeId> = (0..items.len() as u32)
+            .map(|i| first.wrapping_add(i))
+            .collect();
+        let (senders, receivers): (Vec<_>, Vec<_>) = items.iter().map(|_| mpsc::channel()).unzip();
+        for (&invoke_id, tx) in invoke_ids.iter().zip(&senders) {
+            if let Err(e) = self
+                .inner
+                .ads_notifs
+                .pre_register_with(invoke_id, tx.clone())
+            {
+                for &invoke_id in &invoke_ids {
+                    let _ = self.inner.ads_notifs.abandon(invoke_id);
+                }
+                return items.iter().map(|_| Err(e.clone())).collect();
+            }
+        }
+
+        let request = SumAddNotificationItem::encode_request(items);
+        let response = self
+            .send_sum(target, request)
+            .and_then(|data| Ok(SumAddNotificationItem::parse_response(items.len(), &data)?));
+
+        match response {
+            Ok(handles) => invoke_ids
+                .into_iter()
+                .zip(items)
+                .zip(senders.into_iter().zip(receivers))
+                .zip(handles)
+                .map(|(((invoke_id, item), (tx, rx)), handle)| match handle {
+                    Ok(handle) => {
+                        self.inner.ads_notifs.promote(invoke_id, handle)?;
+                        Ok((
+                            rx,
+                            self.track_notification(target, *item, tx.into(), handle)?,
+                        ))
+                    }
+                    Err(code) => {
+                        self.inner.ads_notifs.abandon(invoke_id)?;
+                        Err(code.into())
+                    }
+                })
+                .collect(),
+            Err(e) => invoke_ids
+                .into_iter()
+                .map(|invoke_id| {
+                    self.inner.ads_notifs.abandon(invoke_id)?;
+                    Err(e.clone())
+                })
+                .collect(),
+        }
+    }
+
+    /// Adds the notification described by `item` on `target`, routing its samples to
+    /// `sender`, and returns the handle assigned by the PLC.
+    fn register_notification(
+        &self,
+        target: AmsAddr,
+        item: &SumAddNotificationItem,
+        sender: NotificationSender,
+    ) -> crate::Result<NotificationHandle> {
+        let invoke_id = self.next_invoke_id()?;
+
+        self.inner.ads_notifs.pre_register_with(invoke_id, sender)?;
+
+        let result = (|| {
+            let frame = AdsAddDeviceNotificationRequest::new(
+                target,
+                self.source()?,
+                invoke_id,
+                item.index_group(),
+                item.index_offset(),
+                item.length(),
+                item.trans_mode(),
+                item.max_delay(),
+                item.cycle_time(),
+            )
+            .into_frame();
+            let resp =
+                AdsAddDeviceNotificationResponse::try_from(self.send_and_wait(frame, invoke_id)?)?;
+
+            Self::check_result(resp.result())?;
+
+            Ok(resp.handle())
+        })();
+
+        let handle = match result {
+            Ok(handle) => handle,
+            Err(e) => {
+                self.inner.ads_notifs.abandon(invoke_id)?;
+                return Err(e);
+            }
+        };
+        self.inner.ads_notifs.promote(invoke_id, handle)?;
+
+        Ok(handle)
+    }
+
+    /// Tracks a notification on a reconnecting device and returns the handle to give to
+    /// the caller, which is the PLC `handle` otherwise.
+    fn track_notification(
+        &self,
+        target: AmsAddr,
+        item: SumAddNotificationItem,
+        sender: NotificationSender,
+        handle: NotificationHandle,
+    ) -> crate::Result<NotificationHandle> {
+        match &self.inner.reconnect {
+            Some(reconnect) => reconnect.track_notification(target, item, sender, handle),
+            None => Ok(handle),
+        }
+    }
+
+    /// Stops tracking the notification the caller knows as `handle` and returns the
+    /// handle assigned by the PLC.
+    fn untrack_notification(
+        &self,
+        handle: NotificationHandle,
+    ) -> crate::Result<NotificationHandle> {
+        let Some(reconnect) = &self.inner.reconnect else {
+            return Ok(handle);
+        };
+        let current = reconnect.notification_handle(handle)?;
+        reconnect.forget_notification(handle)?;
+
+        Ok(current.unwrap_or(handle))
+    }
+
+    fn send_sum(&self, target: AmsAddr, request: SumRequest) -> crate::Result<Vec<u8>> {
+        self.read_write(
+            target,
+            request.index_group(),
+            request.count(),
+            request.read_length(),
+            request.into_data(),
+        )
+    }
+
     fn port_connect(&self) -> crate::Result<AmsAddr> {
         let frame = PortConnectRequest::default().into_frame();
-        let rx = self
-            .inner
-            .ams_requests
-            .dispatch(AmsRequestDispatchKey::PortConnect, frame)?;
-        let resp = PortConnectResponse::try_from(self.wait(rx)?)?;
+        let resp = PortConnectResponse::try_from(
+            self.request(AmsRequestDispatchKey::PortConnect, frame)?,
+        )?;
 
         Ok(*resp.addr())
     }
 
     fn send_and_wait(&self, frame: AmsFrame, invoke_id: InvokeId) -> crate::Result<AmsFrame> {
-        let rx = self
-            .inner
-            .ams_requests
-            .dispatch(AmsRequestDispatchKey::AdsCommand(invoke_id), frame)?;
-        self.wait(rx)
+        self.request(AmsRequestDispatchKey::AdsCommand(invoke_id), frame)
     }
 
-    fn wait(&self, rx: Receiver<AmsFrame>) -> crate::Result<AmsFrame> {
-        match self.inner.timeout {
-            Some(duration) => Ok(rx.recv_timeout(duration)?),
-            None => Ok(rx.recv()?),
-        }
+    /// Dispatches `frame` and waits for the response under `key`, for as long as the
+    /// [options](Self::with_options) of this handle allow. A request given up on is
+    /// purged.
+    fn request(&self, key: AmsRequestDispatchKey, frame: AmsFrame) -> crate::Result<AmsFrame> {
+        self.submit(key, frame, Ok)?.wait()
     }
 
-    fn next_invoke_id(&self) -> InvokeId {
-        self.inner.invoke_id.fetch_add(1, Ordering::Relaxed)
+    /// Dispatches `frame` without waiting, deferring `decode` of the response under `key`
+    /// until it is collected.
+    fn submit<T>(
+        &self,
+        key: AmsRequestDispatchKey,
+        frame: AmsFrame,
+        decode: impl FnOnce(AmsFrame) -> crate::Result<T> + Send + 'static,
+    ) -> crate::Result<PendingResponse<T>> {
+        self.options.check()?;
+        let rx = self.inner.ams_requests.dispatch(key, frame)?;
+
+        Ok(PendingResponse::new(
+            rx,
+            key,
+            Arc::clone(&self.inner.ams_requests),
+            self.options.clone(),
+            self.inner.timeout,
+            decode,
+        ))
+    }
+
+    fn read_request(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        length: u32,
+    ) -> crate::Result<(AmsRequestDispatchKey, AmsFrame)> {
+        let invoke_id = self.next_invoke_id()?;
+
+        let frame = AdsReadRequest::new(
+            target,
+            self.source()?,
+            invoke_id,
+            index_group,
+            index_offset,
+            length,
+        )
+        .into_frame();
+
+        Ok((AmsRequestDispatchKey::AdsCommand(invoke_id), frame))
+    }
+
+    fn read_data(frame: AmsFrame) -> crate::Result<Vec<u8>> {
+        let resp = AdsReadResponse::try_from_frame(&frame)?;
+
+        Self::check_result(resp.result())?;
+
+        Ok(resp.data().to_vec())
+    }
+
+    fn next_invoke_id(&self) -> crate::Result<InvokeId> {
+        self.inner.invoke_ids.next(|ids| self.in_flight(ids))
+    }
+
+    /// Allocates `count` consecutive invoke IDs and returns the first.
+    fn next_invoke_ids(&self, count: usize) -> crate::Result<InvokeId> {
+        self.inner
+            .invoke_ids
+            .next_block(count as u32, |ids| self.in_flight(ids))
+    }
+
+    /// Returns `true` if a request or notification is still waiting under any of `ids`.
+    fn in_flight(&self, ids: Range<InvokeId>) -> bool {
+        let requests = self.inner.ams_requests.in_flight(ids.clone());
+        let notifications = self.inner.ads_notifs.in_flight(ids);
+        // A poisoned lock is reported once the ID is used.
+        requests.unwrap_or(false) || notifications.unwrap_or(false)
     }
 
     fn check_result(code: AdsReturnCode) -> crate::Result<()> {
@@ -484,3 +1752,191 @@ impl AdsDevice {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use tcads_core::protocol::AdsReadResponseOwned;
+    use tcads_core::{AdsCommand, AdsHeader, WindowsFileTime};
+
+    /// Accepts one connection and collects `count` `Read` requests. Once `go` fires, it
+    /// answers them in reverse order with the index offset as data.
+    fn spawn_reversing_router(count: usize, go: Receiver<()>) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let (socket, _) = listener.accept().unwrap();
+            let mut stream = AmsStream::new(socket);
+
+            let requests = (0..count)
+                .map(|_| AdsReadRequest::try_from(&stream.read_frame().unwrap()).unwrap())
+                .collect::<Vec<_>>();
+            go.recv().unwrap();
+            for req in requests.iter().rev() {
+                let header = req.header();
+                let resp = AdsReadResponseOwned::new(
+                    *header.source(),
+                    *header.target(),
+                    header.invoke_id(),
+                    AdsReturnCode::Ok,
+                    req.index_offset().to_le_bytes(),
+                );
+                stream.write_frame(&resp.into_frame()).unwrap();
+            }
+            // Keep the connection open until the device hangs up.
+            while stream.read_frame().is_ok() {}
+        });
+
+        addr
+    }
+
+    #[test]
+    fn submitted_reads_resolve_out_of_order_and_drop_cancels_only_its_own() {
+        let (go_tx, go_rx) = mpsc::channel();
+        let addr = spawn_reversing_router(3, go_rx);
+        let source = AmsAddr::new(AmsNetId::new(10, 0, 0, 2, 1, 1), 32768);
+        let target = AmsAddr::new(AmsNetId::new(10, 0, 0, 1, 1, 1), 851);
+        let device =
+            AdsDevice::connect_with_source(addr, source, Some(Duration::from_secs(5))).unwrap();
+
+        let first = device.submit_read(target, 0x4020, 1, 4).unwrap();
+        let second = device.submit_read(target, 0x4020, 2, 4).unwrap();
+        let third = device.submit_read(target, 0x4020, 3, 4).unwrap();
+        assert_eq!(device.inner.ams_requests.pending().unwrap(), 3);
+
+        drop(second);
+        assert_eq!(device.inner.ams_requests.pending().unwrap(), 2);
+        go_tx.send(()).unwrap();
+
+        // The response to `first` is sent last, so the others have been dispatched by then.
+        assert_eq!(first.wait().unwrap(), 1u32.to_le_bytes());
+        assert_eq!(third.wait().unwrap(), 3u32.to_le_bytes());
+        assert_eq!(device.inner.ams_requests.late_responses(), 1);
+        assert_eq!(device.inner.ams_requests.pending().unwrap(), 0);
+    }
+
+    /// Accepts one connection and serves `MAIN.nCount` (UDINT at `0x4040:0`). Notifications
+    /// on it get handle 7, and a sample of 42 stamped with `stamp` follows each time
+    /// `publish` fires. Deleted handles are reported.
+    fn spawn_notifying_router(
+        stamp: WindowsFileTime,
+        publish: Receiver<()>,
+    ) -> (SocketAddr, Receiver<NotificationHandle>) {
+        use tcads_core::protocol::{
+            AdsDeviceNotificationOwned, AdsReadWriteRequest, AdsReadWriteResponseOwned,
+            AdsStampHeaderOwned,
+        };
+        use tcads_core::symbol::AdsDataTypeId;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (deleted_tx, deleted_rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            let (socket, _) = listener.accept().unwrap();
+            let mut stream = AmsStream::new(socket);
+            let entry =
+                AdsSymbolEntry::new("MAIN.nCount", "UDINT", AdsDataTypeId::UInt32, 0x4040, 0, 4);
+
+            while let Ok(frame) = stream.read_frame() {
+                let (header, _) = AdsHeader::parse_prefix(frame.payload()).unwrap();
+                let (target, source, id) = (*header.source(), *header.target(), header.invoke_id());
+
+                match header.command_id() {
+                    AdsCommand::AdsReadWrite => {
+                        let req = AdsReadWriteRequest::try_from(&frame).unwrap();
+                        assert_eq!(req.index_group(), index_group::SYM_INFOBYNAMEEX);
+                        let resp = AdsReadWriteResponseOwned::new(
+                            target,
+                            source,
+                            id,
+                            AdsReturnCode::Ok,
+                            entry.to_bytes().unwrap(),
+                        );
+                        stream.write_frame(&resp.into_frame()).unwrap();
+                    }
+                    AdsCommand::AdsAddDeviceNotification => {
+                        let req = AdsAddDeviceNotificationRequest::try_from(&frame).unwrap();
+                        assert_eq!((req.index_group(), req.length()), (0x4040, 4));
+                        let handle = NotificationHandle::new(7);
+                        let resp = AdsAddDeviceNotificationResponse::new(
+                            target,
+                            source,
+                            id,
+                            AdsReturnCode::Ok,
+                            handle,
+                        );
+                        stream.write_frame(&resp.into_frame()).unwrap();
+
+                        publish.recv().unwrap();
+                        let sample = AdsNotificationSampleOwned::new(handle, 42u32.to_le_bytes());
+                        let stamp = AdsStampHeaderOwned::new(stamp, vec![sample]);
+                        let notification =
+                            AdsDeviceNotificationOwned::new(target, source, vec![stamp]);
+                        stream.write_frame(&notification.into_frame()).unwrap();
+                    }
+                    AdsCommand::AdsDeleteDeviceNotification => {
+                        let req = AdsDeleteDeviceNotificationRequest::try_from(&frame).unwrap();
+                        deleted_tx.send(req.handle()).unwrap();
+                        let resp = AdsDeleteDeviceNotificationResponse::new(
+                            target,
+                            source,
+                            id,
+                            AdsReturnCode::Ok,
+                        );
+                        stream.write_frame(&resp.into_frame()).unwrap();
+                    }
+                    command => panic!("unexpected command {command:?}"),
+                }
+            }
+        });
+
+        (addr, deleted_rx)
+    }
+
+    #[test]
+    fn subscriptions_decode_samples_and_delete_on_drop() {
+        let stamp = WindowsFileTime::from_raw(133_000_000_000_000_000);
+        let (publish, publish_rx) = mpsc::channel();
+        let (addr, deleted) = spawn_notifying_router(stamp, publish_rx);
+        let source = "127.0.0.1.1.1:32768".parse().unwrap();
+        let target = "127.0.0.1.1.1:851".parse().unwrap();
+        let timeout = Duration::from_secs(1);
+        let device = AdsDevice::connect_with_source(addr, source, Some(timeout)).unwrap();
+
+        let count = device
+            .subscribe::<u32>(target, "MAIN.nCount", SubscriptionOptions::new())
+            .unwrap();
+        assert_eq!(count.handle(), NotificationHandle::new(7));
+        publish.send(()).unwrap();
+        assert_eq!(count.recv_timeout(timeout).unwrap(), (stamp, 42));
+
+        drop(count);
+        assert_eq!(
+            deleted.recv_timeout(timeout).unwrap(),
+            NotificationHandle::new(7)
+        );
+
+        let wide = device
+            .subscribe::<u64>(
+                target,
+                NotificationSource::address(0x4040, 0, 4),
+                SubscriptionOptions::new(),
+            )
+            .unwrap();
+        publish.send(()).unwrap();
+        assert!(matches!(
+            wide.recv_timeout(timeout),
+            Err(crate::Error::Type(_))
+        ));
+        wide.unsubscribe().unwrap();
+        // The response to the deletion on drop arrived first and was not counted as late
+        assert_eq!(device.late_responses(), 0);
+        assert_eq!(
+            deleted.recv_timeout(timeout).unwrap(),
+            NotificationHandle::new(7)
+        );
+    }
+}
@@ -1 +1,2396 @@
+use crate::devices::batch::DEFAULT_MAX_ITEMS;
+use crate::devices::batch::tokio::Batch;
+use crate::devices::connection::{
+    ConnectOptions, ConnectionEvent, ConnectionState, Heartbeat, Reconnect,
+};
+use crate::devices::request::RequestOptions;
+use crate::devices::subscription::tokio::Subscription;
+use crate::devices::subscription::{NotificationSource, SubscriptionOptions};
+use crate::devices::symbol_handle::tokio::SymbolHandle;
+use crate::symbols::{AdsValue, SymbolTable, ValueError};
+use crate::tasks::tokio::{
+    AdsNotificationDispatcher, AmsRequestDispatchKey, AmsRequestDispatcher, AmsRequestWriter,
+    AmsResponseReader, ConnectionEventDispatcher, ConnectionStateDispatcher, InvokeIdAllocator,
+    NotificationSender, RouterNotificationDispatcher,
+};
+use std::net::SocketAddr;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, RwLock, Weak};
+use std::time::{Duration, Instant};
+use tcads_core::ads::index_group;
+#[cfg(feature = "tls")]
+use tcads_core::io::TlsConfig;
+#[cfg(feature = "tls")]
+use tcads_core::io::tokio::TlsStream;
+use tcads_core::io::tokio::{AmsReader, AmsStream, AmsWriter};
+use tcads_core::protocol::{
+    AdsAddDeviceNotificationRequest, AdsAddDeviceNotificationResponse,
+    AdsDeleteDeviceNotificationRequest, AdsDeleteDeviceNotificationResponse,
+    AdsNotificationSampleOwned, AdsReadDeviceInfoRequest, AdsReadDeviceInfoResponse,
+    AdsReadRequest, AdsReadResponse, AdsReadStateRequest, AdsReadStateResponse,
+    AdsReadWriteRequestOwned, AdsReadWriteResponse, AdsWriteControlRequestOwned,
+    AdsWriteControlResponse, AdsWriteRequestOwned, AdsWriteResponse, GetLocalNetIdRequest,
+    GetLocalNetIdResponse, PortCloseRequest, PortConnectRequest, PortConnectResponse,
+    SumAddNotificationItem, SumDeleteNotificationItem, SumRequest,
+};
+use tcads_core::symbol::{
+    AdsDatatypeEntry, AdsSymbolEntry, AdsSymbolUploadInfo, encode_symbol_name,
+};
+use tcads_core::{
+    AdsDecode, AdsDeviceVersion, AdsEncode, AdsReturnCode, AdsState, AdsTransMode, AdsType,
+    AmsAddr, AmsCommand, AmsFrame, AmsNetId, DeviceState, IndexGroup, IndexOffset, InvokeId,
+    NotificationHandle, RouterState,
+};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::ToSocketAddrs;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
 
+/// Purges a pending request once its caller stops waiting, including when the future
+/// is dropped. A no-op if the response has already been delivered.
+struct Purge<'a> {
+    requests: &'a AmsRequestDispatcher,
+    key: AmsRequestDispatchKey,
+}
+
+impl Drop for Purge<'_> {
+    fn drop(&mut self) {
+        let _ = self.requests.cancel(self.key);
+    }
+}
+
+/// A freshly dialled connection, before it is split between the reader and writer tasks.
+enum Dialled {
+    Tcp(AmsStream),
+    #[cfg(feature = "tls")]
+    Tls(Box<AmsStream<TlsStream>>),
+}
+
+/// Shared state for an [`AdsDevice`] connection.
+///
+/// Held behind an [`Arc`] so all [`AdsDevice`] clones share the same connection.
+/// Exposed as `pub` for power users who need direct access to the underlying
+/// dispatchers to build custom device abstractions on top of the
+/// same connection without going through the [`AdsDevice`] API.
+///
+/// # Lifetime
+///
+/// The reader and writer tasks are tied to the lifetime of this struct.
+/// When the last [`AdsDevice`] clone is dropped, `AdsDeviceInner` drops,
+/// which drops [`AmsRequestDispatcher`] and its `write_tx`. The writer task
+/// exits when `write_tx` is dropped, the TCP stream closes, and the reader
+/// task exits on the next read returning EOF.
+///
+/// The task watching the connection only holds a [`Weak`] reference, so it never
+/// keeps a device alive, and a reconnecting device stops re-dialling once dropped.
+pub struct AdsDeviceInner {
+    pub ams_requests: Arc<AmsRequestDispatcher>,
+    pub ads_notifs: Arc<AdsNotificationDispatcher>,
+    pub router_notifs: Arc<RouterNotificationDispatcher>,
+    pub connection_events: Arc<ConnectionEventDispatcher>,
+    pub connection_state: Arc<ConnectionStateDispatcher>,
+    pub source: RwLock<AmsAddr>,
+    pub invoke_ids: InvokeIdAllocator,
+    pub timeout: Option<Duration>,
+    pub(crate) reconnect: Option<Reconnect<NotificationSender>>,
+}
+
+/// An asynchronous ADS device client.
+///
+/// The async counterpart of the [blocking `AdsDevice`](crate::devices::blocking::AdsDevice).
+/// It manages a single TCP connection to an AMS router and exposes all standard
+/// ADS commands as `async` methods. It is cheap to clone, and all clones share the same
+/// underlying connection and state.
+///
+/// # Connection
+///
+/// Use one of the `connect` constructors:
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use tcads_client::devices::tokio::AdsDevice;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// // Local router, auto-assigned source
+/// let device = AdsDevice::connect(Some(Duration::from_secs(5))).await?;
+///
+/// // Remote router, auto-assigned source
+/// let device = AdsDevice::connect_to("192.168.1.100:48898", Some(Duration::from_secs(5))).await?;
+///
+/// // Remote router, explicit source, skips PortConnect handshake
+/// let source = "192.168.1.100.1.1:32838".parse()?;
+/// let device = AdsDevice::connect_with_source("192.168.1.100:48898", source, None).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// With the `tls` feature, Secure ADS routers are reached over TLS with `connect_tls` and
+/// `connect_tls_with_source`. Pre-shared key connections also need the `tls-psk` feature.
+///
+/// # Reconnecting
+///
+/// A device connected by [`connect_with_options`](AdsDevice::connect_with_options) with a
+/// [`ReconnectPolicy`](crate::devices::connection::ReconnectPolicy) re-dials whenever the
+/// connection is lost, e.g. because the PLC rebooted. Once reconnected it registers every
+/// active notification and acquires every [`SymbolHandle`] again, so notification
+/// receivers and symbol handles keep working. Calls made while the connection is down
+/// fail with [`Error::Disconnected`](crate::Error::Disconnected). Progress is reported
+/// through [`subscribe_connection`](AdsDevice::subscribe_connection).
+///
+/// # Monitoring
+///
+/// [`watch_state`](AdsDevice::watch_state) observes the
+/// [`ConnectionState`]. With a [`Heartbeat`] configured, the device also probes the
+/// link periodically, reports it as [`Degraded`](ConnectionState::Degraded) while
+/// probes time out, and records their round-trip time in
+/// [`last_rtt`](AdsDevice::last_rtt).
+///
+/// # Concurrency
+///
+/// `AdsDevice` is `Send + Sync`. Multiple tasks can issue ADS commands concurrently.
+/// Responses are matched to their callers by Invoke ID with no global lock on the
+/// connection.
+///
+/// # Cancellation
+///
+/// Dropping an in-flight request future is safe. The pending entry is purged right
+/// away, and a response arriving later is only counted in
+/// [`late_responses`](AdsDevice::late_responses). Per-call timeouts, deadlines and
+/// cancellation are set with [`with_options`](AdsDevice::with_options).
+///
+/// # Shutdown
+///
+/// Call [`shutdown`](AdsDevice::shutdown) for a clean disconnect. Dropping the last
+/// `AdsDevice` clone also tears down the connection automatically. The writer task
+/// exits when its sender is dropped, the reader task exits when the TCP stream closes,
+/// and all pending callers receive [`Error::Disconnected`](crate::Error::Disconnected).
+#[derive(Clone)]
+pub struct AdsDevice {
+    inner: Arc<AdsDeviceInner>,
+    options: RequestOptions,
+}
+
+impl AdsDevice {
+    /// Connects to the local AMS router at `127.0.0.1:48898`.
+    ///
+    /// Performs a [`PortConnect`](PortConnectRequest) handshake to get a
+    /// dynamically assigned source address.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use tcads_client::devices::tokio::AdsDevice;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let device = AdsDevice::connect(None).await?;
+    ///
+    /// println!("Source: {}", device.source()?);
+    /// println!("Local Net ID: {}", device.get_local_net_id().await?);
+    ///
+    /// device.shutdown()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn connect(timeout: Option<Duration>) -> crate::Result<Self> {
+        Self::connect_to("127.0.0.1:48898", timeout).await
+    }
+
+    /// Connects to an AMS router at `addr`.
+    ///
+    /// Performs a [`PortConnect`](PortConnectRequest) handshake to obtain a
+    /// dynamically assigned source address.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use tcads_client::devices::tokio::AdsDevice;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let device = AdsDevice::connect_to("192.168.1.100:48898", None).await?;
+    ///
+    /// println!("Source: {}", device.source()?);
+    /// println!("Local Net ID: {}", device.get_local_net_id().await?);
+    ///
+    /// device.shutdown()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn connect_to(
+        addr: impl ToSocketAddrs,
+        timeout: Option<Duration>,
+    ) -> crate::Result<Self> {
+        let stream = AmsStream::connect(addr).await?;
+        let device = Self::new(stream, AmsAddr::default(), timeout);
+        let source = device.port_connect().await?;
+        *device.inner.source.write()? = source;
+        Ok(device)
+    }
+
+    /// Connects to an AMS router at `addr` using an explicitly provided
+    /// source address, skipping the [`PortConnect`](PortConnectRequest) handshake.
+    ///
+    /// Use this when a static route is configured on the PLC and the source address
+    /// must exactly match the configured route.
+    pub async fn connect_with_source(
+        addr: impl ToSocketAddrs,
+        source: AmsAddr,
+        timeout: Option<Duration>,
+    ) -> crate::Result<Self> {
+        let stream = AmsStream::connect(addr).await?;
+        Ok(Self::new(stream, source, timeout))
+    }
+
+    /// Connects to a Secure ADS router at `addr`, usually on port
+    /// [`TLS_PORT`](tcads_core::ams::AmsTransport::TLS_PORT).
+    ///
+    /// Performs the TLS handshake described by `config`, then a
+    /// [`PortConnect`](PortConnectRequest) handshake to obtain a dynamically assigned
+    /// source address.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use tcads_client::devices::tokio::AdsDevice;
+    /// use tcads_core::io::TlsConfig;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = TlsConfig::shared_ca(std::fs::read("ca.pem")?)?
+    ///     .with_client_cert(std::fs::read("client.pem")?, std::fs::read("client.key")?)?;
+    /// let device = AdsDevice::connect_tls("192.168.1.100:8016", &config, None).await?;
+    ///
+    /// println!("Source: {}", device.source()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "tls")]
+    pub async fn connect_tls(
+        addr: impl ToSocketAddrs,
+        config: &TlsConfig,
+        timeout: Option<Duration>,
+    ) -> crate::Result<Self> {
+        let stream = AmsStream::connect_tls(addr, config).await?;
+        let device = Self::new_tls(stream, AmsAddr::default(), timeout);
+        let source = device.port_connect().await?;
+        *device.inner.source.write()? = source;
+        Ok(device)
+    }
+
+    /// Connects to a Secure ADS router at `addr` using an explicitly provided
+    /// source address, skipping the [`PortConnect`](PortConnectRequest) handshake.
+    ///
+    /// The TLS counterpart of [`connect_with_source`](Self::connect_with_source).
+    #[cfg(feature = "tls")]
+    pub async fn connect_tls_with_source(
+        addr: impl ToSocketAddrs,
+        source: AmsAddr,
+        config: &TlsConfig,
+        timeout: Option<Duration>,
+    ) -> crate::Result<Self> {
+        let stream = AmsStream::connect_tls(addr, config).await?;
+        Ok(Self::new_tls(stream, source, timeout))
+    }
+
+    /// Connects to an AMS router at `addr` as described by `options`.
+    ///
+    /// This is the only constructor that can produce a reconnecting device, see
+    /// [`ConnectOptions::with_reconnect`]. The first connection attempt is made
+    /// immediately and its failure is returned; the reconnect policy only applies once
+    /// connected.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use tcads_client::devices::connection::{ConnectOptions, ReconnectPolicy};
+    /// use tcads_client::devices::tokio::AdsDevice;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let options = ConnectOptions::new().with_reconnect(ReconnectPolicy::default());
+    /// let device = AdsDevice::connect_with_options("192.168.1.100:48898", options).await?;
+    ///
+    /// let mut events = device.subscribe_connection()?;
+    /// while let Some(event) = events.recv().await {
+    ///     println!("{event:?}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn connect_with_options(
+        addr: impl ToSocketAddrs,
+        options: ConnectOptions,
+    ) -> crate::Result<Self> {
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host(addr).await?.collect();
+        let (dialled, source) = Self::dial(&addrs, &options).await?;
+        let timeout = options.timeout;
+        let heartbeat = options.heartbeat;
+        let reconnect = options
+            .reconnect
+            .map(|policy| Reconnect::new(addrs, options, policy));
+
+        Ok(match dialled {
+            Dialled::Tcp(stream) => {
+                let (reader, writer) = stream.into_split();
+                Self::spawn(reader, writer, source, timeout, reconnect, heartbeat)
+            }
+            #[cfg(feature = "tls")]
+            Dialled::Tls(stream) => {
+                let (reader, writer) = (*stream).into_split();
+                Self::spawn(reader, writer, source, timeout, reconnect, heartbeat)
+            }
+        })
+    }
+
+    /// Creates an [`AdsDevice`] from an existing [`AmsStream`].
+    ///
+    /// Unlike [`connect`](Self::connect) and [`connect_to`](Self::connect_to), this
+    /// constructor does **not** perform a [`PortConnect`] handshake. The caller is
+    /// responsible for providing a valid `source` address.
+    ///
+    /// This is intended for power users who need control over the underlying stream,
+    /// for example to use a custom transport, inject test streams, or reuse an
+    /// existing connection.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside the context of a Tokio runtime, since the reader
+    /// and writer tasks are spawned immediately.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use tcads_core::io::tokio::AmsStream;
+    /// use tcads_client::devices::tokio::AdsDevice;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let stream = AmsStream::connect("192.168.1.100:48898").await?;
+    /// let source = "192.168.1.100.1.1:851".parse()?;
+    /// let device = AdsDevice::new(stream, source, None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new(stream: AmsStream, source: AmsAddr, timeout: Option<Duration>) -> Self {
+        let (reader, writer) = stream.into_split();
+        Self::spawn(reader, writer, source, timeout, None, None)
+    }
+
+    /// Creates an [`AdsDevice`] from an existing Secure ADS [`AmsStream`].
+    ///
+    /// Like [`new`](Self::new), this does **not** perform a [`PortConnect`] handshake.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside the context of a Tokio runtime.
+    #[cfg(feature = "tls")]
+    pub fn new_tls(
+        stream: AmsStream<TlsStream>,
+        source: AmsAddr,
+        timeout: Option<Duration>,
+    ) -> Self {
+        let (reader, writer) = stream.into_split();
+        Self::spawn(reader, writer, source, timeout, None, None)
+    }
+
+    /// Spawns the reader and writer tasks over the halves of a connection, the task
+    /// watching it, and the heartbeat task if one is configured.
+    fn spawn<R, W>(
+        reader: AmsReader<R>,
+        writer: AmsWriter<W>,
+        source: AmsAddr,
+        timeout: Option<Duration>,
+        reconnect: Option<Reconnect<NotificationSender>>,
+        heartbeat: Option<Heartbeat>,
+    ) -> Self
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let (write_tx, _) = AmsRequestWriter::spawn(writer);
+
+        let device = Self {
+            options: RequestOptions::default(),
+            inner: Arc::new(AdsDeviceInner {
+                ams_requests: Arc::new(AmsRequestDispatcher::new(write_tx)),
+                ads_notifs: Arc::new(AdsNotificationDispatcher::new()),
+                router_notifs: Arc::new(RouterNotificationDispatcher::new()),
+                connection_events: Arc::new(ConnectionEventDispatcher::new()),
+                connection_state: Arc::new(ConnectionStateDispatcher::default()),
+                source: RwLock::new(source),
+                invoke_ids: InvokeIdAllocator::new(),
+                timeout,
+                reconnect,
+            }),
+        };
+        let reader = device.spawn_reader(reader);
+        tokio::spawn(Self::supervise(Arc::downgrade(&device.inner), reader));
+        if let Some(heartbeat) = heartbeat {
+            tokio::spawn(Self::heartbeat(Arc::downgrade(&device.inner), heartbeat));
+        }
+
+        device
+    }
+
+    fn spawn_reader<R>(&self, reader: AmsReader<R>) -> JoinHandle<crate::Result<()>>
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        AmsResponseReader::spawn(
+            reader,
+            Arc::clone(&self.inner.ams_requests),
+            Arc::clone(&self.inner.ads_notifs),
+            Arc::clone(&self.inner.router_notifs),
+        )
+    }
+
+    /// Replaces a lost connection with the halves of a new one.
+    fn attach<R, W>(
+        &self,
+        reader: AmsReader<R>,
+        writer: AmsWriter<W>,
+    ) -> crate::Result<JoinHandle<crate::Result<()>>>
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let (write_tx, _) = AmsRequestWriter::spawn(writer);
+        self.inner.ams_requests.reopen(write_tx)?;
+        Ok(self.spawn_reader(reader))
+    }
+
+    /// Dials one of `addrs` and obtains the source address for the new connection.
+    async fn dial(
+        addrs: &[SocketAddr],
+        options: &ConnectOptions,
+    ) -> crate::Result<(Dialled, AmsAddr)> {
+        let dial = async {
+            #[cfg(feature = "tls")]
+            if let Some(config) = &options.tls {
+                let mut stream = AmsStream::connect_tls(addrs, config).await?;
+                let source = Self::handshake(&mut stream, options.source).await?;
+                return Ok((Dialled::Tls(Box::new(stream)), source));
+            }
+
+            let mut stream = AmsStream::connect(addrs).await?;
+            let source = Self::handshake(&mut stream, options.source).await?;
+            Ok((Dialled::Tcp(stream), source))
+        };
+
+        match options.timeout {
+            Some(duration) => tokio::time::timeout(duration, dial).await?,
+            None => dial.await,
+        }
+    }
+
+    /// Performs the [`PortConnect`](PortConnectRequest) handshake on a fresh stream,
+    /// unless a static `source` is given.
+    async fn handshake<S>(
+        stream: &mut AmsStream<S>,
+        source: Option<AmsAddr>,
+    ) -> crate::Result<AmsAddr>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        if let Some(source) = source {
+            return Ok(source);
+        }
+
+        stream
+            .write_frame(&PortConnectRequest::default().into_frame())
+            .await?;
+        loop {
+            let frame = stream.read_frame().await?;
+            if frame.header().command() == AmsCommand::PortConnect {
+                return Ok(*PortConnectResponse::try_from(frame)?.addr());
+            }
+        }
+    }
+
+    /// Waits for the reader task of each connection to exit, then reports the loss and
+    /// reconnects if the device is configured to.
+    async fn supervise(inner: Weak<AdsDeviceInner>, mut reader: JoinHandle<crate::Result<()>>) {
+        loop {
+            let _ = reader.await;
+            let Some(device) = Self::upgrade(&inner) else {
+                return;
+            };
+            device
+                .inner
+                .connection_state
+                .set(ConnectionState::Disconnected);
+            let _ = device
+                .inner
+                .connection_events
+                .broadcast(ConnectionEvent::Disconnected);
+            drop(device);
+
+            match Self::reconnect(&inner).await {
+                Some(next) => reader = next,
+                None => {
+                    if let Some(device) = Self::upgrade(&inner) {
+                        device.inner.connection_state.set(ConnectionState::Closed);
+                        let events = &device.inner.connection_events;
+                        let _ = events.broadcast(ConnectionEvent::Closed);
+                        let _ = events.clear();
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Re-dials with backoff until a new connection is up. Returns [`None`] if the device
+    /// does not reconnect, was shut down or dropped, or ran out of attempts.
+    async fn reconnect(inner: &Weak<AdsDeviceInner>) -> Option<JoinHandle<crate::Result<()>>> {
+        for attempt in 1.. {
+            let delay = {
+                let device = Self::upgrade(inner)?;
+                let reconnect = device.inner.reconnect.as_ref()?;
+                if reconnect.is_closing() || !reconnect.policy.allows(attempt) {
+                    return None;
+                }
+                reconnect.policy.delay(attempt)
+            };
+            tokio::time::sleep(delay).await;
+
+            let device = Self::upgrade(inner)?;
+            if device.inner.reconnect.as_ref()?.is_closing() {
+                return None;
+            }
+            let state = &device.inner.connection_state;
+            state.set(ConnectionState::Connecting);
+            let _ = device
+                .inner
+                .connection_events
+                .broadcast(ConnectionEvent::Reconnecting { attempt });
+            match device.redial().await {
+                Ok(reader) => return Some(reader),
+                Err(_) => state.set(ConnectionState::Disconnected),
+            }
+        }
+        None
+    }
+
+    /// Opens a new connection and restores notifications and symbol handles on it.
+    async fn redial(&self) -> crate::Result<JoinHandle<crate::Result<()>>> {
+        let Some(reconnect) = &self.inner.reconnect else {
+            return Err(crate::Error::Disconnected);
+        };
+        let (dialled, source) = Self::dial(&reconnect.addrs, &reconnect.options).await?;
+
+        *self.inner.source.write()? = source;
+        let reader = match dialled {
+            Dialled::Tcp(stream) => {
+                let (reader, writer) = stream.into_split();
+                self.attach(reader, writer)?
+            }
+            #[cfg(feature = "tls")]
+            Dialled::Tls(stream) => {
+                let (reader, writer) = (*stream).into_split();
+                self.attach(reader, writer)?
+            }
+        };
+
+        self.restore(reconnect).await?;
+        self.inner.connection_state.set(ConnectionState::Connected);
+        self.inner
+            .connection_events
+            .broadcast(ConnectionEvent::Reconnected { source })?;
+
+        Ok(reader)
+    }
+
+    /// Registers the tracked notifications and acquires the tracked symbol handles again.
+    ///
+    /// Items the target rejects are reported as lost; items that fail because the new
+    /// connection is already gone are left for the next attempt.
+    async fn restore(&self, reconnect: &Reconnect<NotificationSender>) -> crate::Result<()> {
+        let events = &self.inner.connection_events;
+
+        for (target, notifications) in reconnect.notifications()? {
+            for (key, item, sender) in notifications {
+                match self.register_notification(target, &item, sender).await {
+                    Ok(handle) => reconnect.update_notification(key, handle)?,
+                    Err(error @ crate::Error::AdsReturnCode(_)) => {
+                        reconnect.forget_notification(key)?;
+                        events
+                            .broadcast(ConnectionEvent::NotificationLost { handle: key, error })?;
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+
+        for (target, handles) in reconnect.handles()? {
+            let names: Vec<_> = handles.iter().map(|(name, _)| name.as_str()).collect();
+            let results = self.handles_by_name(target, &names).await;
+            for ((name, cell), result) in handles.into_iter().zip(results) {
+                match result {
+                    Ok(handle) => cell.store(handle, Ordering::Release),
+                    Err(error @ crate::Error::AdsReturnCode(_)) => {
+                        events.broadcast(ConnectionEvent::HandleLost { name, error })?;
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Probes the link while it is up, degrading the connection state once too many probes
+    /// in a row time out and recovering it once one is answered again.
+    async fn heartbeat(inner: Weak<AdsDeviceInner>, heartbeat: Heartbeat) {
+        let mut timeouts = 0;
+        loop {
+            tokio::time::sleep(heartbeat.interval).await;
+            let Some(device) = Self::upgrade(&inner) else {
+                return;
+            };
+            let state = &device.inner.connection_state;
+            match state.state() {
+                ConnectionState::Closed => return,
+                current if !current.is_up() => {
+                    timeouts = 0;
+                    continue;
+                }
+                _ => {}
+            }
+
+            match device.probe(heartbeat.target, heartbeat.timeout).await {
+                Ok(rtt) => {
+                    timeouts = 0;
+                    let _ = state.set_rtt(rtt);
+                    state.transition(ConnectionState::Degraded, ConnectionState::Connected);
+                }
+                Err(crate::Error::Timeout) => {
+                    timeouts += 1;
+                    if timeouts >= heartbeat.max_timeouts {
+                        state.transition(ConnectionState::Connected, ConnectionState::Degraded);
+                    }
+                }
+                Err(_) => {}
+            }
+        }
+    }
+
+    /// Reads the state of `target` and returns the round-trip time. An error code from
+    /// the target still counts as an answer.
+    async fn probe(&self, target: AmsAddr, timeout: Duration) -> crate::Result<Duration> {
+        let device = self.with_options(RequestOptions::new().with_timeout(timeout));
+        let start = Instant::now();
+        match device.read_state(target).await {
+            Ok(_) | Err(crate::Error::AdsReturnCode(_)) => Ok(start.elapsed()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn upgrade(inner: &Weak<AdsDeviceInner>) -> Option<Self> {
+        inner.upgrade().map(|inner| Self {
+            inner,
+            options: RequestOptions::default(),
+        })
+    }
+
+    /// Gracefully shuts down the connection.
+    ///
+    /// Sends a [`PortClose`](PortCloseRequest) frame to the router. The writer
+    /// task writes it and exits, dropping the channel receiver which invalidates
+    /// all senders. The router closes the TCP connection, causing the reader task
+    /// to hit EOF and exit, clearing all pending callers and notification subscribers.
+    ///
+    /// If the send fails (already disconnected) this returns `Ok(())` meaning the
+    /// connection is already gone. A reconnecting device stops re-dialling.
+    pub fn shutdown(&self) -> crate::Result<()> {
+        if let Some(reconnect) = &self.inner.reconnect {
+            reconnect.close();
+        }
+        let frame = PortCloseRequest::new(self.source()?.port()).into_frame();
+        let _ = self.inner.ams_requests.send_only(frame);
+        Ok(())
+    }
+
+    /// Returns the source [`AmsAddr`] currently assigned to this connection.
+    pub fn source(&self) -> crate::Result<AmsAddr> {
+        Ok(*self.inner.source.read()?)
+    }
+
+    /// Returns `true` once the connection has been lost or shut down.
+    ///
+    /// A reconnecting device reports `false` again once it has reconnected.
+    pub fn is_closed(&self) -> bool {
+        self.inner.ams_requests.is_closed()
+    }
+
+    /// Returns a handle to the same connection whose calls are limited by `options`.
+    ///
+    /// The options replace those of this handle and carry over to the [`Batch`]es and
+    /// [`SymbolHandle`]s created from the returned one. Calls that time out or are
+    /// cancelled fail with [`Error::Timeout`](crate::Error::Timeout) or
+    /// [`Error::Cancelled`](crate::Error::Cancelled), and their pending entries are purged.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::{Duration, Instant};
+    /// use tcads_client::devices::request::RequestOptions;
+    /// use tcads_client::devices::tokio::AdsDevice;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let device = AdsDevice::connect(None).await?;
+    /// let target = "192.168.1.100.1.1:851".parse()?;
+    ///
+    /// let deadline = Instant::now() + Duration::from_secs(2);
+    /// let hurried = device.with_options(RequestOptions::new().with_deadline(deadline));
+    /// let symbols = hurried.upload_symbols(target).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_options(&self, options: RequestOptions) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            options,
+        }
+    }
+
+    /// Reserves `count` consecutive invoke IDs for frames built outside the device.
+    ///
+    /// The device never assigns a reserved ID to its own requests, so frames dispatched
+    /// directly through [`AdsDeviceInner::ams_requests`] under these IDs cannot collide
+    /// with them. Dispatching under an ID that is still in flight fails with
+    /// [`Error::InvokeIdInUse`](crate::Error::InvokeIdInUse).
+    pub fn reserve_invoke_ids(&self, count: u32) -> crate::Result<Range<InvokeId>> {
+        self.inner
+            .invoke_ids
+            .reserve(count, |ids| self.in_flight(ids))
+    }
+
+    /// Returns a range obtained from [`reserve_invoke_ids`](Self::reserve_invoke_ids).
+    pub fn release_invoke_ids(&self, range: &Range<InvokeId>) -> crate::Result<()> {
+        self.inner.invoke_ids.release(range)
+    }
+
+    /// Returns how many ADS responses arrived after their caller had given up, e.g.
+    /// because it timed out, was cancelled, or dropped its future.
+    pub fn late_responses(&self) -> u64 {
+        self.inner.ams_requests.late_responses()
+    }
+
+    /// Returns the current [`ConnectionState`].
+    pub fn connection_state(&self) -> ConnectionState {
+        self.inner.connection_state.state()
+    }
+
+    /// Watches the [`ConnectionState`].
+    ///
+    /// The receiver starts at the current state. Once the device is
+    /// [closed](ConnectionState::Closed) no further changes follow, and
+    /// [`changed`](watch::Receiver::changed) returns [`Err`] after all clones are dropped.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use tcads_client::devices::connection::{ConnectOptions, Heartbeat};
+    /// use tcads_client::devices::tokio::AdsDevice;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let target = "192.168.1.100.1.1:851".parse()?;
+    /// let options = ConnectOptions::new().with_heartbeat(Heartbeat::new(target));
+    /// let device = AdsDevice::connect_with_options("192.168.1.100:48898", options).await?;
+    ///
+    /// let mut state = device.watch_state();
+    /// while state.changed().await.is_ok() {
+    ///     println!("{:?}, rtt {:?}", *state.borrow(), device.last_rtt()?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn watch_state(&self) -> watch::Receiver<ConnectionState> {
+        self.inner.connection_state.watch()
+    }
+
+    /// Returns the round-trip time of the last answered [`Heartbeat`] probe, or [`None`]
+    /// if no heartbeat is configured or none was answered yet.
+    pub fn last_rtt(&self) -> crate::Result<Option<Duration>> {
+        self.inner.connection_state.rtt()
+    }
+
+    /// Queries the router's local AMS Net ID.
+    pub async fn get_local_net_id(&self) -> crate::Result<AmsNetId> {
+        let frame = GetLocalNetIdRequest::into_frame();
+        let resp = GetLocalNetIdResponse::try_from(
+            self.request(AmsRequestDispatchKey::GetLocalNetId, frame)
+                .await?,
+        )?;
+
+        Ok(resp.net_id())
+    }
+
+    /// Subscribes to router state changes.
+    ///
+    /// Returns an [`UnboundedReceiver`] that yields each [`RouterState`] transition.
+    /// The receiver yields [`None`] when the connection is lost or all
+    /// `AdsDevice` clones are dropped.
+    pub fn subscribe_router(&self) -> crate::Result<UnboundedReceiver<RouterState>> {
+        self.inner.router_notifs.subscribe()
+    }
+
+    /// Subscribes to [connection events](ConnectionEvent).
+    ///
+    /// The receiver yields [`None`] after [`ConnectionEvent::Closed`]. Unlike
+    /// [`subscribe_router`](Self::subscribe_router), it stays open across reconnects.
+    pub fn subscribe_connection(&self) -> crate::Result<UnboundedReceiver<ConnectionEvent>> {
+        self.inner.connection_events.subscribe()
+    }
+
+    /// Reads the device name and version from `target`.
+    pub async fn read_device_info(
+        &self,
+        target: AmsAddr,
+    ) -> crate::Result<(AdsDeviceVersion, String)> {
+        let invoke_id = self.next_invoke_id()?;
+
+        let frame = AdsReadDeviceInfoRequest::new(target, self.source()?, invoke_id).into_frame();
+        let resp =
+            AdsReadDeviceInfoResponse::try_from(self.send_and_wait(frame, invoke_id).await?)?;
+
+        Self::check_result(resp.result())?;
+
+        Ok((resp.version(), resp.device_name().into_owned()))
+    }
+
+    /// Reads the ADS and device state of `target`.
+    pub async fn read_state(&self, target: AmsAddr) -> crate::Result<(AdsState, DeviceState)> {
+        let invoke_id = self.next_invoke_id()?;
+
+        let frame = AdsReadStateRequest::new(target, self.source()?, invoke_id).into_frame();
+        let resp = AdsReadStateResponse::try_from(self.send_and_wait(frame, invoke_id).await?)?;
+
+        Self::check_result(resp.result())?;
+
+        Ok((resp.ads_state(), resp.device_state()))
+    }
+
+    /// Changes the ADS and device state of `target`.
+    pub async fn write_control(
+        &self,
+        target: AmsAddr,
+        ads_state: AdsState,
+        device_state: DeviceState,
+        data: impl Into<Vec<u8>>,
+    ) -> crate::Result<()> {
+        let invoke_id = self.next_invoke_id()?;
+
+        let frame = AdsWriteControlRequestOwned::with_data(
+            target,
+            self.source()?,
+            invoke_id,
+            ads_state,
+            device_state,
+            data,
+        )
+        .into_frame();
+        let resp = AdsWriteControlResponse::try_from(&self.send_and_wait(frame, invoke_id).await?)?;
+
+        Self::check_result(resp.result())?;
+
+        Ok(())
+    }
+
+    /// Reads `length` of bytes from `target` at specified a `index_group` and `index_offset`.
+    pub async fn read(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        length: u32,
+    ) -> crate::Result<Vec<u8>> {
+        let invoke_id = self.next_invoke_id()?;
+
+        let frame = AdsReadRequest::new(
+            target,
+            self.source()?,
+            invoke_id,
+            index_group,
+            index_offset,
+            length,
+        )
+        .into_frame();
+        let frame = self.send_and_wait(frame, invoke_id).await?;
+        let resp = AdsReadResponse::try_from_frame(&frame)?;
+
+        Self::check_result(resp.result())?;
+
+        Ok(resp.data().to_vec())
+    }
+
+    /// Writes `data` to `target` at a specified `index_group` and `index_offset`.
+    pub async fn write(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        data: impl Into<Vec<u8>>,
+    ) -> crate::Result<()> {
+        let invoke_id = self.next_invoke_id()?;
+        let frame = AdsWriteRequestOwned::new(
+            target,
+            self.source()?,
+            invoke_id,
+            index_group,
+            index_offset,
+            data,
+        )
+        .into_frame();
+        let resp = AdsWriteResponse::try_from(self.send_and_wait(frame, invoke_id).await?)?;
+
+        Self::check_result(resp.result())?;
+
+        Ok(())
+    }
+
+    /// Reads a value of type `T` from `target` at a specified `index_group` and `index_offset`.
+    ///
+    /// Reads exactly [`T::SIZE`](AdsType::SIZE) bytes and decodes them.
+    pub async fn read_value<T: AdsType>(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+    ) -> crate::Result<T> {
+        let data = self
+            .read(target, index_group, index_offset, T::SIZE as u32)
+            .await?;
+
+        Ok(T::decode(&data)?)
+    }
+
+    /// Reads `count` consecutive values of type `T`, such as an array whose length is only
+    /// known at runtime.
+    pub async fn read_values<T: AdsType>(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        count: usize,
+    ) -> crate::Result<Vec<T>> {
+        let data = self
+            .read(target, index_group, index_offset, (T::SIZE * count) as u32)
+            .await?;
+
+        Ok(Vec::<T>::decode(&data)?)
+    }
+
+    /// Encodes `value` and writes it to `target` at a specified `index_group` and
+    /// `index_offset`.
+    pub async fn write_value<T: AdsEncode + ?Sized>(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        value: &T,
+    ) -> crate::Result<()> {
+        self.write(target, index_group, index_offset, value.to_ads_bytes())
+            .await
+    }
+
+    /// Sends a combined read/write to `target` in a single round trip.
+    ///
+    /// Writes `write_data` then reads `read_length` bytes back.
+    pub async fn read_write(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        read_length: u32,
+        write_data: impl Into<Vec<u8>>,
+    ) -> crate::Result<Vec<u8>> {
+        let invoke_id = self.next_invoke_id()?;
+        let frame = AdsReadWriteRequestOwned::new(
+            target,
+            self.source()?,
+            invoke_id,
+            index_group,
+            index_offset,
+            read_length,
+            write_data,
+        )
+        .into_frame();
+        let frame = self.send_and_wait(frame, invoke_id).await?;
+        let resp = AdsReadWriteResponse::try_from_frame(&frame)?;
+
+        Self::check_result(resp.result())?;
+
+        Ok(resp.data().to_vec())
+    }
+
+    /// Registers a device notification on `target`.
+    ///
+    /// Returns an [`UnboundedReceiver`] for incoming samples and the [`NotificationHandle`]
+    /// assigned by the PLC.
+    ///
+    /// The receiver yields [`None`] after [`delete_notification`](Self::delete_notification)
+    /// is called, or when the router transitions to [`RouterState::Stop`] or [`RouterState::Removed`].
+    ///
+    /// On a reconnecting device the notification is registered again after every
+    /// reconnect and the receiver stays open. The returned handle is then assigned by the
+    /// device, stays the same across reconnects, and differs from the
+    /// [handle of the samples](AdsNotificationSampleOwned::handle).
+    ///
+    /// # Note
+    ///
+    /// The target device may fire an initial sample upon registration.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn add_notification(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        length: u32,
+        trans_mode: AdsTransMode,
+        max_delay: u32,
+        cycle_time: u32,
+    ) -> crate::Result<(
+        UnboundedReceiver<AdsNotificationSampleOwned>,
+        NotificationHandle,
+    )> {
+        let item = SumAddNotificationItem::new(
+            index_group,
+            index_offset,
+            length,
+            trans_mode,
+            max_delay,
+            cycle_time,
+        );
+        let (tx, rx) = mpsc::unbounded_channel();
+        let handle = self
+            .register_notification(target, &item, tx.clone().into())
+            .await?;
+
+        Ok((
+            rx,
+            self.track_notification(target, item, tx.into(), handle)?,
+        ))
+    }
+
+    /// Deletes a device notification on `target`.
+    ///
+    /// The receiver obtained from [`add_notification`](Self::add_notification)
+    /// will yield [`None`] on its next [`recv`](UnboundedReceiver::recv) call.
+    pub async fn delete_notification(
+        &self,
+        target: AmsAddr,
+        handle: NotificationHandle,
+    ) -> crate::Result<()> {
+        let handle = self.untrack_notification(handle)?;
+        let invoke_id = self.next_invoke_id()?;
+
+        let frame =
+            AdsDeleteDeviceNotificationRequest::new(target, self.source()?, invoke_id, handle)
+                .into_frame();
+        let resp = AdsDeleteDeviceNotificationResponse::try_from(
+            self.send_and_wait(frame, invoke_id).await?,
+        )?;
+
+        Self::check_result(resp.result())?;
+
+        self.inner.ads_notifs.remove(handle)
+    }
+
+    /// Registers many device notifications on `target` with
+    /// [`SUMUP_ADDDEVNOTE`](index_group::SUMUP_ADDDEVNOTE) sum commands.
+    ///
+    /// Behaves like calling [`add_notification`](Self::add_notification) for every item, but
+    /// takes one round trip per [`DEFAULT_MAX_ITEMS`] items. Returns one result per item, in
+    /// order; a rejected item does not affect the others.
+    pub async fn add_notifications(
+        &self,
+        target: AmsAddr,
+        items: &[SumAddNotificationItem],
+    ) -> Vec<
+        crate::Result<(
+            UnboundedReceiver<AdsNotificationSampleOwned>,
+            NotificationHandle,
+        )>,
+    > {
+        let mut results = Vec::with_capacity(items.len());
+        for chunk in items.chunks(DEFAULT_MAX_ITEMS) {
+            results.extend(self.add_notifications_chunk(target, chunk).await);
+        }
+        results
+    }
+
+    /// Deletes many device notifications on `target` with
+    /// [`SUMUP_DELDEVNOTE`](index_group::SUMUP_DELDEVNOTE) sum commands.
+    ///
+    /// Returns one result per handle, in order.
+    pub async fn delete_notifications(
+        &self,
+        target: AmsAddr,
+        handles: &[NotificationHandle],
+    ) -> Vec<crate::Result<()>> {
+        let handles: Vec<_> = match handles
+            .iter()
+            .map(|&handle| self.untrack_notification(handle))
+            .collect::<crate::Result<_>>()
+        {
+            Ok(handles) => handles,
+            Err(e) => return handles.iter().map(|_| Err(e.clone())).collect(),
+        };
+
+        let mut results = Vec::with_capacity(handles.len());
+        for chunk in handles.chunks(DEFAULT_MAX_ITEMS) {
+            let items: Vec<_> = chunk
+                .iter()
+                .copied()
+                .map(SumDeleteNotificationItem::new)
+                .collect();
+            let request = SumDeleteNotificationItem::encode_request(&items);
+            let response = self.send_sum(target, request).await.and_then(|data| {
+                Ok(SumDeleteNotificationItem::parse_response(
+                    items.len(),
+                    &data,
+                )?)
+            });
+
+            match response {
+                Ok(codes) => results.extend(chunk.iter().zip(codes).map(|(handle, code)| {
+                    code?;
+                    self.inner.ads_notifs.remove(*handle)
+                })),
+                Err(e) => results.extend(chunk.iter().map(|_| Err(e.clone()))),
+            }
+        }
+        results
+    }
+
+    /// Subscribes to `source` on `target` and decodes every sample into `T`.
+    ///
+    /// A [symbol](NotificationSource::Symbol) is resolved to its address and size first.
+    /// The returned [`Subscription`] yields every value together with the timestamp the
+    /// PLC stamped it with, and deletes the notification when dropped.
+    ///
+    /// Otherwise behaves like [`add_notification`](Self::add_notification), including on
+    /// a reconnecting device.
+    pub async fn subscribe<T: AdsDecode>(
+        &self,
+        target: AmsAddr,
+        source: impl Into<NotificationSource>,
+        options: SubscriptionOptions,
+    ) -> crate::Result<Subscription<T>> {
+        let (index_group, index_offset, length) = match source.into() {
+            NotificationSource::Symbol(name) => {
+                let entry = self.read_symbol_info(target, &name).await?;
+                (entry.index_group(), entry.index_offset(), entry.size())
+            }
+            NotificationSource::Address {
+                index_group,
+                index_offset,
+                length,
+            } => (index_group, index_offset, length),
+        };
+        let item = SumAddNotificationItem::new(
+            index_group,
+            index_offset,
+            length,
+            options.trans_mode,
+            options.max_delay_ms(),
+            options.cycle_time_ms(),
+        );
+        let (tx, rx) = mpsc::unbounded_channel();
+        let handle = self
+            .register_notification(target, &item, tx.clone().into())
+            .await?;
+        let handle = self.track_notification(target, item, tx.into(), handle)?;
+
+        Ok(Subscription::new(self.clone(), target, handle, rx))
+    }
+
+    /// Starts a [`Batch`] of reads, writes and read/writes on `target`.
+    ///
+    /// The queued operations are sent as ADS sum commands, so many values can be
+    /// accessed in a handful of round trips.
+    pub fn batch(&self, target: AmsAddr) -> Batch {
+        Batch::new(self.clone(), target)
+    }
+
+    /// Reads the symbol information of `name` from `target`.
+    pub async fn read_symbol_info(
+        &self,
+        target: AmsAddr,
+        name: &str,
+    ) -> crate::Result<AdsSymbolEntry> {
+        let data = self
+            .read_write(
+                target,
+                index_group::SYM_INFOBYNAMEEX,
+                0,
+                u16::MAX as u32,
+                encode_symbol_name(name)?,
+            )
+            .await?;
+
+        Ok(AdsSymbolEntry::try_from(data.as_slice())?)
+    }
+
+    /// Acquires a [`SymbolHandle`] for the variable `name` on `target`.
+    ///
+    /// Reads the symbol information first, so that the handle knows the symbol size,
+    /// then requests the handle. Both round trips happen up front; every read or write
+    /// through the handle is a single round trip afterwards.
+    pub async fn symbol_handle(&self, target: AmsAddr, name: &str) -> crate::Result<SymbolHandle> {
+        let entry = self.read_symbol_info(target, name).await?;
+        let data = self
+            .read_write(
+                target,
+                index_group::SYM_HNDBYNAME,
+                0,
+                size_of::<u32>() as u32,
+                encode_symbol_name(name)?,
+            )
+            .await?;
+        let handle = Arc::new(AtomicU32::new(u32::decode(&data)?));
+        let key = match &self.inner.reconnect {
+            Some(reconnect) => Some(reconnect.track_handle(target, name, Arc::clone(&handle))?),
+            None => None,
+        };
+
+        Ok(SymbolHandle::new(self.clone(), target, handle, key, entry))
+    }
+
+    /// Releases a raw symbol handle on `target`.
+    ///
+    /// Only needed for handles obtained without [`symbol_handle`](Self::symbol_handle);
+    /// a [`SymbolHandle`] releases itself.
+    pub async fn release_handle(&self, target: AmsAddr, handle: u32) -> crate::Result<()> {
+        self.write(target, index_group::SYM_RELEASEHND, 0, handle.to_le_bytes())
+            .await
+    }
+
+    /// Acquires raw symbol handles for many `names` on `target` in one
+    /// [`SUMUP_READWRITE`](index_group::SUMUP_READWRITE) sum command per chunk.
+    ///
+    /// Returns one result per name, in order. The handles must be released with
+    /// [`release_handles`](Self::release_handles) or [`release_handle`](Self::release_handle).
+    pub async fn handles_by_name(
+        &self,
+        target: AmsAddr,
+        names: &[impl AsRef<str>],
+    ) -> Vec<crate::Result<u32>> {
+        let encoded: Vec<_> = names
+            .iter()
+            .map(|name| encode_symbol_name(name.as_ref()))
+            .collect();
+        let batch = encoded
+            .iter()
+            .flatten()
+            .fold(self.batch(target), |batch, name| {
+                batch.read_write(
+                    index_group::SYM_HNDBYNAME,
+                    0,
+                    size_of::<u32>() as u32,
+                    name.as_slice(),
+                )
+            });
+        let mut handles = batch.execute().await.into_iter();
+
+        encoded
+            .into_iter()
+            .map(|name| {
+                name?;
+                let data = handles.next().unwrap_or(Err(crate::Error::Disconnected))?;
+                Ok(u32::decode(&data)?)
+            })
+            .collect()
+    }
+
+    /// Releases many raw symbol handles on `target` in one
+    /// [`SUMUP_WRITE`](index_group::SUMUP_WRITE) sum command per chunk.
+    ///
+    /// Returns one result per handle, in order.
+    pub async fn release_handles(
+        &self,
+        target: AmsAddr,
+        handles: &[u32],
+    ) -> Vec<crate::Result<()>> {
+        handles
+            .iter()
+            .fold(self.batch(target), |batch, handle| {
+                batch.write(index_group::SYM_RELEASEHND, 0, handle.to_le_bytes())
+            })
+            .execute()
+            .await
+            .into_iter()
+            .map(|result| result.map(|_| ()))
+            .collect()
+    }
+
+    /// Stops re-acquiring the symbol handle tracked under `key` after reconnects.
+    pub(crate) fn untrack_handle(&self, key: Option<u64>) -> crate::Result<()> {
+        match (&self.inner.reconnect, key) {
+            (Some(reconnect), Some(key)) => reconnect.forget_handle(key),
+            _ => Ok(()),
+        }
+    }
+
+    /// Queues the deletion of a device notification without waiting for the response.
+    pub(crate) fn delete_notification_nowait(
+        &self,
+        target: AmsAddr,
+        handle: NotificationHandle,
+    ) -> crate::Result<()> {
+        let handle = self.untrack_notification(handle)?;
+        self.inner.ads_notifs.remove(handle)?;
+        let invoke_id = self.next_invoke_id()?;
+        let frame =
+            AdsDeleteDeviceNotificationRequest::new(target, self.source()?, invoke_id, handle)
+                .into_frame();
+
+        self.inner
+            .ams_requests
+            .dispatch_detached(AmsRequestDispatchKey::AdsCommand(invoke_id), frame)
+    }
+
+    /// Queues a handle release without waiting for the response.
+    pub(crate) fn release_handle_nowait(&self, target: AmsAddr, handle: u32) -> crate::Result<()> {
+        let invoke_id = self.next_invoke_id()?;
+        let frame = AdsWriteRequestOwned::new(
+            target,
+            self.source()?,
+            invoke_id,
+            index_group::SYM_RELEASEHND,
+            0,
+            handle.to_le_bytes(),
+        )
+        .into_frame();
+
+        self.inner
+            .ams_requests
+            .dispatch_detached(AmsRequestDispatchKey::AdsCommand(invoke_id), frame)
+    }
+
+    /// Reads the symbol and data type table sizes of `target`.
+    pub async fn read_symbol_upload_info(
+        &self,
+        target: AmsAddr,
+    ) -> crate::Result<AdsSymbolUploadInfo> {
+        let data = self
+            .read(
+                target,
+                index_group::SYM_UPLOADINFO2,
+                0,
+                AdsSymbolUploadInfo::LENGTH as u32,
+            )
+            .await?;
+
+        Ok(AdsSymbolUploadInfo::try_from_slice(&data)?)
+    }
+
+    /// Uploads and parses the symbol table of `target`.
+    pub async fn upload_symbols(&self, target: AmsAddr) -> crate::Result<Vec<AdsSymbolEntry>> {
+        let info = self.read_symbol_upload_info(target).await?;
+        self.upload_symbols_with_length(target, info.symbol_length())
+            .await
+    }
+
+    /// Uploads and parses the data type table of `target`.
+    pub async fn upload_datatypes(&self, target: AmsAddr) -> crate::Result<Vec<AdsDatatypeEntry>> {
+        let info = self.read_symbol_upload_info(target).await?;
+        self.upload_datatypes_with_length(target, info.datatype_length())
+            .await
+    }
+
+    /// Uploads the symbol and data type tables of `target` into a browsable [`SymbolTable`].
+    pub async fn upload_symbol_table(&self, target: AmsAddr) -> crate::Result<SymbolTable> {
+        let info = self.read_symbol_upload_info(target).await?;
+        let symbols = self
+            .upload_symbols_with_length(target, info.symbol_length())
+            .await?;
+        let datatypes = self
+            .upload_datatypes_with_length(target, info.datatype_length())
+            .await?;
+
+        Ok(SymbolTable::new(symbols, datatypes))
+    }
+
+    /// Reads the symbol `name` and decodes it into an [`AdsValue`] using the data types in
+    /// `table`.
+    pub async fn read_symbol_value(
+        &self,
+        target: AmsAddr,
+        table: &SymbolTable,
+        name: &str,
+    ) -> crate::Result<AdsValue> {
+        let symbol = table
+            .symbol(name)
+            .ok_or_else(|| ValueError::UnknownSymbol(name.to_string()))?;
+        let data = self
+            .read(
+                target,
+                symbol.index_group(),
+                symbol.index_offset(),
+                symbol.size(),
+            )
+            .await?;
+
+        Ok(table.decode_symbol(symbol, &data)?)
+    }
+
+    /// Encodes `value` using the data types in `table` and writes it to the symbol `name`.
+    pub async fn write_symbol_value(
+        &self,
+        target: AmsAddr,
+        table: &SymbolTable,
+        name: &str,
+        value: &AdsValue,
+    ) -> crate::Result<()> {
+        let symbol = table
+            .symbol(name)
+            .ok_or_else(|| ValueError::UnknownSymbol(name.to_string()))?;
+        let data = table.encode_symbol(symbol, value)?;
+
+        self.write(target, symbol.index_group(), symbol.index_offset(), data)
+            .await
+    }
+
+    async fn upload_symbols_with_length(
+        &self,
+        target: AmsAddr,
+        length: u32,
+    ) -> crate::Result<Vec<AdsSymbolEntry>> {
+        let data = self
+            .read(target, index_group::SYM_UPLOAD, 0, length)
+            .await?;
+        Ok(AdsSymbolEntry::parse_table(&data)?)
+    }
+
+    async fn upload_datatypes_with_length(
+        &self,
+        target: AmsAddr,
+        length: u32,
+    ) -> crate::Result<Vec<AdsDatatypeEntry>> {
+        let data = self
+            .read(target, index_group::SYM_DT_UPLOAD, 0, length)
+            .await?;
+        Ok(AdsDatatypeEntry::parse_table(&data)?)
+    }
+
+    async fn add_notifications_chunk(
+        &self,
+        target: AmsAddr,
+        items: &[SumAddNotificationItem],
+    ) -> Vec<
+        crate::Result<(
+            UnboundedReceiver<AdsNotificationSampleOwned>,
+            NotificationHandle,
+        )>,
+    > {
+        // Every item is pre-registered under its own invoke ID from a reserved block, so
+        // samples can be routed per item as soon as its handle is known.
+        let first = match self.next_invoke_ids(items.len()) {
+            Ok(first) => first,
+            Err(e) => return items.iter().map(|_| Err(e.clone())).collect(),
+        };
+        let invoke_ids: Vec<InvokeId> = (0..items.len() as u32)
+            .map(|i| first.wrapping_add(i))
+            .collect();
+        let (senders, receivers): (Vec<_>, Vec<_>) =
+            items.iter().map(|_| mpsc::unbounded_channel()).unzip();
+        for (&invoke_id, tx) in invoke_ids.iter().zip(&senders) {
+            if let Err(e) = self
+                .inner
+                .ads_notifs
+                .pre_register_with(invoke_id, tx.clone())
+            {
+                for &invoke_id in &invoke_ids {
+                    let _ = self.inner.ads_notifs.abandon(invoke_id);
+                }
+                return items.iter().map(|_| Err(e.clone())).collect();
+            }
+        }
+
+        let request = SumAddNotificationItem::encode_request(items);
+        let response = self
+            .send_sum(target, request)
+            .await
+            .and_then(|data| Ok(SumAddNotificationItem::parse_response(items.len(), &data)?));
+
+        match response {
+            Ok(handles) => invoke_ids
+                .into_iter()
+                .zip(items)
+                .zip(senders.into_iter().zip(receivers))
+                .zip(handles)
+                .map(|(((invoke_id, item), (tx, rx)), handle)| match handle {
+                    Ok(handle) => {
+                        self.inner.ads_notifs.promote(invoke_id, handle)?;
+                        Ok((
+                            rx,
+                            self.track_notification(target, *item, tx.into(), handle)?,
+                        ))
+                    }
+                    Err(code) => {
+                        self.inner.ads_notifs.abandon(invoke_id)?;
+                        Err(code.into())
+                    }
+                })
+                .collect(),
+            Err(e) => invoke_ids
+                .into_iter()
+                .map(|invoke_id| {
+                    self.inner.ads_notifs.abandon(invoke_id)?;
+                    Err(e.clone())
+                })
+                .collect(),
+        }
+    }
+
+    /// Adds the notification described by `item` on `target`, routing its samples to
+    /// `sender`, and returns the handle assigned by the PLC.
+    async fn register_notification(
+        &self,
+        target: AmsAddr,
+        item: &SumAddNotificationItem,
+        sender: NotificationSender,
+    ) -> crate::Result<NotificationHandle> {
+        let invoke_id = self.next_invoke_id()?;
+
+        self.inner.ads_notifs.pre_register_with(invoke_id, sender)?;
+
+        let result = async {
+            let frame = AdsAddDeviceNotificationRequest::new(
+                target,
+                self.source()?,
+                invoke_id,
+                item.index_group(),
+                item.index_offset(),
+                item.length(),
+                item.trans_mode(),
+                item.max_delay(),
+                item.cycle_time(),
+            )
+            .into_frame();
+            let resp = AdsAddDeviceNotificationResponse::try_from(
+                self.send_and_wait(frame, invoke_id).await?,
+            )?;
+
+            Self::check_result(resp.result())?;
+
+            Ok(resp.handle())
+        }
+        .await;
+
+        let handle = match result {
+            Ok(handle) => handle,
+            Err(e) => {
+                self.inner.ads_notifs.abandon(invoke_id)?;
+                return Err(e);
+            }
+        };
+        self.inner.ads_notifs.promote(invoke_id, handle)?;
+
+        Ok(handle)
+    }
+
+    /// Tracks a notification on a reconnecting device and returns the handle to give to
+    /// the caller, which is the PLC `handle` otherwise.
+    fn track_notification(
+        &self,
+        target: AmsAddr,
+        item: SumAddNotificationItem,
+        sender: NotificationSender,
+        handle: NotificationHandle,
+    ) -> crate::Result<NotificationHandle> {
+        match &self.inner.reconnect {
+            Some(reconnect) => reconnect.track_notification(target, item, sender, handle),
+            None => Ok(handle),
+        }
+    }
+
+    /// Stops tracking the notification the caller knows as `handle` and returns the
+    /// handle assigned by the PLC.
+    fn untrack_notification(
+        &self,
+        handle: NotificationHandle,
+    ) -> crate::Result<NotificationHandle> {
+        let Some(reconnect) = &self.inner.reconnect else {
+            return Ok(handle);
+        };
+        let current = reconnect.notification_handle(handle)?;
+        reconnect.forget_notification(handle)?;
+
+        Ok(current.unwrap_or(handle))
+    }
+
+    async fn send_sum(&self, target: AmsAddr, request: SumRequest) -> crate::Result<Vec<u8>> {
+        self.read_write(
+            target,
+            request.index_group(),
+            request.count(),
+            request.read_length(),
+            request.into_data(),
+        )
+        .await
+    }
+
+    async fn port_connect(&self) -> crate::Result<AmsAddr> {
+        let frame = PortConnectRequest::default().into_frame();
+        let resp = PortConnectResponse::try_from(
+            self.request(AmsRequestDispatchKey::PortConnect, frame)
+                .await?,
+        )?;
+
+        Ok(*resp.addr())
+    }
+
+    async fn send_and_wait(&self, frame: AmsFrame, invoke_id: InvokeId) -> crate::Result<AmsFrame> {
+        self.request(AmsRequestDispatchKey::AdsCommand(invoke_id), frame)
+            .await
+    }
+
+    /// Dispatches `frame` and waits for the response under `key`, for as long as the
+    /// [options](Self::with_options) of this handle allow.
+    async fn request(
+        &self,
+        key: AmsRequestDispatchKey,
+        frame: AmsFrame,
+    ) -> crate::Result<AmsFrame> {
+        self.options.check()?;
+        let rx = self.inner.ams_requests.dispatch(key, frame)?;
+        let _purge = Purge {
+            requests: &self.inner.ams_requests,
+            key,
+        };
+        let _cancel = self.options.cancellation().map(|token| {
+            let requests = Arc::downgrade(&self.inner.ams_requests);
+            token.on_cancel(move || {
+                if let Some(requests) = requests.upgrade() {
+                    let _ = requests.cancel(key);
+                }
+            })
+        });
+
+        let result = match self.options.wait_limit(self.inner.timeout) {
+            Some(duration) => match tokio::time::timeout(duration, rx).await {
+                Ok(response) => response.map_err(crate::Error::from),
+                Err(elapsed) => Err(elapsed.into()),
+            },
+            None => rx.await.map_err(crate::Error::from),
+        };
+        match result {
+            Err(crate::Error::Disconnected) if self.options.is_cancelled() => {
+                Err(crate::Error::Cancelled)
+            }
+            result => result,
+        }
+    }
+
+    fn next_invoke_id(&self) -> crate::Result<InvokeId> {
+        self.inner.invoke_ids.next(|ids| self.in_flight(ids))
+    }
+
+    /// Allocates `count` consecutive invoke IDs and returns the first.
+    fn next_invoke_ids(&self, count: usize) -> crate::Result<InvokeId> {
+        self.inner
+            .invoke_ids
+            .next_block(count as u32, |ids| self.in_flight(ids))
+    }
+
+    /// Returns `true` if a request or notification is still waiting under any of `ids`.
+    fn in_flight(&self, ids: Range<InvokeId>) -> bool {
+        let requests = self.inner.ams_requests.in_flight(ids.clone());
+        let notifications = self.inner.ads_notifs.in_flight(ids);
+        // A poisoned lock is reported once the ID is used.
+        requests.unwrap_or(false) || notifications.unwrap_or(false)
+    }
+
+    fn check_result(code: AdsReturnCode) -> crate::Result<()> {
+        match code {
+            AdsReturnCode::Ok => Ok(()),
+            code => Err(code.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::request::CancellationToken;
+    use std::sync::atomic::AtomicBool;
+    use tcads_core::{AdsCommand, AdsHeader, WindowsFileTime};
+    use tokio::net::TcpListener;
+
+    /// Accepts one connection, answers a single `ReadState` request, then closes.
+    async fn spawn_mock_router() -> std::net::SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut stream = AmsStream::new(socket);
+
+            let frame = stream.read_frame().await.unwrap();
+            let req = AdsReadStateRequest::try_from(&frame).unwrap();
+            let header = req.header();
+
+            let resp = AdsReadStateResponse::new(
+                *header.source(),
+                *header.target(),
+                header.invoke_id(),
+                AdsReturnCode::Ok,
+                AdsState::Run,
+                0,
+            )
+            .into_frame();
+            stream.write_frame(&resp).await.unwrap();
+        });
+
+        addr
+    }
+
+    #[tokio::test]
+    async fn read_state_round_trip() {
+        let addr = spawn_mock_router().await;
+        let source = "127.0.0.1.1.1:32768".parse().unwrap();
+        let target = "127.0.0.1.1.1:851".parse().unwrap();
+
+        let device = AdsDevice::connect_with_source(addr, source, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        let (ads_state, device_state) = device.read_state(target).await.unwrap();
+
+        assert_eq!(ads_state, AdsState::Run);
+        assert_eq!(device_state, 0);
+    }
+
+    #[cfg(feature = "tls")]
+    #[tokio::test]
+    async fn read_state_over_tls() {
+        use rustls::ServerConfig;
+        use rustls::pki_types::pem::PemObject;
+        use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+        use tokio_rustls::TlsAcceptor;
+
+        let ca = include_bytes!("../../../../tcads-core/src/io/testdata/ca.pem");
+        let cert = include_bytes!("../../../../tcads-core/src/io/testdata/server.pem");
+        let key = include_bytes!("../../../../tcads-core/src/io/testdata/server.key");
+
+        let server =
+            ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .unwrap()
+                .with_no_client_auth()
+                .with_single_cert(
+                    vec![CertificateDer::from_pem_slice(cert).unwrap()],
+                    PrivateKeyDer::from_pem_slice(key).unwrap(),
+                )
+                .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let tls = TlsAcceptor::from(Arc::new(server))
+                .accept(socket)
+                .await
+                .unwrap();
+            let mut stream = AmsStream::new(tls);
+            let frame = stream.read_frame().await.unwrap();
+            let req = AdsReadStateRequest::try_from(&frame).unwrap();
+            let header = req.header();
+            let resp = AdsReadStateResponse::new(
+                *header.source(),
+                *header.target(),
+                header.invoke_id(),
+                AdsReturnCode::Ok,
+                AdsState::Config,
+                3,
+            );
+            stream.write_frame(&resp.into_frame()).await.unwrap();
+        });
+
+        let config = TlsConfig::shared_ca(ca).unwrap();
+        let source = "127.0.0.1.1.1:32768".parse().unwrap();
+        let device = AdsDevice::connect_tls_with_source(addr, source, &config, None)
+            .await
+            .unwrap();
+        let state = device
+            .read_state("127.0.0.1.1.1:851".parse().unwrap())
+            .await;
+        assert_eq!(state.unwrap(), (AdsState::Config, 3));
+    }
+
+    /// Accepts one connection and serves reads and writes against 64 bytes of memory,
+    /// addressed by index offset.
+    async fn spawn_memory_router() -> std::net::SocketAddr {
+        use tcads_core::protocol::{AdsReadResponseOwned, AdsWriteRequest};
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut stream = AmsStream::new(socket);
+            let mut memory = [0u8; 64];
+
+            while let Ok(frame) = stream.read_frame().await {
+                let resp = if let Ok(req) = AdsReadRequest::try_from(&frame) {
+                    let header = req.header();
+                    let start = req.index_offset() as usize;
+                    let data = &memory[start..start + req.length() as usize];
+                    AdsReadResponseOwned::new(
+                        *header.source(),
+                        *header.target(),
+                        header.invoke_id(),
+                        AdsReturnCode::Ok,
+                        data,
+                    )
+                    .into_frame()
+                } else {
+                    let req = AdsWriteRequest::try_from(&frame).unwrap();
+                    let header = req.header();
+                    let start = req.index_offset() as usize;
+                    memory[start..start + req.data().len()].copy_from_slice(req.data());
+                    AdsWriteResponse::new(
+                        *header.source(),
+                        *header.target(),
+                        header.invoke_id(),
+                        AdsReturnCode::Ok,
+                    )
+                    .into_frame()
+                };
+                stream.write_frame(&resp).await.unwrap();
+            }
+        });
+
+        addr
+    }
+
+    #[tokio::test]
+    async fn typed_values_round_trip() {
+        let addr = spawn_memory_router().await;
+        let source = "127.0.0.1.1.1:32768".parse().unwrap();
+        let target = "127.0.0.1.1.1:851".parse().unwrap();
+        let device = AdsDevice::connect_with_source(addr, source, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+
+        device
+            .write_value(target, 0x4020, 0, &-1.5f64)
+            .await
+            .unwrap();
+        device
+            .write_value(target, 0x4020, 8, &[7i16, 8, 9][..])
+            .await
+            .unwrap();
+
+        let value: f64 = device.read_value(target, 0x4020, 0).await.unwrap();
+        let array: [i16; 3] = device.read_value(target, 0x4020, 8).await.unwrap();
+        let values: Vec<i16> = device.read_values(target, 0x4020, 8, 2).await.unwrap();
+
+        assert_eq!(value, -1.5);
+        assert_eq!(array, [7, 8, 9]);
+        assert_eq!(values, [7, 8]);
+    }
+
+    /// Accepts one connection and serves sum add/delete notification commands. Item
+    /// offsets of `0xFF` are rejected, every other item gets handle `100 + offset`. One
+    /// sample per handle, carrying its offset, is sent ahead of the delete response.
+    async fn spawn_sum_router() -> std::net::SocketAddr {
+        use tcads_core::WindowsFileTime;
+        use tcads_core::protocol::{
+            AdsDeviceNotificationOwned, AdsReadWriteRequest, AdsReadWriteResponseOwned,
+            AdsStampHeaderOwned,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut stream = AmsStream::new(socket);
+
+            while let Ok(frame) = stream.read_frame().await {
+                let req = AdsReadWriteRequest::try_from(&frame).unwrap();
+                let header = req.header();
+                let (target, source) = (*header.source(), *header.target());
+                let data = match req.index_group() {
+                    index_group::SUMUP_ADDDEVNOTE => {
+                        let items =
+                            SumAddNotificationItem::parse_request(req.index_offset(), req.data())
+                                .unwrap();
+                        let results: Vec<_> = items
+                            .iter()
+                            .map(|item| match item.index_offset() {
+                                0xFF => Err(AdsReturnCode::AdsErrDeviceSymbolNotFound),
+                                offset => Ok(NotificationHandle::new(100 + offset)),
+                            })
+                            .collect();
+                        SumAddNotificationItem::encode_response(&results)
+                    }
+                    index_group::SUMUP_DELDEVNOTE => {
+                        let items = SumDeleteNotificationItem::parse_request(
+                            req.index_offset(),
+                            req.data(),
+                        )
+                        .unwrap();
+                        let samples: Vec<_> = items
+                            .iter()
+                            .map(|item| {
+                                let handle = item.handle();
+                                let offset = handle.as_u32() - 100;
+                                AdsNotificationSampleOwned::new(handle, offset.to_le_bytes())
+                            })
+                            .collect();
+                        let stamp = AdsStampHeaderOwned::new(WindowsFileTime::now(), samples);
+                        let notification =
+                            AdsDeviceNotificationOwned::new(target, source, vec![stamp]);
+                        stream
+                            .write_frame(&notification.into_frame())
+                            .await
+                            .unwrap();
+
+                        SumDeleteNotificationItem::encode_response(&vec![Ok(()); items.len()])
+                    }
+                    group => panic!("unexpected index group {group:#X}"),
+                };
+
+                let resp = AdsReadWriteResponseOwned::new(
+                    target,
+                    source,
+                    header.invoke_id(),
+                    AdsReturnCode::Ok,
+                    data,
+                )
+                .into_frame();
+                stream.write_frame(&resp).await.unwrap();
+            }
+        });
+
+        addr
+    }
+
+    #[tokio::test]
+    async fn add_and_delete_notifications_per_item() {
+        let addr = spawn_sum_router().await;
+        let source = "127.0.0.1.1.1:32768".parse().unwrap();
+        let target = "127.0.0.1.1.1:851".parse().unwrap();
+        let device = AdsDevice::connect_with_source(addr, source, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+
+        let items = [
+            SumAddNotificationItem::new(0x4020, 4, 4, AdsTransMode::ServerOnChange, 0, 100),
+            SumAddNotificationItem::new(0x4020, 0xFF, 4, AdsTransMode::ServerOnChange, 0, 100),
+            SumAddNotificationItem::new(0x4020, 8, 4, AdsTransMode::ServerOnChange, 0, 100),
+        ];
+        let mut results = device.add_notifications(target, &items).await.into_iter();
+
+        let (mut first_rx, first) = results.next().unwrap().unwrap();
+        assert!(results.next().unwrap().is_err());
+        let (mut third_rx, third) = results.next().unwrap().unwrap();
+
+        assert_eq!(first, NotificationHandle::new(104));
+        assert_eq!(third, NotificationHandle::new(108));
+
+        let deleted = device.delete_notifications(target, &[first, third]).await;
+        assert!(deleted.iter().all(Result::is_ok));
+
+        assert_eq!(first_rx.recv().await.unwrap().data(), 4u32.to_le_bytes());
+        assert_eq!(third_rx.recv().await.unwrap().data(), 8u32.to_le_bytes());
+        assert!(first_rx.recv().await.is_none());
+        assert!(third_rx.recv().await.is_none());
+    }
+
+    /// Serves one connection per reboot of a PLC: answers `PortConnect` with port
+    /// `32768 + n` on the `n`th connection, assigns notification handles `100 * n + 1`
+    /// and follows each with a sample carrying `n`. The first connection is dropped
+    /// after its sample, deleted handles are reported.
+    async fn spawn_rebooting_router()
+    -> (std::net::SocketAddr, UnboundedReceiver<NotificationHandle>) {
+        use tcads_core::WindowsFileTime;
+        use tcads_core::protocol::{AdsDeviceNotificationOwned, AdsStampHeaderOwned};
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (deleted_tx, deleted_rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            for n in 0u16.. {
+                let (socket, _) = listener.accept().await.unwrap();
+                let mut stream = AmsStream::new(socket);
+
+                while let Ok(frame) = stream.read_frame().await {
+                    if frame.header().command() == AmsCommand::PortConnect {
+                        let addr = AmsAddr::new("127.0.0.1.1.1".parse().unwrap(), 32768 + n);
+                        let resp = PortConnectResponse::new(addr).into_frame();
+                        stream.write_frame(&resp).await.unwrap();
+                        continue;
+                    }
+
+                    if let Ok(req) = AdsAddDeviceNotificationRequest::try_from(&frame) {
+                        let header = req.header();
+                        let (target, source) = (*header.source(), *header.target());
+                        let handle = NotificationHandle::new(100 * n as u32 + 1);
+                        let resp = AdsAddDeviceNotificationResponse::new(
+                            target,
+                            source,
+                            header.invoke_id(),
+                            AdsReturnCode::Ok,
+                            handle,
+                        );
+                        stream.write_frame(&resp.into_frame()).await.unwrap();
+
+                        // Give the client time to promote the handle before its sample
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        let sample = AdsNotificationSampleOwned::new(handle, [n as u8]);
+                        let stamp = AdsStampHeaderOwned::new(WindowsFileTime::now(), vec![sample]);
+                        let notification =
+                            AdsDeviceNotificationOwned::new(target, source, vec![stamp]);
+                        stream
+                            .write_frame(&notification.into_frame())
+                            .await
+                            .unwrap();
+                        if n == 0 {
+                            break;
+                        }
+                    } else {
+                        let req = AdsDeleteDeviceNotificationRequest::try_from(&frame).unwrap();
+                        let header = req.header();
+                        deleted_tx.send(req.handle()).unwrap();
+                        let resp = AdsDeleteDeviceNotificationResponse::new(
+                            *header.source(),
+                            *header.target(),
+                            header.invoke_id(),
+                            AdsReturnCode::Ok,
+                        );
+                        stream.write_frame(&resp.into_frame()).await.unwrap();
+                    }
+                }
+            }
+        });
+
+        (addr, deleted_rx)
+    }
+
+    #[tokio::test]
+    async fn notifications_survive_reconnect() {
+        use crate::devices::connection::ReconnectPolicy;
+
+        let (addr, mut deleted) = spawn_rebooting_router().await;
+        let target = "127.0.0.1.1.1:851".parse().unwrap();
+        let options = ConnectOptions::new()
+            .with_timeout(Duration::from_secs(1))
+            .with_reconnect(
+                ReconnectPolicy::default().with_initial_delay(Duration::from_millis(10)),
+            );
+
+        let device = AdsDevice::connect_with_options(addr, options)
+            .await
+            .unwrap();
+        assert_eq!(device.source().unwrap().port(), 32768);
+        let mut events = device.subscribe_connection().unwrap();
+
+        let (mut rx, handle) = device
+            .add_notification(target, 0x4020, 0, 1, AdsTransMode::ServerOnChange, 0, 0)
+            .await
+            .unwrap();
+        assert_eq!(rx.recv().await.unwrap().data(), [0]);
+
+        assert!(matches!(
+            events.recv().await,
+            Some(ConnectionEvent::Disconnected)
+        ));
+        assert!(matches!(
+            events.recv().await,
+            Some(ConnectionEvent::Reconnecting { attempt: 1 })
+        ));
+        assert!(matches!(
+            events.recv().await,
+            Some(ConnectionEvent::Reconnected { source }) if source.port() == 32769
+        ));
+
+        let sample = rx.recv().await.unwrap();
+        assert_eq!(sample.handle(), NotificationHandle::new(101));
+        assert_eq!(sample.data(), [1]);
+        assert!(!device.is_closed());
+
+        device.delete_notification(target, handle).await.unwrap();
+        assert_eq!(deleted.recv().await, Some(NotificationHandle::new(101)));
+        assert!(rx.recv().await.is_none());
+    }
+
+    /// Accepts one connection and answers `ReadState` requests while `answer` is set.
+    /// Closes the connection on `PortClose`.
+    async fn spawn_stalling_router(answer: Arc<AtomicBool>) -> std::net::SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut stream = AmsStream::new(socket);
+
+            while let Ok(frame) = stream.read_frame().await {
+                if frame.header().command() == AmsCommand::PortClose {
+                    return;
+                }
+                if !answer.load(Ordering::Acquire) {
+                    continue;
+                }
+                let req = AdsReadStateRequest::try_from(&frame).unwrap();
+                let header = req.header();
+                let resp = AdsReadStateResponse::new(
+                    *header.source(),
+                    *header.target(),
+                    header.invoke_id(),
+                    AdsReturnCode::Ok,
+                    AdsState::Run,
+                    0,
+                )
+                .into_frame();
+                stream.write_frame(&resp).await.unwrap();
+            }
+        });
+
+        addr
+    }
+
+    async fn wait_for_state(
+        rx: &mut watch::Receiver<ConnectionState>,
+        state: ConnectionState,
+    ) -> ConnectionState {
+        let wait = rx.wait_for(|current| *current == state);
+        *tokio::time::timeout(Duration::from_secs(2), wait)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn heartbeat_degrades_and_recovers_connection() {
+        let answer = Arc::new(AtomicBool::new(true));
+        let addr = spawn_stalling_router(Arc::clone(&answer)).await;
+        let target = "127.0.0.1.1.1:10000".parse().unwrap();
+        let heartbeat = Heartbeat::new(target)
+            .with_interval(Duration::from_millis(10))
+            .with_timeout(Duration::from_millis(20))
+            .with_max_timeouts(2);
+        let options = ConnectOptions::new()
+            .with_source("127.0.0.1.1.1:32768".parse().unwrap())
+            .with_heartbeat(heartbeat);
+
+        let device = AdsDevice::connect_with_options(addr, options)
+            .await
+            .unwrap();
+        let mut state = device.watch_state();
+        assert_eq!(*state.borrow_and_update(), ConnectionState::Connected);
+        while device.last_rtt().unwrap().is_none() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        answer.store(false, Ordering::Release);
+        wait_for_state(&mut state, ConnectionState::Degraded).await;
+
+        answer.store(true, Ordering::Release);
+        wait_for_state(&mut state, ConnectionState::Connected).await;
+
+        device.shutdown().unwrap();
+        wait_for_state(&mut state, ConnectionState::Closed).await;
+        assert_eq!(device.connection_state(), ConnectionState::Closed);
+    }
+
+    /// Accepts one connection and answers every `ReadState` request after `delay`.
+    async fn spawn_slow_router(delay: Duration) -> std::net::SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut stream = AmsStream::new(socket);
+
+            while let Ok(frame) = stream.read_frame().await {
+                tokio::time::sleep(delay).await;
+                let req = AdsReadStateRequest::try_from(&frame).unwrap();
+                let header = req.header();
+                let resp = AdsReadStateResponse::new(
+                    *header.source(),
+                    *header.target(),
+                    header.invoke_id(),
+                    AdsReturnCode::Ok,
+                    AdsState::Run,
+                    0,
+                )
+                .into_frame();
+                stream.write_frame(&resp).await.unwrap();
+            }
+        });
+
+        addr
+    }
+
+    #[tokio::test]
+    async fn abandoned_requests_are_purged_and_late_responses_counted() {
+        let addr = spawn_slow_router(Duration::from_millis(100)).await;
+        let source = "127.0.0.1.1.1:32768".parse().unwrap();
+        let target = "127.0.0.1.1.1:851".parse().unwrap();
+        let device = AdsDevice::connect_with_source(addr, source, None)
+            .await
+            .unwrap();
+
+        let hurried =
+            device.with_options(RequestOptions::new().with_timeout(Duration::from_millis(20)));
+        let result = hurried.read_state(target).await;
+        assert!(matches!(result, Err(crate::Error::Timeout)));
+        assert_eq!(device.inner.ams_requests.pending().unwrap(), 0);
+
+        let token = CancellationToken::new();
+        let cancellable =
+            device.with_options(RequestOptions::new().with_cancellation(token.clone()));
+        let call = tokio::spawn({
+            let cancellable = cancellable.clone();
+            async move { cancellable.read_state(target).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        token.cancel();
+        assert!(matches!(call.await.unwrap(), Err(crate::Error::Cancelled)));
+        assert_eq!(device.inner.ams_requests.pending().unwrap(), 0);
+        let result = cancellable.read_state(target).await;
+        assert!(matches!(result, Err(crate::Error::Cancelled)));
+
+        let late = async {
+            while device.late_responses() < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(2), late)
+            .await
+            .unwrap();
+    }
+
+    /// Accepts one connection and serves `MAIN.nCount` (UDINT at `0x4040:0`). Notifications
+    /// on it get handle 7, and a sample of 42 stamped with `stamp` follows each time
+    /// `publish` fires. Deleted handles are reported.
+    async fn spawn_notifying_router(
+        stamp: WindowsFileTime,
+        mut publish: UnboundedReceiver<()>,
+    ) -> (std::net::SocketAddr, UnboundedReceiver<NotificationHandle>) {
+        use tcads_core::protocol::{
+            AdsDeviceNotificationOwned, AdsReadWriteRequest, AdsReadWriteResponseOwned,
+            AdsStampHeaderOwned,
+        };
+        use tcads_core::symbol::AdsDataTypeId;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (deleted_tx, deleted_rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut stream = AmsStream::new(socket);
+            let entry =
+                AdsSymbolEntry::new("MAIN.nCount", "UDINT", AdsDataTypeId::UInt32, 0x4040, 0, 4);
+
+            while let Ok(frame) = stream.read_frame().await {
+                let (header, _) = AdsHeader::parse_prefix(frame.payload()).unwrap();
+                let (target, source, id) = (*header.source(), *header.target(), header.invoke_id());
+
+                match header.command_id() {
+                    AdsCommand::AdsReadWrite => {
+                        let req = AdsReadWriteRequest::try_from(&frame).unwrap();
+                        assert_eq!(req.index_group(), index_group::SYM_INFOBYNAMEEX);
+                        let resp = AdsReadWriteResponseOwned::new(
+                            target,
+                            source,
+                            id,
+                            AdsReturnCode::Ok,
+                            entry.to_bytes().unwrap(),
+                        );
+                        stream.write_frame(&resp.into_frame()).await.unwrap();
+                    }
+                    AdsCommand::AdsAddDeviceNotification => {
+                        let req = AdsAddDeviceNotificationRequest::try_from(&frame).unwrap();
+                        assert_eq!((req.index_group(), req.length()), (0x4040, 4));
+                        let handle = NotificationHandle::new(7);
+                        let resp = AdsAddDeviceNotificationResponse::new(
+                            target,
+                            source,
+                            id,
+                            AdsReturnCode::Ok,
+                            handle,
+                        );
+                        stream.write_frame(&resp.into_frame()).await.unwrap();
+
+                        publish.recv().await.unwrap();
+                        let sample = AdsNotificationSampleOwned::new(handle, 42u32.to_le_bytes());
+                        let stamp = AdsStampHeaderOwned::new(stamp, vec![sample]);
+                        let notification =
+                            AdsDeviceNotificationOwned::new(target, source, vec![stamp]);
+                        stream
+                            .write_frame(&notification.into_frame())
+                            .await
+                            .unwrap();
+                    }
+                    AdsCommand::AdsDeleteDeviceNotification => {
+                        let req = AdsDeleteDeviceNotificationRequest::try_from(&frame).unwrap();
+                        deleted_tx.send(req.handle()).unwrap();
+                        let resp = AdsDeleteDeviceNotificationResponse::new(
+                            target,
+                            source,
+                            id,
+                            AdsReturnCode::Ok,
+                        );
+                        stream.write_frame(&resp.into_frame()).await.unwrap();
+                    }
+                    command => panic!("unexpected command {command:?}"),
+                }
+            }
+        });
+
+        (addr, deleted_rx)
+    }
+
+    #[tokio::test]
+    async fn subscriptions_decode_samples_and_delete_on_drop() {
+        let stamp = WindowsFileTime::from_raw(133_000_000_000_000_000);
+        let (publish, publish_rx) = mpsc::unbounded_channel();
+        let (addr, mut deleted) = spawn_notifying_router(stamp, publish_rx).await;
+        let source = "127.0.0.1.1.1:32768".parse().unwrap();
+        let target = "127.0.0.1.1.1:851".parse().unwrap();
+        let device = AdsDevice::connect_with_source(addr, source, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+
+        let mut count = device
+            .subscribe::<u32>(target, "MAIN.nCount", SubscriptionOptions::new())
+            .await
+            .unwrap();
+        assert_eq!(count.handle(), NotificationHandle::new(7));
+        publish.send(()).unwrap();
+        assert_eq!(count.recv().await.unwrap().unwrap(), (stamp, 42));
+
+        drop(count);
+        assert_eq!(deleted.recv().await, Some(NotificationHandle::new(7)));
+
+        let mut wide = device
+            .subscribe::<u64>(
+                target,
+                NotificationSource::address(0x4040, 0, 4),
+                SubscriptionOptions::new(),
+            )
+            .await
+            .unwrap();
+        publish.send(()).unwrap();
+        assert!(wide.recv().await.unwrap().is_err());
+        wide.unsubscribe().await.unwrap();
+        // The response to the deletion on drop arrived first and was not counted as late
+        assert_eq!(device.late_responses(), 0);
+        assert_eq!(deleted.recv().await, Some(NotificationHandle::new(7)));
+    }
+}
@@ -0,0 +1,139 @@
+use super::{BatchPlan, collect_results};
+use crate::devices::blocking::AdsDevice;
+use tcads_core::protocol::{SumReadItem, SumReadWriteItem, SumWriteItem};
+use tcads_core::{AmsAddr, IndexGroup, IndexOffset};
+
+/// A batch of reads, writes and read/writes sent as ADS sum commands.
+///
+/// Obtained from [`AdsDevice::batch`]. Operations are queued with the builder methods and
+/// sent by [`execute`](Self::execute), which combines consecutive operations of the same kind
+/// into [`SUMUP_READ`](crate::index_group::SUMUP_READ),
+/// [`SUMUP_WRITE`](crate::index_group::SUMUP_WRITE) and
+/// [`SUMUP_READWRITE`](crate::index_group::SUMUP_READWRITE) commands. The commands are sent
+/// one after another in the order the operations were queued, so a write queued before a read
+/// of the same variable is applied before that read. A large batch is split into several sum
+/// commands so that none exceeds [`max_items`](Self::max_items) sub-requests or
+/// [`max_bytes`](Self::max_bytes) of request or response data.
+///
+/// # Example
+///
+/// ```no_run
+/// use tcads_client::AmsAddr;
+/// use tcads_client::devices::blocking::AdsDevice;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let device = AdsDevice::connect(None)?;
+/// let target = AmsAddr::new(device.get_local_net_id()?, 851);
+///
+/// let results = device
+///     .batch(target)
+///     .read(0x4020, 0, 4)
+///     .write(0x4020, 4, 42u32.to_le_bytes())
+///     .execute();
+///
+/// for result in results {
+///     println!("{:?}", result?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Batch {
+    device: AdsDevice,
+    target: AmsAddr,
+    plan: BatchPlan,
+}
+
+impl Batch {
+    pub(crate) fn new(device: AdsDevice, target: AmsAddr) -> Self {
+        Self {
+            device,
+            target,
+            plan: BatchPlan::default(),
+        }
+    }
+
+    /// Queues a read of `length` bytes.
+    pub fn read(mut self, index_group: IndexGroup, index_offset: IndexOffset, length: u32) -> Self {
+        self.plan
+            .read(SumReadItem::new(index_group, index_offset, length));
+        self
+    }
+
+    /// Queues a write of `data`. Its result is an empty buffer on success.
+    pub fn write(
+        mut self,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        data: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.plan
+            .write(SumWriteItem::new(index_group, index_offset, data));
+        self
+    }
+
+    /// Queues a read/write that writes `data` and reads up to `read_length` bytes.
+    pub fn read_write(
+        mut self,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        read_length: u32,
+        data: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.plan.read_write(SumReadWriteItem::new(
+            index_group,
+            index_offset,
+            read_length,
+            data,
+        ));
+        self
+    }
+
+    /// Sets the maximum number of sub-requests per sum command.
+    ///
+    /// Defaults to [`DEFAULT_MAX_ITEMS`](super::DEFAULT_MAX_ITEMS).
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        self.plan.set_max_items(max_items);
+        self
+    }
+
+    /// Sets the maximum request or response data size per sum command.
+    ///
+    /// Defaults to [`DEFAULT_MAX_BYTES`](super::DEFAULT_MAX_BYTES).
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.plan.set_max_bytes(max_bytes);
+        self
+    }
+
+    /// Returns the number of queued operations.
+    pub fn len(&self) -> usize {
+        self.plan.len()
+    }
+
+    /// Returns `true` if no operations are queued.
+    pub fn is_empty(&self) -> bool {
+        self.plan.len() == 0
+    }
+
+    /// Sends the batch and returns one result per operation, in the order they were queued.
+    ///
+    /// A failed sub-request does not affect the others. If a whole sum command fails, every
+    /// operation it carried fails with the same error.
+    pub fn execute(self) -> Vec<crate::Result<Vec<u8>>> {
+        let mut results = vec![None; self.plan.len()];
+
+        for chunk in self.plan.into_chunks() {
+            let request = chunk.request();
+            let response = self.device.read_write(
+                self.target,
+                request.index_group(),
+                request.count(),
+                request.read_length(),
+                request.into_data(),
+            );
+            chunk.complete(response, &mut results);
+        }
+
+        collect_results(results)
+    }
+}
@@ -0,0 +1,314 @@
+pub mod blocking;
+pub mod tokio;
+
+use tcads_core::protocol::{SumReadItem, SumReadWriteItem, SumRequest, SumWriteItem};
+
+/// The default maximum number of sub-requests sent in a single sum command.
+///
+/// TwinCAT rejects sum commands with more than 500 sub-requests.
+pub const DEFAULT_MAX_ITEMS: usize = 500;
+
+/// The default maximum size of a single sum command's request or response data.
+///
+/// Kept well below the AMS router buffer so that batches also go through on small
+/// embedded targets.
+pub const DEFAULT_MAX_BYTES: usize = 64 * 1024;
+
+/// The queued operations of a batch and the limits used to split them.
+#[derive(Debug, Clone)]
+pub(crate) struct BatchPlan {
+    runs: Vec<BatchRun>,
+    len: usize,
+    max_items: usize,
+    max_bytes: usize,
+}
+
+/// Consecutive queued operations of the same kind, with their positions in the queue.
+#[derive(Debug, Clone)]
+enum BatchRun {
+    Read(Vec<(usize, SumReadItem)>),
+    Write(Vec<(usize, SumWriteItem)>),
+    ReadWrite(Vec<(usize, SumReadWriteItem)>),
+}
+
+impl Default for BatchPlan {
+    fn default() -> Self {
+        Self {
+            runs: Vec::new(),
+            len: 0,
+            max_items: DEFAULT_MAX_ITEMS,
+            max_bytes: DEFAULT_MAX_BYTES,
+        }
+    }
+}
+
+impl BatchPlan {
+    pub(crate) fn read(&mut self, item: SumReadItem) {
+        let index = self.next_index();
+        match self.runs.last_mut() {
+            Some(BatchRun::Read(run)) => run.push((index, item)),
+            _ => self.runs.push(BatchRun::Read(vec![(index, item)])),
+        }
+    }
+
+    pub(crate) fn write(&mut self, item: SumWriteItem) {
+        let index = self.next_index();
+        match self.runs.last_mut() {
+            Some(BatchRun::Write(run)) => run.push((index, item)),
+            _ => self.runs.push(BatchRun::Write(vec![(index, item)])),
+        }
+    }
+
+    pub(crate) fn read_write(&mut self, item: SumReadWriteItem) {
+        let index = self.next_index();
+        match self.runs.last_mut() {
+            Some(BatchRun::ReadWrite(run)) => run.push((index, item)),
+            _ => self.runs.push(BatchRun::ReadWrite(vec![(index, item)])),
+        }
+    }
+
+    fn next_index(&mut self) -> usize {
+        self.len += 1;
+        self.len - 1
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn set_max_items(&mut self, max_items: usize) {
+        self.max_items = max_items.max(1);
+    }
+
+    pub(crate) fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.max_bytes = max_bytes;
+    }
+
+    /// Splits each run of consecutive operations of the same kind into sum commands that
+    /// respect the item and byte limits, keeping the order the operations were queued in.
+    ///
+    /// An item that exceeds the byte limit on its own is sent in a chunk by itself.
+    pub(crate) fn into_chunks(self) -> Vec<BatchChunk> {
+        let (max_items, max_bytes) = (self.max_items, self.max_bytes);
+        let mut chunks = Vec::new();
+
+        for run in self.runs {
+            match run {
+                BatchRun::Read(items) => chunks.extend(
+                    split(items, max_items, max_bytes, |i| {
+                        (i.request_len(), i.response_len())
+                    })
+                    .into_iter()
+                    .map(|(indices, items)| BatchChunk::Read(indices, items)),
+                ),
+                BatchRun::Write(items) => chunks.extend(
+                    split(items, max_items, max_bytes, |i| {
+                        (i.request_len(), i.response_len())
+                    })
+                    .into_iter()
+                    .map(|(indices, items)| BatchChunk::Write(indices, items)),
+                ),
+                BatchRun::ReadWrite(items) => chunks.extend(
+                    split(items, max_items, max_bytes, |i| {
+                        (i.request_len(), i.response_len())
+                    })
+                    .into_iter()
+                    .map(|(indices, items)| BatchChunk::ReadWrite(indices, items)),
+                ),
+            }
+        }
+
+        chunks
+    }
+}
+
+/// A group of operations of the same kind that fit into a single sum command.
+#[derive(Debug, Clone)]
+pub(crate) enum BatchChunk {
+    Read(Vec<usize>, Vec<SumReadItem>),
+    Write(Vec<usize>, Vec<SumWriteItem>),
+    ReadWrite(Vec<usize>, Vec<SumReadWriteItem>),
+}
+
+impl BatchChunk {
+    /// Encodes the chunk into a sum command.
+    pub(crate) fn request(&self) -> SumRequest {
+        match self {
+            Self::Read(_, items) => SumReadItem::encode_request(items),
+            Self::Write(_, items) => SumWriteItem::encode_request(items),
+            Self::ReadWrite(_, items) => SumReadWriteItem::encode_request(items),
+        }
+    }
+
+    /// Stores the outcome of each operation in `results`, at the position the operation
+    /// was queued in.
+    ///
+    /// If the sum command itself failed, every operation in the chunk fails with that error.
+    pub(crate) fn complete(
+        self,
+        response: crate::Result<Vec<u8>>,
+        results: &mut [Option<crate::Result<Vec<u8>>>],
+    ) {
+        let (indices, parsed) = match self {
+            Self::Read(indices, items) => (
+                indices,
+                response.and_then(|data| Ok(SumReadItem::parse_response(&items, &data)?)),
+            ),
+            Self::Write(indices, items) => (
+                indices,
+                response.and_then(|data| Ok(SumWriteItem::parse_response(&items, &data)?)),
+            ),
+            Self::ReadWrite(indices, items) => (
+                indices,
+                response.and_then(|data| Ok(SumReadWriteItem::parse_response(&items, &data)?)),
+            ),
+        };
+
+        match parsed {
+            Ok(parsed) => {
+                for (index, result) in indices.into_iter().zip(parsed) {
+                    results[index] = Some(result.map_err(Into::into));
+                }
+            }
+            Err(e) => {
+                for index in indices {
+                    results[index] = Some(Err(e.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// Unwraps the per-operation results once every chunk has completed.
+pub(crate) fn collect_results(
+    results: Vec<Option<crate::Result<Vec<u8>>>>,
+) -> Vec<crate::Result<Vec<u8>>> {
+    results
+        .into_iter()
+        .map(|result| result.unwrap_or(Err(crate::Error::Disconnected)))
+        .collect()
+}
+
+fn split<T>(
+    items: Vec<(usize, T)>,
+    max_items: usize,
+    max_bytes: usize,
+    size: impl Fn(&T) -> (usize, usize),
+) -> Vec<(Vec<usize>, Vec<T>)> {
+    let mut chunks = Vec::new();
+    let (mut indices, mut chunk) = (Vec::new(), Vec::new());
+    let (mut request_len, mut response_len) = (0, 0);
+
+    for (index, item) in items {
+        let (req, resp) = size(&item);
+        let full = chunk.len() >= max_items
+            || request_len + req > max_bytes
+            || response_len + resp > max_bytes;
+
+        if full && !chunk.is_empty() {
+            chunks.push((std::mem::take(&mut indices), std::mem::take(&mut chunk)));
+            (request_len, response_len) = (0, 0);
+        }
+
+        request_len += req;
+        response_len += resp;
+        indices.push(index);
+        chunk.push(item);
+    }
+
+    if !chunk.is_empty() {
+        chunks.push((indices, chunk));
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_by_item_count() {
+        let mut plan = BatchPlan::default();
+        plan.set_max_items(2);
+        for offset in 0..5 {
+            plan.read(SumReadItem::new(0x4020, offset, 4));
+        }
+
+        let sizes: Vec<usize> = plan
+            .into_chunks()
+            .iter()
+            .map(|chunk| match chunk {
+                BatchChunk::Read(_, items) => items.len(),
+                _ => unreachable!(),
+            })
+            .collect();
+
+        assert_eq!(sizes, [2, 2, 1]);
+    }
+
+    #[test]
+    fn splits_by_bytes_and_keeps_oversized_items() {
+        let mut plan = BatchPlan::default();
+        plan.set_max_bytes(32);
+        plan.write(SumWriteItem::new(0x4020, 0, [0; 8]));
+        plan.write(SumWriteItem::new(0x4020, 8, [0; 8]));
+        plan.write(SumWriteItem::new(0x4020, 16, [0; 64]));
+
+        let sizes: Vec<usize> = plan
+            .into_chunks()
+            .iter()
+            .map(|chunk| match chunk {
+                BatchChunk::Write(_, items) => items.len(),
+                _ => unreachable!(),
+            })
+            .collect();
+
+        assert_eq!(sizes, [1, 1, 1]);
+    }
+
+    #[test]
+    fn keeps_queue_order_across_kinds() {
+        let mut plan = BatchPlan::default();
+        plan.write(SumWriteItem::new(0x4020, 0, [1]));
+        plan.read(SumReadItem::new(0x4020, 0, 1));
+        plan.read(SumReadItem::new(0x4020, 1, 1));
+        plan.write(SumWriteItem::new(0x4020, 1, [2]));
+
+        let chunks: Vec<(&str, Vec<usize>)> = plan
+            .into_chunks()
+            .into_iter()
+            .map(|chunk| match chunk {
+                BatchChunk::Read(indices, _) => ("read", indices),
+                BatchChunk::Write(indices, _) => ("write", indices),
+                BatchChunk::ReadWrite(indices, _) => ("read_write", indices),
+            })
+            .collect();
+
+        assert_eq!(
+            chunks,
+            [("write", vec![0]), ("read", vec![1, 2]), ("write", vec![3]),]
+        );
+    }
+
+    #[test]
+    fn completes_in_queue_order() {
+        let mut plan = BatchPlan::default();
+        plan.write(SumWriteItem::new(0x4020, 0, [1]));
+        plan.read(SumReadItem::new(0x4020, 0, 1));
+
+        let mut results = vec![None, None];
+        for chunk in plan.into_chunks() {
+            let response = match &chunk {
+                BatchChunk::Read(..) => Ok(vec![0, 0, 0, 0, 9]),
+                BatchChunk::Write(..) => Err(crate::Error::Timeout),
+                BatchChunk::ReadWrite(..) => unreachable!(),
+            };
+            chunk.complete(response, &mut results);
+        }
+        let results = collect_results(results);
+
+        assert!(matches!(results[0], Err(crate::Error::Timeout)));
+        assert_eq!(results[1].as_ref().unwrap(), &[9]);
+    }
+}
@@ -0,0 +1,242 @@
+use super::{BatchPlan, collect_results};
+use crate::devices::tokio::AdsDevice;
+use tcads_core::protocol::{SumReadItem, SumReadWriteItem, SumWriteItem};
+use tcads_core::{AmsAddr, IndexGroup, IndexOffset};
+
+/// A batch of reads, writes and read/writes sent as ADS sum commands.
+///
+/// Obtained from [`AdsDevice::batch`]. Operations are queued with the builder methods and
+/// sent by [`execute`](Self::execute), which combines consecutive operations of the same kind
+/// into [`SUMUP_READ`](crate::index_group::SUMUP_READ),
+/// [`SUMUP_WRITE`](crate::index_group::SUMUP_WRITE) and
+/// [`SUMUP_READWRITE`](crate::index_group::SUMUP_READWRITE) commands. The commands are sent
+/// one after another in the order the operations were queued, so a write queued before a read
+/// of the same variable is applied before that read. A large batch is split into several sum
+/// commands so that none exceeds [`max_items`](Self::max_items) sub-requests or
+/// [`max_bytes`](Self::max_bytes) of request or response data.
+///
+/// # Example
+///
+/// ```no_run
+/// use tcads_client::AmsAddr;
+/// use tcads_client::devices::tokio::AdsDevice;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let device = AdsDevice::connect(None).await?;
+/// let target = AmsAddr::new(device.get_local_net_id().await?, 851);
+///
+/// let results = device
+///     .batch(target)
+///     .read(0x4020, 0, 4)
+///     .write(0x4020, 4, 42u32.to_le_bytes())
+///     .execute()
+///     .await;
+///
+/// for result in results {
+///     println!("{:?}", result?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Batch {
+    device: AdsDevice,
+    target: AmsAddr,
+    plan: BatchPlan,
+}
+
+impl Batch {
+    pub(crate) fn new(device: AdsDevice, target: AmsAddr) -> Self {
+        Self {
+            device,
+            target,
+            plan: BatchPlan::default(),
+        }
+    }
+
+    /// Queues a read of `length` bytes.
+    pub fn read(mut self, index_group: IndexGroup, index_offset: IndexOffset, length: u32) -> Self {
+        self.plan
+            .read(SumReadItem::new(index_group, index_offset, length));
+        self
+    }
+
+    /// Queues a write of `data`. Its result is an empty buffer on success.
+    pub fn write(
+        mut self,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        data: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.plan
+            .write(SumWriteItem::new(index_group, index_offset, data));
+        self
+    }
+
+    /// Queues a read/write that writes `data` and reads up to `read_length` bytes.
+    pub fn read_write(
+        mut self,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        read_length: u32,
+        data: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.plan.read_write(SumReadWriteItem::new(
+            index_group,
+            index_offset,
+            read_length,
+            data,
+        ));
+        self
+    }
+
+    /// Sets the maximum number of sub-requests per sum command.
+    ///
+    /// Defaults to [`DEFAULT_MAX_ITEMS`](super::DEFAULT_MAX_ITEMS).
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        self.plan.set_max_items(max_items);
+        self
+    }
+
+    /// Sets the maximum request or response data size per sum command.
+    ///
+    /// Defaults to [`DEFAULT_MAX_BYTES`](super::DEFAULT_MAX_BYTES).
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.plan.set_max_bytes(max_bytes);
+        self
+    }
+
+    /// Returns the number of queued operations.
+    pub fn len(&self) -> usize {
+        self.plan.len()
+    }
+
+    /// Returns `true` if no operations are queued.
+    pub fn is_empty(&self) -> bool {
+        self.plan.len() == 0
+    }
+
+    /// Sends the batch and returns one result per operation, in the order they were queued.
+    ///
+    /// A failed sub-request does not affect the others. If a whole sum command fails, every
+    /// operation it carried fails with the same error.
+    pub async fn execute(self) -> Vec<crate::Result<Vec<u8>>> {
+        let mut results = vec![None; self.plan.len()];
+
+        for chunk in self.plan.into_chunks() {
+            let request = chunk.request();
+            let response = self
+                .device
+                .read_write(
+                    self.target,
+                    request.index_group(),
+                    request.count(),
+                    request.read_length(),
+                    request.into_data(),
+                )
+                .await;
+            chunk.complete(response, &mut results);
+        }
+
+        collect_results(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tcads_core::AdsReturnCode;
+    use tcads_core::ads::index_group;
+    use tcads_core::io::tokio::AmsStream;
+    use tcads_core::protocol::{AdsReadWriteRequest, AdsReadWriteResponseOwned};
+    use tokio::net::TcpListener;
+
+    /// Serves a sum command device whose memory is `0..=255` at every offset, where offset
+    /// `0xFF` does not exist.
+    async fn spawn_mock_plc() -> std::net::SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut stream = AmsStream::new(socket);
+            let lookup = |offset: u32, len: u32| match offset {
+                0xFF => Err(AdsReturnCode::AdsErrDeviceSymbolNotFound),
+                _ => Ok((offset..offset + len).map(|b| b as u8).collect()),
+            };
+
+            while let Ok(frame) = stream.read_frame().await {
+                let req = AdsReadWriteRequest::try_from(&frame).unwrap();
+                let header = req.header();
+                let count = req.index_offset();
+
+                let data = match req.index_group() {
+                    index_group::SUMUP_READ => {
+                        let items = SumReadItem::parse_request(count, req.data()).unwrap();
+                        let results: Vec<_> = items
+                            .iter()
+                            .map(|i| lookup(i.index_offset(), i.length()))
+                            .collect();
+                        SumReadItem::encode_response(&items, &results)
+                    }
+                    index_group::SUMUP_WRITE => {
+                        let items = SumWriteItem::parse_request(count, req.data()).unwrap();
+                        let results: Vec<_> = items
+                            .iter()
+                            .map(|i| lookup(i.index_offset(), 0).map(|_| ()))
+                            .collect();
+                        SumWriteItem::encode_response(&results)
+                    }
+                    group => panic!("unexpected index group {group:#X}"),
+                };
+
+                let resp = AdsReadWriteResponseOwned::new(
+                    *header.source(),
+                    *header.target(),
+                    header.invoke_id(),
+                    AdsReturnCode::Ok,
+                    data,
+                )
+                .into_frame();
+                stream.write_frame(&resp).await.unwrap();
+            }
+        });
+
+        addr
+    }
+
+    #[tokio::test]
+    async fn returns_results_in_queue_order() {
+        let addr = spawn_mock_plc().await;
+        let source = "127.0.0.1.1.1:32768".parse().unwrap();
+        let target = "127.0.0.1.1.1:851".parse().unwrap();
+        let device = AdsDevice::connect_with_source(addr, source, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+
+        let results = device
+            .batch(target)
+            .read(0x4020, 1, 2)
+            .write(0x4020, 0xFF, [0])
+            .read(0x4020, 0xFF, 1)
+            .write(0x4020, 8, [0])
+            .read(0x4020, 10, 3)
+            .max_items(2)
+            .execute()
+            .await;
+
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[0].as_ref().unwrap(), &[1, 2]);
+        assert!(matches!(
+            results[1],
+            Err(crate::Error::AdsReturnCode(
+                AdsReturnCode::AdsErrDeviceSymbolNotFound
+            ))
+        ));
+        assert!(results[2].is_err());
+        assert!(results[3].as_ref().unwrap().is_empty());
+        assert_eq!(results[4].as_ref().unwrap(), &[10, 11, 12]);
+    }
+}
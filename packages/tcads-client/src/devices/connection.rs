@@ -0,0 +1,462 @@
+use crate::Error;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+#[cfg(feature = "tls")]
+use tcads_core::io::TlsConfig;
+use tcads_core::protocol::SumAddNotificationItem;
+use tcads_core::{AmsAddr, NotificationHandle};
+
+/// Options for [`AdsDevice::connect_with_options`](crate::devices::tokio::AdsDevice::connect_with_options).
+///
+/// By default the device connects over plain TCP, obtains its source address with a
+/// [`PortConnect`](tcads_core::protocol::PortConnectRequest) handshake, waits on responses
+/// without a timeout, does not probe the link, and stays closed once the connection is
+/// lost.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use tcads_client::devices::connection::{ConnectOptions, ReconnectPolicy};
+///
+/// let options = ConnectOptions::new()
+///     .with_timeout(Duration::from_secs(5))
+///     .with_reconnect(ReconnectPolicy::default().with_max_delay(Duration::from_secs(10)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConnectOptions {
+    pub(crate) source: Option<AmsAddr>,
+    pub(crate) timeout: Option<Duration>,
+    #[cfg(feature = "tls")]
+    pub(crate) tls: Option<TlsConfig>,
+    pub(crate) reconnect: Option<ReconnectPolicy>,
+    pub(crate) heartbeat: Option<Heartbeat>,
+}
+
+impl ConnectOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses `source` for every connection instead of the
+    /// [`PortConnect`](tcads_core::protocol::PortConnectRequest) handshake.
+    ///
+    /// Use this when a static route is configured on the PLC.
+    pub fn with_source(mut self, source: AmsAddr) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Sets the response timeout, which also bounds connecting and the handshake.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Connects to a Secure ADS router over TLS.
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, config: TlsConfig) -> Self {
+        self.tls = Some(config);
+        self
+    }
+
+    /// Re-dials according to `policy` whenever the connection is lost.
+    pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = Some(policy);
+        self
+    }
+
+    /// Monitors the link with periodic probes, as described by `heartbeat`.
+    pub fn with_heartbeat(mut self, heartbeat: Heartbeat) -> Self {
+        self.heartbeat = Some(heartbeat);
+        self
+    }
+}
+
+/// How a reconnecting device re-dials after losing its connection.
+///
+/// The delay before attempt `n` is `initial_delay * multiplier^(n - 1)`, capped at
+/// `max_delay`. Attempts go on forever unless [`with_max_attempts`](Self::with_max_attempts)
+/// is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    initial_delay: Duration,
+    max_delay: Duration,
+    multiplier: u32,
+    max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    /// Starts at 500 ms and doubles up to 30 s, without an attempt limit.
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2,
+            max_attempts: None,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Sets the delay before the first attempt.
+    pub fn with_initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Sets the upper bound of the delay between attempts.
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Sets the factor the delay grows by after every failed attempt.
+    pub fn with_multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Gives up after `attempts` failed attempts in a row.
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = Some(attempts);
+        self
+    }
+
+    /// Returns the delay before `attempt`, counted from 1.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.saturating_pow(attempt.saturating_sub(1));
+        self.initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay)
+    }
+
+    /// Returns `true` if `attempt`, counted from 1, may still be made.
+    pub fn allows(&self, attempt: u32) -> bool {
+        self.max_attempts.is_none_or(|max| attempt <= max)
+    }
+}
+
+/// Periodic liveness probes of a device's link.
+///
+/// Every `interval` the device reads the state of `target`. Once `max_timeouts` probes in
+/// a row go unanswered within `timeout`, the connection is reported as
+/// [`Degraded`](ConnectionState::Degraded) until a probe is answered again. Any ADS
+/// response counts as an answer, including an error code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Heartbeat {
+    pub(crate) target: AmsAddr,
+    pub(crate) interval: Duration,
+    pub(crate) timeout: Duration,
+    pub(crate) max_timeouts: u32,
+}
+
+impl Heartbeat {
+    /// Probes `target` every second, with a one second timeout, degrading the connection
+    /// after 3 timeouts in a row.
+    pub fn new(target: AmsAddr) -> Self {
+        Self {
+            target,
+            interval: Duration::from_secs(1),
+            timeout: Duration::from_secs(1),
+            max_timeouts: 3,
+        }
+    }
+
+    /// Sets the time between probes.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets how long to wait for the answer to a probe.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how many probes in a row may time out before the connection is degraded.
+    pub fn with_max_timeouts(mut self, max_timeouts: u32) -> Self {
+        self.max_timeouts = max_timeouts.max(1);
+        self
+    }
+}
+
+/// The state of a device's connection.
+///
+/// Watched through `watch_state` on either `AdsDevice`. Unlike
+/// [`ConnectionEvent`]s, intermediate states may be skipped by a slow watcher, which
+/// only ever sees the latest one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConnectionState {
+    /// A new connection is being dialled.
+    Connecting,
+    /// The connection is up.
+    Connected,
+    /// The connection is up, but [heartbeat](Heartbeat) probes are timing out.
+    Degraded,
+    /// The connection was lost. A reconnecting device waits to dial again.
+    Disconnected,
+    /// The device was shut down or gave up reconnecting.
+    Closed,
+}
+
+impl ConnectionState {
+    /// Returns `true` for [`Connected`](Self::Connected) and [`Degraded`](Self::Degraded).
+    pub fn is_up(&self) -> bool {
+        matches!(self, Self::Connected | Self::Degraded)
+    }
+}
+
+/// A change in the connection of a device.
+///
+/// Obtained from `subscribe_connection` on either `AdsDevice`.
+#[derive(Debug, Clone)]
+pub enum ConnectionEvent {
+    /// The connection was lost.
+    Disconnected,
+    /// A new connection is being dialled. `attempt` counts from 1 since the loss.
+    Reconnecting { attempt: u32 },
+    /// The connection is back under `source`, and notifications and symbol handles have
+    /// been registered again.
+    Reconnected { source: AmsAddr },
+    /// The notification with the device-assigned `handle` could not be registered again.
+    /// Its receiver is closed.
+    NotificationLost {
+        handle: NotificationHandle,
+        error: Error,
+    },
+    /// The symbol handle for `name` could not be acquired again.
+    HandleLost { name: String, error: Error },
+    /// The device was shut down or gave up reconnecting. No further events follow.
+    Closed,
+}
+
+/// A notification added through a reconnecting device.
+struct TrackedNotification<S> {
+    target: AmsAddr,
+    item: SumAddNotificationItem,
+    sender: S,
+    /// The handle the PLC assigned on the current connection.
+    handle: NotificationHandle,
+}
+
+/// A symbol handle acquired through a reconnecting device.
+struct TrackedHandle {
+    target: AmsAddr,
+    name: String,
+    handle: Arc<AtomicU32>,
+}
+
+/// What a reconnecting device keeps across connections: where and how to dial, and the
+/// notifications and symbol handles to restore once a new connection is up.
+///
+/// Notifications are tracked under handles assigned by the device rather than the PLC,
+/// since the PLC hands out new ones on every connection. `S` is the sample sender of
+/// the flavor in use.
+pub(crate) struct Reconnect<S> {
+    pub(crate) addrs: Vec<SocketAddr>,
+    pub(crate) options: ConnectOptions,
+    pub(crate) policy: ReconnectPolicy,
+    closing: AtomicBool,
+    next_notification: AtomicU32,
+    next_handle: AtomicU64,
+    notifications: Mutex<HashMap<NotificationHandle, TrackedNotification<S>>>,
+    handles: Mutex<HashMap<u64, TrackedHandle>>,
+}
+
+impl<S: Clone> Reconnect<S> {
+    pub(crate) fn new(
+        addrs: Vec<SocketAddr>,
+        options: ConnectOptions,
+        policy: ReconnectPolicy,
+    ) -> Self {
+        Self {
+            addrs,
+            options,
+            policy,
+            closing: AtomicBool::new(false),
+            next_notification: AtomicU32::new(1),
+            next_handle: AtomicU64::new(1),
+            notifications: Mutex::new(HashMap::new()),
+            handles: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Stops reconnecting once the current connection is gone.
+    pub(crate) fn close(&self) {
+        self.closing.store(true, Ordering::Release);
+    }
+
+    pub(crate) fn is_closing(&self) -> bool {
+        self.closing.load(Ordering::Acquire)
+    }
+
+    /// Tracks a notification registered under the PLC `handle` and returns the handle
+    /// assigned to it by the device.
+    pub(crate) fn track_notification(
+        &self,
+        target: AmsAddr,
+        item: SumAddNotificationItem,
+        sender: S,
+        handle: NotificationHandle,
+    ) -> crate::Result<NotificationHandle> {
+        let key = NotificationHandle::new(self.next_notification.fetch_add(1, Ordering::Relaxed));
+        let tracked = TrackedNotification {
+            target,
+            item,
+            sender,
+            handle,
+        };
+        self.notifications.lock()?.insert(key, tracked);
+        Ok(key)
+    }
+
+    /// Returns the PLC handle currently behind the device-assigned `key`.
+    pub(crate) fn notification_handle(
+        &self,
+        key: NotificationHandle,
+    ) -> crate::Result<Option<NotificationHandle>> {
+        Ok(self.notifications.lock()?.get(&key).map(|n| n.handle))
+    }
+
+    pub(crate) fn forget_notification(&self, key: NotificationHandle) -> crate::Result<()> {
+        self.notifications.lock()?.remove(&key);
+        Ok(())
+    }
+
+    /// Returns the tracked notifications grouped by target, to be registered again.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn notifications(
+        &self,
+    ) -> crate::Result<HashMap<AmsAddr, Vec<(NotificationHandle, SumAddNotificationItem, S)>>> {
+        let mut by_target: HashMap<_, Vec<_>> = HashMap::new();
+        for (key, n) in self.notifications.lock()?.iter() {
+            by_target
+                .entry(n.target)
+                .or_default()
+                .push((*key, n.item, n.sender.clone()));
+        }
+        Ok(by_target)
+    }
+
+    /// Records the PLC handle a tracked notification was registered again under.
+    pub(crate) fn update_notification(
+        &self,
+        key: NotificationHandle,
+        handle: NotificationHandle,
+    ) -> crate::Result<()> {
+        if let Some(n) = self.notifications.lock()?.get_mut(&key) {
+            n.handle = handle;
+        }
+        Ok(())
+    }
+
+    /// Tracks a symbol handle and returns the key to [forget](Self::forget_handle) it by.
+    pub(crate) fn track_handle(
+        &self,
+        target: AmsAddr,
+        name: &str,
+        handle: Arc<AtomicU32>,
+    ) -> crate::Result<u64> {
+        let key = self.next_handle.fetch_add(1, Ordering::Relaxed);
+        let tracked = TrackedHandle {
+            target,
+            name: name.to_string(),
+            handle,
+        };
+        self.handles.lock()?.insert(key, tracked);
+        Ok(key)
+    }
+
+    pub(crate) fn forget_handle(&self, key: u64) -> crate::Result<()> {
+        self.handles.lock()?.remove(&key);
+        Ok(())
+    }
+
+    /// Returns the tracked symbol handles grouped by target, to be acquired again.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn handles(&self) -> crate::Result<HashMap<AmsAddr, Vec<(String, Arc<AtomicU32>)>>> {
+        let mut by_target: HashMap<_, Vec<_>> = HashMap::new();
+        for h in self.handles.lock()?.values() {
+            by_target
+                .entry(h.target)
+                .or_default()
+                .push((h.name.clone(), Arc::clone(&h.handle)));
+        }
+        Ok(by_target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_grows_up_to_max() {
+        let policy = ReconnectPolicy::default()
+            .with_initial_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_secs(1))
+            .with_multiplier(3);
+
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(300));
+        assert_eq!(policy.delay(3), Duration::from_millis(900));
+        assert_eq!(policy.delay(4), Duration::from_secs(1));
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn attempts_are_limited_only_when_configured() {
+        assert!(ReconnectPolicy::default().allows(u32::MAX));
+
+        let policy = ReconnectPolicy::default().with_max_attempts(2);
+        assert!(policy.allows(2));
+        assert!(!policy.allows(3));
+    }
+
+    #[test]
+    fn heartbeat_needs_at_least_one_timeout() {
+        let target = "127.0.0.1.1.1:10000".parse().unwrap();
+        let heartbeat = Heartbeat::new(target).with_max_timeouts(0);
+        assert_eq!(heartbeat.max_timeouts, 1);
+    }
+
+    #[test]
+    fn notifications_are_tracked_under_device_handles() {
+        let reconnect: Reconnect<()> = Reconnect::new(
+            Vec::new(),
+            ConnectOptions::new(),
+            ReconnectPolicy::default(),
+        );
+        let target = "127.0.0.1.1.1:851".parse().unwrap();
+        let item = SumAddNotificationItem::new(
+            0x4020,
+            0,
+            4,
+            tcads_core::AdsTransMode::ServerOnChange,
+            0,
+            100,
+        );
+
+        let key = reconnect
+            .track_notification(target, item, (), NotificationHandle::new(7))
+            .unwrap();
+        reconnect
+            .update_notification(key, NotificationHandle::new(3))
+            .unwrap();
+
+        assert_eq!(
+            reconnect.notification_handle(key).unwrap(),
+            Some(NotificationHandle::new(3))
+        );
+        assert_eq!(reconnect.notifications().unwrap()[&target].len(), 1);
+
+        reconnect.forget_notification(key).unwrap();
+        assert_eq!(reconnect.notification_handle(key).unwrap(), None);
+    }
+}
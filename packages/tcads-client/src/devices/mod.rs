@@ -1,7 +1,25 @@
 pub mod ads_device;
+pub mod batch;
+pub mod connection;
+pub mod pending;
+pub mod request;
+pub mod routes;
+pub mod subscription;
+pub mod symbol_handle;
 
 pub mod blocking {
     pub use super::ads_device::blocking::AdsDevice;
+    pub use super::batch::blocking::Batch;
+    pub use super::pending::PendingResponse;
+    pub use super::routes::blocking::AdsRoutes;
+    pub use super::subscription::blocking::Subscription;
+    pub use super::symbol_handle::blocking::SymbolHandle;
 }
 
-pub mod tokio {}
+pub mod tokio {
+    pub use super::ads_device::tokio::AdsDevice;
+    pub use super::batch::tokio::Batch;
+    pub use super::routes::tokio::AdsRoutes;
+    pub use super::subscription::tokio::Subscription;
+    pub use super::symbol_handle::tokio::SymbolHandle;
+}
@@ -0,0 +1,242 @@
+use crate::devices::request::RequestOptions;
+use crate::tasks::AmsRequestDispatchKey;
+use crate::tasks::blocking::AmsRequestDispatcher;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::Duration;
+use tcads_core::AmsFrame;
+
+type Decode<T> = Box<dyn FnOnce(AmsFrame) -> crate::Result<T> + Send>;
+
+/// A request submitted through a blocking [`AdsDevice`](crate::devices::blocking::AdsDevice)
+/// whose response has not been collected yet.
+///
+/// Returned by the `submit_*` methods, so that one thread can have many requests in
+/// flight at once. The response is only decoded into `T` when it is collected with
+/// [`wait`](Self::wait), [`wait_timeout`](Self::wait_timeout) or [`try_get`](Self::try_get).
+///
+/// Dropping an uncollected `PendingResponse` purges its pending entry, so a response
+/// arriving afterwards is counted as late.
+///
+/// # Example
+///
+/// ```no_run
+/// use tcads_client::devices::blocking::AdsDevice;
+///
+/// let device = AdsDevice::connect(None)?;
+/// let target = "192.168.1.100.1.1:851".parse()?;
+///
+/// let pending = (0..50)
+///     .map(|i| device.submit_read_value::<u16>(target, 0x4020, i * 2))
+///     .collect::<Result<Vec<_>, _>>()?;
+/// let values = pending
+///     .into_iter()
+///     .map(|response| response.wait())
+///     .collect::<Result<Vec<_>, _>>()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct PendingResponse<T> {
+    rx: Receiver<AmsFrame>,
+    key: AmsRequestDispatchKey,
+    requests: Arc<AmsRequestDispatcher>,
+    options: RequestOptions,
+    timeout: Option<Duration>,
+    decode: Option<Decode<T>>,
+    /// Set once the response channel has yielded a frame or been closed. The pending entry
+    /// is gone by then and its invoke ID may already belong to a newer request.
+    collected: bool,
+}
+
+impl<T> PendingResponse<T> {
+    pub(crate) fn new(
+        rx: Receiver<AmsFrame>,
+        key: AmsRequestDispatchKey,
+        requests: Arc<AmsRequestDispatcher>,
+        options: RequestOptions,
+        timeout: Option<Duration>,
+        decode: impl FnOnce(AmsFrame) -> crate::Result<T> + Send + 'static,
+    ) -> Self {
+        Self {
+            rx,
+            key,
+            requests,
+            options,
+            timeout,
+            decode: Some(Box::new(decode)),
+            collected: false,
+        }
+    }
+
+    /// Blocks until the response arrives and decodes it.
+    ///
+    /// Gives up as the device would for a regular call: after the timeout of the
+    /// device, or as set by the options of the handle the request was submitted through.
+    pub fn wait(self) -> crate::Result<T> {
+        let limit = self.options.wait_limit(self.timeout);
+        self.wait_for(limit)
+    }
+
+    /// Blocks until the response arrives, for at most `timeout`, and decodes it.
+    ///
+    /// Returns [`Error::Timeout`](crate::Error::Timeout) and purges the request if no
+    /// response arrived in time. A deadline set in the options of the device still
+    /// applies.
+    pub fn wait_timeout(self, timeout: Duration) -> crate::Result<T> {
+        let limit = self.options.clone().with_timeout(timeout).wait_limit(None);
+        self.wait_for(limit)
+    }
+
+    /// Returns the decoded response if it has arrived, without blocking.
+    ///
+    /// Returns `Ok(None)` while the request is still pending. Once a response or error
+    /// has been returned, the `PendingResponse` is spent and further calls fail with
+    /// [`Error::Disconnected`](crate::Error::Disconnected).
+    pub fn try_get(&mut self) -> crate::Result<Option<T>> {
+        if self.options.is_cancelled() {
+            self.requests.cancel(self.key)?;
+            return Err(crate::Error::Cancelled);
+        }
+        match self.rx.try_recv() {
+            Ok(frame) => self.decode(frame).map(Some),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => {
+                self.collected = true;
+                Err(crate::Error::Disconnected)
+            }
+        }
+    }
+
+    fn wait_for(mut self, limit: Option<Duration>) -> crate::Result<T> {
+        let _cancel = self.options.cancellation().map(|token| {
+            let requests = Arc::downgrade(&self.requests);
+            let key = self.key;
+            token.on_cancel(move || {
+                if let Some(requests) = requests.upgrade() {
+                    let _ = requests.cancel(key);
+                }
+            })
+        });
+
+        let result = match limit {
+            Some(duration) => self.rx.recv_timeout(duration).map_err(crate::Error::from),
+            None => self.rx.recv().map_err(crate::Error::from),
+        };
+        self.collected = !matches!(result, Err(crate::Error::Timeout));
+        match result {
+            Ok(frame) => self.decode(frame),
+            Err(crate::Error::Disconnected) if self.options.is_cancelled() => {
+                Err(crate::Error::Cancelled)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn decode(&mut self, frame: AmsFrame) -> crate::Result<T> {
+        self.collected = true;
+        match self.decode.take() {
+            Some(decode) => decode(frame),
+            None => Err(crate::Error::Disconnected),
+        }
+    }
+}
+
+impl<T> Drop for PendingResponse<T> {
+    fn drop(&mut self) {
+        if !self.collected {
+            let _ = self.requests.cancel(self.key);
+        }
+    }
+}
+
+impl<T> fmt::Debug for PendingResponse<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingResponse")
+            .field("key", &self.key)
+            .field("collected", &self.collected)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use tcads_core::AmsCommand;
+
+    fn submit(requests: &Arc<AmsRequestDispatcher>, invoke_id: u32) -> PendingResponse<usize> {
+        let key = AmsRequestDispatchKey::AdsCommand(invoke_id);
+        let rx = requests
+            .dispatch(key, AmsFrame::empty(AmsCommand::AdsCommand))
+            .unwrap();
+        PendingResponse::new(
+            rx,
+            key,
+            Arc::clone(requests),
+            RequestOptions::new(),
+            None,
+            |frame| Ok(frame.payload().len()),
+        )
+    }
+
+    #[test]
+    fn responses_are_decoded_when_collected() {
+        let (write_tx, _write_rx) = mpsc::channel();
+        let requests = Arc::new(AmsRequestDispatcher::new(write_tx));
+        let mut first = submit(&requests, 1);
+        let second = submit(&requests, 2);
+
+        assert!(first.try_get().unwrap().is_none());
+        let response = AmsFrame::new(AmsCommand::AdsCommand, [0u8; 3]);
+        requests
+            .complete(AmsRequestDispatchKey::AdsCommand(2), response.clone())
+            .unwrap();
+        requests
+            .complete(AmsRequestDispatchKey::AdsCommand(1), response)
+            .unwrap();
+
+        assert_eq!(second.wait().unwrap(), 3);
+        assert_eq!(first.try_get().unwrap(), Some(3));
+        assert!(matches!(first.try_get(), Err(crate::Error::Disconnected)));
+    }
+
+    #[test]
+    fn dropping_a_collected_response_leaves_a_reused_invoke_id_alone() {
+        let (write_tx, _write_rx) = mpsc::channel();
+        let requests = Arc::new(AmsRequestDispatcher::new(write_tx));
+        let key = AmsRequestDispatchKey::AdsCommand(1);
+
+        let mut first = submit(&requests, 1);
+        requests
+            .complete(key, AmsFrame::empty(AmsCommand::AdsCommand))
+            .unwrap();
+        assert_eq!(first.try_get().unwrap(), Some(0));
+
+        let second = submit(&requests, 1);
+        drop(first);
+        assert_eq!(requests.pending().unwrap(), 1);
+        requests
+            .complete(key, AmsFrame::empty(AmsCommand::AdsCommand))
+            .unwrap();
+        assert_eq!(second.wait().unwrap(), 0);
+        assert_eq!(requests.late_responses(), 0);
+    }
+
+    #[test]
+    fn abandoned_responses_are_purged() {
+        let (write_tx, _write_rx) = mpsc::channel();
+        let requests = Arc::new(AmsRequestDispatcher::new(write_tx));
+
+        let pending = submit(&requests, 1);
+        let result = pending.wait_timeout(Duration::from_millis(10));
+        assert!(matches!(result, Err(crate::Error::Timeout)));
+        drop(submit(&requests, 2));
+        assert_eq!(requests.pending().unwrap(), 0);
+
+        let response = AmsFrame::empty(AmsCommand::AdsCommand);
+        requests
+            .complete(AmsRequestDispatchKey::AdsCommand(1), response)
+            .unwrap();
+        assert_eq!(requests.late_responses(), 1);
+    }
+}
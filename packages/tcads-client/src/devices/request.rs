@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Options for the calls made through one `AdsDevice` handle.
+///
+/// Applied with `with_options` on either `AdsDevice`, which returns a handle to the
+/// same connection. A call that times out or is cancelled gives up its pending entry
+/// in the [`AmsRequestDispatcher`](crate::tasks::tokio::AmsRequestDispatcher), so a
+/// response arriving afterwards is counted as late instead of being delivered.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use tcads_client::devices::request::{CancellationToken, RequestOptions};
+///
+/// let token = CancellationToken::new();
+/// let options = RequestOptions::new()
+///     .with_timeout(Duration::from_millis(200))
+///     .with_cancellation(token.clone());
+///
+/// // Elsewhere, e.g. when the operator closes a dialog:
+/// token.cancel();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+    cancellation: Option<CancellationToken>,
+}
+
+impl RequestOptions {
+    /// Creates options that defer to the timeout of the device.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Waits at most `timeout` for each response, instead of the timeout of the device.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Gives up on any response still outstanding at `deadline`.
+    ///
+    /// Unlike a timeout, the deadline is shared by every call made with these options,
+    /// so it also bounds operations that take several round trips.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Gives up on outstanding responses once `token` is cancelled.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Fails with [`Error::Cancelled`](crate::Error::Cancelled) or
+    /// [`Error::Timeout`](crate::Error::Timeout) if a call should not even be sent.
+    pub(crate) fn check(&self) -> crate::Result<()> {
+        if self.is_cancelled() {
+            return Err(crate::Error::Cancelled);
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| deadline <= Instant::now())
+        {
+            return Err(crate::Error::Timeout);
+        }
+        Ok(())
+    }
+
+    /// Returns how long to wait for a response, given the `default` timeout of the
+    /// device, or [`None`] to wait indefinitely.
+    pub(crate) fn wait_limit(&self, default: Option<Duration>) -> Option<Duration> {
+        let timeout = self.timeout.or(default);
+        let remaining = self
+            .deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        match (timeout, remaining) {
+            (Some(timeout), Some(remaining)) => Some(timeout.min(remaining)),
+            (timeout, remaining) => timeout.or(remaining),
+        }
+    }
+
+    pub(crate) fn cancellation(&self) -> Option<&CancellationToken> {
+        self.cancellation.as_ref()
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+}
+
+type Hook = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+    next_hook: AtomicU64,
+    hooks: Mutex<HashMap<u64, Hook>>,
+}
+
+/// Cancels the calls made with [`RequestOptions::with_cancellation`].
+///
+/// Clones share the same state, so one clone can cancel the calls waiting with another.
+/// Cancelling is permanent: later calls with the same token fail immediately with
+/// [`Error::Cancelled`](crate::Error::Cancelled).
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    state: Arc<TokenState>,
+}
+
+impl CancellationToken {
+    /// Creates a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels every call waiting with this token, and every later one.
+    pub fn cancel(&self) {
+        if self.state.cancelled.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let hooks = match self.state.hooks.lock() {
+            Ok(mut hooks) => std::mem::take(&mut *hooks),
+            Err(_) => return,
+        };
+        for hook in hooks.into_values() {
+            hook();
+        }
+    }
+
+    /// Returns `true` once [`cancel`](Self::cancel) has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Runs `hook` when the token is cancelled, or right away if it already is, until
+    /// the returned guard is dropped.
+    pub(crate) fn on_cancel(&self, hook: impl FnOnce() + Send + 'static) -> CancelGuard {
+        let id = self.state.next_hook.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut hooks) = self.state.hooks.lock() {
+            hooks.insert(id, Box::new(hook));
+        }
+        // Checked after registering, so a concurrent cancel runs the hook either way.
+        if self.is_cancelled()
+            && let Some(hook) = self.remove(id)
+        {
+            hook();
+        }
+        CancelGuard {
+            token: self.clone(),
+            id,
+        }
+    }
+
+    fn remove(&self, id: u64) -> Option<Hook> {
+        self.state.hooks.lock().ok()?.remove(&id)
+    }
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// Unregisters a [cancellation hook](CancellationToken::on_cancel) when dropped.
+pub(crate) struct CancelGuard {
+    token: CancellationToken,
+    id: u64,
+}
+
+impl Drop for CancelGuard {
+    fn drop(&mut self) {
+        self.token.remove(self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    #[test]
+    fn wait_limit_is_bounded_by_deadline() {
+        let options = RequestOptions::new();
+        assert_eq!(options.wait_limit(None), None);
+        assert_eq!(
+            options.wait_limit(Some(Duration::from_secs(5))),
+            Some(Duration::from_secs(5))
+        );
+
+        let options = options.with_timeout(Duration::from_secs(1));
+        assert_eq!(
+            options.wait_limit(Some(Duration::from_secs(5))),
+            Some(Duration::from_secs(1))
+        );
+
+        let options = options.with_deadline(Instant::now() - Duration::from_secs(1));
+        assert_eq!(options.wait_limit(None), Some(Duration::ZERO));
+        assert!(matches!(options.check(), Err(crate::Error::Timeout)));
+    }
+
+    #[test]
+    fn hooks_run_once_on_cancel_unless_dropped() {
+        let token = CancellationToken::new();
+        let runs = Arc::new(AtomicU32::new(0));
+
+        let counter = Arc::clone(&runs);
+        let kept = token.on_cancel(move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        let counter = Arc::clone(&runs);
+        drop(token.on_cancel(move || {
+            counter.fetch_add(10, Ordering::Relaxed);
+        }));
+
+        token.clone().cancel();
+        token.cancel();
+        assert_eq!(runs.load(Ordering::Relaxed), 1);
+
+        let counter = Arc::clone(&runs);
+        let _late = token.on_cancel(move || {
+            counter.fetch_add(100, Ordering::Relaxed);
+        });
+        assert_eq!(runs.load(Ordering::Relaxed), 101);
+        drop(kept);
+
+        let options = RequestOptions::new().with_cancellation(token);
+        assert!(matches!(options.check(), Err(crate::Error::Cancelled)));
+    }
+}
@@ -0,0 +1,243 @@
+use crate::devices::ads_device::blocking::AdsDevice;
+use crate::error::Error;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tcads_core::ams::{AmsRoute, AmsRouteTable, AmsTransport};
+#[cfg(feature = "tls")]
+use tcads_core::io::TlsConfig;
+use tcads_core::{AdsState, AmsAddr, AmsNetId, DeviceState, IndexGroup, IndexOffset};
+
+/// Connections to many AMS routers, opened on demand from a route table.
+///
+/// Maps the net ID of each target to the router its [route](AmsRoute) points at, so
+/// requests can be addressed with plain [`AmsAddr`]s. A connection is opened on first use,
+/// shared by every request to that net ID, and reopened once it is lost.
+///
+/// Connections skip the [`PortConnect`](tcads_core::protocol::PortConnectRequest)
+/// handshake and send from `source`, which each target must have a route back to.
+///
+/// # Example
+///
+/// ```no_run
+/// use tcads_client::devices::blocking::AdsRoutes;
+/// use tcads_core::ams::{AmsRoute, AmsRouteTable};
+///
+/// let source = "192.168.0.10.1.1:32768".parse()?;
+/// let table = AmsRouteTable::from_iter([
+///     AmsRoute::new("Line 1", "192.168.0.20.1.1".parse()?, "192.168.0.20"),
+///     AmsRoute::new("Line 2", "192.168.0.21.1.1".parse()?, "192.168.0.21"),
+/// ]);
+/// let routes = AdsRoutes::new(source, table, None);
+///
+/// for target in ["192.168.0.20.1.1:851", "192.168.0.21.1.1:851"] {
+///     let (state, _) = routes.read_state(target.parse()?)?;
+///     println!("{target}: {state:?}");
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct AdsRoutes {
+    source: AmsAddr,
+    timeout: Option<Duration>,
+    #[cfg(feature = "tls")]
+    tls: Option<TlsConfig>,
+    routes: RwLock<AmsRouteTable>,
+    devices: Mutex<HashMap<AmsNetId, Arc<Mutex<Option<AdsDevice>>>>>,
+}
+
+impl AdsRoutes {
+    /// Creates a set of connections over `routes`, sending from `source`.
+    ///
+    /// No connection is opened until a net ID is first used.
+    pub fn new(source: AmsAddr, routes: AmsRouteTable, timeout: Option<Duration>) -> Self {
+        Self {
+            source,
+            timeout,
+            #[cfg(feature = "tls")]
+            tls: None,
+            routes: RwLock::new(routes),
+            devices: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Connects over Secure ADS routes with `config`.
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, config: TlsConfig) -> Self {
+        self.tls = Some(config);
+        self
+    }
+
+    /// Returns the source address requests are sent from.
+    pub fn source(&self) -> AmsAddr {
+        self.source
+    }
+
+    /// Returns a copy of the route table.
+    pub fn routes(&self) -> crate::Result<AmsRouteTable> {
+        Ok(self.routes.read()?.clone())
+    }
+
+    /// Adds `route`, replacing any route to the same net ID.
+    ///
+    /// A connection opened over the replaced route is no longer used.
+    pub fn add_route(&self, route: AmsRoute) -> crate::Result<Option<AmsRoute>> {
+        let net_id = route.net_id();
+        let replaced = self.routes.write()?.insert(route);
+        self.devices.lock()?.remove(&net_id);
+        Ok(replaced)
+    }
+
+    /// Removes the route to `net_id`, returning it.
+    ///
+    /// A connection opened over the route is no longer used.
+    pub fn remove_route(&self, net_id: AmsNetId) -> crate::Result<Option<AmsRoute>> {
+        let removed = self.routes.write()?.remove(net_id);
+        self.devices.lock()?.remove(&net_id);
+        Ok(removed)
+    }
+
+    /// Returns the connection to the router of `net_id`, opening it if needed.
+    ///
+    /// Returns [`Error::NoRoute`] if there is no enabled route to `net_id`, and
+    /// [`Error::UnsupportedTransport`] for Secure ADS routes unless the `tls` feature is
+    /// enabled and a TLS configuration was given with `with_tls`.
+    pub fn device(&self, net_id: AmsNetId) -> crate::Result<AdsDevice> {
+        let route = self
+            .routes
+            .read()?
+            .find(net_id)
+            .cloned()
+            .ok_or(Error::NoRoute(net_id))?;
+
+        // Each net ID has its own slot, so a slow connect only holds up requests to the
+        // same router.
+        let slot = Arc::clone(self.devices.lock()?.entry(net_id).or_default());
+        let mut slot = slot.lock()?;
+        if let Some(device) = slot.as_ref().filter(|device| !device.is_closed()) {
+            return Ok(device.clone());
+        }
+
+        let device = self.connect(&route)?;
+        *slot = Some(device.clone());
+        Ok(device)
+    }
+
+    /// Opens a connection to the router of `route`.
+    fn connect(&self, route: &AmsRoute) -> crate::Result<AdsDevice> {
+        let addr = route.address();
+        match route.transport() {
+            AmsTransport::Tcp => AdsDevice::connect_with_source(addr, self.source, self.timeout),
+            #[cfg(feature = "tls")]
+            AmsTransport::Tls => match &self.tls {
+                Some(config) => {
+                    AdsDevice::connect_tls_with_source(addr, self.source, config, self.timeout)
+                }
+                None => Err(Error::UnsupportedTransport(AmsTransport::Tls)),
+            },
+            #[cfg(not(feature = "tls"))]
+            AmsTransport::Tls => Err(Error::UnsupportedTransport(AmsTransport::Tls)),
+        }
+    }
+
+    /// Reads the ADS and device state of `target`.
+    pub fn read_state(&self, target: AmsAddr) -> crate::Result<(AdsState, DeviceState)> {
+        self.device(target.net_id())?.read_state(target)
+    }
+
+    /// Reads `length` bytes from `target`.
+    pub fn read(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        length: u32,
+    ) -> crate::Result<Vec<u8>> {
+        self.device(target.net_id())?
+            .read(target, index_group, index_offset, length)
+    }
+
+    /// Writes `data` to `target`.
+    pub fn write(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        data: impl Into<Vec<u8>>,
+    ) -> crate::Result<()> {
+        self.device(target.net_id())?
+            .write(target, index_group, index_offset, data)
+    }
+
+    /// Writes `write_data` to `target` and reads up to `read_length` bytes back.
+    pub fn read_write(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        read_length: u32,
+        write_data: impl Into<Vec<u8>>,
+    ) -> crate::Result<Vec<u8>> {
+        self.device(target.net_id())?.read_write(
+            target,
+            index_group,
+            index_offset,
+            read_length,
+            write_data,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+    use tcads_core::AdsReturnCode;
+    use tcads_core::io::blocking::AmsStream;
+    use tcads_core::protocol::{AdsReadStateRequest, AdsReadStateResponse};
+
+    #[test]
+    fn opens_connections_lazily() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (socket, _) = listener.accept().unwrap();
+            let mut stream = AmsStream::new(socket);
+            while let Ok(frame) = stream.read_frame() {
+                let req = AdsReadStateRequest::try_from(&frame).unwrap();
+                let header = req.header();
+                let resp = AdsReadStateResponse::new(
+                    *header.source(),
+                    *header.target(),
+                    header.invoke_id(),
+                    AdsReturnCode::Ok,
+                    AdsState::Run,
+                    7,
+                );
+                stream.write_frame(&resp.into_frame()).ok();
+            }
+        });
+
+        let plc = AmsNetId::new(10, 0, 0, 1, 1, 1);
+        let secure = AmsNetId::new(10, 0, 0, 2, 1, 1);
+        let routes = AmsRouteTable::from_iter([
+            AmsRoute::new("PLC", plc, addr.to_string()),
+            AmsRoute::new("Secure", secure, "10.0.0.2").with_transport(AmsTransport::Tls),
+        ]);
+        let source = AmsAddr::new(AmsNetId::new(10, 0, 0, 9, 1, 1), 32768);
+        let routes = AdsRoutes::new(source, routes, Some(Duration::from_secs(5)));
+
+        // The mock accepts a single connection, so both requests share it.
+        for _ in 0..2 {
+            let state = routes.read_state(AmsAddr::new(plc, 851)).unwrap();
+            assert_eq!(state, (AdsState::Run, 7));
+        }
+
+        assert!(matches!(
+            routes.device(secure),
+            Err(Error::UnsupportedTransport(AmsTransport::Tls))
+        ));
+        routes.remove_route(plc).unwrap();
+        assert!(matches!(routes.device(plc), Err(Error::NoRoute(_))));
+    }
+}
@@ -0,0 +1,277 @@
+use crate::devices::ads_device::tokio::AdsDevice;
+use crate::error::Error;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tcads_core::ams::{AmsRoute, AmsRouteTable, AmsTransport};
+#[cfg(feature = "tls")]
+use tcads_core::io::TlsConfig;
+use tcads_core::{AdsState, AmsAddr, AmsNetId, DeviceState, IndexGroup, IndexOffset};
+
+/// Connections to many AMS routers, opened on demand from a route table.
+///
+/// The async counterpart of the [blocking `AdsRoutes`](crate::devices::blocking::AdsRoutes).
+/// Maps the net ID of each target to the router its [route](AmsRoute) points at, so
+/// requests can be addressed with plain [`AmsAddr`]s. A connection is opened on first use,
+/// shared by every request to that net ID, and reopened once it is lost.
+///
+/// Connections skip the [`PortConnect`](tcads_core::protocol::PortConnectRequest)
+/// handshake and send from `source`, which each target must have a route back to.
+///
+/// # Example
+///
+/// ```no_run
+/// use tcads_client::devices::tokio::AdsRoutes;
+/// use tcads_core::ams::{AmsRoute, AmsRouteTable};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let source = "192.168.0.10.1.1:32768".parse()?;
+/// let table = AmsRouteTable::from_iter([
+///     AmsRoute::new("Line 1", "192.168.0.20.1.1".parse()?, "192.168.0.20"),
+///     AmsRoute::new("Line 2", "192.168.0.21.1.1".parse()?, "192.168.0.21"),
+/// ]);
+/// let routes = AdsRoutes::new(source, table, None);
+///
+/// for target in ["192.168.0.20.1.1:851", "192.168.0.21.1.1:851"] {
+///     let (state, _) = routes.read_state(target.parse()?).await?;
+///     println!("{target}: {state:?}");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct AdsRoutes {
+    source: AmsAddr,
+    timeout: Option<Duration>,
+    #[cfg(feature = "tls")]
+    tls: Option<TlsConfig>,
+    routes: RwLock<AmsRouteTable>,
+    devices: Mutex<HashMap<AmsNetId, Arc<tokio::sync::Mutex<Option<AdsDevice>>>>>,
+}
+
+impl AdsRoutes {
+    /// Creates a set of connections over `routes`, sending from `source`.
+    ///
+    /// No connection is opened until a net ID is first used.
+    pub fn new(source: AmsAddr, routes: AmsRouteTable, timeout: Option<Duration>) -> Self {
+        Self {
+            source,
+            timeout,
+            #[cfg(feature = "tls")]
+            tls: None,
+            routes: RwLock::new(routes),
+            devices: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Connects over Secure ADS routes with `config`.
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, config: TlsConfig) -> Self {
+        self.tls = Some(config);
+        self
+    }
+
+    /// Returns the source address requests are sent from.
+    pub fn source(&self) -> AmsAddr {
+        self.source
+    }
+
+    /// Returns a copy of the route table.
+    pub fn routes(&self) -> crate::Result<AmsRouteTable> {
+        Ok(self.routes.read()?.clone())
+    }
+
+    /// Adds `route`, replacing any route to the same net ID.
+    ///
+    /// A connection opened over the replaced route is no longer used.
+    pub fn add_route(&self, route: AmsRoute) -> crate::Result<Option<AmsRoute>> {
+        let net_id = route.net_id();
+        let replaced = self.routes.write()?.insert(route);
+        self.devices.lock()?.remove(&net_id);
+        Ok(replaced)
+    }
+
+    /// Removes the route to `net_id`, returning it.
+    ///
+    /// A connection opened over the route is no longer used.
+    pub fn remove_route(&self, net_id: AmsNetId) -> crate::Result<Option<AmsRoute>> {
+        let removed = self.routes.write()?.remove(net_id);
+        self.devices.lock()?.remove(&net_id);
+        Ok(removed)
+    }
+
+    /// Returns the connection to the router of `net_id`, opening it if needed.
+    ///
+    /// Returns [`Error::NoRoute`] if there is no enabled route to `net_id`, and
+    /// [`Error::UnsupportedTransport`] for Secure ADS routes unless the `tls` feature is
+    /// enabled and a TLS configuration was given with `with_tls`.
+    pub async fn device(&self, net_id: AmsNetId) -> crate::Result<AdsDevice> {
+        let route = self
+            .routes
+            .read()?
+            .find(net_id)
+            .cloned()
+            .ok_or(Error::NoRoute(net_id))?;
+
+        // Each net ID has its own slot, so a slow connect only holds up requests to the
+        // same router.
+        let slot = Arc::clone(self.devices.lock()?.entry(net_id).or_default());
+        let mut slot = slot.lock().await;
+        if let Some(device) = slot.as_ref().filter(|device| !device.is_closed()) {
+            return Ok(device.clone());
+        }
+
+        let device = self.connect(&route).await?;
+        *slot = Some(device.clone());
+        Ok(device)
+    }
+
+    /// Opens a connection to the router of `route`.
+    async fn connect(&self, route: &AmsRoute) -> crate::Result<AdsDevice> {
+        let addr = route.address();
+        match route.transport() {
+            AmsTransport::Tcp => {
+                AdsDevice::connect_with_source(addr, self.source, self.timeout).await
+            }
+            #[cfg(feature = "tls")]
+            AmsTransport::Tls => match &self.tls {
+                Some(config) => {
+                    AdsDevice::connect_tls_with_source(addr, self.source, config, self.timeout)
+                        .await
+                }
+                None => Err(Error::UnsupportedTransport(AmsTransport::Tls)),
+            },
+            #[cfg(not(feature = "tls"))]
+            AmsTransport::Tls => Err(Error::UnsupportedTransport(AmsTransport::Tls)),
+        }
+    }
+
+    /// Reads the ADS and device state of `target`.
+    pub async fn read_state(&self, target: AmsAddr) -> crate::Result<(AdsState, DeviceState)> {
+        self.device(target.net_id()).await?.read_state(target).await
+    }
+
+    /// Reads `length` bytes from `target`.
+    pub async fn read(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        length: u32,
+    ) -> crate::Result<Vec<u8>> {
+        let device = self.device(target.net_id()).await?;
+        device.read(target, index_group, index_offset, length).await
+    }
+
+    /// Writes `data` to `target`.
+    pub async fn write(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        data: impl Into<Vec<u8>>,
+    ) -> crate::Result<()> {
+        let device = self.device(target.net_id()).await?;
+        device.write(target, index_group, index_offset, data).await
+    }
+
+    /// Writes `write_data` to `target` and reads up to `read_length` bytes back.
+    pub async fn read_write(
+        &self,
+        target: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        read_length: u32,
+        write_data: impl Into<Vec<u8>>,
+    ) -> crate::Result<Vec<u8>> {
+        let device = self.device(target.net_id()).await?;
+        device
+            .read_write(target, index_group, index_offset, read_length, write_data)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tcads_core::AdsReturnCode;
+    use tcads_core::io::tokio::AmsStream;
+    use tcads_core::protocol::{AdsReadStateRequest, AdsReadStateResponse};
+    use tokio::net::TcpListener;
+
+    /// Answers read state requests with `device_state`, closing each connection after
+    /// `requests` requests. Returns the address and the number of accepted connections.
+    async fn spawn_mock_plc(
+        device_state: DeviceState,
+        requests: usize,
+    ) -> (SocketAddr, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let accepted = Arc::new(AtomicUsize::new(0));
+
+        let count = Arc::clone(&accepted);
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                count.fetch_add(1, Ordering::SeqCst);
+                let mut stream = AmsStream::new(socket);
+                for _ in 0..requests {
+                    let Ok(frame) = stream.read_frame().await else {
+                        break;
+                    };
+                    let req = AdsReadStateRequest::try_from(&frame).unwrap();
+                    let header = req.header();
+                    let resp = AdsReadStateResponse::new(
+                        *header.source(),
+                        *header.target(),
+                        header.invoke_id(),
+                        AdsReturnCode::Ok,
+                        AdsState::Run,
+                        device_state,
+                    );
+                    stream.write_frame(&resp.into_frame()).await.ok();
+                }
+            }
+        });
+
+        (addr, accepted)
+    }
+
+    #[tokio::test]
+    async fn shares_connections_per_net_id() {
+        let plc1 = AmsNetId::new(10, 0, 0, 1, 1, 1);
+        let plc2 = AmsNetId::new(10, 0, 0, 2, 1, 1);
+        let (addr1, accepted1) = spawn_mock_plc(1, 2).await;
+        let (addr2, accepted2) = spawn_mock_plc(2, usize::MAX).await;
+
+        let routes = AmsRouteTable::from_iter([
+            AmsRoute::new("PLC 1", plc1, addr1.to_string()),
+            AmsRoute::new("PLC 2", plc2, addr2.to_string()),
+        ]);
+        let source = AmsAddr::new(AmsNetId::new(10, 0, 0, 9, 1, 1), 32768);
+        let routes = AdsRoutes::new(source, routes, Some(Duration::from_secs(5)));
+
+        let first = routes.device(plc1).await.unwrap();
+        for _ in 0..2 {
+            let state = routes.read_state(AmsAddr::new(plc1, 851)).await.unwrap();
+            assert_eq!(state, (AdsState::Run, 1));
+            let state = routes.read_state(AmsAddr::new(plc2, 851)).await.unwrap();
+            assert_eq!(state, (AdsState::Run, 2));
+        }
+        assert_eq!(accepted1.load(Ordering::SeqCst), 1);
+        assert_eq!(accepted2.load(Ordering::SeqCst), 1);
+
+        // The first PLC closed the connection, so the next request reopens it.
+        while !first.is_closed() {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        let state = routes.read_state(AmsAddr::new(plc1, 851)).await.unwrap();
+        assert_eq!(state, (AdsState::Run, 1));
+        assert_eq!(accepted1.load(Ordering::SeqCst), 2);
+
+        let unknown = AmsNetId::new(10, 0, 0, 3, 1, 1);
+        let err = routes.read_state(AmsAddr::new(unknown, 851)).await;
+        assert!(matches!(err, Err(Error::NoRoute(id)) if id == unknown));
+    }
+}
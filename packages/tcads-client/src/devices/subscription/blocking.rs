@@ -0,0 +1,136 @@
+use super::decode;
+use crate::devices::blocking::AdsDevice;
+use std::marker::PhantomData;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::Duration;
+use tcads_core::ads::NotificationHandle;
+use tcads_core::protocol::AdsNotificationSampleOwned;
+use tcads_core::{AdsDecode, AmsAddr, WindowsFileTime};
+
+/// A device notification whose samples are decoded into `T`.
+///
+/// Obtained from [`AdsDevice::subscribe`]. Every sample is yielded together with the
+/// timestamp the PLC stamped it with. The subscription is also an [`Iterator`] over the
+/// samples, which ends once the notification is gone.
+///
+/// # Deletion
+///
+/// Dropping the subscription queues the deletion of the notification on the writer thread
+/// without waiting for the response, so `drop` never blocks. Use
+/// [`unsubscribe`](Self::unsubscribe) to wait for the response and observe any error.
+///
+/// The subscription keeps its [`AdsDevice`] connection alive until it is dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// use tcads_client::AmsAddr;
+/// use tcads_client::devices::blocking::AdsDevice;
+/// use tcads_client::devices::subscription::SubscriptionOptions;
+///
+/// let device = AdsDevice::connect(None)?;
+/// let target = AmsAddr::new(device.get_local_net_id()?, 851);
+///
+/// let count = device.subscribe::<u32>(target, "MAIN.nCount", SubscriptionOptions::new())?;
+/// for sample in count {
+///     let (timestamp, value) = sample?;
+///     println!("{timestamp:?}: {value}");
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct Subscription<T> {
+    device: AdsDevice,
+    target: AmsAddr,
+    handle: NotificationHandle,
+    rx: Receiver<(WindowsFileTime, AdsNotificationSampleOwned)>,
+    deleted: bool,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T> Subscription<T> {
+    pub(crate) fn new(
+        device: AdsDevice,
+        target: AmsAddr,
+        handle: NotificationHandle,
+        rx: Receiver<(WindowsFileTime, AdsNotificationSampleOwned)>,
+    ) -> Self {
+        Self {
+            device,
+            target,
+            handle,
+            rx,
+            deleted: false,
+            _value: PhantomData,
+        }
+    }
+
+    /// Returns the handle of the notification, as returned by
+    /// [`add_notification`](AdsDevice::add_notification).
+    pub fn handle(&self) -> NotificationHandle {
+        self.handle
+    }
+
+    /// Returns the target the notification is registered on.
+    pub fn target(&self) -> AmsAddr {
+        self.target
+    }
+
+    /// Deletes the notification and waits for the target to confirm.
+    pub fn unsubscribe(mut self) -> crate::Result<()> {
+        self.deleted = true;
+        self.device.delete_notification(self.target, self.handle)
+    }
+}
+
+impl<T: AdsDecode> Subscription<T> {
+    /// Blocks until the next sample arrives and decodes it.
+    ///
+    /// Fails with [`Error::Disconnected`](crate::Error::Disconnected) once the
+    /// notification is gone, e.g. because the connection was lost. A sample that cannot
+    /// be decoded into `T` yields an error and the subscription carries on.
+    pub fn recv(&self) -> crate::Result<(WindowsFileTime, T)> {
+        decode(self.rx.recv()?)
+    }
+
+    /// Blocks until the next sample arrives, for at most `timeout`, and decodes it.
+    pub fn recv_timeout(&self, timeout: Duration) -> crate::Result<(WindowsFileTime, T)> {
+        decode(self.rx.recv_timeout(timeout)?)
+    }
+
+    /// Returns the next sample if one has arrived, without blocking.
+    pub fn try_recv(&self) -> crate::Result<Option<(WindowsFileTime, T)>> {
+        match self.rx.try_recv() {
+            Ok(sample) => decode(sample).map(Some),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(crate::Error::Disconnected),
+        }
+    }
+}
+
+impl<T: AdsDecode> Iterator for Subscription<T> {
+    type Item = crate::Result<(WindowsFileTime, T)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rx.recv().ok().map(decode)
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        if !self.deleted {
+            // The connection may already be gone, in which case the notification is gone too
+            let _ = self
+                .device
+                .delete_notification_nowait(self.target, self.handle);
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Subscription<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Subscription")
+            .field("target", &self.target)
+            .field("handle", &self.handle)
+            .finish()
+    }
+}
@@ -0,0 +1,109 @@
+pub mod blocking;
+pub mod tokio;
+
+use std::time::Duration;
+use tcads_core::ads::{AdsTransMode, IndexGroup, IndexOffset};
+use tcads_core::protocol::AdsNotificationSampleOwned;
+use tcads_core::{AdsDecode, WindowsFileTime};
+
+/// The variable watched by a subscription.
+///
+/// Converts from a symbol name, e.g. `"MAIN.nCount"`, or can be given as an
+/// [`address`](Self::address).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum NotificationSource {
+    /// A symbol, resolved to its address and size when subscribing.
+    Symbol(String),
+    /// A raw address.
+    Address {
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        length: u32,
+    },
+}
+
+impl NotificationSource {
+    /// Watches `length` bytes at `index_group` / `index_offset`.
+    pub fn address(index_group: IndexGroup, index_offset: IndexOffset, length: u32) -> Self {
+        Self::Address {
+            index_group,
+            index_offset,
+            length,
+        }
+    }
+}
+
+impl From<&str> for NotificationSource {
+    fn from(name: &str) -> Self {
+        Self::Symbol(name.to_string())
+    }
+}
+
+impl From<String> for NotificationSource {
+    fn from(name: String) -> Self {
+        Self::Symbol(name)
+    }
+}
+
+/// When the PLC sends the samples of a subscription.
+///
+/// Defaults to [`ServerOnChange`](AdsTransMode::ServerOnChange), checked every PLC
+/// cycle and sent without delay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionOptions {
+    pub(crate) trans_mode: AdsTransMode,
+    pub(crate) max_delay: Duration,
+    pub(crate) cycle_time: Duration,
+}
+
+impl SubscriptionOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the transition mode.
+    pub fn with_trans_mode(mut self, trans_mode: AdsTransMode) -> Self {
+        self.trans_mode = trans_mode;
+        self
+    }
+
+    /// Sets how long the PLC may buffer samples before sending them.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Sets how often the PLC checks the variable.
+    pub fn with_cycle_time(mut self, cycle_time: Duration) -> Self {
+        self.cycle_time = cycle_time;
+        self
+    }
+
+    /// Returns the maximum delay in milliseconds, as sent to the PLC.
+    pub(crate) fn max_delay_ms(&self) -> u32 {
+        self.max_delay.as_millis().try_into().unwrap_or(u32::MAX)
+    }
+
+    /// Returns the cycle time in milliseconds, as sent to the PLC.
+    pub(crate) fn cycle_time_ms(&self) -> u32 {
+        self.cycle_time.as_millis().try_into().unwrap_or(u32::MAX)
+    }
+}
+
+impl Default for SubscriptionOptions {
+    fn default() -> Self {
+        Self {
+            trans_mode: AdsTransMode::ServerOnChange,
+            max_delay: Duration::ZERO,
+            cycle_time: Duration::ZERO,
+        }
+    }
+}
+
+/// Decodes a sample received by a subscription.
+fn decode<T: AdsDecode>(
+    (timestamp, sample): (WindowsFileTime, AdsNotificationSampleOwned),
+) -> crate::Result<(WindowsFileTime, T)> {
+    Ok((timestamp, T::decode(sample.data())?))
+}
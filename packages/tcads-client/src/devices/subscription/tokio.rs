@@ -0,0 +1,129 @@
+use super::decode;
+use crate::devices::tokio::AdsDevice;
+use std::marker::PhantomData;
+use std::task::{Context, Poll};
+use tcads_core::ads::NotificationHandle;
+use tcads_core::protocol::AdsNotificationSampleOwned;
+use tcads_core::{AdsDecode, AmsAddr, WindowsFileTime};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+/// A device notification whose samples are decoded into `T`.
+///
+/// Obtained from [`AdsDevice::subscribe`]. Every sample is yielded together with the
+/// timestamp the PLC stamped it with.
+///
+/// # Deletion
+///
+/// Dropping the subscription queues the deletion of the notification on the writer task
+/// without waiting for the response, so `drop` never blocks or awaits. Use
+/// [`unsubscribe`](Self::unsubscribe) to wait for the response and observe any error.
+///
+/// The subscription keeps its [`AdsDevice`] connection alive until it is dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// use tcads_client::AmsAddr;
+/// use tcads_client::devices::subscription::SubscriptionOptions;
+/// use tcads_client::devices::tokio::AdsDevice;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let device = AdsDevice::connect(None).await?;
+/// let target = AmsAddr::new(device.get_local_net_id().await?, 851);
+///
+/// let mut count = device
+///     .subscribe::<u32>(target, "MAIN.nCount", SubscriptionOptions::new())
+///     .await?;
+/// while let Some(sample) = count.recv().await {
+///     let (timestamp, value) = sample?;
+///     println!("{timestamp:?}: {value}");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Subscription<T> {
+    device: AdsDevice,
+    target: AmsAddr,
+    handle: NotificationHandle,
+    rx: UnboundedReceiver<(WindowsFileTime, AdsNotificationSampleOwned)>,
+    deleted: bool,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T> Subscription<T> {
+    pub(crate) fn new(
+        device: AdsDevice,
+        target: AmsAddr,
+        handle: NotificationHandle,
+        rx: UnboundedReceiver<(WindowsFileTime, AdsNotificationSampleOwned)>,
+    ) -> Self {
+        Self {
+            device,
+            target,
+            handle,
+            rx,
+            deleted: false,
+            _value: PhantomData,
+        }
+    }
+
+    /// Returns the handle of the notification, as returned by
+    /// [`add_notification`](AdsDevice::add_notification).
+    pub fn handle(&self) -> NotificationHandle {
+        self.handle
+    }
+
+    /// Returns the target the notification is registered on.
+    pub fn target(&self) -> AmsAddr {
+        self.target
+    }
+
+    /// Deletes the notification and waits for the target to confirm.
+    pub async fn unsubscribe(mut self) -> crate::Result<()> {
+        self.deleted = true;
+        self.device
+            .delete_notification(self.target, self.handle)
+            .await
+    }
+}
+
+impl<T: AdsDecode> Subscription<T> {
+    /// Receives the next sample and decodes it.
+    ///
+    /// Returns [`None`] once the notification is gone, e.g. because the connection was
+    /// lost. A sample that cannot be decoded into `T` yields an error and the
+    /// subscription carries on.
+    pub async fn recv(&mut self) -> Option<crate::Result<(WindowsFileTime, T)>> {
+        std::future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Polls for the next sample, for use in a manual [`Future`] or `Stream`
+    /// implementation.
+    pub fn poll_recv(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<crate::Result<(WindowsFileTime, T)>>> {
+        self.rx.poll_recv(cx).map(|sample| sample.map(decode))
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        if !self.deleted {
+            // The connection may already be gone, in which case the notification is gone too
+            let _ = self
+                .device
+                .delete_notification_nowait(self.target, self.handle);
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Subscription<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Subscription")
+            .field("target", &self.target)
+            .field("handle", &self.handle)
+            .finish()
+    }
+}
@@ -0,0 +1,149 @@
+use crate::devices::blocking::AdsDevice;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tcads_core::ads::index_group;
+use tcads_core::symbol::AdsSymbolEntry;
+use tcads_core::{AdsDecode, AdsEncode, AmsAddr};
+
+/// A symbol handle that is released automatically when dropped.
+///
+/// Obtained from [`AdsDevice::symbol_handle`]. Reads and writes go through
+/// [`SYM_VALBYHND`](index_group::SYM_VALBYHND), which is faster than resolving the
+/// name on every access and stays valid for as long as the handle is held.
+///
+/// # Release
+///
+/// Dropping the handle queues a [`SYM_RELEASEHND`](index_group::SYM_RELEASEHND) write on
+/// the writer thread without waiting for the response, so `drop` never blocks. Use
+/// [`release`](Self::release) to wait for the response and observe any error.
+///
+/// The handle keeps its [`AdsDevice`] connection alive until it is dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// use tcads_client::AmsAddr;
+/// use tcads_client::devices::blocking::AdsDevice;
+///
+/// let device = AdsDevice::connect(None)?;
+/// let target = AmsAddr::new(device.get_local_net_id()?, 851);
+///
+/// let count = device.symbol_handle(target, "MAIN.nCount")?;
+/// count.write(&42u32)?;
+/// assert_eq!(count.read::<u32>()?, 42);
+///
+/// count.release()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct SymbolHandle {
+    device: AdsDevice,
+    target: AmsAddr,
+    /// Shared with a reconnecting device, which replaces it after every reconnect.
+    handle: Arc<AtomicU32>,
+    /// The key the handle is tracked under by a reconnecting device.
+    key: Option<u64>,
+    entry: AdsSymbolEntry,
+    released: bool,
+}
+
+impl SymbolHandle {
+    pub(crate) fn new(
+        device: AdsDevice,
+        target: AmsAddr,
+        handle: Arc<AtomicU32>,
+        key: Option<u64>,
+        entry: AdsSymbolEntry,
+    ) -> Self {
+        Self {
+            device,
+            target,
+            handle,
+            key,
+            entry,
+            released: false,
+        }
+    }
+
+    /// Returns the raw handle value assigned by the target.
+    ///
+    /// On a reconnecting device the value changes when the handle is acquired again
+    /// after a reconnect.
+    pub fn handle(&self) -> u32 {
+        self.handle.load(Ordering::Acquire)
+    }
+
+    /// Returns the target the handle belongs to.
+    pub fn target(&self) -> AmsAddr {
+        self.target
+    }
+
+    /// Returns the symbol name.
+    pub fn name(&self) -> &str {
+        self.entry.name()
+    }
+
+    /// Returns the size of the symbol in bytes.
+    pub fn size(&self) -> u32 {
+        self.entry.size()
+    }
+
+    /// Returns the symbol information read when the handle was acquired.
+    pub fn entry(&self) -> &AdsSymbolEntry {
+        &self.entry
+    }
+
+    /// Reads the raw bytes of the symbol.
+    pub fn read_raw(&self) -> crate::Result<Vec<u8>> {
+        self.device.read(
+            self.target,
+            index_group::SYM_VALBYHND,
+            self.handle(),
+            self.entry.size(),
+        )
+    }
+
+    /// Writes raw bytes to the symbol.
+    pub fn write_raw(&self, data: impl Into<Vec<u8>>) -> crate::Result<()> {
+        self.device
+            .write(self.target, index_group::SYM_VALBYHND, self.handle(), data)
+    }
+
+    /// Reads and decodes the symbol value.
+    pub fn read<T: AdsDecode>(&self) -> crate::Result<T> {
+        Ok(T::decode(&self.read_raw()?)?)
+    }
+
+    /// Encodes and writes the symbol value.
+    pub fn write<T: AdsEncode + ?Sized>(&self, value: &T) -> crate::Result<()> {
+        self.write_raw(value.to_ads_bytes())
+    }
+
+    /// Releases the handle and waits for the target to confirm.
+    pub fn release(mut self) -> crate::Result<()> {
+        self.released = true;
+        self.device.untrack_handle(self.key)?;
+        self.device.release_handle(self.target, self.handle())
+    }
+}
+
+impl Drop for SymbolHandle {
+    fn drop(&mut self) {
+        if !self.released {
+            let _ = self.device.untrack_handle(self.key);
+            // The connection may already be gone, in which case the handle is gone too
+            let _ = self
+                .device
+                .release_handle_nowait(self.target, self.handle());
+        }
+    }
+}
+
+impl std::fmt::Debug for SymbolHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SymbolHandle")
+            .field("name", &self.entry.name())
+            .field("target", &self.target)
+            .field("handle", &self.handle())
+            .finish()
+    }
+}
@@ -0,0 +1,269 @@
+use crate::devices::tokio::AdsDevice;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tcads_core::ads::index_group;
+use tcads_core::symbol::AdsSymbolEntry;
+use tcads_core::{AdsDecode, AdsEncode, AmsAddr};
+
+/// A symbol handle that is released automatically when dropped.
+///
+/// Obtained from [`AdsDevice::symbol_handle`]. Reads and writes go through
+/// [`SYM_VALBYHND`](index_group::SYM_VALBYHND), which is faster than resolving the
+/// name on every access and stays valid for as long as the handle is held.
+///
+/// # Release
+///
+/// Dropping the handle queues a [`SYM_RELEASEHND`](index_group::SYM_RELEASEHND) write on
+/// the writer task without waiting for the response, so `drop` never blocks or awaits. Use
+/// [`release`](Self::release) to wait for the response and observe any error.
+///
+/// The handle keeps its [`AdsDevice`] connection alive until it is dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// use tcads_client::AmsAddr;
+/// use tcads_client::devices::tokio::AdsDevice;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let device = AdsDevice::connect(None).await?;
+/// let target = AmsAddr::new(device.get_local_net_id().await?, 851);
+///
+/// let count = device.symbol_handle(target, "MAIN.nCount").await?;
+/// count.write(&42u32).await?;
+/// assert_eq!(count.read::<u32>().await?, 42);
+///
+/// count.release().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct SymbolHandle {
+    device: AdsDevice,
+    target: AmsAddr,
+    /// Shared with a reconnecting device, which replaces it after every reconnect.
+    handle: Arc<AtomicU32>,
+    /// The key the handle is tracked under by a reconnecting device.
+    key: Option<u64>,
+    entry: AdsSymbolEntry,
+    released: bool,
+}
+
+impl SymbolHandle {
+    pub(crate) fn new(
+        device: AdsDevice,
+        target: AmsAddr,
+        handle: Arc<AtomicU32>,
+        key: Option<u64>,
+        entry: AdsSymbolEntry,
+    ) -> Self {
+        Self {
+            device,
+            target,
+            handle,
+            key,
+            entry,
+            released: false,
+        }
+    }
+
+    /// Returns the raw handle value assigned by the target.
+    ///
+    /// On a reconnecting device the value changes when the handle is acquired again
+    /// after a reconnect.
+    pub fn handle(&self) -> u32 {
+        self.handle.load(Ordering::Acquire)
+    }
+
+    /// Returns the target the handle belongs to.
+    pub fn target(&self) -> AmsAddr {
+        self.target
+    }
+
+    /// Returns the symbol name.
+    pub fn name(&self) -> &str {
+        self.entry.name()
+    }
+
+    /// Returns the size of the symbol in bytes.
+    pub fn size(&self) -> u32 {
+        self.entry.size()
+    }
+
+    /// Returns the symbol information read when the handle was acquired.
+    pub fn entry(&self) -> &AdsSymbolEntry {
+        &self.entry
+    }
+
+    /// Reads the raw bytes of the symbol.
+    pub async fn read_raw(&self) -> crate::Result<Vec<u8>> {
+        self.device
+            .read(
+                self.target,
+                index_group::SYM_VALBYHND,
+                self.handle(),
+                self.entry.size(),
+            )
+            .await
+    }
+
+    /// Writes raw bytes to the symbol.
+    pub async fn write_raw(&self, data: impl Into<Vec<u8>>) -> crate::Result<()> {
+        self.device
+            .write(self.target, index_group::SYM_VALBYHND, self.handle(), data)
+            .await
+    }
+
+    /// Reads and decodes the symbol value.
+    pub async fn read<T: AdsDecode>(&self) -> crate::Result<T> {
+        Ok(T::decode(&self.read_raw().await?)?)
+    }
+
+    /// Encodes and writes the symbol value.
+    pub async fn write<T: AdsEncode + ?Sized>(&self, value: &T) -> crate::Result<()> {
+        self.write_raw(value.to_ads_bytes()).await
+    }
+
+    /// Releases the handle and waits for the target to confirm.
+    pub async fn release(mut self) -> crate::Result<()> {
+        self.released = true;
+        self.device.untrack_handle(self.key)?;
+        self.device.release_handle(self.target, self.handle()).await
+    }
+}
+
+impl Drop for SymbolHandle {
+    fn drop(&mut self) {
+        if !self.released {
+            let _ = self.device.untrack_handle(self.key);
+            // The connection may already be gone, in which case the handle is gone too
+            let _ = self
+                .device
+                .release_handle_nowait(self.target, self.handle());
+        }
+    }
+}
+
+impl std::fmt::Debug for SymbolHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SymbolHandle")
+            .field("name", &self.entry.name())
+            .field("target", &self.target)
+            .field("handle", &self.handle())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tcads_core::io::tokio::AmsStream;
+    use tcads_core::protocol::{
+        AdsReadResponseOwned, AdsReadWriteRequest, AdsReadWriteResponseOwned, AdsWriteRequest,
+        AdsWriteResponse,
+    };
+    use tcads_core::symbol::AdsDataTypeId;
+    use tcads_core::{AdsCommand, AdsHeader, AdsReturnCode};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+    /// Serves `MAIN.nCount` (UDINT = 42) under handle 7 and reports released handles.
+    async fn spawn_mock_plc() -> (std::net::SocketAddr, UnboundedReceiver<u32>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (released_tx, released_rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut stream = AmsStream::new(socket);
+            let entry =
+                AdsSymbolEntry::new("MAIN.nCount", "UDINT", AdsDataTypeId::UInt32, 0x4040, 0, 4);
+
+            while let Ok(frame) = stream.read_frame().await {
+                let (header, _) = AdsHeader::parse_prefix(frame.payload()).unwrap();
+                let (target, source, id) = (*header.source(), *header.target(), header.invoke_id());
+
+                let resp = match header.command_id() {
+                    AdsCommand::AdsReadWrite => {
+                        let req = AdsReadWriteRequest::try_from(&frame).unwrap();
+                        let data = match req.index_group() {
+                            index_group::SYM_INFOBYNAMEEX => entry.to_bytes().unwrap(),
+                            index_group::SYM_HNDBYNAME => 7u32.to_le_bytes().to_vec(),
+                            group => panic!("unexpected index group {group:#X}"),
+                        };
+                        AdsReadWriteResponseOwned::new(target, source, id, AdsReturnCode::Ok, data)
+                            .into_frame()
+                    }
+                    AdsCommand::AdsRead => AdsReadResponseOwned::new(
+                        target,
+                        source,
+                        id,
+                        AdsReturnCode::Ok,
+                        42u32.to_le_bytes(),
+                    )
+                    .into_frame(),
+                    AdsCommand::AdsWrite => {
+                        let req = AdsWriteRequest::try_from(&frame).unwrap();
+                        if req.index_group() == index_group::SYM_RELEASEHND {
+                            released_tx.send(u32::decode(req.data()).unwrap()).unwrap();
+                        }
+                        AdsWriteResponse::new(target, source, id, AdsReturnCode::Ok).into_frame()
+                    }
+                    command => panic!("unexpected command {command:?}"),
+                };
+                stream.write_frame(&resp).await.unwrap();
+            }
+        });
+
+        (addr, released_rx)
+    }
+
+    async fn connect(addr: std::net::SocketAddr) -> (AdsDevice, AmsAddr) {
+        let source = "127.0.0.1.1.1:32768".parse().unwrap();
+        let device = AdsDevice::connect_with_source(addr, source, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        (device, "127.0.0.1.1.1:851".parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn reads_typed_value_through_handle() {
+        let (addr, _released) = spawn_mock_plc().await;
+        let (device, target) = connect(addr).await;
+
+        let handle = device.symbol_handle(target, "MAIN.nCount").await.unwrap();
+        assert_eq!(handle.handle(), 7);
+        assert_eq!(handle.size(), 4);
+        assert_eq!(handle.read::<u32>().await.unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn drop_releases_handle_without_awaiting() {
+        let (addr, mut released) = spawn_mock_plc().await;
+        let (device, target) = connect(addr).await;
+
+        let handle = device.symbol_handle(target, "MAIN.nCount").await.unwrap();
+        drop(handle);
+        assert_eq!(released.recv().await, Some(7));
+
+        // The release response arrives before this one and is discarded without being late
+        device.read(target, 0x4040, 0, 4).await.unwrap();
+        assert_eq!(device.late_responses(), 0);
+    }
+
+    #[tokio::test]
+    async fn explicit_release_waits_for_confirmation() {
+        let (addr, mut released) = spawn_mock_plc().await;
+        let (device, target) = connect(addr).await;
+
+        let handle = device.symbol_handle(target, "MAIN.nCount").await.unwrap();
+        handle.release().await.unwrap();
+
+        assert_eq!(released.try_recv(), Ok(7));
+        assert!(
+            released.try_recv().is_err(),
+            "Handle must be released only once"
+        );
+    }
+}
@@ -1,8 +1,14 @@
+use crate::symbols::ValueError;
 use std::io;
 use std::sync::mpsc::{RecvError, RecvTimeoutError, SendError};
 use std::sync::{Arc, PoisonError};
-use tcads_core::ads::AdsReturnCode;
+use tcads_core::ads::{AdsReturnCode, AdsTypeError, InvokeId};
+use tcads_core::ams::{AmsNetId, AmsTransport};
 use tcads_core::protocol::ProtocolError;
+use tcads_core::symbol::SymbolError;
+use tcads_core::udp::UdpError;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::error::Elapsed;
 
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum Error {
@@ -12,10 +18,30 @@ pub enum Error {
     Protocol(#[from] ProtocolError),
     #[error(transparent)]
     AdsReturnCode(#[from] AdsReturnCode),
+    #[error("Symbol error: {0}")]
+    Symbol(#[from] SymbolError),
+    #[error("Value error: {0}")]
+    Type(#[from] AdsTypeError),
+    #[error("Dynamic value error: {0}")]
+    Value(#[from] ValueError),
+    #[error("AMS/UDP error: {0}")]
+    Udp(#[from] UdpError),
+    #[error("Authentication failed: {0}")]
+    AuthenticationFailed(AdsReturnCode),
+    #[error("No route to {0}")]
+    NoRoute(AmsNetId),
+    #[error("{0} transport is not supported")]
+    UnsupportedTransport(AmsTransport),
     #[error("Disconnected")]
     Disconnected,
     #[error("Timed out")]
     Timeout,
+    #[error("Cancelled")]
+    Cancelled,
+    #[error("Invoke ID {0} is already in use")]
+    InvokeIdInUse(InvokeId),
+    #[error("No invoke IDs left")]
+    InvokeIdsExhausted,
     #[error("Poisoned lock")]
     PoisonedLock,
 }
@@ -55,6 +81,24 @@ impl From<RecvTimeoutError> for Error {
     }
 }
 
+impl From<oneshot::error::RecvError> for Error {
+    fn from(_: oneshot::error::RecvError) -> Self {
+        Error::Disconnected
+    }
+}
+
+impl<T> From<mpsc::error::SendError<T>> for Error {
+    fn from(_: mpsc::error::SendError<T>) -> Self {
+        Error::Disconnected
+    }
+}
+
+impl From<Elapsed> for Error {
+    fn from(_: Elapsed) -> Self {
+        Error::Timeout
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -78,4 +122,32 @@ mod tests {
         let err = Error::from(mutex.lock().unwrap_err());
         assert!(matches!(err, Error::PoisonedLock));
     }
+
+    #[tokio::test]
+    async fn tokio_channel_errors_convert_to_disconnected() {
+        let (tx, rx) = oneshot::channel::<()>();
+        drop(tx);
+        assert!(matches!(
+            Error::from(rx.await.unwrap_err()),
+            Error::Disconnected
+        ));
+
+        let (tx, rx) = mpsc::unbounded_channel::<()>();
+        drop(rx);
+        assert!(matches!(
+            Error::from(tx.send(()).unwrap_err()),
+            Error::Disconnected
+        ));
+    }
+
+    #[tokio::test]
+    async fn elapsed_converts_to_timeout() {
+        let elapsed = tokio::time::timeout(
+            std::time::Duration::from_millis(1),
+            std::future::pending::<()>(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(Error::from(elapsed), Error::Timeout));
+    }
 }
@@ -1,9 +1,14 @@
 pub mod devices;
 pub mod error;
+pub mod symbols;
 pub mod tasks;
+pub mod udp;
 
 pub use tcads_core::{
-    ads::{AdsReturnCode, AdsState, AdsTransMode, DeviceState, IndexGroup, IndexOffset, InvokeId},
+    ads::{
+        AdsDecode, AdsEncode, AdsReturnCode, AdsState, AdsTransMode, AdsType, DeviceState,
+        IndexGroup, IndexOffset, InvokeId, index_group,
+    },
     ams::{AmsAddr, AmsNetId, AmsPort, RouterState},
     protocol::{AdsNotificationSampleOwned, ProtocolError},
 };
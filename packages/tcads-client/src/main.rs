@@ -1,11 +1,8 @@
 use std::time::Duration;
 use tcads_client::devices::ads_device::blocking::AdsDevice;
+use tcads_client::index_group::SYM_VALBYHND;
 use tcads_client::{AdsState, AdsTransMode, AmsAddr};
 
-const GET_SYMHANDLE_BYNAME: u32 = 0xF003;
-const READ_WRITE_SYMVAL_BYHANDLE: u32 = 0xF005;
-const RELEASE_SYMHANDLE: u32 = 0xF006;
-
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let device = AdsDevice::connect(None)?;
 
@@ -29,43 +26,19 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     device.write_control(target, AdsState::Run, 0, [])?;
     println!("PLC state: {:?}", device.read_state(target)?);
 
-    let var_handle = u32::from_le_bytes(
-        (*device.read_write(
-            target,
-            GET_SYMHANDLE_BYNAME,
-            0,
-            size_of::<u32>() as u32,
-            b"MAIN.nCount\0",
-        )?)
-        .try_into()?,
-    );
-
-    println!("Variable handle is for MAIN.nCount is {}", var_handle);
+    let count = device.symbol_handle(target, "MAIN.nCount")?;
 
-    device.write(
-        target,
-        READ_WRITE_SYMVAL_BYHANDLE,
-        var_handle,
-        42u32.to_le_bytes(),
-    )?;
+    println!("Variable handle is for MAIN.nCount is {}", count.handle());
 
-    let value = u32::from_le_bytes(
-        (*device.read(
-            target,
-            READ_WRITE_SYMVAL_BYHANDLE,
-            var_handle,
-            size_of::<u32>() as u32,
-        )?)
-        .try_into()?,
-    );
+    count.write(&42u32)?;
 
-    println!("Value of MAIN.nCount is {}", value);
+    println!("Value of MAIN.nCount is {}", count.read::<u32>()?);
 
     let (sample_rx, notif_handle) = device.add_notification(
         target,
-        READ_WRITE_SYMVAL_BYHANDLE,
-        var_handle,
-        size_of::<u32>() as u32,
+        SYM_VALBYHND,
+        count.handle(),
+        count.size(),
         AdsTransMode::ServerOnChange,
         0,
         10,
@@ -85,7 +58,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     device.delete_notification(target, notif_handle)?;
-    device.write(target, RELEASE_SYMHANDLE, 0, var_handle.to_le_bytes())?;
+    count.release()?;
     device.shutdown()?;
 
     Ok(())
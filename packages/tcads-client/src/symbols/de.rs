@@ -0,0 +1,451 @@
+//! A [`serde::Deserializer`] over PLC memory.
+//!
+//! The bytes of a symbol or data type instance are walked using the layout from the
+//! [`SymbolTable`], so any `#[derive(Deserialize)]` type, or a self-describing value such as
+//! `serde_json::Value`, can be filled straight from the output of `AdsDevice::read`. Struct
+//! members are presented under their PLC names, in declaration order.
+
+use super::shape::{Member, Primitive, Shape, TypeRef, members};
+use super::table::SymbolTable;
+use super::value::{ValueError, bit, decode_string, decode_wstring, skip, take};
+use serde::de::value::{BoolDeserializer, StrDeserializer};
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use tcads_core::AdsDecode;
+use tcads_core::ads::{
+    AdsDate, AdsDateTime, AdsLDate, AdsLDateTime, AdsLTime, AdsLTimeOfDay, AdsTime, AdsTimeOfDay,
+};
+use tcads_core::symbol::{AdsDatatypeEntry, AdsSymbolEntry};
+
+/// Deserializes a `T` from the bytes read from `symbol`.
+///
+/// # Example
+///
+/// ```no_run
+/// use serde::Deserialize;
+/// use tcads_client::AmsAddr;
+/// use tcads_client::devices::blocking::AdsDevice;
+/// use tcads_client::symbols::de;
+///
+/// #[derive(Deserialize)]
+/// struct Axis {
+///     #[serde(rename = "fVelocity")]
+///     velocity: f64,
+///     #[serde(rename = "bEnabled")]
+///     enabled: bool,
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let device = AdsDevice::connect(None)?;
+/// let target = AmsAddr::new(device.get_local_net_id()?, 851);
+/// let table = device.upload_symbol_table(target)?;
+///
+/// let symbol = table.symbol("MAIN.stAxis").ok_or("no such symbol")?;
+/// let bytes = device.read(target, symbol.index_group(), symbol.index_offset(), symbol.size())?;
+/// let axis: Axis = de::from_symbol(&table, symbol, &bytes)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn from_symbol<'de, T: de::Deserialize<'de>>(
+    table: &'de SymbolTable,
+    symbol: &'de AdsSymbolEntry,
+    bytes: &'de [u8],
+) -> Result<T, ValueError> {
+    T::deserialize(Deserializer::from_symbol(table, symbol, bytes)?)
+}
+
+/// Deserializes a `T` from an instance of `datatype`.
+pub fn from_datatype<'de, T: de::Deserialize<'de>>(
+    table: &'de SymbolTable,
+    datatype: &'de AdsDatatypeEntry,
+    bytes: &'de [u8],
+) -> Result<T, ValueError> {
+    T::deserialize(Deserializer::from_datatype(table, datatype, bytes)?)
+}
+
+/// A [`serde::Deserializer`] over the memory of one PLC value.
+pub struct Deserializer<'de> {
+    table: &'de SymbolTable,
+    shape: Shape<'de>,
+    bytes: &'de [u8],
+}
+
+impl<'de> Deserializer<'de> {
+    /// Creates a deserializer over the bytes read from `symbol`.
+    pub fn from_symbol(
+        table: &'de SymbolTable,
+        symbol: &'de AdsSymbolEntry,
+        bytes: &'de [u8],
+    ) -> Result<Self, ValueError> {
+        Ok(Self {
+            table,
+            shape: table.shape(TypeRef::of_symbol(symbol))?,
+            bytes,
+        })
+    }
+
+    /// Creates a deserializer over an instance of `datatype`.
+    pub fn from_datatype(
+        table: &'de SymbolTable,
+        datatype: &'de AdsDatatypeEntry,
+        bytes: &'de [u8],
+    ) -> Result<Self, ValueError> {
+        Ok(Self {
+            table,
+            shape: table.shape_of(datatype)?,
+            bytes,
+        })
+    }
+
+    /// Returns the name of the enumeration variant, if the value is an enumeration with one.
+    fn enum_name(&self) -> Result<Option<&'de str>, ValueError> {
+        let Shape::Enum { base, infos } = self.shape else {
+            return Ok(None);
+        };
+        let value = int(base, self.bytes)?;
+        Ok(infos
+            .iter()
+            .find(|info| info.value() == Some(value))
+            .map(|info| info.name()))
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = ValueError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        let bytes = self.bytes;
+        match self.shape {
+            Shape::Primitive(primitive) => visit_primitive(primitive, bytes, visitor),
+            Shape::String(size) => visitor.visit_string(decode_string(bytes, size)?),
+            Shape::WString(size) => visitor.visit_string(decode_wstring(bytes, size)?),
+            Shape::Enum { base, .. } => match self.enum_name()? {
+                Some(name) => visitor.visit_borrowed_str(name),
+                None => visitor.visit_i64(int(base, bytes)?),
+            },
+            Shape::Array {
+                count,
+                stride,
+                element,
+                ..
+            } => visitor.visit_seq(SeqAccess {
+                table: self.table,
+                shape: self.table.shape(element)?,
+                bytes,
+                index: 0,
+                count,
+                stride,
+            }),
+            Shape::Struct(datatype) => visitor.visit_map(MapAccess {
+                table: self.table,
+                bytes,
+                members: Box::new(members(datatype)),
+                pending: None,
+            }),
+            Shape::Raw(size) => visitor.visit_borrowed_bytes(take(bytes, size)?),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ValueError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ValueError> {
+        match self.shape {
+            Shape::Enum { base, .. } => match self.enum_name()? {
+                Some(name) => visitor.visit_enum(name.into_deserializer()),
+                None => Err(ValueError::Custom(format!(
+                    "no variant with value {}",
+                    int(base, self.bytes)?
+                ))),
+            },
+            Shape::String(size) => {
+                visitor.visit_enum(decode_string(self.bytes, size)?.into_deserializer())
+            }
+            Shape::WString(size) => {
+                visitor.visit_enum(decode_wstring(self.bytes, size)?.into_deserializer())
+            }
+            shape => Err(ValueError::Mismatch {
+                expected: "enum",
+                got: shape.describe(),
+            }),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ValueError> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+    }
+}
+
+/// Visits the elements of one array dimension.
+struct SeqAccess<'de> {
+    table: &'de SymbolTable,
+    shape: Shape<'de>,
+    bytes: &'de [u8],
+    index: usize,
+    count: usize,
+    stride: usize,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'de> {
+    type Error = ValueError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, ValueError> {
+        if self.index == self.count {
+            return Ok(None);
+        }
+
+        let bytes = skip(self.bytes, self.index * self.stride)?;
+        self.index += 1;
+        seed.deserialize(Deserializer {
+            table: self.table,
+            shape: self.shape,
+            bytes,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.count - self.index)
+    }
+}
+
+/// Visits the members of a struct, keyed by their PLC names.
+struct MapAccess<'de> {
+    table: &'de SymbolTable,
+    bytes: &'de [u8],
+    members: Box<dyn Iterator<Item = Member<'de>> + 'de>,
+    pending: Option<Member<'de>>,
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess<'de> {
+    type Error = ValueError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, ValueError> {
+        let Some(member) = self.members.next() else {
+            return Ok(None);
+        };
+        let name = match member {
+            Member::Bit { name, .. } | Member::Field { name, .. } => name,
+        };
+        self.pending = Some(member);
+        seed.deserialize(StrDeserializer::<ValueError>::new(name))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, ValueError> {
+        match self.pending.take() {
+            Some(Member::Bit { offset, .. }) => {
+                seed.deserialize(BoolDeserializer::new(bit(self.bytes, offset)?))
+            }
+            Some(Member::Field { offset, ty, .. }) => seed.deserialize(Deserializer {
+                table: self.table,
+                shape: self.table.shape(ty)?,
+                bytes: skip(self.bytes, offset)?,
+            }),
+            None => Err(ValueError::Custom("value requested before key".into())),
+        }
+    }
+}
+
+fn visit_primitive<'de, V: Visitor<'de>>(
+    primitive: Primitive,
+    bytes: &[u8],
+    visitor: V,
+) -> Result<V::Value, ValueError> {
+    match primitive {
+        Primitive::Bool => visitor.visit_bool(bool::decode(bytes)?),
+        Primitive::SInt => visitor.visit_i8(i8::decode(bytes)?),
+        Primitive::USInt => visitor.visit_u8(u8::decode(bytes)?),
+        Primitive::Int => visitor.visit_i16(i16::decode(bytes)?),
+        Primitive::UInt => visitor.visit_u16(u16::decode(bytes)?),
+        Primitive::DInt => visitor.visit_i32(i32::decode(bytes)?),
+        Primitive::UDInt => visitor.visit_u32(u32::decode(bytes)?),
+        Primitive::LInt => visitor.visit_i64(i64::decode(bytes)?),
+        Primitive::ULInt => visitor.visit_u64(u64::decode(bytes)?),
+        Primitive::Real => visitor.visit_f32(f32::decode(bytes)?),
+        Primitive::LReal => visitor.visit_f64(f64::decode(bytes)?),
+        // Time and date types are presented as their IEC literals, which is also how their
+        // `Deserialize` implementations read them.
+        Primitive::Time => visitor.visit_string(AdsTime::decode(bytes)?.to_string()),
+        Primitive::LTime => visitor.visit_string(AdsLTime::decode(bytes)?.to_string()),
+        Primitive::Date => visitor.visit_string(AdsDate::decode(bytes)?.to_string()),
+        Primitive::LDate => visitor.visit_string(AdsLDate::decode(bytes)?.to_string()),
+        Primitive::TimeOfDay => visitor.visit_string(AdsTimeOfDay::decode(bytes)?.to_string()),
+        Primitive::LTimeOfDay => visitor.visit_string(AdsLTimeOfDay::decode(bytes)?.to_string()),
+        Primitive::DateTime => visitor.visit_string(AdsDateTime::decode(bytes)?.to_string()),
+        Primitive::LDateTime => visitor.visit_string(AdsLDateTime::decode(bytes)?.to_string()),
+    }
+}
+
+/// Reads the integer base type of an enumeration.
+fn int(base: Primitive, bytes: &[u8]) -> Result<i64, ValueError> {
+    Ok(match base {
+        Primitive::SInt => i8::decode(bytes)? as i64,
+        Primitive::USInt => u8::decode(bytes)? as i64,
+        Primitive::Int => i16::decode(bytes)? as i64,
+        Primitive::UInt => u16::decode(bytes)? as i64,
+        Primitive::DInt => i32::decode(bytes)? as i64,
+        Primitive::UDInt => u32::decode(bytes)? as i64,
+        Primitive::LInt => i64::decode(bytes)?,
+        Primitive::ULInt => {
+            i64::try_from(u64::decode(bytes)?).map_err(|_| ValueError::OutOfRange("enum"))?
+        }
+        _ => return Err(ValueError::OutOfRange("enum")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbols::ser;
+    use serde::{Deserialize, Serialize};
+    use tcads_core::symbol::{AdsArrayInfo, AdsDataTypeId, AdsDatatypeFlags, AdsEnumInfo};
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    enum Mode {
+        Idle,
+        Auto,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Data {
+        #[serde(rename = "eMode")]
+        mode: Mode,
+        #[serde(rename = "sName")]
+        name: String,
+        #[serde(rename = "bFlag")]
+        flag: bool,
+        #[serde(rename = "tDelay")]
+        delay: AdsTime,
+        #[serde(rename = "aValues")]
+        values: [i16; 2],
+    }
+
+    /// `ST_Data` with an `E_Mode` enum, a `STRING(7)`, a `BIT`, a `TIME` and an
+    /// `ARRAY[1..2] OF INT`, and a symbol `MAIN.stData`.
+    fn table() -> SymbolTable {
+        let mode =
+            AdsDatatypeEntry::new("E_Mode", "INT", AdsDataTypeId::Int16, 2).with_enum_infos(vec![
+                AdsEnumInfo::new("Idle", 0i16.to_le_bytes()),
+                AdsEnumInfo::new("Auto", 1i16.to_le_bytes()),
+            ]);
+        let data =
+            AdsDatatypeEntry::new("ST_Data", "", AdsDataTypeId::BigType, 20).with_sub_items(vec![
+                AdsDatatypeEntry::new_item("eMode", "E_Mode", AdsDataTypeId::Int16, 2, 0),
+                AdsDatatypeEntry::new_item("sName", "STRING(7)", AdsDataTypeId::String, 8, 2),
+                AdsDatatypeEntry::new_item("bFlag", "BIT", AdsDataTypeId::Bit, 1, 81).with_flags(
+                    AdsDatatypeFlags::new(
+                        AdsDatatypeFlags::DATAITEM | AdsDatatypeFlags::BIT_VALUES,
+                    ),
+                ),
+                AdsDatatypeEntry::new_item("tDelay", "TIME", AdsDataTypeId::UInt32, 4, 12),
+                AdsDatatypeEntry::new_item(
+                    "aValues",
+                    "ARRAY [1..2] OF INT",
+                    AdsDataTypeId::Int16,
+                    4,
+                    16,
+                )
+                .with_array_info(vec![AdsArrayInfo::new(1, 2)]),
+            ]);
+
+        SymbolTable::new(
+            vec![AdsSymbolEntry::new(
+                "MAIN.stData",
+                "ST_Data",
+                AdsDataTypeId::BigType,
+                0x4040,
+                0,
+                20,
+            )],
+            vec![mode, data],
+        )
+    }
+
+    fn bytes() -> Vec<u8> {
+        let mut bytes = vec![0u8; 20];
+        bytes[0..2].copy_from_slice(&1i16.to_le_bytes());
+        bytes[2..6].copy_from_slice(b"Axis");
+        bytes[10] = 0b10;
+        bytes[12..16].copy_from_slice(&1500u32.to_le_bytes());
+        bytes[16..18].copy_from_slice(&(-3i16).to_le_bytes());
+        bytes[18..20].copy_from_slice(&7i16.to_le_bytes());
+        bytes
+    }
+
+    fn data() -> Data {
+        Data {
+            mode: Mode::Auto,
+            name: "Axis".into(),
+            flag: true,
+            delay: AdsTime::from_millis(1500),
+            values: [-3, 7],
+        }
+    }
+
+    #[test]
+    fn deserializes_derived_struct() {
+        let table = table();
+        let symbol = table.symbol("MAIN.stData").unwrap();
+        let bytes = bytes();
+
+        assert_eq!(from_symbol::<Data>(&table, symbol, &bytes).unwrap(), data());
+    }
+
+    #[test]
+    fn deserializes_json_value() {
+        let table = table();
+        let symbol = table.symbol("MAIN.stData").unwrap();
+        let bytes = bytes();
+
+        let value: serde_json::Value = from_symbol(&table, symbol, &bytes).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "eMode": "Auto",
+                "sName": "Axis",
+                "bFlag": true,
+                "tDelay": "T#1s500ms",
+                "aValues": [-3, 7],
+            })
+        );
+    }
+
+    #[test]
+    fn serializes_back_to_memory() {
+        let table = table();
+        let symbol = table.symbol("MAIN.stData").unwrap();
+
+        assert_eq!(ser::to_symbol(&table, symbol, &data()).unwrap(), bytes());
+
+        let json: serde_json::Value = from_symbol(&table, symbol, &bytes()).unwrap();
+        assert_eq!(ser::to_symbol(&table, symbol, &json).unwrap(), bytes());
+    }
+}
@@ -0,0 +1,23 @@
+//! Symbol table browsing.
+//!
+//! [`SymbolTable`] holds the symbols and data types uploaded from a PLC with
+//! `AdsDevice::upload_symbol_table` and provides case-insensitive lookup, search,
+//! and a hierarchical [`SymbolNode`] tree expanded through the data type table. Values of
+//! any symbol can be decoded into a dynamic [`AdsValue`] through the same table, or into any
+//! serde type with [`de::from_symbol`] and back with [`ser::to_symbol`].
+
+pub mod de;
+pub mod ser;
+pub(crate) mod shape;
+pub mod table;
+pub mod tree;
+pub mod value;
+
+pub use table::SymbolTable;
+pub use tree::{SymbolNode, SymbolNodeKind};
+pub use value::{AdsValue, ValueError};
+
+pub use tcads_core::symbol::{
+    AdsArrayInfo, AdsAttribute, AdsDataTypeId, AdsDatatypeEntry, AdsDatatypeFlags, AdsEnumInfo,
+    AdsSymbolEntry, AdsSymbolFlags, AdsSymbolUploadInfo, SymbolError,
+};
@@ -0,0 +1,403 @@
+//! A [`serde::Serializer`] producing PLC memory.
+//!
+//! Any `Serialize` type is first turned into an [`AdsValue`], which is then encoded against
+//! the layout from the [`SymbolTable`]. Struct fields are matched to PLC members by name
+//! (case-insensitive), so a Rust type only needs the PLC's field names, not its offsets.
+//! Every member of the PLC type must be present.
+
+use super::table::SymbolTable;
+use super::value::{AdsValue, ValueError};
+use serde::Serialize;
+use serde::ser::{self, Impossible};
+use tcads_core::symbol::{AdsDatatypeEntry, AdsSymbolEntry};
+
+/// Serializes `value` into the bytes to write to `symbol`.
+///
+/// # Example
+///
+/// ```no_run
+/// use serde::Serialize;
+/// use tcads_client::AmsAddr;
+/// use tcads_client::devices::blocking::AdsDevice;
+/// use tcads_client::symbols::ser;
+///
+/// #[derive(Serialize)]
+/// struct Axis {
+///     #[serde(rename = "fVelocity")]
+///     velocity: f64,
+///     #[serde(rename = "bEnabled")]
+///     enabled: bool,
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let device = AdsDevice::connect(None)?;
+/// let target = AmsAddr::new(device.get_local_net_id()?, 851);
+/// let table = device.upload_symbol_table(target)?;
+///
+/// let symbol = table.symbol("MAIN.stAxis").ok_or("no such symbol")?;
+/// let axis = Axis { velocity: 250.0, enabled: true };
+/// let bytes = ser::to_symbol(&table, symbol, &axis)?;
+/// device.write(target, symbol.index_group(), symbol.index_offset(), bytes)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn to_symbol<T: Serialize + ?Sized>(
+    table: &SymbolTable,
+    symbol: &AdsSymbolEntry,
+    value: &T,
+) -> Result<Vec<u8>, ValueError> {
+    table.encode_symbol(symbol, &to_value(value)?)
+}
+
+/// Serializes `value` as an instance of `datatype`.
+pub fn to_datatype<T: Serialize + ?Sized>(
+    table: &SymbolTable,
+    datatype: &AdsDatatypeEntry,
+    value: &T,
+) -> Result<Vec<u8>, ValueError> {
+    table.encode_datatype(datatype, &to_value(value)?)
+}
+
+/// Converts `value` into an [`AdsValue`].
+///
+/// Sequences become arrays indexed from 0, structs and maps with string keys become
+/// structs, and unit enum variants become enumerations matched by name. `None`, unit values
+/// and enum variants carrying data have no PLC equivalent and are rejected.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<AdsValue, ValueError> {
+    value.serialize(Serializer)
+}
+
+/// A [`serde::Serializer`] producing an [`AdsValue`].
+pub struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = AdsValue;
+    type Error = ValueError;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = Impossible<AdsValue, ValueError>;
+    type SerializeMap = SerializeStruct;
+    type SerializeStruct = SerializeStruct;
+    type SerializeStructVariant = Impossible<AdsValue, ValueError>;
+
+    fn serialize_bool(self, v: bool) -> Result<AdsValue, ValueError> {
+        Ok(AdsValue::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<AdsValue, ValueError> {
+        Ok(AdsValue::SInt(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<AdsValue, ValueError> {
+        Ok(AdsValue::Int(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<AdsValue, ValueError> {
+        Ok(AdsValue::DInt(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<AdsValue, ValueError> {
+        Ok(AdsValue::LInt(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<AdsValue, ValueError> {
+        Ok(AdsValue::USInt(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<AdsValue, ValueError> {
+        Ok(AdsValue::UInt(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<AdsValue, ValueError> {
+        Ok(AdsValue::UDInt(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<AdsValue, ValueError> {
+        Ok(AdsValue::ULInt(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<AdsValue, ValueError> {
+        Ok(AdsValue::Real(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<AdsValue, ValueError> {
+        Ok(AdsValue::LReal(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<AdsValue, ValueError> {
+        Ok(AdsValue::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<AdsValue, ValueError> {
+        Ok(AdsValue::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<AdsValue, ValueError> {
+        Ok(AdsValue::Raw(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<AdsValue, ValueError> {
+        Err(ValueError::Unsupported("None"))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<AdsValue, ValueError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<AdsValue, ValueError> {
+        Err(ValueError::Unsupported("unit"))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<AdsValue, ValueError> {
+        Err(ValueError::Unsupported("unit struct"))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<AdsValue, ValueError> {
+        Ok(AdsValue::Enum {
+            name: Some(variant.to_string()),
+            value: variant_index as i64,
+        })
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<AdsValue, ValueError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<AdsValue, ValueError> {
+        Err(ValueError::Unsupported("enum variant with data"))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, ValueError> {
+        Ok(SerializeArray(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, ValueError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, ValueError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, ValueError> {
+        Err(ValueError::Unsupported("enum variant with data"))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeStruct, ValueError> {
+        Ok(SerializeStruct {
+            fields: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeStruct, ValueError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, ValueError> {
+        Err(ValueError::Unsupported("enum variant with data"))
+    }
+}
+
+/// Collects the elements of an array.
+pub struct SerializeArray(Vec<AdsValue>);
+
+impl SerializeArray {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        self.0.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn finish(self) -> AdsValue {
+        AdsValue::Array {
+            lower_bound: 0,
+            items: self.0,
+        }
+    }
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = AdsValue;
+    type Error = ValueError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<AdsValue, ValueError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = AdsValue;
+    type Error = ValueError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<AdsValue, ValueError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = AdsValue;
+    type Error = ValueError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<AdsValue, ValueError> {
+        Ok(self.finish())
+    }
+}
+
+/// Collects the members of a struct.
+pub struct SerializeStruct {
+    fields: Vec<(String, AdsValue)>,
+    key: Option<String>,
+}
+
+impl ser::SerializeMap for SerializeStruct {
+    type Ok = AdsValue;
+    type Error = ValueError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), ValueError> {
+        match to_value(key)? {
+            AdsValue::String(key) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            key => Err(ValueError::Mismatch {
+                expected: "STRING",
+                got: key.kind(),
+            }),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ValueError::Custom("value serialized before key".into()))?;
+        self.fields.push((key, to_value(value)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<AdsValue, ValueError> {
+        Ok(AdsValue::Struct(self.fields))
+    }
+}
+
+impl ser::SerializeStruct for SerializeStruct {
+    type Ok = AdsValue;
+    type Error = ValueError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ValueError> {
+        self.fields.push((key.to_string(), to_value(value)?));
+        Ok(())
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), ValueError> {
+        Err(ValueError::MissingField(key.to_string()))
+    }
+
+    fn end(self) -> Result<AdsValue, ValueError> {
+        Ok(AdsValue::Struct(self.fields))
+    }
+}
+
+impl Serialize for AdsValue {
+    /// Serializes the value in the shape [`de::Deserializer`](super::de::Deserializer)
+    /// presents PLC memory in: enumerations by name where known, time and date types as
+    /// their IEC literals, and structs as maps keyed by member name.
+    fn serialize<S: ser::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        use ser::{SerializeMap, SerializeSeq};
+
+        match self {
+            Self::Bool(v) => s.serialize_bool(*v),
+            Self::SInt(v) => s.serialize_i8(*v),
+            Self::USInt(v) => s.serialize_u8(*v),
+            Self::Int(v) => s.serialize_i16(*v),
+            Self::UInt(v) => s.serialize_u16(*v),
+            Self::DInt(v) => s.serialize_i32(*v),
+            Self::UDInt(v) => s.serialize_u32(*v),
+            Self::LInt(v) => s.serialize_i64(*v),
+            Self::ULInt(v) => s.serialize_u64(*v),
+            Self::Real(v) => s.serialize_f32(*v),
+            Self::LReal(v) => s.serialize_f64(*v),
+            Self::String(v) | Self::WString(v) => s.serialize_str(v),
+            Self::Time(v) => v.serialize(s),
+            Self::LTime(v) => v.serialize(s),
+            Self::Date(v) => v.serialize(s),
+            Self::LDate(v) => v.serialize(s),
+            Self::TimeOfDay(v) => v.serialize(s),
+            Self::LTimeOfDay(v) => v.serialize(s),
+            Self::DateTime(v) => v.serialize(s),
+            Self::LDateTime(v) => v.serialize(s),
+            Self::Enum {
+                name: Some(name), ..
+            } => s.serialize_str(name),
+            Self::Enum { value, .. } => s.serialize_i64(*value),
+            Self::Array { items, .. } => {
+                let mut seq = s.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            Self::Struct(fields) => {
+                let mut map = s.serialize_map(Some(fields.len()))?;
+                for (name, value) in fields {
+                    map.serialize_entry(name, value)?;
+                }
+                map.end()
+            }
+            Self::Raw(bytes) => s.serialize_bytes(bytes),
+        }
+    }
+}
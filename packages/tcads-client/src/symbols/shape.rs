@@ -0,0 +1,354 @@
+use super::table::SymbolTable;
+use super::value::ValueError;
+use tcads_core::symbol::{
+    AdsArrayInfo, AdsDataTypeId, AdsDatatypeEntry, AdsDatatypeFlags, AdsEnumInfo, AdsSymbolEntry,
+};
+
+/// Maximum nesting depth when resolving types, guarding against recursive type definitions.
+const MAX_DEPTH: usize = 32;
+
+/// A reference to a PLC type, as found on a symbol or a struct member.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TypeRef<'a> {
+    pub(crate) type_name: &'a str,
+    pub(crate) data_type: AdsDataTypeId,
+    pub(crate) size: usize,
+    pub(crate) array_info: &'a [AdsArrayInfo],
+    pub(crate) is_reference: bool,
+}
+
+impl<'a> TypeRef<'a> {
+    pub(crate) fn of_symbol(symbol: &'a AdsSymbolEntry) -> Self {
+        Self {
+            type_name: symbol.type_name(),
+            data_type: symbol.data_type(),
+            size: symbol.size() as usize,
+            array_info: symbol.array_info(),
+            is_reference: symbol.flags().is_reference(),
+        }
+    }
+
+    pub(crate) fn of_item(item: &'a AdsDatatypeEntry) -> Self {
+        Self {
+            type_name: item.type_name(),
+            data_type: item.data_type(),
+            size: item.size() as usize,
+            array_info: item.array_info(),
+            is_reference: item.flags().is_reference(),
+        }
+    }
+}
+
+/// The scalar types with a fixed encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Primitive {
+    Bool,
+    SInt,
+    USInt,
+    Int,
+    UInt,
+    DInt,
+    UDInt,
+    LInt,
+    ULInt,
+    Real,
+    LReal,
+    Time,
+    LTime,
+    Date,
+    LDate,
+    TimeOfDay,
+    LTimeOfDay,
+    DateTime,
+    LDateTime,
+}
+
+impl Primitive {
+    /// Returns the IEC name of the type.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Bool => "BOOL",
+            Self::SInt => "SINT",
+            Self::USInt => "USINT",
+            Self::Int => "INT",
+            Self::UInt => "UINT",
+            Self::DInt => "DINT",
+            Self::UDInt => "UDINT",
+            Self::LInt => "LINT",
+            Self::ULInt => "ULINT",
+            Self::Real => "REAL",
+            Self::LReal => "LREAL",
+            Self::Time => "TIME",
+            Self::LTime => "LTIME",
+            Self::Date => "DATE",
+            Self::LDate => "LDATE",
+            Self::TimeOfDay => "TOD",
+            Self::LTimeOfDay => "LTOD",
+            Self::DateTime => "DT",
+            Self::LDateTime => "LDT",
+        }
+    }
+
+    /// Returns `true` for the time and date types, which have IEC literals.
+    pub(crate) fn is_time(self) -> bool {
+        !matches!(
+            self,
+            Self::Bool
+                | Self::SInt
+                | Self::USInt
+                | Self::Int
+                | Self::UInt
+                | Self::DInt
+                | Self::UDInt
+                | Self::LInt
+                | Self::ULInt
+                | Self::Real
+                | Self::LReal
+        )
+    }
+
+    /// Looks up the types that share a base type ID with others, e.g. `TIME` and `UDINT`.
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_uppercase().as_str() {
+            "BOOL" | "BIT" => Self::Bool,
+            "TIME" => Self::Time,
+            "LTIME" => Self::LTime,
+            "DATE" => Self::Date,
+            "LDATE" => Self::LDate,
+            "TIME_OF_DAY" | "TOD" => Self::TimeOfDay,
+            "LTIME_OF_DAY" | "LTOD" => Self::LTimeOfDay,
+            "DATE_AND_TIME" | "DT" => Self::DateTime,
+            "LDATE_AND_TIME" | "LDT" => Self::LDateTime,
+            _ => return None,
+        })
+    }
+
+    fn from_id(id: AdsDataTypeId) -> Option<Self> {
+        Some(match id {
+            AdsDataTypeId::Int8 => Self::SInt,
+            AdsDataTypeId::UInt8 => Self::USInt,
+            AdsDataTypeId::Int16 => Self::Int,
+            AdsDataTypeId::UInt16 => Self::UInt,
+            AdsDataTypeId::Int32 => Self::DInt,
+            AdsDataTypeId::UInt32 => Self::UDInt,
+            AdsDataTypeId::Int64 => Self::LInt,
+            AdsDataTypeId::UInt64 => Self::ULInt,
+            AdsDataTypeId::Real32 => Self::Real,
+            AdsDataTypeId::Real64 => Self::LReal,
+            AdsDataTypeId::Bit => Self::Bool,
+            _ => return None,
+        })
+    }
+}
+
+/// The memory layout of a PLC type, resolved through the data type table.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Shape<'a> {
+    Primitive(Primitive),
+    /// A `STRING` of `size` bytes, including the terminator.
+    String(usize),
+    /// A `WSTRING` of `size` bytes, including the terminator.
+    WString(usize),
+    Enum {
+        base: Primitive,
+        infos: &'a [AdsEnumInfo],
+    },
+    /// One dimension of an array. Multi-dimensional arrays nest, outermost first.
+    Array {
+        lower_bound: i32,
+        count: usize,
+        stride: usize,
+        element: TypeRef<'a>,
+    },
+    Struct(&'a AdsDatatypeEntry),
+    /// Memory that is passed through untouched, such as pointers and references.
+    Raw(usize),
+}
+
+/// A struct member with storage in the instance.
+pub(crate) enum Member<'a> {
+    /// A `BIT` member at bit `offset` from the start of the struct.
+    Bit { name: &'a str, offset: usize },
+    Field {
+        name: &'a str,
+        offset: usize,
+        ty: TypeRef<'a>,
+    },
+}
+
+impl Shape<'_> {
+    /// Returns a short description for error messages.
+    pub(crate) fn describe(&self) -> &'static str {
+        match self {
+            Self::Primitive(primitive) => primitive.name(),
+            Self::String(_) => "STRING",
+            Self::WString(_) => "WSTRING",
+            Self::Enum { .. } => "enum",
+            Self::Array { .. } => "array",
+            Self::Struct(_) => "struct",
+            Self::Raw(_) => "raw bytes",
+        }
+    }
+}
+
+/// Returns the members of `datatype` that occupy instance memory, skipping properties and
+/// static members.
+pub(crate) fn members(datatype: &AdsDatatypeEntry) -> impl Iterator<Item = Member<'_>> {
+    datatype
+        .sub_items()
+        .iter()
+        .filter(|item| {
+            !item.flags().is_property() && !item.flags().contains(AdsDatatypeFlags::STATIC)
+        })
+        .map(|item| match item.flags().is_bit_values() {
+            true => Member::Bit {
+                name: item.name(),
+                offset: item.offset() as usize,
+            },
+            false => Member::Field {
+                name: item.name(),
+                offset: item.offset() as usize,
+                ty: TypeRef::of_item(item),
+            },
+        })
+}
+
+impl SymbolTable {
+    /// Resolves `ty` to its memory layout.
+    pub(crate) fn shape<'a>(&'a self, ty: TypeRef<'a>) -> Result<Shape<'a>, ValueError> {
+        self.shape_at(ty, 0)
+    }
+
+    /// Resolves a data type entry to its memory layout.
+    pub(crate) fn shape_of<'a>(
+        &'a self,
+        datatype: &'a AdsDatatypeEntry,
+    ) -> Result<Shape<'a>, ValueError> {
+        self.entry_shape(datatype, datatype.size() as usize, 0)
+    }
+
+    fn shape_at<'a>(&'a self, ty: TypeRef<'a>, depth: usize) -> Result<Shape<'a>, ValueError> {
+        if depth >= MAX_DEPTH {
+            return Err(ValueError::TooDeep);
+        }
+
+        if let Some((dim, rest)) = ty.array_info.split_first() {
+            let count = dim.elements() as usize;
+            return Ok(Shape::Array {
+                lower_bound: dim.lower_bound(),
+                count,
+                stride: ty.size.checked_div(count).unwrap_or(0),
+                element: TypeRef {
+                    type_name: element_name(ty.type_name),
+                    size: ty.size.checked_div(count).unwrap_or(0),
+                    array_info: rest,
+                    ..ty
+                },
+            });
+        }
+
+        if ty.is_reference {
+            return Ok(Shape::Raw(ty.size));
+        }
+
+        if let Some(primitive) = Primitive::from_name(ty.type_name) {
+            return Ok(Shape::Primitive(primitive));
+        }
+
+        if let Some(datatype) = self.datatype(ty.type_name) {
+            return self.entry_shape(datatype, ty.size, depth);
+        }
+
+        Ok(id_shape(ty.data_type, ty.size))
+    }
+
+    fn entry_shape<'a>(
+        &'a self,
+        datatype: &'a AdsDatatypeEntry,
+        size: usize,
+        depth: usize,
+    ) -> Result<Shape<'a>, ValueError> {
+        if !datatype.array_info().is_empty() {
+            return self.shape_at(
+                TypeRef {
+                    type_name: datatype.type_name(),
+                    data_type: datatype.data_type(),
+                    size,
+                    array_info: datatype.array_info(),
+                    is_reference: false,
+                },
+                depth + 1,
+            );
+        }
+
+        if !datatype.enum_infos().is_empty()
+            && let Some(base) = Primitive::from_id(datatype.data_type())
+        {
+            return Ok(Shape::Enum {
+                base,
+                infos: datatype.enum_infos(),
+            });
+        }
+
+        if !datatype.sub_items().is_empty() {
+            return Ok(Shape::Struct(datatype));
+        }
+
+        if let Some(primitive) = Primitive::from_name(datatype.name()) {
+            return Ok(Shape::Primitive(primitive));
+        }
+
+        // Follow aliases (`TYPE T_Alias : ST_Data; END_TYPE`) to the underlying type
+        let base = datatype.type_name();
+        if !base.is_empty() && !base.eq_ignore_ascii_case(datatype.name()) {
+            return self.shape_at(
+                TypeRef {
+                    type_name: base,
+                    data_type: datatype.data_type(),
+                    size,
+                    array_info: &[],
+                    is_reference: datatype.flags().is_reference(),
+                },
+                depth + 1,
+            );
+        }
+
+        Ok(id_shape(datatype.data_type(), size))
+    }
+}
+
+fn id_shape(data_type: AdsDataTypeId, size: usize) -> Shape<'static> {
+    match data_type {
+        AdsDataTypeId::String => Shape::String(size),
+        AdsDataTypeId::WString => Shape::WString(size),
+        id => match Primitive::from_id(id) {
+            Some(primitive) => Shape::Primitive(primitive),
+            None => Shape::Raw(size),
+        },
+    }
+}
+
+/// Returns `INT` for `ARRAY [0..9] OF INT`, or `type_name` if it does not name an array.
+fn element_name(type_name: &str) -> &str {
+    let upper = type_name.to_ascii_uppercase();
+    match upper
+        .starts_with("ARRAY")
+        .then(|| upper.find(" OF "))
+        .flatten()
+    {
+        Some(index) => type_name[index + 4..].trim(),
+        None => type_name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn element_name_strips_array_prefix() {
+        assert_eq!(element_name("ARRAY [0..9] OF INT"), "INT");
+        assert_eq!(element_name("ARRAY [1..2, 0..3] OF ST_Data"), "ST_Data");
+        assert_eq!(element_name("ST_Data"), "ST_Data");
+    }
+}
@@ -0,0 +1,151 @@
+use super::tree::SymbolNode;
+use std::collections::HashMap;
+use tcads_core::symbol::{AdsDatatypeEntry, AdsSymbolEntry};
+
+/// An uploaded PLC symbol table together with its data type table.
+///
+/// Lookups are case-insensitive, matching the PLC's identifier rules.
+///
+/// # Example
+///
+/// ```no_run
+/// use tcads_client::AmsAddr;
+/// use tcads_client::devices::blocking::AdsDevice;
+///
+/// let device = AdsDevice::connect(None)?;
+/// let target = AmsAddr::new(device.get_local_net_id()?, 851);
+/// let table = device.upload_symbol_table(target)?;
+///
+/// for symbol in table.search("main.") {
+///     println!("{}: {}", symbol.name(), symbol.type_name());
+/// }
+///
+/// let tree = table.tree();
+/// if let Some(node) = tree.find("MAIN.stData.nValue") {
+///     println!("{} @ {:#X}:{:#X}", node.path(), node.index_group(), node.index_offset());
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    symbols: Vec<AdsSymbolEntry>,
+    datatypes: Vec<AdsDatatypeEntry>,
+    symbols_by_name: HashMap<String, usize>,
+    datatypes_by_name: HashMap<String, usize>,
+}
+
+impl SymbolTable {
+    /// Creates a symbol table from uploaded symbols and data types.
+    pub fn new(symbols: Vec<AdsSymbolEntry>, datatypes: Vec<AdsDatatypeEntry>) -> Self {
+        let symbols_by_name = symbols
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.name().to_ascii_lowercase(), i))
+            .collect();
+        let datatypes_by_name = datatypes
+            .iter()
+            .enumerate()
+            .map(|(i, d)| (d.name().to_ascii_lowercase(), i))
+            .collect();
+
+        Self {
+            symbols,
+            datatypes,
+            symbols_by_name,
+            datatypes_by_name,
+        }
+    }
+
+    /// Returns all symbols in upload order.
+    pub fn symbols(&self) -> &[AdsSymbolEntry] {
+        &self.symbols
+    }
+
+    /// Returns all data types in upload order.
+    pub fn datatypes(&self) -> &[AdsDatatypeEntry] {
+        &self.datatypes
+    }
+
+    /// Returns the number of symbols.
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Returns `true` if the table contains no symbols.
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Returns the symbol `name`, e.g. `MAIN.nCount`.
+    pub fn symbol(&self, name: &str) -> Option<&AdsSymbolEntry> {
+        self.symbols_by_name
+            .get(&name.to_ascii_lowercase())
+            .map(|&i| &self.symbols[i])
+    }
+
+    /// Returns the data type `name`, e.g. `ST_Data`.
+    pub fn datatype(&self, name: &str) -> Option<&AdsDatatypeEntry> {
+        self.datatypes_by_name
+            .get(&name.to_ascii_lowercase())
+            .map(|&i| &self.datatypes[i])
+    }
+
+    /// Returns the data type of `symbol`, if it is a user-defined or structured type.
+    pub fn datatype_of(&self, symbol: &AdsSymbolEntry) -> Option<&AdsDatatypeEntry> {
+        self.datatype(symbol.type_name())
+    }
+
+    /// Returns every symbol whose name contains `pattern` (case-insensitive).
+    pub fn search<'a>(&'a self, pattern: &str) -> impl Iterator<Item = &'a AdsSymbolEntry> + 'a {
+        let pattern = pattern.to_ascii_lowercase();
+        self.symbols
+            .iter()
+            .filter(move |s| s.name().to_ascii_lowercase().contains(&pattern))
+    }
+
+    /// Builds the hierarchical symbol tree.
+    ///
+    /// Symbol names are split on `.` into [namespace](super::SymbolNodeKind::Namespace)
+    /// nodes (e.g. `MAIN`, `GVL`), and the members of structured symbols are expanded
+    /// from the data type table.
+    pub fn tree(&self) -> SymbolNode {
+        SymbolNode::build(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tcads_core::symbol::AdsDataTypeId;
+
+    fn table() -> SymbolTable {
+        SymbolTable::new(
+            vec![
+                AdsSymbolEntry::new("MAIN.nCount", "DINT", AdsDataTypeId::Int32, 0x4040, 0, 4),
+                AdsSymbolEntry::new("GVL.bRun", "BOOL", AdsDataTypeId::BigType, 0x4040, 8, 1),
+            ],
+            vec![AdsDatatypeEntry::new(
+                "ST_Data",
+                "",
+                AdsDataTypeId::BigType,
+                8,
+            )],
+        )
+    }
+
+    #[test]
+    fn lookups_are_case_insensitive() {
+        let table = table();
+        assert_eq!(table.symbol("main.NCOUNT").unwrap().size(), 4);
+        assert!(table.datatype("st_data").is_some());
+        assert!(table.symbol("MAIN.missing").is_none());
+    }
+
+    #[test]
+    fn search_matches_substrings() {
+        let table = table();
+        let names: Vec<_> = table.search("run").map(|s| s.name()).collect();
+        assert_eq!(names, ["GVL.bRun"]);
+        assert_eq!(table.search("").count(), 2);
+    }
+}
@@ -0,0 +1,330 @@
+use super::table::SymbolTable;
+use std::collections::HashMap;
+use tcads_core::symbol::{AdsDataTypeId, AdsDatatypeEntry};
+use tcads_core::{IndexGroup, IndexOffset};
+
+/// Maximum nesting depth when expanding members, guarding against recursive type definitions.
+const MAX_DEPTH: usize = 32;
+
+/// The kind of a [`SymbolNode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolNodeKind {
+    /// A grouping node with no storage of its own, e.g. `MAIN` or `GVL`.
+    Namespace,
+    /// A symbol from the symbol table, e.g. `MAIN.stData`.
+    Symbol,
+    /// A member of a structured symbol, e.g. `MAIN.stData.nValue`.
+    Member,
+}
+
+/// A node of the hierarchical symbol tree built by [`SymbolTable::tree`].
+///
+/// [`Symbol`](SymbolNodeKind::Symbol) and [`Member`](SymbolNodeKind::Member) nodes carry the
+/// index group and offset needed to read or write them directly.
+///
+/// # Note
+///
+/// Members of bit-packed types report the offset of the byte containing them, and their
+/// [`size`](Self::size) is given in bits. Members reached through a `REFERENCE TO` are
+/// not expanded since their storage lives elsewhere.
+#[derive(Debug, Clone)]
+pub struct SymbolNode {
+    name: String,
+    path: String,
+    kind: SymbolNodeKind,
+    type_name: String,
+    comment: String,
+    data_type: AdsDataTypeId,
+    size: u32,
+    index_group: IndexGroup,
+    index_offset: IndexOffset,
+    children: Vec<SymbolNode>,
+    child_index: HashMap<String, usize>,
+}
+
+impl SymbolNode {
+    fn namespace(name: &str, path: String) -> Self {
+        Self {
+            name: name.to_string(),
+            path,
+            kind: SymbolNodeKind::Namespace,
+            type_name: String::new(),
+            comment: String::new(),
+            data_type: AdsDataTypeId::Void,
+            size: 0,
+            index_group: 0,
+            index_offset: 0,
+            children: Vec::new(),
+            child_index: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn build(table: &SymbolTable) -> Self {
+        let mut root = Self::namespace("", String::new());
+
+        for symbol in table.symbols() {
+            let mut node = &mut root;
+            let mut segments = symbol.name().split('.').peekable();
+
+            while let Some(segment) = segments.next() {
+                let path = match node.path.is_empty() {
+                    true => segment.to_string(),
+                    false => format!("{}.{}", node.path, segment),
+                };
+                let is_leaf = segments.peek().is_none();
+
+                let index = match node.child_index.get(&segment.to_ascii_lowercase()) {
+                    Some(&index) => index,
+                    None => node.push(Self::namespace(segment, path)),
+                };
+                node = &mut node.children[index];
+
+                if is_leaf {
+                    node.kind = SymbolNodeKind::Symbol;
+                    node.type_name = symbol.type_name().to_string();
+                    node.comment = symbol.comment().to_string();
+                    node.data_type = symbol.data_type();
+                    node.size = symbol.size();
+                    node.index_group = symbol.index_group();
+                    node.index_offset = symbol.index_offset();
+
+                    if !symbol.flags().is_reference() {
+                        node.expand(table, symbol.type_name(), 0);
+                    }
+                }
+            }
+        }
+
+        root
+    }
+
+    fn push(&mut self, child: SymbolNode) -> usize {
+        let index = self.children.len();
+        self.child_index
+            .insert(child.name.to_ascii_lowercase(), index);
+        self.children.push(child);
+        index
+    }
+
+    fn expand(&mut self, table: &SymbolTable, type_name: &str, depth: usize) {
+        if depth >= MAX_DEPTH {
+            return;
+        }
+        let Some(datatype) = table.datatype(type_name) else {
+            return;
+        };
+
+        // Follow aliases (`TYPE T_Alias : ST_Data; END_TYPE`) to the underlying type
+        if datatype.sub_items().is_empty() && datatype.array_info().is_empty() {
+            let base = datatype.type_name();
+            if !base.is_empty() && !base.eq_ignore_ascii_case(type_name) {
+                self.expand(table, base, depth + 1);
+            }
+            return;
+        }
+
+        for item in datatype.sub_items() {
+            let mut child = self.member(item);
+            if !item.flags().is_reference() {
+                child.expand(table, item.type_name(), depth + 1);
+            }
+            self.push(child);
+        }
+    }
+
+    fn member(&self, item: &AdsDatatypeEntry) -> Self {
+        let offset = match item.flags().is_bit_values() {
+            true => item.offset() / 8,
+            false => item.offset(),
+        };
+
+        Self {
+            name: item.name().to_string(),
+            path: format!("{}.{}", self.path, item.name()),
+            kind: SymbolNodeKind::Member,
+            type_name: item.type_name().to_string(),
+            comment: item.comment().to_string(),
+            data_type: item.data_type(),
+            size: item.size(),
+            index_group: self.index_group,
+            index_offset: self.index_offset + offset,
+            children: Vec::new(),
+            child_index: HashMap::new(),
+        }
+    }
+
+    /// Returns the last segment of the path, e.g. `nValue`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the full path, e.g. `MAIN.stData.nValue`. Empty for the root.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the kind of node.
+    pub fn kind(&self) -> SymbolNodeKind {
+        self.kind
+    }
+
+    /// Returns the PLC type name. Empty for namespaces.
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    /// Returns the comment.
+    pub fn comment(&self) -> &str {
+        &self.comment
+    }
+
+    /// Returns the base data type.
+    pub fn data_type(&self) -> AdsDataTypeId {
+        self.data_type
+    }
+
+    /// Returns the size in bytes.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns the index group. `0` for namespaces.
+    pub fn index_group(&self) -> IndexGroup {
+        self.index_group
+    }
+
+    /// Returns the index offset. `0` for namespaces.
+    pub fn index_offset(&self) -> IndexOffset {
+        self.index_offset
+    }
+
+    /// Returns the child nodes.
+    pub fn children(&self) -> &[SymbolNode] {
+        &self.children
+    }
+
+    /// Returns the direct child `name` (case-insensitive).
+    pub fn child(&self, name: &str) -> Option<&SymbolNode> {
+        self.child_index
+            .get(&name.to_ascii_lowercase())
+            .map(|&i| &self.children[i])
+    }
+
+    /// Returns the descendant at the dotted `path` (case-insensitive), relative to this node.
+    ///
+    /// Array element paths such as `MAIN.arr[1]` are not part of the tree.
+    pub fn find(&self, path: &str) -> Option<&SymbolNode> {
+        path.split('.')
+            .try_fold(self, |node, segment| node.child(segment))
+    }
+
+    /// Iterates over this node and all its descendants, depth-first.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { stack: vec![self] }
+    }
+}
+
+/// Depth-first iterator over a [`SymbolNode`] and its descendants.
+pub struct Iter<'a> {
+    stack: Vec<&'a SymbolNode>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a SymbolNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children.iter().rev());
+        Some(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tcads_core::symbol::{AdsDatatypeFlags, AdsSymbolEntry, AdsSymbolFlags};
+
+    fn table() -> SymbolTable {
+        SymbolTable::new(
+            vec![
+                AdsSymbolEntry::new("MAIN.nCount", "DINT", AdsDataTypeId::Int32, 0x4040, 0, 4),
+                AdsSymbolEntry::new(
+                    "MAIN.stData",
+                    "T_Alias",
+                    AdsDataTypeId::BigType,
+                    0x4040,
+                    100,
+                    12,
+                ),
+                AdsSymbolEntry::new(
+                    "MAIN.refData",
+                    "ST_Data",
+                    AdsDataTypeId::BigType,
+                    0x4040,
+                    200,
+                    12,
+                )
+                .with_flags(AdsSymbolFlags::new(AdsSymbolFlags::REFERENCE_TO)),
+                AdsSymbolEntry::new("GVL.bRun", "BOOL", AdsDataTypeId::BigType, 0x4040, 8, 1),
+            ],
+            vec![
+                AdsDatatypeEntry::new("T_Alias", "ST_Data", AdsDataTypeId::BigType, 12),
+                AdsDatatypeEntry::new("ST_Data", "", AdsDataTypeId::BigType, 12).with_sub_items([
+                    AdsDatatypeEntry::new_item("nValue", "DINT", AdsDataTypeId::Int32, 4, 0),
+                    AdsDatatypeEntry::new_item("stInner", "ST_Inner", AdsDataTypeId::BigType, 4, 4),
+                    AdsDatatypeEntry::new_item("bBit", "BIT", AdsDataTypeId::Bit, 1, 65)
+                        .with_flags(AdsDatatypeFlags::new(
+                            AdsDatatypeFlags::DATAITEM | AdsDatatypeFlags::BIT_VALUES,
+                        )),
+                ]),
+                AdsDatatypeEntry::new("ST_Inner", "", AdsDataTypeId::BigType, 4).with_sub_items([
+                    AdsDatatypeEntry::new_item("wWord", "WORD", AdsDataTypeId::UInt16, 2, 2),
+                ]),
+            ],
+        )
+    }
+
+    #[test]
+    fn groups_symbols_under_namespaces() {
+        let tree = table().tree();
+        let names: Vec<_> = tree.children().iter().map(|n| n.name()).collect();
+        assert_eq!(names, ["MAIN", "GVL"]);
+
+        let main = tree.child("main").unwrap();
+        assert_eq!(main.kind(), SymbolNodeKind::Namespace);
+        assert_eq!(main.children().len(), 3);
+    }
+
+    #[test]
+    fn expands_members_through_aliases() {
+        let tree = table().tree();
+
+        let inner = tree.find("MAIN.stData.stInner.wWord").unwrap();
+        assert_eq!(inner.kind(), SymbolNodeKind::Member);
+        assert_eq!(inner.path(), "MAIN.stData.stInner.wWord");
+        assert_eq!(inner.index_group(), 0x4040);
+        assert_eq!(inner.index_offset(), 106);
+    }
+
+    #[test]
+    fn bit_members_report_containing_byte() {
+        let tree = table().tree();
+        assert_eq!(tree.find("MAIN.stData.bBit").unwrap().index_offset(), 108);
+    }
+
+    #[test]
+    fn references_are_not_expanded() {
+        let tree = table().tree();
+        assert!(tree.find("MAIN.refData").unwrap().children().is_empty());
+    }
+
+    #[test]
+    fn iter_walks_depth_first() {
+        let tree = table().tree();
+        let paths: Vec<_> = tree.iter().skip(1).take(4).map(|n| n.path()).collect();
+        assert_eq!(
+            paths,
+            ["MAIN", "MAIN.nCount", "MAIN.stData", "MAIN.stData.nValue"]
+        );
+    }
+}
@@ -0,0 +1,784 @@
+use super::shape::{Member, Primitive, Shape, TypeRef, members};
+use super::table::SymbolTable;
+use encoding_rs::WINDOWS_1252;
+use tcads_core::ads::{
+    AdsDate, AdsDateTime, AdsLDate, AdsLDateTime, AdsLTime, AdsLTimeOfDay, AdsTime, AdsTimeError,
+    AdsTimeOfDay, AdsTypeError, AdsWStringBuf,
+};
+use tcads_core::symbol::{AdsDatatypeEntry, AdsSymbolEntry};
+use tcads_core::{AdsDecode, AdsEncode};
+
+/// Error returned when decoding or encoding an [`AdsValue`] fails.
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+pub enum ValueError {
+    #[error("Unexpected length: expected {expected} bytes, got {got}")]
+    UnexpectedLength { expected: usize, got: usize },
+    #[error("Type mismatch: expected a value for {expected}, got {got}")]
+    Mismatch {
+        expected: &'static str,
+        got: &'static str,
+    },
+    #[error("Value out of range for {0}")]
+    OutOfRange(&'static str),
+    #[error("Missing field: {0}")]
+    MissingField(String),
+    #[error("Unknown enum value: {0}")]
+    UnknownEnumName(String),
+    #[error("Array length mismatch: expected {expected} elements, got {got}")]
+    ArrayLength { expected: usize, got: usize },
+    #[error("String too long: maximum {max} characters, got {got}")]
+    StringTooLong { max: usize, got: usize },
+    #[error("String contains characters not supported by Windows-1252 encoding")]
+    EncodingError,
+    #[error("Unknown symbol: {0}")]
+    UnknownSymbol(String),
+    #[error("Type nesting too deep")]
+    TooDeep,
+    #[error("Invalid time: {0}")]
+    InvalidTime(#[from] AdsTimeError),
+    #[error("Unsupported value: {0}")]
+    Unsupported(&'static str),
+    #[error("{0}")]
+    Custom(String),
+}
+
+impl serde::de::Error for ValueError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+}
+
+impl serde::ser::Error for ValueError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+}
+
+impl From<AdsTypeError> for ValueError {
+    fn from(err: AdsTypeError) -> Self {
+        match err {
+            AdsTypeError::UnexpectedLength { expected, got } => {
+                Self::UnexpectedLength { expected, got }
+            }
+            AdsTypeError::UnknownEnumValue { type_name, .. } => Self::OutOfRange(type_name),
+        }
+    }
+}
+
+/// A PLC value of any type, decoded at runtime from the data type table.
+///
+/// Produced by [`SymbolTable::decode_symbol`] from the raw bytes of a read, and turned back
+/// into bytes by [`SymbolTable::encode_symbol`], so arbitrary structures can be inspected and
+/// edited without a matching Rust type.
+///
+/// # Example
+///
+/// ```no_run
+/// use tcads_client::AmsAddr;
+/// use tcads_client::devices::blocking::AdsDevice;
+/// use tcads_client::symbols::AdsValue;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let device = AdsDevice::connect(None)?;
+/// let target = AmsAddr::new(device.get_local_net_id()?, 851);
+/// let table = device.upload_symbol_table(target)?;
+///
+/// let mut value = device.read_symbol_value(target, &table, "MAIN.stAxis")?;
+/// if let Some(field) = value.field_mut("fVelocity") {
+///     *field = AdsValue::LReal(250.0);
+/// }
+/// device.write_symbol_value(target, &table, "MAIN.stAxis", &value)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdsValue {
+    Bool(bool),
+    SInt(i8),
+    USInt(u8),
+    Int(i16),
+    UInt(u16),
+    DInt(i32),
+    UDInt(u32),
+    LInt(i64),
+    ULInt(u64),
+    Real(f32),
+    LReal(f64),
+    String(String),
+    WString(String),
+    Time(AdsTime),
+    LTime(AdsLTime),
+    Date(AdsDate),
+    LDate(AdsLDate),
+    TimeOfDay(AdsTimeOfDay),
+    LTimeOfDay(AdsLTimeOfDay),
+    DateTime(AdsDateTime),
+    LDateTime(AdsLDateTime),
+    /// An enumeration value, with the name of the matching variant if there is one.
+    Enum {
+        name: Option<String>,
+        value: i64,
+    },
+    /// One dimension of an array, indexed from `lower_bound`. Multi-dimensional arrays nest.
+    Array {
+        lower_bound: i32,
+        items: Vec<AdsValue>,
+    },
+    /// The members of a structure or function block, in declaration order.
+    Struct(Vec<(String, AdsValue)>),
+    /// Memory passed through untouched, such as pointers, references and interfaces.
+    Raw(Vec<u8>),
+}
+
+impl AdsValue {
+    /// Returns the member `name` of a struct (case-insensitive).
+    pub fn field(&self, name: &str) -> Option<&AdsValue> {
+        match self {
+            Self::Struct(fields) => fields
+                .iter()
+                .find(|(field, _)| field.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Returns the member `name` of a struct (case-insensitive) for editing.
+    pub fn field_mut(&mut self, name: &str) -> Option<&mut AdsValue> {
+        match self {
+            Self::Struct(fields) => fields
+                .iter_mut()
+                .find(|(field, _)| field.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Returns the array element at PLC index `index`, honouring the lower bound.
+    pub fn element(&self, index: i32) -> Option<&AdsValue> {
+        match self {
+            Self::Array { lower_bound, items } => {
+                let i = usize::try_from(index.checked_sub(*lower_bound)?).ok()?;
+                items.get(i)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the array element at PLC index `index` for editing.
+    pub fn element_mut(&mut self, index: i32) -> Option<&mut AdsValue> {
+        match self {
+            Self::Array { lower_bound, items } => {
+                let i = usize::try_from(index.checked_sub(*lower_bound)?).ok()?;
+                items.get_mut(i)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `BOOL`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of any integer or enumeration, if it fits an `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_i128().and_then(|value| i64::try_from(value).ok())
+    }
+
+    /// Returns the value of any number as an `f64`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Real(value) => Some(*value as f64),
+            Self::LReal(value) => Some(*value),
+            other => other.as_i128().map(|value| value as f64),
+        }
+    }
+
+    /// Returns the content of a `STRING` or `WSTRING`.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) | Self::WString(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns a short description of the variant for error messages.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Bool(_) => "BOOL",
+            Self::SInt(_) => "SINT",
+            Self::USInt(_) => "USINT",
+            Self::Int(_) => "INT",
+            Self::UInt(_) => "UINT",
+            Self::DInt(_) => "DINT",
+            Self::UDInt(_) => "UDINT",
+            Self::LInt(_) => "LINT",
+            Self::ULInt(_) => "ULINT",
+            Self::Real(_) => "REAL",
+            Self::LReal(_) => "LREAL",
+            Self::String(_) => "STRING",
+            Self::WString(_) => "WSTRING",
+            Self::Time(_) => "TIME",
+            Self::LTime(_) => "LTIME",
+            Self::Date(_) => "DATE",
+            Self::LDate(_) => "LDATE",
+            Self::TimeOfDay(_) => "TOD",
+            Self::LTimeOfDay(_) => "LTOD",
+            Self::DateTime(_) => "DT",
+            Self::LDateTime(_) => "LDT",
+            Self::Enum { .. } => "enum",
+            Self::Array { .. } => "array",
+            Self::Struct(_) => "struct",
+            Self::Raw(_) => "raw bytes",
+        }
+    }
+
+    fn as_i128(&self) -> Option<i128> {
+        Some(match self {
+            Self::SInt(value) => *value as i128,
+            Self::USInt(value) => *value as i128,
+            Self::Int(value) => *value as i128,
+            Self::UInt(value) => *value as i128,
+            Self::DInt(value) => *value as i128,
+            Self::UDInt(value) => *value as i128,
+            Self::LInt(value) => *value as i128,
+            Self::ULInt(value) => *value as i128,
+            Self::Enum { value, .. } => *value as i128,
+            _ => return None,
+        })
+    }
+}
+
+impl SymbolTable {
+    /// Decodes the bytes read from `symbol` into an [`AdsValue`].
+    pub fn decode_symbol(
+        &self,
+        symbol: &AdsSymbolEntry,
+        bytes: &[u8],
+    ) -> Result<AdsValue, ValueError> {
+        decode(self, self.shape(TypeRef::of_symbol(symbol))?, bytes)
+    }
+
+    /// Decodes an instance of `datatype` into an [`AdsValue`].
+    pub fn decode_datatype(
+        &self,
+        datatype: &AdsDatatypeEntry,
+        bytes: &[u8],
+    ) -> Result<AdsValue, ValueError> {
+        decode(self, self.shape_of(datatype)?, bytes)
+    }
+
+    /// Encodes `value` into the bytes to write to `symbol`.
+    ///
+    /// Struct members are matched by name and members not present in the symbol's type are
+    /// ignored. Padding is zero-filled. Integers are accepted for any integer type they fit
+    /// in, enumerations are matched by name if one is given and by value otherwise, and time
+    /// and date types also accept their IEC literal as a string, e.g. `T#1s`.
+    pub fn encode_symbol(
+        &self,
+        symbol: &AdsSymbolEntry,
+        value: &AdsValue,
+    ) -> Result<Vec<u8>, ValueError> {
+        let mut buf = vec![0; symbol.size() as usize];
+        encode(
+            self,
+            self.shape(TypeRef::of_symbol(symbol))?,
+            value,
+            &mut buf,
+        )?;
+        Ok(buf)
+    }
+
+    /// Encodes `value` as an instance of `datatype`. See [`encode_symbol`](Self::encode_symbol).
+    pub fn encode_datatype(
+        &self,
+        datatype: &AdsDatatypeEntry,
+        value: &AdsValue,
+    ) -> Result<Vec<u8>, ValueError> {
+        let mut buf = vec![0; datatype.size() as usize];
+        encode(self, self.shape_of(datatype)?, value, &mut buf)?;
+        Ok(buf)
+    }
+}
+
+/// Returns `len` bytes from the start of `bytes`.
+pub(crate) fn take(bytes: &[u8], len: usize) -> Result<&[u8], ValueError> {
+    bytes.get(..len).ok_or(ValueError::UnexpectedLength {
+        expected: len,
+        got: bytes.len(),
+    })
+}
+
+/// Returns the bytes from `offset` onwards.
+pub(crate) fn skip(bytes: &[u8], offset: usize) -> Result<&[u8], ValueError> {
+    bytes.get(offset..).ok_or(ValueError::UnexpectedLength {
+        expected: offset,
+        got: bytes.len(),
+    })
+}
+
+/// Returns the bit at bit `offset` of `bytes`.
+pub(crate) fn bit(bytes: &[u8], offset: usize) -> Result<bool, ValueError> {
+    let byte = take(skip(bytes, offset / 8)?, 1)?[0];
+    Ok(byte & (1 << (offset % 8)) != 0)
+}
+
+/// Decodes a Windows-1252 `STRING` of at most `size` bytes.
+pub(crate) fn decode_string(bytes: &[u8], size: usize) -> Result<String, ValueError> {
+    let bytes = take(bytes, size)?;
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    Ok(WINDOWS_1252
+        .decode_without_bom_handling(&bytes[..end])
+        .0
+        .into_owned())
+}
+
+/// Decodes a UTF-16 `WSTRING` of at most `size` bytes.
+pub(crate) fn decode_wstring(bytes: &[u8], size: usize) -> Result<String, ValueError> {
+    Ok(AdsWStringBuf::decode(take(bytes, size)?)?.as_str())
+}
+
+fn decode(table: &SymbolTable, shape: Shape<'_>, bytes: &[u8]) -> Result<AdsValue, ValueError> {
+    Ok(match shape {
+        Shape::Primitive(primitive) => decode_primitive(primitive, bytes)?,
+        Shape::String(size) => AdsValue::String(decode_string(bytes, size)?),
+        Shape::WString(size) => AdsValue::WString(decode_wstring(bytes, size)?),
+        Shape::Enum { base, infos } => {
+            let value = decode_primitive(base, bytes)?
+                .as_i64()
+                .ok_or(ValueError::OutOfRange("enum"))?;
+            let name = infos
+                .iter()
+                .find(|info| info.value() == Some(value))
+                .map(|info| info.name().to_string());
+            AdsValue::Enum { name, value }
+        }
+        Shape::Array {
+            lower_bound,
+            count,
+            stride,
+            element,
+        } => {
+            let shape = table.shape(element)?;
+            let items = (0..count)
+                .map(|i| decode(table, shape, skip(bytes, i * stride)?))
+                .collect::<Result<_, _>>()?;
+            AdsValue::Array { lower_bound, items }
+        }
+        Shape::Struct(datatype) => {
+            let fields = members(datatype)
+                .map(|member| match member {
+                    Member::Bit { name, offset } => {
+                        Ok((name.to_string(), AdsValue::Bool(bit(bytes, offset)?)))
+                    }
+                    Member::Field { name, offset, ty } => {
+                        let value = decode(table, table.shape(ty)?, skip(bytes, offset)?)?;
+                        Ok((name.to_string(), value))
+                    }
+                })
+                .collect::<Result<_, ValueError>>()?;
+            AdsValue::Struct(fields)
+        }
+        Shape::Raw(size) => AdsValue::Raw(take(bytes, size)?.to_vec()),
+    })
+}
+
+fn decode_primitive(primitive: Primitive, bytes: &[u8]) -> Result<AdsValue, ValueError> {
+    Ok(match primitive {
+        Primitive::Bool => AdsValue::Bool(bool::decode(bytes)?),
+        Primitive::SInt => AdsValue::SInt(i8::decode(bytes)?),
+        Primitive::USInt => AdsValue::USInt(u8::decode(bytes)?),
+        Primitive::Int => AdsValue::Int(i16::decode(bytes)?),
+        Primitive::UInt => AdsValue::UInt(u16::decode(bytes)?),
+        Primitive::DInt => AdsValue::DInt(i32::decode(bytes)?),
+        Primitive::UDInt => AdsValue::UDInt(u32::decode(bytes)?),
+        Primitive::LInt => AdsValue::LInt(i64::decode(bytes)?),
+        Primitive::ULInt => AdsValue::ULInt(u64::decode(bytes)?),
+        Primitive::Real => AdsValue::Real(f32::decode(bytes)?),
+        Primitive::LReal => AdsValue::LReal(f64::decode(bytes)?),
+        Primitive::Time => AdsValue::Time(AdsTime::decode(bytes)?),
+        Primitive::LTime => AdsValue::LTime(AdsLTime::decode(bytes)?),
+        Primitive::Date => AdsValue::Date(AdsDate::decode(bytes)?),
+        Primitive::LDate => AdsValue::LDate(AdsLDate::decode(bytes)?),
+        Primitive::TimeOfDay => AdsValue::TimeOfDay(AdsTimeOfDay::decode(bytes)?),
+        Primitive::LTimeOfDay => AdsValue::LTimeOfDay(AdsLTimeOfDay::decode(bytes)?),
+        Primitive::DateTime => AdsValue::DateTime(AdsDateTime::decode(bytes)?),
+        Primitive::LDateTime => AdsValue::LDateTime(AdsLDateTime::decode(bytes)?),
+    })
+}
+
+/// Copies `data` to the start of `out`.
+pub(crate) fn put(out: &mut [u8], data: &[u8]) -> Result<(), ValueError> {
+    let len = out.len();
+    out.get_mut(..data.len())
+        .ok_or(ValueError::UnexpectedLength {
+            expected: data.len(),
+            got: len,
+        })?
+        .copy_from_slice(data);
+    Ok(())
+}
+
+/// Sets or clears the bit at bit `offset` of `out`.
+pub(crate) fn put_bit(out: &mut [u8], offset: usize, on: bool) -> Result<(), ValueError> {
+    let len = out.len();
+    let byte = out
+        .get_mut(offset / 8)
+        .ok_or(ValueError::UnexpectedLength {
+            expected: offset / 8 + 1,
+            got: len,
+        })?;
+    match on {
+        true => *byte |= 1 << (offset % 8),
+        false => *byte &= !(1 << (offset % 8)),
+    }
+    Ok(())
+}
+
+/// Writes `value` as a Windows-1252 `STRING` of `size` bytes.
+pub(crate) fn put_string(out: &mut [u8], value: &str, size: usize) -> Result<(), ValueError> {
+    let (encoded, _, has_errors) = WINDOWS_1252.encode(value);
+    if has_errors {
+        return Err(ValueError::EncodingError);
+    }
+    if encoded.len() >= size {
+        return Err(ValueError::StringTooLong {
+            max: size.saturating_sub(1),
+            got: encoded.len(),
+        });
+    }
+
+    let mut buf = vec![0; size];
+    buf[..encoded.len()].copy_from_slice(&encoded);
+    put(out, &buf)
+}
+
+/// Writes `value` as a UTF-16 `WSTRING` of `size` bytes.
+pub(crate) fn put_wstring(out: &mut [u8], value: &str, size: usize) -> Result<(), ValueError> {
+    let value = AdsWStringBuf::from(value);
+    if value.byte_len() > size {
+        return Err(ValueError::StringTooLong {
+            max: (size / 2).saturating_sub(1),
+            got: value.len(),
+        });
+    }
+
+    let mut buf = value.to_ads_bytes();
+    buf.resize(size, 0);
+    put(out, &buf)
+}
+
+fn encode(
+    table: &SymbolTable,
+    shape: Shape<'_>,
+    value: &AdsValue,
+    out: &mut [u8],
+) -> Result<(), ValueError> {
+    let mismatch = || ValueError::Mismatch {
+        expected: shape.describe(),
+        got: value.kind(),
+    };
+
+    match shape {
+        Shape::Primitive(primitive) => put(out, &encode_primitive(primitive, value)?),
+        Shape::String(size) => put_string(out, value.as_str().ok_or_else(mismatch)?, size),
+        Shape::WString(size) => put_wstring(out, value.as_str().ok_or_else(mismatch)?, size),
+        Shape::Enum { base, infos } => {
+            let by_name = |name: &str| {
+                infos
+                    .iter()
+                    .find(|info| info.name().eq_ignore_ascii_case(name))
+                    .and_then(|info| info.value())
+            };
+            let raw = match value {
+                AdsValue::Enum {
+                    name: Some(name), ..
+                }
+                | AdsValue::String(name) => {
+                    by_name(name).ok_or_else(|| ValueError::UnknownEnumName(name.clone()))?
+                }
+                other => other.as_i64().ok_or_else(mismatch)?,
+            };
+            put(out, &encode_primitive(base, &AdsValue::LInt(raw))?)
+        }
+        Shape::Array {
+            count,
+            stride,
+            element,
+            ..
+        } => {
+            let AdsValue::Array { items, .. } = value else {
+                return Err(mismatch());
+            };
+            if items.len() != count {
+                return Err(ValueError::ArrayLength {
+                    expected: count,
+                    got: items.len(),
+                });
+            }
+
+            let shape = table.shape(element)?;
+            for (i, item) in items.iter().enumerate() {
+                let len = out.len();
+                let slot = out
+                    .get_mut(i * stride..)
+                    .ok_or(ValueError::UnexpectedLength {
+                        expected: i * stride,
+                        got: len,
+                    })?;
+                encode(table, shape, item, slot)?;
+            }
+            Ok(())
+        }
+        Shape::Struct(datatype) => {
+            if !matches!(value, AdsValue::Struct(_)) {
+                return Err(mismatch());
+            }
+
+            for member in members(datatype) {
+                match member {
+                    Member::Bit { name, offset } => {
+                        let field = value
+                            .field(name)
+                            .ok_or_else(|| ValueError::MissingField(name.to_string()))?;
+                        let on = field.as_bool().ok_or(ValueError::Mismatch {
+                            expected: "BOOL",
+                            got: field.kind(),
+                        })?;
+                        put_bit(out, offset, on)?;
+                    }
+                    Member::Field { name, offset, ty } => {
+                        let field = value
+                            .field(name)
+                            .ok_or_else(|| ValueError::MissingField(name.to_string()))?;
+                        let len = out.len();
+                        let slot = out.get_mut(offset..).ok_or(ValueError::UnexpectedLength {
+                            expected: offset,
+                            got: len,
+                        })?;
+                        encode(table, table.shape(ty)?, field, slot)?;
+                    }
+                }
+            }
+            Ok(())
+        }
+        Shape::Raw(size) => match value {
+            AdsValue::Raw(bytes) if bytes.len() == size => put(out, bytes),
+            AdsValue::Raw(bytes) => Err(ValueError::UnexpectedLength {
+                expected: size,
+                got: bytes.len(),
+            }),
+            _ => Err(mismatch()),
+        },
+    }
+}
+
+fn encode_primitive(primitive: Primitive, value: &AdsValue) -> Result<Vec<u8>, ValueError> {
+    let parsed;
+    let value = match value {
+        AdsValue::String(literal) if primitive.is_time() => {
+            parsed = parse_literal(primitive, literal)?;
+            &parsed
+        }
+        value => value,
+    };
+
+    let mismatch = || ValueError::Mismatch {
+        expected: primitive.name(),
+        got: value.kind(),
+    };
+    let int = || value.as_i128().ok_or_else(mismatch);
+    let out_of_range = |_| ValueError::OutOfRange(primitive.name());
+
+    Ok(match (primitive, value) {
+        (Primitive::Bool, value) => value.as_bool().ok_or_else(mismatch)?.to_ads_bytes(),
+        (Primitive::Real, value) => (value.as_f64().ok_or_else(mismatch)? as f32).to_ads_bytes(),
+        (Primitive::LReal, value) => value.as_f64().ok_or_else(mismatch)?.to_ads_bytes(),
+        (Primitive::Time, AdsValue::Time(time)) => time.to_ads_bytes(),
+        (Primitive::LTime, AdsValue::LTime(time)) => time.to_ads_bytes(),
+        (Primitive::Date, AdsValue::Date(date)) => date.to_ads_bytes(),
+        (Primitive::LDate, AdsValue::LDate(date)) => date.to_ads_bytes(),
+        (Primitive::TimeOfDay, AdsValue::TimeOfDay(time)) => time.to_ads_bytes(),
+        (Primitive::LTimeOfDay, AdsValue::LTimeOfDay(time)) => time.to_ads_bytes(),
+        (Primitive::DateTime, AdsValue::DateTime(dt)) => dt.to_ads_bytes(),
+        (Primitive::LDateTime, AdsValue::LDateTime(dt)) => dt.to_ads_bytes(),
+        // Everything else, including time and date types given as their raw value, is an
+        // integer of the primitive's size.
+        (Primitive::SInt, _) => i8::try_from(int()?).map_err(out_of_range)?.to_ads_bytes(),
+        (Primitive::USInt, _) => u8::try_from(int()?).map_err(out_of_range)?.to_ads_bytes(),
+        (Primitive::Int, _) => i16::try_from(int()?).map_err(out_of_range)?.to_ads_bytes(),
+        (Primitive::UInt, _) => u16::try_from(int()?).map_err(out_of_range)?.to_ads_bytes(),
+        (Primitive::DInt, _) => i32::try_from(int()?).map_err(out_of_range)?.to_ads_bytes(),
+        (Primitive::LInt, _) => i64::try_from(int()?).map_err(out_of_range)?.to_ads_bytes(),
+        (
+            Primitive::UDInt
+            | Primitive::Time
+            | Primitive::Date
+            | Primitive::TimeOfDay
+            | Primitive::DateTime,
+            _,
+        ) => u32::try_from(int()?).map_err(out_of_range)?.to_ads_bytes(),
+        (
+            Primitive::ULInt
+            | Primitive::LTime
+            | Primitive::LDate
+            | Primitive::LTimeOfDay
+            | Primitive::LDateTime,
+            _,
+        ) => u64::try_from(int()?).map_err(out_of_range)?.to_ads_bytes(),
+    })
+}
+
+/// Parses the IEC literal of a time or date type, e.g. `T#1s`.
+fn parse_literal(primitive: Primitive, literal: &str) -> Result<AdsValue, ValueError> {
+    Ok(match primitive {
+        Primitive::Time => AdsValue::Time(literal.parse()?),
+        Primitive::LTime => AdsValue::LTime(literal.parse()?),
+        Primitive::Date => AdsValue::Date(literal.parse()?),
+        Primitive::LDate => AdsValue::LDate(literal.parse()?),
+        Primitive::TimeOfDay => AdsValue::TimeOfDay(literal.parse()?),
+        Primitive::LTimeOfDay => AdsValue::LTimeOfDay(literal.parse()?),
+        Primitive::DateTime => AdsValue::DateTime(literal.parse()?),
+        Primitive::LDateTime => AdsValue::LDateTime(literal.parse()?),
+        _ => AdsValue::String(literal.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tcads_core::symbol::{
+        AdsArrayInfo, AdsDataTypeId, AdsDatatypeFlags, AdsEnumInfo, AdsSymbolEntry,
+    };
+
+    /// `E_Mode : (Idle, Auto) INT`, `ST_Data` with an enum, a `STRING(7)`, a `BIT`, a
+    /// `TIME` and an `ARRAY[1..2] OF INT`, and a symbol `MAIN.stData`.
+    fn table() -> SymbolTable {
+        let mode =
+            AdsDatatypeEntry::new("E_Mode", "INT", AdsDataTypeId::Int16, 2).with_enum_infos(vec![
+                AdsEnumInfo::new("Idle", 0i16.to_le_bytes()),
+                AdsEnumInfo::new("Auto", 1i16.to_le_bytes()),
+            ]);
+        let array = AdsDatatypeEntry::new("ARRAY [1..2] OF INT", "INT", AdsDataTypeId::Int16, 4)
+            .with_array_info(vec![AdsArrayInfo::new(1, 2)]);
+        let data =
+            AdsDatatypeEntry::new("ST_Data", "", AdsDataTypeId::BigType, 20).with_sub_items(vec![
+                AdsDatatypeEntry::new_item("eMode", "E_Mode", AdsDataTypeId::Int16, 2, 0),
+                AdsDatatypeEntry::new_item("sName", "STRING(7)", AdsDataTypeId::String, 8, 2),
+                AdsDatatypeEntry::new_item("bFlag", "BIT", AdsDataTypeId::Bit, 1, 81).with_flags(
+                    AdsDatatypeFlags::new(
+                        AdsDatatypeFlags::DATAITEM | AdsDatatypeFlags::BIT_VALUES,
+                    ),
+                ),
+                AdsDatatypeEntry::new_item("tDelay", "TIME", AdsDataTypeId::UInt32, 4, 12),
+                AdsDatatypeEntry::new_item(
+                    "aValues",
+                    "ARRAY [1..2] OF INT",
+                    AdsDataTypeId::Int16,
+                    4,
+                    16,
+                ),
+            ]);
+
+        SymbolTable::new(
+            vec![AdsSymbolEntry::new(
+                "MAIN.stData",
+                "ST_Data",
+                AdsDataTypeId::BigType,
+                0x4040,
+                0,
+                20,
+            )],
+            vec![mode, array, data],
+        )
+    }
+
+    fn bytes() -> Vec<u8> {
+        let mut bytes = vec![0u8; 20];
+        bytes[0..2].copy_from_slice(&1i16.to_le_bytes());
+        bytes[2..6].copy_from_slice(b"Axis");
+        bytes[10] = 0b10;
+        bytes[12..16].copy_from_slice(&1500u32.to_le_bytes());
+        bytes[16..18].copy_from_slice(&(-3i16).to_le_bytes());
+        bytes[18..20].copy_from_slice(&7i16.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn decodes_nested_struct() {
+        let table = table();
+        let symbol = table.symbol("MAIN.stData").unwrap();
+        let value = table.decode_symbol(symbol, &bytes()).unwrap();
+
+        assert_eq!(
+            value.field("eMode"),
+            Some(&AdsValue::Enum {
+                name: Some("Auto".into()),
+                value: 1
+            })
+        );
+        assert_eq!(
+            value.field("sName").and_then(AdsValue::as_str),
+            Some("Axis")
+        );
+        assert_eq!(value.field("bFlag"), Some(&AdsValue::Bool(true)));
+        assert_eq!(
+            value.field("tDelay"),
+            Some(&AdsValue::Time(AdsTime::from_millis(1500)))
+        );
+
+        let values = value.field("aValues").unwrap();
+        assert_eq!(values.element(1), Some(&AdsValue::Int(-3)));
+        assert_eq!(values.element(2), Some(&AdsValue::Int(7)));
+        assert_eq!(values.element(0), None);
+    }
+
+    #[test]
+    fn encodes_edited_struct() {
+        let table = table();
+        let symbol = table.symbol("MAIN.stData").unwrap();
+        let mut value = table.decode_symbol(symbol, &bytes()).unwrap();
+
+        assert_eq!(table.encode_symbol(symbol, &value).unwrap(), bytes());
+
+        *value.field_mut("eMode").unwrap() = AdsValue::String("Idle".into());
+        *value.field_mut("bFlag").unwrap() = AdsValue::Bool(false);
+        *value.field_mut("aValues").unwrap().element_mut(2).unwrap() = AdsValue::DInt(100);
+
+        let encoded = table.encode_symbol(symbol, &value).unwrap();
+        assert_eq!(encoded[0..2], 0i16.to_le_bytes());
+        assert_eq!(encoded[10], 0);
+        assert_eq!(encoded[18..20], 100i16.to_le_bytes());
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        let table = table();
+        let symbol = table.symbol("MAIN.stData").unwrap();
+        let mut value = table.decode_symbol(symbol, &bytes()).unwrap();
+
+        *value.field_mut("sName").unwrap() = AdsValue::String("Too long".into());
+        assert_eq!(
+            table.encode_symbol(symbol, &value),
+            Err(ValueError::StringTooLong { max: 7, got: 8 })
+        );
+
+        *value.field_mut("sName").unwrap() = AdsValue::String("X".into());
+        *value.field_mut("aValues").unwrap().element_mut(1).unwrap() = AdsValue::DInt(40_000);
+        assert_eq!(
+            table.encode_symbol(symbol, &value),
+            Err(ValueError::OutOfRange("INT"))
+        );
+
+        assert_eq!(
+            table.encode_symbol(symbol, &AdsValue::Bool(true)),
+            Err(ValueError::Mismatch {
+                expected: "struct",
+                got: "BOOL"
+            })
+        );
+    }
+}
@@ -1,9 +1,43 @@
+use crate::tasks::invoke_id;
 use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::ops::Range;
 use std::sync::mpsc::{Receiver, Sender};
 use std::sync::{Mutex, mpsc};
-use tcads_core::InvokeId;
 use tcads_core::ads::NotificationHandle;
 use tcads_core::protocol::AdsNotificationSampleOwned;
+use tcads_core::{InvokeId, WindowsFileTime};
+
+/// The sending end of a subscription registered with the [`AdsNotificationDispatcher`].
+#[derive(Debug, Clone)]
+pub enum NotificationSender {
+    /// Receives the samples alone.
+    Samples(Sender<AdsNotificationSampleOwned>),
+    /// Receives every sample together with the timestamp of its stamp header.
+    Stamped(Sender<(WindowsFileTime, AdsNotificationSampleOwned)>),
+}
+
+impl NotificationSender {
+    /// Sends a sample, returning `false` if the receiver has been dropped.
+    fn send(&self, timestamp: WindowsFileTime, sample: AdsNotificationSampleOwned) -> bool {
+        match self {
+            Self::Samples(tx) => tx.send(sample).is_ok(),
+            Self::Stamped(tx) => tx.send((timestamp, sample)).is_ok(),
+        }
+    }
+}
+
+impl From<Sender<AdsNotificationSampleOwned>> for NotificationSender {
+    fn from(sender: Sender<AdsNotificationSampleOwned>) -> Self {
+        Self::Samples(sender)
+    }
+}
+
+impl From<Sender<(WindowsFileTime, AdsNotificationSampleOwned)>> for NotificationSender {
+    fn from(sender: Sender<(WindowsFileTime, AdsNotificationSampleOwned)>) -> Self {
+        Self::Stamped(sender)
+    }
+}
 
 /// Manages ADS device notification subscriptions.
 ///
@@ -22,9 +56,9 @@ use tcads_core::protocol::AdsNotificationSampleOwned;
 /// incoming notification frame. Dead receivers are pruned silently on dispatch.
 pub struct AdsNotificationDispatcher {
     /// Temporary storage keyed by invoke ID, waiting for handle assignment from the PLC.
-    pending: Mutex<HashMap<InvokeId, Sender<AdsNotificationSampleOwned>>>,
+    pending: Mutex<HashMap<InvokeId, NotificationSender>>,
     /// Permanent storage keyed by notification handle once assigned by the PLC.
-    subscriptions: Mutex<HashMap<NotificationHandle, Sender<AdsNotificationSampleOwned>>>,
+    subscriptions: Mutex<HashMap<NotificationHandle, NotificationSender>>,
 }
 
 impl AdsNotificationDispatcher {
@@ -48,10 +82,36 @@ impl AdsNotificationDispatcher {
         invoke_id: InvokeId,
     ) -> crate::Result<Receiver<AdsNotificationSampleOwned>> {
         let (tx, rx) = mpsc::channel();
-        self.pending.lock()?.insert(invoke_id, tx);
+        self.pre_register_with(invoke_id, tx)?;
         Ok(rx)
     }
 
+    /// Registers an existing `sender` under a temporary [`InvokeId`] key.
+    ///
+    /// Like [`pre_register`](Self::pre_register), but lets the caller keep the receiving
+    /// end, e.g. to register a subscription again after a reconnect.
+    ///
+    /// Fails with [`Error::InvokeIdInUse`](crate::Error::InvokeIdInUse) if another
+    /// subscription is still pre-registered under `invoke_id`.
+    pub fn pre_register_with(
+        &self,
+        invoke_id: InvokeId,
+        sender: impl Into<NotificationSender>,
+    ) -> crate::Result<()> {
+        match self.pending.lock()?.entry(invoke_id) {
+            Entry::Occupied(_) => Err(crate::Error::InvokeIdInUse(invoke_id)),
+            Entry::Vacant(entry) => {
+                entry.insert(sender.into());
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns `true` if a subscription is pre-registered under any of `ids`.
+    pub fn in_flight(&self, ids: Range<InvokeId>) -> crate::Result<bool> {
+        Ok(invoke_id::any_in(&*self.pending.lock()?, ids))
+    }
+
     /// Promotes a pre-registered subscription from its temporary [`InvokeId`] key
     /// to the permanent [`NotificationHandle`] assigned by the PLC.
     ///
@@ -71,6 +131,13 @@ impl AdsNotificationDispatcher {
         }
     }
 
+    /// Drops a pre-registered subscription that will never be promoted,
+    /// e.g. because the PLC rejected the add notification request.
+    pub fn abandon(&self, invoke_id: InvokeId) -> crate::Result<()> {
+        self.pending.lock()?.remove(&invoke_id);
+        Ok(())
+    }
+
     /// Routes an incoming [`AdsNotificationSample`](AdsNotificationSampleOwned)
     /// to the registered subscriber.
     ///
@@ -82,12 +149,13 @@ impl AdsNotificationDispatcher {
     pub fn dispatch(
         &self,
         handle: NotificationHandle,
+        timestamp: WindowsFileTime,
         sample: AdsNotificationSampleOwned,
     ) -> crate::Result<()> {
         let mut map = self.subscriptions.lock()?;
 
         let dead = if let Some(tx) = map.get(&handle) {
-            tx.send(sample).is_err()
+            !tx.send(timestamp, sample)
         } else {
             false
         };
@@ -136,7 +204,9 @@ mod tests {
         dispatcher.promote(1, handle).unwrap();
 
         let sample = make_sample(handle);
-        dispatcher.dispatch(handle, sample.clone()).unwrap();
+        dispatcher
+            .dispatch(handle, WindowsFileTime::now(), sample.clone())
+            .unwrap();
 
         assert_eq!(rx.recv().unwrap(), sample);
     }
@@ -147,7 +217,11 @@ mod tests {
         let handle = NotificationHandle::from(42);
 
         let sample = make_sample(handle);
-        assert!(dispatcher.dispatch(handle, sample).is_ok());
+        assert!(
+            dispatcher
+                .dispatch(handle, WindowsFileTime::now(), sample)
+                .is_ok()
+        );
     }
 
     #[test]
@@ -161,7 +235,9 @@ mod tests {
         drop(rx);
 
         let sample = make_sample(handle);
-        dispatcher.dispatch(handle, sample).unwrap();
+        dispatcher
+            .dispatch(handle, WindowsFileTime::now(), sample)
+            .unwrap();
 
         assert!(dispatcher.subscriptions.lock().unwrap().is_empty());
     }
@@ -203,4 +279,21 @@ mod tests {
         let promoted = dispatcher.promote(999, handle).unwrap();
         assert!(!promoted);
     }
+
+    #[test]
+    fn pre_register_rejects_colliding_invoke_id() {
+        let dispatcher = AdsNotificationDispatcher::new();
+
+        let _rx = dispatcher.pre_register(1).unwrap();
+        assert!(matches!(
+            dispatcher.pre_register(1),
+            Err(crate::Error::InvokeIdInUse(1))
+        ));
+        assert!(dispatcher.in_flight(0..2).unwrap());
+
+        dispatcher
+            .promote(1, NotificationHandle::from(1u32))
+            .unwrap();
+        assert!(!dispatcher.in_flight(0..2).unwrap());
+    }
 }
@@ -1,6 +1,10 @@
 use super::AmsRequestDispatchKey;
+use crate::tasks::invoke_id;
+use std::collections::hash_map::Entry;
 use std::collections::{HashMap, VecDeque};
+use std::ops::Range;
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
 use tcads_core::InvokeId;
 use tcads_core::io::AmsFrame;
@@ -18,8 +22,12 @@ pub struct AmsRequestDispatcher {
     port_connect: Mutex<VecDeque<Sender<AmsFrame>>>,
     /// Pending [GetLocalNetId](tcads_core::protocol::GetLocalNetIdResponse) responses;
     net_id: Mutex<VecDeque<Sender<AmsFrame>>>,
-    /// Channel to the writer thread.
-    write_tx: Sender<AmsFrame>,
+    /// Set once the reader thread has exited.
+    closed: AtomicBool,
+    /// Responses that arrived for no pending request.
+    late_responses: AtomicU64,
+    /// Channel to the writer thread, replaced when the connection is [reopened](Self::reopen).
+    write_tx: Mutex<Sender<AmsFrame>>,
 }
 
 impl AmsRequestDispatcher {
@@ -29,13 +37,17 @@ impl AmsRequestDispatcher {
             port_connect: Mutex::new(VecDeque::new()),
             ads: Mutex::new(HashMap::new()),
             net_id: Mutex::new(VecDeque::new()),
-            write_tx,
+            closed: AtomicBool::new(false),
+            late_responses: AtomicU64::new(0),
+            write_tx: Mutex::new(write_tx),
         }
     }
 
     /// Registers a waiter, enqueues the frame for writing, and returns the response receiver.
     ///
     /// Registration and dispatch happen together, closing the window between the two.
+    /// Fails with [`Error::InvokeIdInUse`](crate::Error::InvokeIdInUse) instead of
+    /// replacing an ADS request that is still waiting under the same invoke ID.
     pub fn dispatch(
         &self,
         key: AmsRequestDispatchKey,
@@ -43,30 +55,87 @@ impl AmsRequestDispatcher {
     ) -> Result<Receiver<AmsFrame>, crate::Error> {
         let (tx, rx) = mpsc::channel();
         self.register(key, tx)?;
-        self.write_tx.send(frame)?;
+        self.write_tx.lock()?.send(frame)?;
         Ok(rx)
     }
 
+    /// Registers a waiter that discards the response, then enqueues the frame for writing.
+    ///
+    /// Use this for requests whose response the caller does not wait for, e.g. releasing a
+    /// handle on drop. The invoke ID stays reserved until the response arrives, and the
+    /// response is not counted as [late](Self::late_responses).
+    pub fn dispatch_detached(
+        &self,
+        key: AmsRequestDispatchKey,
+        frame: AmsFrame,
+    ) -> crate::Result<()> {
+        let (tx, _) = mpsc::channel();
+        self.register(key, tx)?;
+        self.write_tx.lock()?.send(frame)?;
+        Ok(())
+    }
+
     /// Called by the reader thread to complete a pending request.
+    ///
+    /// If the caller has stopped waiting the frame is discarded. An ADS response whose
+    /// request was [cancelled](Self::cancel), or was never sent, is counted as
+    /// [late](Self::late_responses).
     pub fn complete(&self, key: AmsRequestDispatchKey, frame: AmsFrame) -> crate::Result<()> {
-        if let Some(tx) = self.take(key)? {
-            tx.send(frame)?;
+        match self.take(key)? {
+            Some(tx) => {
+                let _ = tx.send(frame);
+            }
+            None if matches!(key, AmsRequestDispatchKey::AdsCommand(_)) => {
+                self.late_responses.fetch_add(1, Ordering::Relaxed);
+            }
+            None => {}
         }
         Ok(())
     }
 
+    /// Purges the pending ADS request under `key` once its caller has stopped waiting,
+    /// e.g. after a timeout. Returns `true` if the request was still pending.
+    ///
+    /// Dropping the sender wakes a caller that is still blocked with
+    /// [`Error::Disconnected`](crate::Error::Disconnected). Router requests are answered
+    /// in order rather than by invoke ID, so they stay queued to keep later responses
+    /// matched to their callers.
+    pub fn cancel(&self, key: AmsRequestDispatchKey) -> crate::Result<bool> {
+        match key {
+            AmsRequestDispatchKey::AdsCommand(id) => Ok(self.ads.lock()?.remove(&id).is_some()),
+            AmsRequestDispatchKey::PortConnect | AmsRequestDispatchKey::GetLocalNetId => Ok(false),
+        }
+    }
+
+    /// Returns how many ADS responses arrived after their request was
+    /// [cancelled](Self::cancel), or for no request at all.
+    pub fn late_responses(&self) -> u64 {
+        self.late_responses.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if an ADS request under any of `ids` is waiting for a response.
+    pub fn in_flight(&self, ids: Range<InvokeId>) -> crate::Result<bool> {
+        Ok(invoke_id::any_in(&*self.ads.lock()?, ids))
+    }
+
+    /// Returns the number of ADS requests waiting for a response.
+    pub fn pending(&self) -> crate::Result<usize> {
+        Ok(self.ads.lock()?.len())
+    }
+
     /// Sends `frame` directly to the writer thread without registering a response waiter.
     ///
     /// Use this for frames where no response is expected i.e.
     /// [`PortClose`](tcads_core::protocol::PortCloseRequest). For all other frames
-    /// use [`dispatch`](Self::dispatch), which registers a waiter before sending to
-    /// close the window between send and response arrival.
+    /// use [`dispatch`](Self::dispatch) or [`dispatch_detached`](Self::dispatch_detached),
+    /// which register a waiter before sending to close the window between send and
+    /// response arrival.
     ///
     /// Returns [`Err`] if the writer channel is already closed, which means the
     /// connection is already gone. Callers should generally ignore this error
     /// since the goal (closing the connection) is already achieved.
     pub fn send_only(&self, frame: AmsFrame) -> crate::Result<()> {
-        self.write_tx.send(frame)?;
+        self.write_tx.lock()?.send(frame)?;
         Ok(())
     }
 
@@ -81,11 +150,40 @@ impl AmsRequestDispatcher {
         Ok(())
     }
 
+    /// Clears all pending requests and marks the connection as closed.
+    ///
+    /// Called by the reader thread when it exits.
+    pub fn close(&self) -> crate::Result<()> {
+        self.closed.store(true, Ordering::Release);
+        self.clear()
+    }
+
+    /// Returns `true` once the connection is [closed](Self::close).
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    /// Points the dispatcher at the writer thread of a new connection and clears the
+    /// [closed](Self::close) flag.
+    ///
+    /// The previous writer thread exits once its sender is dropped here. Must only be
+    /// called after the reader thread of the previous connection has exited, so that its
+    /// [`close`](Self::close) cannot race the new connection.
+    pub fn reopen(&self, write_tx: Sender<AmsFrame>) -> crate::Result<()> {
+        self.clear()?;
+        *self.write_tx.lock()? = write_tx;
+        self.closed.store(false, Ordering::Release);
+        Ok(())
+    }
+
     fn register(&self, key: AmsRequestDispatchKey, sender: Sender<AmsFrame>) -> crate::Result<()> {
         match key {
-            AmsRequestDispatchKey::AdsCommand(id) => {
-                self.ads.lock()?.insert(id, sender);
-            }
+            AmsRequestDispatchKey::AdsCommand(id) => match self.ads.lock()?.entry(id) {
+                Entry::Occupied(_) => return Err(crate::Error::InvokeIdInUse(id)),
+                Entry::Vacant(entry) => {
+                    entry.insert(sender);
+                }
+            },
             AmsRequestDispatchKey::PortConnect => {
                 self.port_connect.lock()?.push_back(sender);
             }
@@ -187,4 +285,54 @@ mod tests {
         assert_eq!(rx1.recv().unwrap(), resp1);
         assert_eq!(rx2.recv().unwrap(), resp2);
     }
+
+    #[test]
+    fn cancelled_request_is_purged_and_late_response_counted() {
+        let (dispatcher, _write_rx) = make_dispatcher();
+        let frame = AmsFrame::empty(AmsCommand::AdsCommand);
+        let key = AmsRequestDispatchKey::AdsCommand(3);
+
+        let rx = dispatcher.dispatch(key, frame.clone()).unwrap();
+        assert_eq!(dispatcher.pending().unwrap(), 1);
+        assert!(dispatcher.cancel(key).unwrap());
+        assert!(!dispatcher.cancel(key).unwrap());
+        assert_eq!(dispatcher.pending().unwrap(), 0);
+        assert!(rx.recv().is_err());
+
+        dispatcher.complete(key, frame).unwrap();
+        assert_eq!(dispatcher.late_responses(), 1);
+    }
+
+    #[test]
+    fn detached_response_is_discarded_without_counting_as_late() {
+        let (dispatcher, _write_rx) = make_dispatcher();
+        let frame = AmsFrame::empty(AmsCommand::AdsCommand);
+        let key = AmsRequestDispatchKey::AdsCommand(4);
+
+        dispatcher.dispatch_detached(key, frame.clone()).unwrap();
+        assert!(dispatcher.in_flight(4..5).unwrap());
+
+        dispatcher.complete(key, frame).unwrap();
+        assert_eq!(dispatcher.pending().unwrap(), 0);
+        assert_eq!(dispatcher.late_responses(), 0);
+    }
+
+    #[test]
+    fn colliding_invoke_id_is_rejected() {
+        let (dispatcher, write_rx) = make_dispatcher();
+        let frame = AmsFrame::empty(AmsCommand::AdsCommand);
+        let key = AmsRequestDispatchKey::AdsCommand(5);
+
+        let _rx = dispatcher.dispatch(key, frame.clone()).unwrap();
+        assert!(matches!(
+            dispatcher.dispatch(key, frame),
+            Err(crate::Error::InvokeIdInUse(5))
+        ));
+        assert_eq!(dispatcher.pending().unwrap(), 1);
+        assert!(write_rx.try_recv().is_ok());
+        assert!(write_rx.try_recv().is_err());
+
+        assert!(dispatcher.in_flight(5..6).unwrap());
+        assert!(!dispatcher.in_flight(6..100).unwrap());
+    }
 }
@@ -0,0 +1,69 @@
+use crate::devices::connection::ConnectionEvent;
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// Fans out [connection events](ConnectionEvent) to all registered subscribers.
+///
+/// Unlike the [`RouterNotificationDispatcher`](super::RouterNotificationDispatcher), it
+/// outlives individual connections: subscribers keep receiving events across reconnects
+/// until [`clear`](Self::clear) is called once the device is closed for good.
+pub struct ConnectionEventDispatcher {
+    subscribers: Mutex<Vec<Sender<ConnectionEvent>>>,
+}
+
+impl ConnectionEventDispatcher {
+    /// Creates a new dispatcher with no subscribers.
+    pub fn new() -> Self {
+        Self {
+            subscribers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Registers a new subscriber and returns a [`Receiver<ConnectionEvent>`].
+    pub fn subscribe(&self) -> crate::Result<Receiver<ConnectionEvent>> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.lock()?.push(tx);
+        Ok(rx)
+    }
+
+    /// Broadcasts `event` to all live subscribers, pruning dead receivers.
+    pub fn broadcast(&self, event: ConnectionEvent) -> crate::Result<()> {
+        let mut subscribers = self.subscribers.lock()?;
+        subscribers.retain(|tx| tx.send(event.clone()).is_ok());
+        Ok(())
+    }
+
+    /// Drops all subscribers, causing their receivers to return [`Err`].
+    pub fn clear(&self) -> crate::Result<()> {
+        self.subscribers.lock()?.clear();
+        Ok(())
+    }
+}
+
+impl Default for ConnectionEventDispatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subscribers_receive_events_until_cleared() {
+        let dispatcher = ConnectionEventDispatcher::new();
+        let rx = dispatcher.subscribe().unwrap();
+
+        dispatcher
+            .broadcast(ConnectionEvent::Reconnecting { attempt: 1 })
+            .unwrap();
+        dispatcher.clear().unwrap();
+
+        assert!(matches!(
+            rx.recv(),
+            Ok(ConnectionEvent::Reconnecting { attempt: 1 })
+        ));
+        assert!(rx.recv().is_err());
+    }
+}
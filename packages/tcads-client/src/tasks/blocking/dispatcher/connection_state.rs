@@ -0,0 +1,217 @@
+use crate::devices::connection::ConnectionState;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// The state shared between a [`ConnectionStateDispatcher`] and its receivers.
+struct Shared {
+    value: Mutex<Versioned>,
+    changed: Condvar,
+}
+
+struct Versioned {
+    state: ConnectionState,
+    version: u64,
+    /// Set once the dispatcher is dropped, so no further changes can follow.
+    dropped: bool,
+}
+
+/// Holds the [`ConnectionState`] of a device and the round-trip time of its last
+/// heartbeat, and publishes state changes to [receivers](ConnectionStateReceiver).
+///
+/// Like the [`ConnectionEventDispatcher`](super::ConnectionEventDispatcher), it outlives
+/// individual connections. Receivers see [`ConnectionState::Closed`] last; once the
+/// device is dropped, [`ConnectionStateReceiver::changed`] returns [`Err`].
+pub struct ConnectionStateDispatcher {
+    shared: Arc<Shared>,
+    rtt: Mutex<Option<Duration>>,
+}
+
+impl ConnectionStateDispatcher {
+    /// Creates a new dispatcher in `state`.
+    pub fn new(state: ConnectionState) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                value: Mutex::new(Versioned {
+                    state,
+                    version: 0,
+                    dropped: false,
+                }),
+                changed: Condvar::new(),
+            }),
+            rtt: Mutex::new(None),
+        }
+    }
+
+    /// Returns the current state.
+    pub fn state(&self) -> crate::Result<ConnectionState> {
+        Ok(self.shared.value.lock()?.state)
+    }
+
+    /// Returns a receiver that observes the current state and every later change.
+    pub fn watch(&self) -> crate::Result<ConnectionStateReceiver> {
+        let seen = self.shared.value.lock()?.version;
+        Ok(ConnectionStateReceiver {
+            shared: Arc::clone(&self.shared),
+            seen,
+        })
+    }
+
+    /// Moves to `state`, unless the device is already [closed](ConnectionState::Closed).
+    ///
+    /// Receivers are only woken if the state actually changes.
+    pub fn set(&self, state: ConnectionState) -> crate::Result<()> {
+        self.update(
+            |current| current != state && current != ConnectionState::Closed,
+            state,
+        )?;
+        Ok(())
+    }
+
+    /// Moves from `from` to `to`, returning `false` if the current state is not `from`.
+    pub fn transition(&self, from: ConnectionState, to: ConnectionState) -> crate::Result<bool> {
+        self.update(|current| current == from && from != to, to)
+    }
+
+    fn update(
+        &self,
+        applies: impl FnOnce(ConnectionState) -> bool,
+        state: ConnectionState,
+    ) -> crate::Result<bool> {
+        let mut value = self.shared.value.lock()?;
+        if !applies(value.state) {
+            return Ok(false);
+        }
+        value.state = state;
+        value.version += 1;
+        self.shared.changed.notify_all();
+        Ok(true)
+    }
+
+    /// Returns the round-trip time of the last answered heartbeat.
+    pub fn rtt(&self) -> crate::Result<Option<Duration>> {
+        Ok(*self.rtt.lock()?)
+    }
+
+    /// Records the round-trip time of an answered heartbeat.
+    pub fn set_rtt(&self, rtt: Duration) -> crate::Result<()> {
+        *self.rtt.lock()? = Some(rtt);
+        Ok(())
+    }
+}
+
+impl Default for ConnectionStateDispatcher {
+    /// Creates a dispatcher for a device that is already
+    /// [connected](ConnectionState::Connected).
+    fn default() -> Self {
+        Self::new(ConnectionState::Connected)
+    }
+}
+
+impl Drop for ConnectionStateDispatcher {
+    fn drop(&mut self) {
+        if let Ok(mut value) = self.shared.value.lock() {
+            value.dropped = true;
+        }
+        self.shared.changed.notify_all();
+    }
+}
+
+/// Observes the [`ConnectionState`] of a blocking device.
+///
+/// The blocking counterpart of [`tokio::sync::watch::Receiver`]: it always has the
+/// latest state at hand, and [`changed`](Self::changed) waits for the next one.
+/// Intermediate states are skipped if several changes happen between two calls.
+#[derive(Clone)]
+pub struct ConnectionStateReceiver {
+    shared: Arc<Shared>,
+    seen: u64,
+}
+
+impl ConnectionStateReceiver {
+    /// Returns the current state without marking it as seen.
+    pub fn current(&self) -> crate::Result<ConnectionState> {
+        Ok(self.shared.value.lock()?.state)
+    }
+
+    /// Blocks until the state changes from the last one seen, and returns it.
+    ///
+    /// Returns [`Error::Disconnected`](crate::Error::Disconnected) once the device has been
+    /// dropped and no unseen change is left.
+    pub fn changed(&mut self) -> crate::Result<ConnectionState> {
+        let mut value = self.shared.value.lock()?;
+        loop {
+            if let Some(state) = Self::take(&mut self.seen, &value)? {
+                return Ok(state);
+            }
+            value = self.shared.changed.wait(value)?;
+        }
+    }
+
+    /// Like [`changed`](Self::changed), but gives up with
+    /// [`Error::Timeout`](crate::Error::Timeout) after `timeout`.
+    pub fn changed_timeout(&mut self, timeout: Duration) -> crate::Result<ConnectionState> {
+        let deadline = Instant::now() + timeout;
+        let mut value = self.shared.value.lock()?;
+        loop {
+            if let Some(state) = Self::take(&mut self.seen, &value)? {
+                return Ok(state);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(crate::Error::Timeout);
+            }
+            value = self.shared.changed.wait_timeout(value, remaining)?.0;
+        }
+    }
+
+    /// Returns the state in `value` if it has not been `seen` yet.
+    fn take(seen: &mut u64, value: &Versioned) -> crate::Result<Option<ConnectionState>> {
+        if value.version != *seen {
+            *seen = value.version;
+            return Ok(Some(value.state));
+        }
+        if value.dropped {
+            return Err(crate::Error::Disconnected);
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn receivers_see_changes_until_dropped() {
+        let dispatcher = ConnectionStateDispatcher::default();
+        let mut rx = dispatcher.watch().unwrap();
+
+        assert!(matches!(
+            rx.changed_timeout(Duration::from_millis(10)),
+            Err(crate::Error::Timeout)
+        ));
+        assert!(
+            !dispatcher
+                .transition(ConnectionState::Degraded, ConnectionState::Connected)
+                .unwrap()
+        );
+
+        let changes = thread::spawn(move || {
+            dispatcher
+                .transition(ConnectionState::Connected, ConnectionState::Degraded)
+                .unwrap();
+            dispatcher
+        });
+        assert_eq!(rx.changed().unwrap(), ConnectionState::Degraded);
+
+        let dispatcher = changes.join().unwrap();
+        dispatcher.set(ConnectionState::Closed).unwrap();
+        dispatcher.set(ConnectionState::Connecting).unwrap();
+        assert_eq!(dispatcher.state().unwrap(), ConnectionState::Closed);
+        drop(dispatcher);
+
+        assert_eq!(rx.changed().unwrap(), ConnectionState::Closed);
+        assert!(matches!(rx.changed(), Err(crate::Error::Disconnected)));
+    }
+}
@@ -2,9 +2,11 @@ pub mod dispatcher;
 pub mod reader;
 pub mod writer;
 
-pub use super::AmsRequestDispatchKey;
+pub use super::{AmsRequestDispatchKey, InvokeIdAllocator};
 pub use dispatcher::{
-    AdsNotificationDispatcher, AmsRequestDispatcher, RouterNotificationDispatcher,
+    AdsNotificationDispatcher, AmsRequestDispatcher, ConnectionEventDispatcher,
+    ConnectionStateDispatcher, ConnectionStateReceiver, NotificationSender,
+    RouterNotificationDispatcher,
 };
 pub use reader::AmsResponseReader;
 pub use writer::AmsRequestWriter;
@@ -46,7 +46,7 @@ impl AmsResponseReader {
     ) -> JoinHandle<crate::Result<()>> {
         thread::spawn(move || {
             let result = handle(reader, &ams_requests, &ads_notifs, &router_notifs);
-            ams_requests.clear()?;
+            ams_requests.close()?;
             ads_notifs.clear()?;
             router_notifs.clear()?;
             result
@@ -84,8 +84,8 @@ fn handle<R: Read>(
                             continue;
                         };
 
-                        for (_, sample) in notif.iter_samples() {
-                            ads_notifs.dispatch(sample.handle(), sample.to_owned())?;
+                        for (timestamp, sample) in notif.iter_samples() {
+                            ads_notifs.dispatch(sample.handle(), timestamp, sample.to_owned())?;
                         }
                     }
                     _ => ams_requests
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tcads_core::InvokeId;
+
+/// Hands out [invoke IDs](InvokeId) for the requests of one connection.
+///
+/// IDs are allocated in increasing order from 1 and wrap around to 1, so ID 0 is never
+/// handed out. An ID is skipped while it is still in flight, as reported by the caller for
+/// a range of candidates, or while it lies in a range [reserved](Self::reserve) for frames
+/// built outside the device. This keeps two pending requests from ever sharing an ID,
+/// which would misroute their responses.
+pub struct InvokeIdAllocator {
+    next: AtomicU32,
+    reserved: Mutex<Vec<Range<InvokeId>>>,
+}
+
+impl InvokeIdAllocator {
+    /// Creates an allocator starting at ID 1 with nothing reserved.
+    pub fn new() -> Self {
+        Self {
+            next: AtomicU32::new(1),
+            reserved: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Allocates the next ID that is neither reserved nor `in_flight`.
+    pub fn next(&self, in_flight: impl Fn(Range<InvokeId>) -> bool) -> crate::Result<InvokeId> {
+        self.next_block(1, in_flight)
+    }
+
+    /// Allocates `count` consecutive IDs, none of them reserved or `in_flight`, and
+    /// returns the first. A block never wraps around.
+    pub fn next_block(
+        &self,
+        count: u32,
+        in_flight: impl Fn(Range<InvokeId>) -> bool,
+    ) -> crate::Result<InvokeId> {
+        self.find(count, &self.reserved()?, in_flight)
+            .map(|block| block.start)
+    }
+
+    /// Reserves `count` consecutive IDs for frames built outside the device, e.g. through
+    /// [`AmsRequestDispatcher::dispatch`](crate::tasks::tokio::AmsRequestDispatcher::dispatch).
+    ///
+    /// The allocator skips the range until it is [released](Self::release).
+    pub fn reserve(
+        &self,
+        count: u32,
+        in_flight: impl Fn(Range<InvokeId>) -> bool,
+    ) -> crate::Result<Range<InvokeId>> {
+        let block = self.find(count, &self.reserved()?, in_flight)?;
+        self.reserved.lock()?.push(block.clone());
+        Ok(block)
+    }
+
+    /// Returns a [reserved](Self::reserve) range to the allocator.
+    pub fn release(&self, range: &Range<InvokeId>) -> crate::Result<()> {
+        self.reserved.lock()?.retain(|r| r != range);
+        Ok(())
+    }
+
+    /// Returns `true` if `id` lies in a [reserved](Self::reserve) range.
+    pub fn is_reserved(&self, id: InvokeId) -> crate::Result<bool> {
+        Ok(self.reserved.lock()?.iter().any(|r| r.contains(&id)))
+    }
+
+    /// Returns a snapshot of the reserved ranges.
+    ///
+    /// The lock is not held while `in_flight` takes the locks of the caller. A range
+    /// reserved after the snapshot cannot overlap the block found, since both are taken
+    /// from the shared counter.
+    fn reserved(&self) -> crate::Result<Vec<Range<InvokeId>>> {
+        Ok(self.reserved.lock()?.clone())
+    }
+
+    fn find(
+        &self,
+        count: u32,
+        reserved: &[Range<InvokeId>],
+        in_flight: impl Fn(Range<InvokeId>) -> bool,
+    ) -> crate::Result<Range<InvokeId>> {
+        let count = count.max(1);
+        // Gives up once every ID has been considered.
+        let mut skipped = 0u64;
+        while skipped <= u64::from(u32::MAX) {
+            let start = self.next.fetch_add(count, Ordering::Relaxed);
+            if start == 0 {
+                let _ = self
+                    .next
+                    .compare_exchange(count, 1, Ordering::Relaxed, Ordering::Relaxed);
+                skipped += 1;
+                continue;
+            }
+            let Some(end) = start.checked_add(count) else {
+                skipped += u64::from(u32::MAX - start) + 1;
+                continue;
+            };
+
+            if let Some(r) = reserved.iter().find(|r| r.start < end && start < r.end) {
+                if r.end > end {
+                    let _ = self.next.compare_exchange(
+                        end,
+                        r.end,
+                        Ordering::Relaxed,
+                        Ordering::Relaxed,
+                    );
+                }
+                skipped += u64::from(r.end.max(end) - start);
+                continue;
+            }
+            if in_flight(start..end) {
+                skipped += u64::from(count);
+                continue;
+            }
+            return Ok(start..end);
+        }
+        Err(crate::Error::InvokeIdsExhausted)
+    }
+}
+
+/// Returns `true` if any of `ids` is a key of `map`, looking up whichever is smaller.
+pub(crate) fn any_in<V>(map: &HashMap<InvokeId, V>, ids: Range<InvokeId>) -> bool {
+    if ids.len() <= map.len() {
+        ids.into_iter().any(|id| map.contains_key(&id))
+    } else {
+        map.keys().any(|id| ids.contains(id))
+    }
+}
+
+impl Default for InvokeIdAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_ids_in_flight_and_wraps_around() {
+        let ids = InvokeIdAllocator::new();
+        assert_eq!(ids.next(|_| false).unwrap(), 1);
+        assert_eq!(ids.next(|ids| ids.contains(&2)).unwrap(), 3);
+
+        ids.next.store(u32::MAX - 1, Ordering::Relaxed);
+        assert_eq!(ids.next(|ids| ids.contains(&(u32::MAX - 1))).unwrap(), 1);
+        assert_eq!(ids.next_block(3, |ids| ids.contains(&2)).unwrap(), 5);
+    }
+
+    #[test]
+    fn never_hands_out_zero() {
+        let ids = InvokeIdAllocator::new();
+        ids.next.store(0, Ordering::Relaxed);
+        assert_eq!(ids.next(|_| false).unwrap(), 1);
+
+        ids.next.store(0, Ordering::Relaxed);
+        assert_eq!(ids.next_block(4, |_| false).unwrap(), 1);
+
+        ids.next.store(u32::MAX - 2, Ordering::Relaxed);
+        assert_eq!(ids.next_block(3, |_| false).unwrap(), 1);
+    }
+
+    #[test]
+    fn in_flight_is_checked_without_holding_the_reserved_lock() {
+        let ids = InvokeIdAllocator::new();
+        let id = ids.next(|_| ids.is_reserved(0).unwrap()).unwrap();
+        assert_eq!(id, 1);
+        let range = ids.reserve(2, |_| ids.is_reserved(0).unwrap()).unwrap();
+        assert_eq!(range, 2..4);
+    }
+
+    #[test]
+    fn blocks_do_not_wrap_around() {
+        let ids = InvokeIdAllocator::new();
+        ids.next.store(u32::MAX - 2, Ordering::Relaxed);
+        assert_eq!(ids.next_block(4, |_| false).unwrap(), 1);
+    }
+
+    #[test]
+    fn reserved_ranges_are_skipped_until_released() {
+        let ids = InvokeIdAllocator::new();
+        let range = ids.reserve(100, |_| false).unwrap();
+        assert_eq!(range, 1..101);
+        assert!(ids.is_reserved(50).unwrap());
+
+        ids.next.store(1, Ordering::Relaxed);
+        assert_eq!(ids.next(|_| false).unwrap(), 101);
+
+        ids.release(&range).unwrap();
+        ids.next.store(1, Ordering::Relaxed);
+        assert_eq!(ids.next(|_| false).unwrap(), 1);
+    }
+
+    #[test]
+    fn reports_exhaustion() {
+        let ids = InvokeIdAllocator::new();
+        ids.reserve(u32::MAX - 16, |_| false).unwrap();
+        assert!(matches!(
+            ids.next(|_| true),
+            Err(crate::Error::InvokeIdsExhausted)
+        ));
+    }
+}
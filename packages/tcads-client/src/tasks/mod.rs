@@ -1,10 +1,13 @@
 use tcads_core::InvokeId;
 
 pub mod blocking;
+pub mod invoke_id;
 pub mod tokio;
 
+pub use invoke_id::InvokeIdAllocator;
+
 /// Identifies the type of pending request for routing incoming responses.
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum AmsRequestDispatchKey {
     AdsCommand(InvokeId),
     PortConnect,
@@ -0,0 +1,255 @@
+use crate::tasks::invoke_id;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::ops::Range;
+use std::sync::Mutex;
+use tcads_core::ads::NotificationHandle;
+use tcads_core::protocol::AdsNotificationSampleOwned;
+use tcads_core::{InvokeId, WindowsFileTime};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// The sending end of a subscription registered with the [`AdsNotificationDispatcher`].
+#[derive(Debug, Clone)]
+pub enum NotificationSender {
+    /// Receives the samples alone.
+    Samples(UnboundedSender<AdsNotificationSampleOwned>),
+    /// Receives every sample together with the timestamp of its stamp header.
+    Stamped(UnboundedSender<(WindowsFileTime, AdsNotificationSampleOwned)>),
+}
+
+impl NotificationSender {
+    /// Sends a sample, returning `false` if the receiver has been dropped.
+    fn send(&self, timestamp: WindowsFileTime, sample: AdsNotificationSampleOwned) -> bool {
+        match self {
+            Self::Samples(tx) => tx.send(sample).is_ok(),
+            Self::Stamped(tx) => tx.send((timestamp, sample)).is_ok(),
+        }
+    }
+}
+
+impl From<UnboundedSender<AdsNotificationSampleOwned>> for NotificationSender {
+    fn from(sender: UnboundedSender<AdsNotificationSampleOwned>) -> Self {
+        Self::Samples(sender)
+    }
+}
+
+impl From<UnboundedSender<(WindowsFileTime, AdsNotificationSampleOwned)>> for NotificationSender {
+    fn from(sender: UnboundedSender<(WindowsFileTime, AdsNotificationSampleOwned)>) -> Self {
+        Self::Stamped(sender)
+    }
+}
+
+/// Manages ADS device notification subscriptions.
+///
+/// The async counterpart of the [blocking dispatcher](crate::tasks::blocking::AdsNotificationDispatcher),
+/// following the same two-phase lifecycle:
+///
+/// 1. **Pre-registration**: Before the [`AdsAddDeviceNotificationRequest`](tcads_core::protocol::AdsAddDeviceNotificationRequest)
+///    is sent, a sender is registered under the request's [`InvokeId`] via [`pre_register`](Self::pre_register).
+///    This ensures no samples are lost if the PLC sends a notification before the response arrives.
+///
+/// 2. **Promotion**: Once the [`AdsAddDeviceNotificationResponse`](tcads_core::protocol::AdsAddDeviceNotificationResponse)
+///    is received, the entry is re-keyed from its temporary [`InvokeId`] to the assigned
+///    [`NotificationHandle`] via [`promote`](Self::promote).
+///
+/// Incoming samples are routed by [`dispatch`](Self::dispatch), which is called by the
+/// reader task for each sample in an incoming notification frame. Dead receivers are
+/// pruned silently on dispatch.
+pub struct AdsNotificationDispatcher {
+    /// Temporary storage keyed by invoke ID, waiting for handle assignment from the PLC.
+    pending: Mutex<HashMap<InvokeId, NotificationSender>>,
+    /// Permanent storage keyed by notification handle once assigned by the PLC.
+    subscriptions: Mutex<HashMap<NotificationHandle, NotificationSender>>,
+}
+
+impl AdsNotificationDispatcher {
+    /// Creates a new dispatcher with empty pending and subscription maps.
+    pub fn new() -> Self {
+        Self {
+            pending: Mutex::new(HashMap::new()),
+            subscriptions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Registers a subscription under a temporary [`InvokeId`] key.
+    ///
+    /// Must be called before dispatching the add notification request to the PLC,
+    /// since the PLC may send an initial sample before the response is received.
+    pub fn pre_register(
+        &self,
+        invoke_id: InvokeId,
+    ) -> crate::Result<UnboundedReceiver<AdsNotificationSampleOwned>> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.pre_register_with(invoke_id, tx)?;
+        Ok(rx)
+    }
+
+    /// Registers an existing `sender` under a temporary [`InvokeId`] key.
+    ///
+    /// Like [`pre_register`](Self::pre_register), but lets the caller keep the receiving
+    /// end, e.g. to register a subscription again after a reconnect.
+    ///
+    /// Fails with [`Error::InvokeIdInUse`](crate::Error::InvokeIdInUse) if another
+    /// subscription is still pre-registered under `invoke_id`.
+    pub fn pre_register_with(
+        &self,
+        invoke_id: InvokeId,
+        sender: impl Into<NotificationSender>,
+    ) -> crate::Result<()> {
+        match self.pending.lock()?.entry(invoke_id) {
+            Entry::Occupied(_) => Err(crate::Error::InvokeIdInUse(invoke_id)),
+            Entry::Vacant(entry) => {
+                entry.insert(sender.into());
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns `true` if a subscription is pre-registered under any of `ids`.
+    pub fn in_flight(&self, ids: Range<InvokeId>) -> crate::Result<bool> {
+        Ok(invoke_id::any_in(&*self.pending.lock()?, ids))
+    }
+
+    /// Promotes a pre-registered subscription from its temporary [`InvokeId`] key
+    /// to the permanent [`NotificationHandle`] assigned by the PLC.
+    ///
+    /// Returns `false` if no pre-registered entry was found for `invoke_id`.
+    pub fn promote(&self, invoke_id: InvokeId, handle: NotificationHandle) -> crate::Result<bool> {
+        let sender = self.pending.lock()?.remove(&invoke_id);
+
+        match sender {
+            Some(tx) => {
+                self.subscriptions.lock()?.insert(handle, tx);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Drops a pre-registered subscription that will never be promoted,
+    /// e.g. because the PLC rejected the add notification request.
+    pub fn abandon(&self, invoke_id: InvokeId) -> crate::Result<()> {
+        self.pending.lock()?.remove(&invoke_id);
+        Ok(())
+    }
+
+    /// Routes an incoming [`AdsNotificationSample`](AdsNotificationSampleOwned)
+    /// to the registered subscriber.
+    ///
+    /// If no subscriber is registered for the handle the sample is dropped silently.
+    /// If the subscriber's receiver has been dropped the entry is pruned.
+    pub fn dispatch(
+        &self,
+        handle: NotificationHandle,
+        timestamp: WindowsFileTime,
+        sample: AdsNotificationSampleOwned,
+    ) -> crate::Result<()> {
+        let mut map = self.subscriptions.lock()?;
+
+        let dead = if let Some(tx) = map.get(&handle) {
+            !tx.send(timestamp, sample)
+        } else {
+            false
+        };
+
+        if dead {
+            map.remove(&handle);
+        }
+
+        Ok(())
+    }
+
+    /// Removes the subscription for a [`NotificationHandle`], dropping the sender.
+    pub fn remove(&self, handle: NotificationHandle) -> crate::Result<()> {
+        self.subscriptions.lock()?.remove(&handle);
+        Ok(())
+    }
+
+    /// Clears all pending and active subscriptions.
+    pub fn clear(&self) -> crate::Result<()> {
+        self.pending.lock()?.clear();
+        self.subscriptions.lock()?.clear();
+        Ok(())
+    }
+}
+
+impl Default for AdsNotificationDispatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_sample(handle: NotificationHandle) -> AdsNotificationSampleOwned {
+        AdsNotificationSampleOwned::new(handle, vec![0x01, 0x02, 0x03, 0x04])
+    }
+
+    #[tokio::test]
+    async fn pre_register_and_promote_routes_samples() {
+        let dispatcher = AdsNotificationDispatcher::new();
+        let handle = NotificationHandle::from(42);
+
+        let mut rx = dispatcher.pre_register(1).unwrap();
+        dispatcher.promote(1, handle).unwrap();
+
+        let sample = make_sample(handle);
+        dispatcher
+            .dispatch(handle, WindowsFileTime::now(), sample.clone())
+            .unwrap();
+
+        assert_eq!(rx.recv().await.unwrap(), sample);
+    }
+
+    #[tokio::test]
+    async fn dispatch_prunes_dead_receiver() {
+        let dispatcher = AdsNotificationDispatcher::new();
+        let handle = NotificationHandle::from(1u32);
+
+        let rx = dispatcher.pre_register(1).unwrap();
+        dispatcher.promote(1, handle).unwrap();
+
+        drop(rx);
+
+        dispatcher
+            .dispatch(handle, WindowsFileTime::now(), make_sample(handle))
+            .unwrap();
+
+        assert!(dispatcher.subscriptions.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn abandon_closes_pending_receiver() {
+        let dispatcher = AdsNotificationDispatcher::new();
+
+        let mut rx = dispatcher.pre_register(1).unwrap();
+        dispatcher.abandon(1).unwrap();
+
+        assert!(rx.recv().await.is_none());
+        assert!(
+            !dispatcher
+                .promote(1, NotificationHandle::from(1u32))
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn remove_and_clear_close_receivers() {
+        let dispatcher = AdsNotificationDispatcher::new();
+        let h1 = NotificationHandle::from(1u32);
+        let h2 = NotificationHandle::from(2u32);
+
+        let mut rx1 = dispatcher.pre_register(1).unwrap();
+        let mut rx2 = dispatcher.pre_register(2).unwrap();
+        dispatcher.promote(1, h1).unwrap();
+        dispatcher.promote(2, h2).unwrap();
+
+        dispatcher.remove(h1).unwrap();
+        assert!(rx1.recv().await.is_none());
+
+        dispatcher.clear().unwrap();
+        assert!(rx2.recv().await.is_none());
+    }
+}
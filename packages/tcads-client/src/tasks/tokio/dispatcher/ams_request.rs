@@ -0,0 +1,378 @@
+use super::AmsRequestDispatchKey;
+use crate::tasks::invoke_id;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::ops::Range;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tcads_core::InvokeId;
+use tcads_core::io::AmsFrame;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::oneshot::{self, Receiver, Sender};
+
+/// Tracks pending requests and dispatches frames to the writer task.
+///
+/// The async counterpart of the [blocking dispatcher](crate::tasks::blocking::AmsRequestDispatcher).
+/// [`AmsRequestDispatcher::dispatch`] registers the caller's [`oneshot`] response channel then
+/// forwards the frame to the writer. When the reader task receives a response, it calls
+/// [`AmsRequestDispatcher::complete`] to route the frame back to the waiting caller.
+///
+/// The internal maps are guarded by [`std::sync::Mutex`] since no lock is ever held
+/// across an `.await` point.
+pub struct AmsRequestDispatcher {
+    /// Pending ADS command responses, keyed by [invoke ID](InvokeId).
+    ads: Mutex<HashMap<InvokeId, Sender<AmsFrame>>>,
+    /// Pending [PortConnect](tcads_core::protocol::PortConnectResponse) responses.
+    port_connect: Mutex<VecDeque<Sender<AmsFrame>>>,
+    /// Pending [GetLocalNetId](tcads_core::protocol::GetLocalNetIdResponse) responses.
+    net_id: Mutex<VecDeque<Sender<AmsFrame>>>,
+    /// Set once the reader task has exited.
+    closed: AtomicBool,
+    /// Responses that arrived for no pending request.
+    late_responses: AtomicU64,
+    /// Channel to the writer task, replaced when the connection is [reopened](Self::reopen).
+    write_tx: Mutex<UnboundedSender<AmsFrame>>,
+}
+
+impl AmsRequestDispatcher {
+    /// Creates a new dispatcher with the given writer channel sender.
+    pub fn new(write_tx: UnboundedSender<AmsFrame>) -> Self {
+        Self {
+            ads: Mutex::new(HashMap::new()),
+            port_connect: Mutex::new(VecDeque::new()),
+            net_id: Mutex::new(VecDeque::new()),
+            closed: AtomicBool::new(false),
+            late_responses: AtomicU64::new(0),
+            write_tx: Mutex::new(write_tx),
+        }
+    }
+
+    /// Registers a waiter, enqueues the frame for writing, and returns the response receiver.
+    ///
+    /// Registration and dispatch happen together, closing the window between the two.
+    /// Fails with [`Error::InvokeIdInUse`](crate::Error::InvokeIdInUse) instead of
+    /// replacing an ADS request that is still waiting under the same invoke ID.
+    pub fn dispatch(
+        &self,
+        key: AmsRequestDispatchKey,
+        frame: AmsFrame,
+    ) -> crate::Result<Receiver<AmsFrame>> {
+        let (tx, rx) = oneshot::channel();
+        self.register(key, tx)?;
+        self.write_tx.lock()?.send(frame)?;
+        Ok(rx)
+    }
+
+    /// Registers a waiter that discards the response, then enqueues the frame for writing.
+    ///
+    /// Use this for requests whose response the caller does not wait for, e.g. releasing a
+    /// handle on drop. The invoke ID stays reserved until the response arrives, and the
+    /// response is not counted as [late](Self::late_responses).
+    pub fn dispatch_detached(
+        &self,
+        key: AmsRequestDispatchKey,
+        frame: AmsFrame,
+    ) -> crate::Result<()> {
+        let (tx, _) = oneshot::channel();
+        self.register(key, tx)?;
+        self.write_tx.lock()?.send(frame)?;
+        Ok(())
+    }
+
+    /// Called by the reader task to complete a pending request.
+    ///
+    /// If the caller has stopped waiting the frame is discarded. An ADS response whose
+    /// request was [cancelled](Self::cancel), or was never sent, is counted as
+    /// [late](Self::late_responses).
+    pub fn complete(&self, key: AmsRequestDispatchKey, frame: AmsFrame) -> crate::Result<()> {
+        match self.take(key)? {
+            Some(tx) => {
+                let _ = tx.send(frame);
+            }
+            None if matches!(key, AmsRequestDispatchKey::AdsCommand(_)) => {
+                self.late_responses.fetch_add(1, Ordering::Relaxed);
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Purges the pending ADS request under `key` once its caller has stopped waiting,
+    /// e.g. after a timeout. Returns `true` if the request was still pending.
+    ///
+    /// Dropping the sender wakes a caller that is still waiting with
+    /// [`Error::Disconnected`](crate::Error::Disconnected). Router requests are answered
+    /// in order rather than by invoke ID, so they stay queued to keep later responses
+    /// matched to their callers.
+    pub fn cancel(&self, key: AmsRequestDispatchKey) -> crate::Result<bool> {
+        match key {
+            AmsRequestDispatchKey::AdsCommand(id) => Ok(self.ads.lock()?.remove(&id).is_some()),
+            AmsRequestDispatchKey::PortConnect | AmsRequestDispatchKey::GetLocalNetId => Ok(false),
+        }
+    }
+
+    /// Returns how many ADS responses arrived after their request was
+    /// [cancelled](Self::cancel), or for no request at all.
+    pub fn late_responses(&self) -> u64 {
+        self.late_responses.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if an ADS request under any of `ids` is waiting for a response.
+    pub fn in_flight(&self, ids: Range<InvokeId>) -> crate::Result<bool> {
+        Ok(invoke_id::any_in(&*self.ads.lock()?, ids))
+    }
+
+    /// Returns the number of ADS requests waiting for a response.
+    pub fn pending(&self) -> crate::Result<usize> {
+        Ok(self.ads.lock()?.len())
+    }
+
+    /// Sends `frame` directly to the writer task without registering a response waiter.
+    ///
+    /// Use this for frames where no response is expected i.e.
+    /// [`PortClose`](tcads_core::protocol::PortCloseRequest).
+    ///
+    /// Returns [`Err`] if the writer channel is already closed, which means the
+    /// connection is already gone.
+    pub fn send_only(&self, frame: AmsFrame) -> crate::Result<()> {
+        self.write_tx.lock()?.send(frame)?;
+        Ok(())
+    }
+
+    /// Clears all pending requests, waking waiting callers with a disconnected error.
+    ///
+    /// Dropping the senders causes all awaited receivers to resolve to [`Err`],
+    /// which maps to [`Error::Disconnected`](crate::Error::Disconnected).
+    pub fn clear(&self) -> crate::Result<()> {
+        self.port_connect.lock()?.clear();
+        self.ads.lock()?.clear();
+        self.net_id.lock()?.clear();
+        Ok(())
+    }
+
+    /// Clears all pending requests and marks the connection as closed.
+    ///
+    /// Called by the reader task when it exits.
+    pub fn close(&self) -> crate::Result<()> {
+        self.closed.store(true, Ordering::Release);
+        self.clear()
+    }
+
+    /// Returns `true` once the connection is [closed](Self::close).
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    /// Points the dispatcher at the writer task of a new connection and clears the
+    /// [closed](Self::close) flag.
+    ///
+    /// The previous writer task exits once its sender is dropped here. Must only be
+    /// called after the reader task of the previous connection has exited, so that its
+    /// [`close`](Self::close) cannot race the new connection.
+    pub fn reopen(&self, write_tx: UnboundedSender<AmsFrame>) -> crate::Result<()> {
+        self.clear()?;
+        *self.write_tx.lock()? = write_tx;
+        self.closed.store(false, Ordering::Release);
+        Ok(())
+    }
+
+    fn register(&self, key: AmsRequestDispatchKey, sender: Sender<AmsFrame>) -> crate::Result<()> {
+        match key {
+            AmsRequestDispatchKey::AdsCommand(id) => match self.ads.lock()?.entry(id) {
+                Entry::Occupied(_) => return Err(crate::Error::InvokeIdInUse(id)),
+                Entry::Vacant(entry) => {
+                    entry.insert(sender);
+                }
+            },
+            AmsRequestDispatchKey::PortConnect => {
+                self.port_connect.lock()?.push_back(sender);
+            }
+            AmsRequestDispatchKey::GetLocalNetId => {
+                self.net_id.lock()?.push_back(sender);
+            }
+        }
+        Ok(())
+    }
+
+    fn take(&self, key: AmsRequestDispatchKey) -> crate::Result<Option<Sender<AmsFrame>>> {
+        let value = match key {
+            AmsRequestDispatchKey::PortConnect => self.port_connect.lock()?.pop_front(),
+            AmsRequestDispatchKey::GetLocalNetId => self.net_id.lock()?.pop_front(),
+            AmsRequestDispatchKey::AdsCommand(id) => self.ads.lock()?.remove(&id),
+        };
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tcads_core::ams::AmsCommand;
+    use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+    fn make_dispatcher() -> (AmsRequestDispatcher, UnboundedReceiver<AmsFrame>) {
+        let (write_tx, write_rx) = mpsc::unbounded_channel();
+        (AmsRequestDispatcher::new(write_tx), write_rx)
+    }
+
+    #[tokio::test]
+    async fn dispatch_enqueues_frame_and_returns_receiver() {
+        let (dispatcher, mut write_rx) = make_dispatcher();
+        let frame = AmsFrame::empty(AmsCommand::AdsCommand);
+
+        let mut rx = dispatcher
+            .dispatch(AmsRequestDispatchKey::AdsCommand(1), frame.clone())
+            .expect("dispatch should succeed");
+
+        let sent = write_rx.recv().await.expect("writer should receive frame");
+        assert_eq!(sent, frame);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn complete_routes_frame_to_waiting_caller() {
+        let (dispatcher, _write_rx) = make_dispatcher();
+        let frame = AmsFrame::empty(AmsCommand::AdsCommand);
+        let response = AmsFrame::empty(AmsCommand::AdsCommand);
+
+        let rx = dispatcher
+            .dispatch(AmsRequestDispatchKey::AdsCommand(42), frame)
+            .expect("dispatch should succeed");
+
+        dispatcher
+            .complete(AmsRequestDispatchKey::AdsCommand(42), response.clone())
+            .expect("complete should succeed");
+
+        assert_eq!(rx.await.expect("should receive response"), response);
+    }
+
+    #[tokio::test]
+    async fn complete_after_caller_gave_up_is_ignored() {
+        let (dispatcher, _write_rx) = make_dispatcher();
+        let frame = AmsFrame::empty(AmsCommand::AdsCommand);
+
+        let rx = dispatcher
+            .dispatch(AmsRequestDispatchKey::AdsCommand(7), frame.clone())
+            .expect("dispatch should succeed");
+        drop(rx);
+
+        assert!(
+            dispatcher
+                .complete(AmsRequestDispatchKey::AdsCommand(7), frame)
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn clear_wakes_waiting_callers_with_error() {
+        let (dispatcher, _write_rx) = make_dispatcher();
+        let frame = AmsFrame::empty(AmsCommand::AdsCommand);
+
+        let rx = dispatcher
+            .dispatch(AmsRequestDispatchKey::AdsCommand(1), frame)
+            .expect("dispatch should succeed");
+
+        dispatcher.clear().expect("clear should succeed");
+
+        assert!(rx.await.is_err());
+    }
+
+    #[tokio::test]
+    async fn netid_queue_handles_multiple_concurrent_callers() {
+        let (dispatcher, _write_rx) = make_dispatcher();
+        let frame = AmsFrame::empty(AmsCommand::GetLocalNetId);
+
+        let rx1 = dispatcher
+            .dispatch(AmsRequestDispatchKey::GetLocalNetId, frame.clone())
+            .expect("first dispatch");
+        let rx2 = dispatcher
+            .dispatch(AmsRequestDispatchKey::GetLocalNetId, frame)
+            .expect("second dispatch");
+
+        let resp1 = AmsFrame::new(AmsCommand::GetLocalNetId, [0x01]);
+        let resp2 = AmsFrame::new(AmsCommand::GetLocalNetId, [0x02]);
+
+        dispatcher
+            .complete(AmsRequestDispatchKey::GetLocalNetId, resp1.clone())
+            .expect("complete should succeed");
+        dispatcher
+            .complete(AmsRequestDispatchKey::GetLocalNetId, resp2.clone())
+            .expect("complete should succeed");
+
+        assert_eq!(rx1.await.unwrap(), resp1);
+        assert_eq!(rx2.await.unwrap(), resp2);
+    }
+
+    #[tokio::test]
+    async fn reopen_switches_writer_and_clears_closed_flag() {
+        let (dispatcher, mut old_rx) = make_dispatcher();
+        dispatcher.close().expect("close should succeed");
+        assert!(dispatcher.is_closed());
+
+        let (write_tx, mut new_rx) = mpsc::unbounded_channel();
+        dispatcher.reopen(write_tx).expect("reopen should succeed");
+        assert!(!dispatcher.is_closed());
+
+        let frame = AmsFrame::empty(AmsCommand::AdsCommand);
+        dispatcher
+            .send_only(frame.clone())
+            .expect("send should succeed");
+
+        assert_eq!(new_rx.recv().await, Some(frame));
+        assert!(
+            old_rx.recv().await.is_none(),
+            "Old writer channel must be closed"
+        );
+    }
+
+    #[tokio::test]
+    async fn cancelled_request_is_purged_and_late_response_counted() {
+        let (dispatcher, _write_rx) = make_dispatcher();
+        let frame = AmsFrame::empty(AmsCommand::AdsCommand);
+        let key = AmsRequestDispatchKey::AdsCommand(3);
+
+        let rx = dispatcher.dispatch(key, frame.clone()).unwrap();
+        assert_eq!(dispatcher.pending().unwrap(), 1);
+        assert!(dispatcher.cancel(key).unwrap());
+        assert!(!dispatcher.cancel(key).unwrap());
+        assert_eq!(dispatcher.pending().unwrap(), 0);
+        assert!(rx.await.is_err());
+
+        dispatcher.complete(key, frame).unwrap();
+        assert_eq!(dispatcher.late_responses(), 1);
+    }
+
+    #[tokio::test]
+    async fn detached_response_is_discarded_without_counting_as_late() {
+        let (dispatcher, _write_rx) = make_dispatcher();
+        let frame = AmsFrame::empty(AmsCommand::AdsCommand);
+        let key = AmsRequestDispatchKey::AdsCommand(4);
+
+        dispatcher.dispatch_detached(key, frame.clone()).unwrap();
+        assert!(dispatcher.in_flight(4..5).unwrap());
+
+        dispatcher.complete(key, frame).unwrap();
+        assert_eq!(dispatcher.pending().unwrap(), 0);
+        assert_eq!(dispatcher.late_responses(), 0);
+    }
+
+    #[tokio::test]
+    async fn colliding_invoke_id_is_rejected() {
+        let (dispatcher, mut write_rx) = make_dispatcher();
+        let frame = AmsFrame::empty(AmsCommand::AdsCommand);
+        let key = AmsRequestDispatchKey::AdsCommand(5);
+
+        let _rx = dispatcher.dispatch(key, frame.clone()).unwrap();
+        assert!(matches!(
+            dispatcher.dispatch(key, frame),
+            Err(crate::Error::InvokeIdInUse(5))
+        ));
+        assert_eq!(dispatcher.pending().unwrap(), 1);
+        assert!(write_rx.recv().await.is_some());
+        assert!(write_rx.try_recv().is_err());
+
+        assert!(dispatcher.in_flight(5..6).unwrap());
+        assert!(!dispatcher.in_flight(6..100).unwrap());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::devices::connection::ConnectionState;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Holds the [`ConnectionState`] of a device and the round-trip time of its last
+/// heartbeat, and publishes state changes to watchers.
+///
+/// Like the [`ConnectionEventDispatcher`](super::ConnectionEventDispatcher), it outlives
+/// individual connections. Watchers see [`ConnectionState::Closed`] last; once the
+/// device is dropped, [`watch::Receiver::changed`] returns [`Err`].
+pub struct ConnectionStateDispatcher {
+    state: watch::Sender<ConnectionState>,
+    rtt: Mutex<Option<Duration>>,
+}
+
+impl ConnectionStateDispatcher {
+    /// Creates a new dispatcher in `state`.
+    pub fn new(state: ConnectionState) -> Self {
+        Self {
+            state: watch::Sender::new(state),
+            rtt: Mutex::new(None),
+        }
+    }
+
+    /// Returns the current state.
+    pub fn state(&self) -> ConnectionState {
+        *self.state.borrow()
+    }
+
+    /// Returns a receiver that observes the current state and every later change.
+    pub fn watch(&self) -> watch::Receiver<ConnectionState> {
+        self.state.subscribe()
+    }
+
+    /// Moves to `state`, unless the device is already [closed](ConnectionState::Closed).
+    ///
+    /// Watchers are only woken if the state actually changes.
+    pub fn set(&self, state: ConnectionState) {
+        self.state.send_if_modified(|current| {
+            if *current == state || *current == ConnectionState::Closed {
+                return false;
+            }
+            *current = state;
+            true
+        });
+    }
+
+    /// Moves from `from` to `to`, returning `false` if the current state is not `from`.
+    pub fn transition(&self, from: ConnectionState, to: ConnectionState) -> bool {
+        self.state.send_if_modified(|current| {
+            if *current != from || from == to {
+                return false;
+            }
+            *current = to;
+            true
+        })
+    }
+
+    /// Returns the round-trip time of the last answered heartbeat.
+    pub fn rtt(&self) -> crate::Result<Option<Duration>> {
+        Ok(*self.rtt.lock()?)
+    }
+
+    /// Records the round-trip time of an answered heartbeat.
+    pub fn set_rtt(&self, rtt: Duration) -> crate::Result<()> {
+        *self.rtt.lock()? = Some(rtt);
+        Ok(())
+    }
+}
+
+impl Default for ConnectionStateDispatcher {
+    /// Creates a dispatcher for a device that is already
+    /// [connected](ConnectionState::Connected).
+    fn default() -> Self {
+        Self::new(ConnectionState::Connected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn watchers_see_changes_until_closed() {
+        let dispatcher = ConnectionStateDispatcher::default();
+        let mut rx = dispatcher.watch();
+
+        assert!(!dispatcher.transition(ConnectionState::Degraded, ConnectionState::Connected));
+        assert!(dispatcher.transition(ConnectionState::Connected, ConnectionState::Degraded));
+        rx.changed().await.unwrap();
+        assert_eq!(*rx.borrow_and_update(), ConnectionState::Degraded);
+
+        dispatcher.set(ConnectionState::Closed);
+        dispatcher.set(ConnectionState::Connecting);
+        rx.changed().await.unwrap();
+        assert_eq!(*rx.borrow_and_update(), ConnectionState::Closed);
+        assert_eq!(dispatcher.state(), ConnectionState::Closed);
+
+        drop(dispatcher);
+        assert!(rx.changed().await.is_err());
+    }
+}
@@ -0,0 +1,12 @@
+pub mod ads_notification;
+pub mod ams_request;
+pub mod connection_event;
+pub mod connection_state;
+pub mod router_notification;
+
+pub use super::AmsRequestDispatchKey;
+pub use ads_notification::{AdsNotificationDispatcher, NotificationSender};
+pub use ams_request::AmsRequestDispatcher;
+pub use connection_event::ConnectionEventDispatcher;
+pub use connection_state::ConnectionStateDispatcher;
+pub use router_notification::RouterNotificationDispatcher;
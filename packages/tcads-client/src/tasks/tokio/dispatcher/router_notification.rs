@@ -0,0 +1,99 @@
+use std::sync::Mutex;
+use tcads_core::RouterState;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Fans out [router state](RouterState) changes to all registered subscribers.
+///
+/// Subscribers register via [`subscribe`](Self::subscribe), which returns an
+/// [`UnboundedReceiver<RouterState>`]. Every call to [`broadcast`](Self::broadcast) sends
+/// the state to all live subscribers. This will subsequently silently prune dead receivers.
+pub struct RouterNotificationDispatcher {
+    subscribers: Mutex<Vec<UnboundedSender<RouterState>>>,
+}
+
+impl RouterNotificationDispatcher {
+    /// Creates a new dispatcher with no subscribers.
+    pub fn new() -> Self {
+        Self {
+            subscribers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Registers a new subscriber and returns an [`UnboundedReceiver<RouterState>`].
+    ///
+    /// The receiver will yield every [`RouterState`] broadcast until either:
+    /// - The receiver is dropped by the caller, or
+    /// - [`clear`](Self::clear) is called on connection loss.
+    pub fn subscribe(&self) -> crate::Result<UnboundedReceiver<RouterState>> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.subscribers.lock()?.push(tx);
+        Ok(rx)
+    }
+
+    /// Broadcasts `state` to all live subscribers, pruning dead receivers.
+    ///
+    /// Called by the reader task on every incoming
+    /// [`RouterNotification`](tcads_core::protocol::RouterNotification) frame.
+    pub fn broadcast(&self, state: RouterState) -> crate::Result<()> {
+        let mut subscribers = self.subscribers.lock()?;
+        subscribers.retain(|tx| tx.send(state).is_ok());
+        Ok(())
+    }
+
+    /// Drops all subscribers, causing their receivers to yield [`None`].
+    ///
+    /// Called on connection loss so subscriber loops can exit naturally.
+    pub fn clear(&self) -> crate::Result<()> {
+        self.subscribers.lock()?.clear();
+        Ok(())
+    }
+}
+
+impl Default for RouterNotificationDispatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn multiple_subscribers_all_receive_broadcast() {
+        let dispatcher = RouterNotificationDispatcher::new();
+        let mut rx1 = dispatcher.subscribe().unwrap();
+        let mut rx2 = dispatcher.subscribe().unwrap();
+
+        dispatcher.broadcast(RouterState::Start).unwrap();
+
+        assert_eq!(rx1.recv().await, Some(RouterState::Start));
+        assert_eq!(rx2.recv().await, Some(RouterState::Start));
+    }
+
+    #[tokio::test]
+    async fn dead_receivers_are_pruned_on_broadcast() {
+        let dispatcher = RouterNotificationDispatcher::new();
+        let mut rx1 = dispatcher.subscribe().unwrap();
+        let rx2 = dispatcher.subscribe().unwrap();
+
+        drop(rx2);
+
+        dispatcher.broadcast(RouterState::Stop).unwrap();
+
+        assert_eq!(rx1.recv().await, Some(RouterState::Stop));
+        assert_eq!(dispatcher.subscribers.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn clear_closes_all_subscribers() {
+        let dispatcher = RouterNotificationDispatcher::new();
+        let mut rx1 = dispatcher.subscribe().unwrap();
+        let mut rx2 = dispatcher.subscribe().unwrap();
+
+        dispatcher.clear().unwrap();
+
+        assert_eq!(rx1.recv().await, None);
+        assert_eq!(rx2.recv().await, None);
+    }
+}
@@ -1 +1,11 @@
+pub mod dispatcher;
+pub mod reader;
+pub mod writer;
 
+pub use super::{AmsRequestDispatchKey, InvokeIdAllocator};
+pub use dispatcher::{
+    AdsNotificationDispatcher, AmsRequestDispatcher, ConnectionEventDispatcher,
+    ConnectionStateDispatcher, NotificationSender, RouterNotificationDispatcher,
+};
+pub use reader::AmsResponseReader;
+pub use writer::AmsRequestWriter;
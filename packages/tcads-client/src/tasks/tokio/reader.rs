@@ -0,0 +1,329 @@
+use super::{
+    AdsNotificationDispatcher, AmsRequestDispatchKey, AmsRequestDispatcher,
+    RouterNotificationDispatcher,
+};
+use std::io;
+use std::sync::Arc;
+use tcads_core::io::tokio::AmsReader;
+use tcads_core::protocol::{AdsDeviceNotification, RouterNotification};
+use tcads_core::{AdsCommand, AdsHeader, AmsCommand, RouterState};
+use tokio::io::AsyncRead;
+use tokio::task::JoinHandle;
+
+/// Spawns a dedicated reader task for deserializing incoming [`AmsFrame`](tcads_core::AmsFrame)s
+/// and routing them to their waiting callers.
+///
+/// # Frame Routing
+///
+/// | [`AmsCommand`]                      | Routed to                          |
+/// |-------------------------------------|------------------------------------|
+/// | [`PortConnect`]                     | [`AmsRequestDispatcher`]           |
+/// | [`GetLocalNetId`]                   | [`AmsRequestDispatcher`]           |
+/// | [`AdsCommand`] (non-notification)   | [`AmsRequestDispatcher`]           |
+/// | [`AdsCommand`] (notification)       | [`AdsNotificationDispatcher`]      |
+/// | [`RouterNotification`]              | [`RouterNotificationDispatcher`]   |
+/// | [`PortClose`]                       | exits loop                         |
+/// | Malformed / unknown                 | skipped silently                   |
+///
+/// [`PortConnect`]: AmsCommand::PortConnect
+/// [`GetLocalNetId`]: AmsCommand::GetLocalNetId
+/// [`PortClose`]: AmsCommand::PortClose
+pub struct AmsResponseReader;
+
+impl AmsResponseReader {
+    /// Spawns the reader task on the current Tokio runtime.
+    ///
+    /// The task runs until the underlying stream reaches EOF, a
+    /// [`PortClose`](AmsCommand::PortClose) frame is received, or the connection
+    /// is lost via [`RouterState::Removed`].
+    ///
+    /// On exit, all dispatchers are cleared unconditionally. Pending callers receive
+    /// [`Error::Disconnected`](crate::Error::Disconnected) and notification subscribers
+    /// receive [`None`] on their next [`recv`](tokio::sync::mpsc::UnboundedReceiver::recv) call.
+    ///
+    /// The returned [`JoinHandle`] carries a [`crate::Result`] so the caller can
+    /// surface any error that caused the reader to exit unexpectedly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside the context of a Tokio runtime.
+    pub fn spawn<R: AsyncRead + Unpin + Send + 'static>(
+        reader: AmsReader<R>,
+        ams_requests: Arc<AmsRequestDispatcher>,
+        ads_notifs: Arc<AdsNotificationDispatcher>,
+        router_notifs: Arc<RouterNotificationDispatcher>,
+    ) -> JoinHandle<crate::Result<()>> {
+        tokio::spawn(async move {
+            let result = handle(reader, &ams_requests, &ads_notifs, &router_notifs).await;
+            ams_requests.close()?;
+            ads_notifs.clear()?;
+            router_notifs.clear()?;
+            result
+        })
+    }
+}
+
+async fn handle<R: AsyncRead + Unpin>(
+    mut reader: AmsReader<R>,
+    ams_requests: &AmsRequestDispatcher,
+    ads_notifs: &AdsNotificationDispatcher,
+    router_notifs: &RouterNotificationDispatcher,
+) -> crate::Result<()> {
+    loop {
+        let frame = match reader.read_frame().await {
+            Ok(frame) => frame,
+            // EOF is expected when the connection is closed
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        };
+
+        match frame.header().command() {
+            AmsCommand::PortConnect => {
+                ams_requests.complete(AmsRequestDispatchKey::PortConnect, frame)?
+            }
+            AmsCommand::GetLocalNetId => {
+                ams_requests.complete(AmsRequestDispatchKey::GetLocalNetId, frame)?
+            }
+            AmsCommand::AdsCommand => {
+                let Ok((header, _)) = AdsHeader::parse_prefix(frame.payload()) else {
+                    continue;
+                };
+
+                match header.command_id() {
+                    AdsCommand::AdsDeviceNotification => {
+                        let Ok(notif) = AdsDeviceNotification::try_from(&frame) else {
+                            continue;
+                        };
+
+                        for (timestamp, sample) in notif.iter_samples() {
+                            ads_notifs.dispatch(sample.handle(), timestamp, sample.to_owned())?;
+                        }
+                    }
+                    _ => ams_requests
+                        .complete(AmsRequestDispatchKey::AdsCommand(header.invoke_id()), frame)?,
+                }
+            }
+            AmsCommand::RouterNotification => {
+                let Ok(notif) = RouterNotification::try_from(frame) else {
+                    continue;
+                };
+
+                match notif.state() {
+                    RouterState::Stop => {
+                        ads_notifs.clear()?;
+                        router_notifs.broadcast(RouterState::Stop)?;
+                    }
+                    RouterState::Removed => {
+                        ads_notifs.clear()?;
+                        ams_requests.clear()?;
+                        router_notifs.broadcast(RouterState::Removed)?;
+                        break;
+                    }
+                    state => router_notifs.broadcast(state)?,
+                }
+            }
+            AmsCommand::PortClose => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::tokio::NotificationSender;
+    use std::io::Cursor;
+    use tcads_core::ads::{AdsReturnCode, NotificationHandle, StateFlag};
+    use tcads_core::protocol::{
+        AdsDeviceNotificationOwned, AdsNotificationSampleOwned, AdsStampHeaderOwned,
+    };
+    use tcads_core::{AmsAddr, AmsFrame, WindowsFileTime};
+    use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+    fn make_dispatchers() -> (
+        Arc<AmsRequestDispatcher>,
+        Arc<AdsNotificationDispatcher>,
+        Arc<RouterNotificationDispatcher>,
+        UnboundedReceiver<AmsFrame>,
+    ) {
+        let (write_tx, write_rx) = mpsc::unbounded_channel();
+        (
+            Arc::new(AmsRequestDispatcher::new(write_tx)),
+            Arc::new(AdsNotificationDispatcher::new()),
+            Arc::new(RouterNotificationDispatcher::new()),
+            write_rx,
+        )
+    }
+
+    async fn run_handle(
+        frames: Vec<AmsFrame>,
+        requests: &AmsRequestDispatcher,
+        ads_notifs: &AdsNotificationDispatcher,
+        router_notifs: &RouterNotificationDispatcher,
+    ) -> crate::Result<()> {
+        let data: Vec<u8> = frames.into_iter().flat_map(|f| f.to_vec()).collect();
+        let reader = AmsReader::new(Cursor::new(data));
+
+        handle(reader, requests, ads_notifs, router_notifs).await
+    }
+
+    #[tokio::test]
+    async fn port_connect_is_routed_to_requests() {
+        let (requests, ads_notifs, router_notifs, _write_rx) = make_dispatchers();
+        let frame = AmsFrame::empty(AmsCommand::PortConnect);
+        let rx = requests
+            .dispatch(AmsRequestDispatchKey::PortConnect, frame.clone())
+            .unwrap();
+
+        run_handle(vec![frame], &requests, &ads_notifs, &router_notifs)
+            .await
+            .unwrap();
+
+        assert!(rx.await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn ads_response_is_routed_by_invoke_id() {
+        let (requests, ads_notifs, router_notifs, _write_rx) = make_dispatchers();
+        let response = AdsHeader::new(
+            AmsAddr::default(),
+            AmsAddr::default(),
+            AdsCommand::AdsReadState,
+            StateFlag::tcp_ads_response(),
+            0,
+            AdsReturnCode::Ok,
+            7,
+        );
+        let frame = AmsFrame::new(AmsCommand::AdsCommand, response.to_bytes());
+        let rx = requests
+            .dispatch(AmsRequestDispatchKey::AdsCommand(7), frame.clone())
+            .unwrap();
+
+        run_handle(vec![frame.clone()], &requests, &ads_notifs, &router_notifs)
+            .await
+            .unwrap();
+
+        assert_eq!(rx.await.unwrap(), frame);
+    }
+
+    #[tokio::test]
+    async fn notification_samples_are_dispatched_by_handle() {
+        let (requests, ads_notifs, router_notifs, _write_rx) = make_dispatchers();
+        let handle = NotificationHandle::from(5u32);
+        let mut notif_rx = ads_notifs.pre_register(1).unwrap();
+        ads_notifs.promote(1, handle).unwrap();
+
+        let sample = AdsNotificationSampleOwned::new(handle, vec![0x2A, 0, 0, 0]);
+        let stamp = AdsStampHeaderOwned::new(WindowsFileTime::now(), vec![sample.clone()]);
+        let frame =
+            AdsDeviceNotificationOwned::new(AmsAddr::default(), AmsAddr::default(), vec![stamp])
+                .into_frame();
+
+        run_handle(vec![frame], &requests, &ads_notifs, &router_notifs)
+            .await
+            .unwrap();
+
+        assert_eq!(notif_rx.try_recv().unwrap(), sample);
+    }
+
+    #[tokio::test]
+    async fn notification_timestamps_reach_stamped_subscribers() {
+        let (requests, ads_notifs, router_notifs, _write_rx) = make_dispatchers();
+        let handle = NotificationHandle::from(5u32);
+        let (tx, mut notif_rx) = mpsc::unbounded_channel();
+        ads_notifs
+            .pre_register_with(1, NotificationSender::Stamped(tx))
+            .unwrap();
+        ads_notifs.promote(1, handle).unwrap();
+
+        let sample = AdsNotificationSampleOwned::new(handle, vec![0x2A, 0, 0, 0]);
+        let timestamp = WindowsFileTime::from_raw(133_000_000_000_000_000);
+        let stamp = AdsStampHeaderOwned::new(timestamp, vec![sample.clone()]);
+        let frame =
+            AdsDeviceNotificationOwned::new(AmsAddr::default(), AmsAddr::default(), vec![stamp])
+                .into_frame();
+
+        run_handle(vec![frame], &requests, &ads_notifs, &router_notifs)
+            .await
+            .unwrap();
+
+        assert_eq!(notif_rx.try_recv().unwrap(), (timestamp, sample));
+    }
+
+    #[tokio::test]
+    async fn port_close_exits_loop_cleanly() {
+        let (requests, ads_notifs, router_notifs, _write_rx) = make_dispatchers();
+        let frame = AmsFrame::empty(AmsCommand::PortClose);
+
+        run_handle(vec![frame], &requests, &ads_notifs, &router_notifs)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn router_stop_clears_ads_notifs_before_broadcasting() {
+        let (requests, ads_notifs, router_notifs, _write_rx) = make_dispatchers();
+
+        let handle = NotificationHandle::from(1u32);
+        let mut notif_rx = ads_notifs.pre_register(1).unwrap();
+        ads_notifs.promote(1, handle).unwrap();
+
+        let mut router_rx = router_notifs.subscribe().unwrap();
+
+        let stop_frame = RouterNotification::new(RouterState::Stop).to_frame();
+        run_handle(vec![stop_frame], &requests, &ads_notifs, &router_notifs)
+            .await
+            .unwrap();
+
+        assert!(
+            notif_rx.recv().await.is_none(),
+            "Notification subscription should be dead"
+        );
+        assert_eq!(router_rx.recv().await, Some(RouterState::Stop));
+    }
+
+    #[tokio::test]
+    async fn router_removed_clears_requests_and_exits() {
+        let (requests, ads_notifs, router_notifs, _write_rx) = make_dispatchers();
+
+        let pending_rx = requests
+            .dispatch(
+                AmsRequestDispatchKey::AdsCommand(1),
+                AmsFrame::empty(AmsCommand::AdsCommand),
+            )
+            .unwrap();
+
+        let mut router_rx = router_notifs.subscribe().unwrap();
+
+        let removed_frame = RouterNotification::new(RouterState::Removed).to_frame();
+        run_handle(vec![removed_frame], &requests, &ads_notifs, &router_notifs)
+            .await
+            .unwrap();
+
+        assert!(
+            pending_rx.await.is_err(),
+            "Pending request should be woken with Disconnected"
+        );
+        assert_eq!(router_rx.recv().await, Some(RouterState::Removed));
+    }
+
+    #[tokio::test]
+    async fn spawned_reader_clears_dispatchers_on_eof() {
+        let (requests, ads_notifs, router_notifs, _write_rx) = make_dispatchers();
+        let pending_rx = requests
+            .dispatch(
+                AmsRequestDispatchKey::AdsCommand(1),
+                AmsFrame::empty(AmsCommand::AdsCommand),
+            )
+            .unwrap();
+
+        let reader = AmsReader::new(Cursor::new(Vec::<u8>::new()));
+        AmsResponseReader::spawn(reader, requests, ads_notifs, router_notifs)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(pending_rx.await.is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use tcads_core::AmsCommand;
+use tcads_core::io::AmsFrame;
+use tcads_core::io::tokio::AmsWriter;
+use tokio::io::AsyncWrite;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+
+/// Spawns a dedicated writer task for serializing [AMS frames](AmsFrame) onto an async byte stream.
+///
+/// Returns an [`UnboundedSender`] for enqueuing frames and a [`JoinHandle`] for awaiting
+/// task completion. The task exits cleanly when all [`UnboundedSender`] clones are dropped,
+/// or immediately after writing a [`PortClose`](AmsCommand::PortClose) frame.
+///
+/// Frames are written in FIFO order, eliminating lock contention between
+/// concurrent callers.
+///
+/// # Note
+///
+/// When the task exits it drops the [`UnboundedReceiver`], invalidating all remaining
+/// [`UnboundedSender`] clones and causing future [`send`](UnboundedSender::send) calls
+/// to return [`Err`].
+pub struct AmsRequestWriter;
+
+impl AmsRequestWriter {
+    /// Spawns a writer task on the current Tokio runtime and returns an [`UnboundedSender`]
+    /// for enqueuing frames and a [`JoinHandle`] for awaiting task completion.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside the context of a Tokio runtime.
+    pub fn spawn<W: AsyncWrite + Unpin + Send + 'static>(
+        writer: AmsWriter<W>,
+    ) -> (UnboundedSender<AmsFrame>, JoinHandle<()>) {
+        let (tx, rx) = mpsc::unbounded_channel::<AmsFrame>();
+        let handle = tokio::spawn(Self::run(writer, rx));
+        (tx, handle)
+    }
+
+    async fn run<W: AsyncWrite + Unpin>(
+        mut writer: AmsWriter<W>,
+        mut rx: UnboundedReceiver<AmsFrame>,
+    ) {
+        while let Some(frame) = rx.recv().await {
+            let is_close = frame.header().command() == AmsCommand::PortClose;
+            if writer.write_frame(&frame).await.is_err() {
+                break;
+            }
+            if is_close {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::pin::Pin;
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll};
+
+    #[derive(Clone, Default)]
+    struct MockWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl MockWriter {
+        fn bytes(&self) -> Vec<u8> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    impl AsyncWrite for MockWriter {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    struct FailingWriter;
+
+    impl AsyncWrite for FailingWriter {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            _: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "pipe broken",
+            )))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn spawn_mock() -> (UnboundedSender<AmsFrame>, JoinHandle<()>, MockWriter) {
+        let mock = MockWriter::default();
+        let (tx, handle) = AmsRequestWriter::spawn(AmsWriter::new(mock.clone()));
+        (tx, handle, mock)
+    }
+
+    #[tokio::test]
+    async fn frames_are_written_in_order() {
+        let (tx, handle, mock) = spawn_mock();
+
+        let f1 = AmsFrame::new(AmsCommand::AdsCommand, vec![0x01]);
+        let f2 = AmsFrame::new(AmsCommand::AdsCommand, vec![0x02]);
+
+        tx.send(f1.clone()).unwrap();
+        tx.send(f2.clone()).unwrap();
+
+        drop(tx);
+        handle.await.unwrap();
+
+        assert_eq!(mock.bytes(), [f1.to_vec(), f2.to_vec()].concat());
+    }
+
+    #[tokio::test]
+    async fn task_exits_on_write_error() {
+        let (tx, handle) = AmsRequestWriter::spawn(AmsWriter::new(FailingWriter));
+        tx.send(AmsFrame::empty(AmsCommand::AdsCommand)).unwrap();
+
+        handle.await.unwrap();
+        assert!(tx.is_closed());
+    }
+
+    #[tokio::test]
+    async fn port_close_exits_task_and_invalidates_sender() {
+        let (tx, handle, mock) = spawn_mock();
+
+        let before = AmsFrame::new(AmsCommand::AdsCommand, vec![0xAA]);
+        let close = AmsFrame::empty(AmsCommand::PortClose);
+        let after = AmsFrame::new(AmsCommand::AdsCommand, vec![0xBB]);
+
+        tx.send(before.clone()).unwrap();
+        tx.send(close.clone()).unwrap();
+
+        handle.await.unwrap();
+
+        assert!(
+            tx.send(after).is_err(),
+            "Frame after PortClose was never enqueued, tx is now invalid"
+        );
+        assert_eq!(
+            mock.bytes(),
+            [before.to_vec(), close.to_vec()].concat(),
+            "Only the two frames before and including PortClose were written"
+        );
+    }
+}
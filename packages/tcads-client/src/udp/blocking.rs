@@ -0,0 +1,236 @@
+use super::{BROADCAST_ADDR, MAX_DATAGRAM, SOURCE, bind_addr, next_invoke_id, route_result};
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+use tcads_core::ams::AmsRoute;
+use tcads_core::udp::{
+    AddRouteRequest, DeleteRouteRequest, DiscoverRequest, DiscoverResponse, RouteResponse,
+    UdpMessage,
+};
+
+/// Finds the TwinCAT systems answering at `addr` within `timeout`.
+///
+/// `addr` is usually a broadcast address such as [`BROADCAST_ADDR`] or a subnet's
+/// `x.x.x.255:48899`, but may be a single host. Returns each system once, with the address
+/// it answered from, in the order the answers arrived. Malformed answers are skipped.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use tcads_client::udp::{BROADCAST_ADDR, blocking};
+///
+/// for (addr, system) in blocking::discover(BROADCAST_ADDR, Duration::from_secs(1))? {
+///     println!("{} {} at {addr}, TwinCAT {}", system.host_name(), system.net_id(), system.tc_version());
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn discover(
+    addr: impl ToSocketAddrs,
+    timeout: Duration,
+) -> crate::Result<Vec<(SocketAddr, DiscoverResponse)>> {
+    let request = DiscoverRequest::new(next_invoke_id(), SOURCE).to_message();
+    let mut systems: Vec<(SocketAddr, DiscoverResponse)> = Vec::new();
+
+    exchange(addr, &request, timeout, |from, message| {
+        if let Ok(system) = DiscoverResponse::try_from(&message)
+            && !systems.iter().any(|(_, s)| s.net_id() == system.net_id())
+        {
+            systems.push((from, system));
+        }
+        false
+    })?;
+
+    Ok(systems)
+}
+
+/// Finds the TwinCAT systems on the local subnet, as with [`discover`] to
+/// [`BROADCAST_ADDR`].
+pub fn discover_local(timeout: Duration) -> crate::Result<Vec<(SocketAddr, DiscoverResponse)>> {
+    discover(BROADCAST_ADDR, timeout)
+}
+
+/// Adds `route` to the system answering at `addr`, authenticating as `user_name`.
+///
+/// `route` leads back to this machine: its net ID and host are the ones the remote system
+/// should use to reach us. The route is temporary if it has the
+/// [`TEMPORARY`](tcads_core::ams::AmsRouteFlags::TEMPORARY) flag.
+///
+/// Returns [`Error::AuthenticationFailed`](crate::Error::AuthenticationFailed) if the
+/// credentials are rejected, and [`Error::Timeout`](crate::Error::Timeout) if the system does
+/// not answer within `timeout`.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use tcads_client::udp::blocking;
+/// use tcads_core::ams::AmsRoute;
+/// use tcads_core::udp::ADS_UDP_PORT;
+///
+/// let route = AmsRoute::new("provisioning", "192.168.0.10.1.1".parse()?, "192.168.0.10");
+/// let plc = ("192.168.0.20", ADS_UDP_PORT);
+/// blocking::add_route(plc, &route, "Administrator", "1", Duration::from_secs(5))?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn add_route(
+    addr: impl ToSocketAddrs,
+    route: &AmsRoute,
+    user_name: &str,
+    password: &str,
+    timeout: Duration,
+) -> crate::Result<()> {
+    let request = AddRouteRequest::new(next_invoke_id(), route, user_name, password);
+    let mut response = None;
+    exchange(addr, &request.to_message(), timeout, |_, message| {
+        response = RouteResponse::try_from(&message).ok();
+        response.is_some()
+    })?;
+    route_result(response)
+}
+
+/// Deletes the route named `route_name` from the system answering at `addr`,
+/// authenticating as `user_name`.
+///
+/// Fails as [`add_route`] does.
+pub fn delete_route(
+    addr: impl ToSocketAddrs,
+    route_name: &str,
+    user_name: &str,
+    password: &str,
+    timeout: Duration,
+) -> crate::Result<()> {
+    let request =
+        DeleteRouteRequest::new(next_invoke_id(), SOURCE, route_name, user_name, password);
+    let mut response = None;
+    exchange(addr, &request.to_message(), timeout, |_, message| {
+        response = RouteResponse::try_from(&message).ok();
+        response.is_some()
+    })?;
+    route_result(response)
+}
+
+/// Sends `request` to `addr`, passing each response to it to `on_response` until
+/// `on_response` returns `true` or `timeout` elapses.
+pub(crate) fn exchange(
+    addr: impl ToSocketAddrs,
+    request: &UdpMessage,
+    timeout: Duration,
+    mut on_response: impl FnMut(SocketAddr, UdpMessage) -> bool,
+) -> crate::Result<()> {
+    let target = addr
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no address to send to"))?;
+    let socket = UdpSocket::bind(bind_addr(target))?;
+    socket.set_broadcast(true)?;
+    socket.send_to(&request.to_bytes(), target)?;
+
+    let deadline = Instant::now() + timeout;
+    let mut buf = [0; MAX_DATAGRAM];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(());
+        }
+        socket.set_read_timeout(Some(remaining))?;
+
+        let (len, from) = match socket.recv_from(&mut buf) {
+            Ok(received) => received,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let Ok(message) = UdpMessage::parse(&buf[..len]) else {
+            continue;
+        };
+        if message.is_response()
+            && message.service() == request.service()
+            && message.invoke_id() == request.invoke_id()
+            && on_response(from, message)
+        {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use std::thread;
+    use tcads_core::udp::{OsVersion, TcVersion, UdpServiceId};
+    use tcads_core::{AdsReturnCode, AmsAddr, AmsNetId};
+
+    #[test]
+    fn discovers_local_responder() {
+        let responder = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = responder.local_addr().unwrap();
+        thread::spawn(move || {
+            let mut buf = [0; MAX_DATAGRAM];
+            let (len, from) = responder.recv_from(&mut buf).unwrap();
+            let request =
+                DiscoverRequest::try_from(&UdpMessage::parse(&buf[..len]).unwrap()).unwrap();
+
+            let system = AmsAddr::new(AmsNetId::new(5, 1, 2, 3, 1, 1), 10000);
+            let response = DiscoverResponse::new(
+                request.invoke_id(),
+                system,
+                "CX-010203",
+                TcVersion::new(3, 1, 4026),
+                OsVersion::default(),
+            )
+            .with_fingerprint("abc123");
+            // Answer twice, plus some noise, to check duplicates and garbage are dropped.
+            responder.send_to(&response.to_bytes(), from).unwrap();
+            responder.send_to(b"garbage", from).unwrap();
+            responder.send_to(&response.to_bytes(), from).unwrap();
+        });
+
+        let systems = discover(addr, Duration::from_millis(300)).unwrap();
+        assert_eq!(systems.len(), 1);
+        let (from, system) = &systems[0];
+        assert_eq!(*from, addr);
+        assert_eq!(system.host_name(), "CX-010203");
+        assert_eq!(system.net_id(), AmsNetId::new(5, 1, 2, 3, 1, 1));
+        assert_eq!(system.tc_version(), TcVersion::new(3, 1, 4026));
+        assert_eq!(system.fingerprint(), Some("abc123"));
+    }
+
+    #[test]
+    fn adds_route_with_credentials() {
+        let responder = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = responder.local_addr().unwrap();
+        thread::spawn(move || {
+            let mut buf = [0; MAX_DATAGRAM];
+            while let Ok((len, from)) = responder.recv_from(&mut buf) {
+                let request =
+                    AddRouteRequest::try_from(&UdpMessage::parse(&buf[..len]).unwrap()).unwrap();
+                let result = match request.password() {
+                    "1" => AdsReturnCode::Ok,
+                    _ => AdsReturnCode::AdsErrDeviceInvalidAccess,
+                };
+                let system = AmsAddr::new(AmsNetId::new(5, 1, 2, 3, 1, 1), 10000);
+                let response =
+                    RouteResponse::new(request.invoke_id(), UdpServiceId::AddRoute, system, result);
+                responder.send_to(&response.to_bytes(), from).unwrap();
+            }
+        });
+
+        let route = AmsRoute::new("test", AmsNetId::new(127, 0, 0, 1, 1, 1), "127.0.0.1");
+        let timeout = Duration::from_secs(1);
+        add_route(addr, &route, "Administrator", "1", timeout).unwrap();
+        assert!(matches!(
+            add_route(addr, &route, "Administrator", "wrong", timeout),
+            Err(Error::AuthenticationFailed(
+                AdsReturnCode::AdsErrDeviceInvalidAccess
+            ))
+        ));
+    }
+}
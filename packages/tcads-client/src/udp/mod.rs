@@ -0,0 +1,49 @@
+//! AMS/UDP services, which reach TwinCAT systems without an AMS route: discovery, and adding
+//! and deleting routes.
+//!
+//! Each function sends a [`UdpMessage`](tcads_core::udp::UdpMessage) to port
+//! [`ADS_UDP_PORT`] and collects the answers until a timeout.
+
+pub mod blocking;
+pub mod tokio;
+
+use crate::error::Error;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::atomic::{AtomicU32, Ordering};
+use tcads_core::udp::{ADS_UDP_PORT, RouteResponse, SYSTEM_SERVICE_PORT};
+use tcads_core::{AdsReturnCode, AmsAddr, AmsNetId};
+
+/// Limited broadcast address, reaching every system on the local subnet.
+pub const BROADCAST_ADDR: SocketAddr =
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::BROADCAST), ADS_UDP_PORT);
+
+/// Largest datagram accepted.
+const MAX_DATAGRAM: usize = 2048;
+
+/// Source address of requests. Systems answer to the socket, so no net ID is needed.
+const SOURCE: AmsAddr = AmsAddr::new(AmsNetId::new(0, 0, 0, 0, 0, 0), SYSTEM_SERVICE_PORT);
+
+fn next_invoke_id() -> u32 {
+    static INVOKE_ID: AtomicU32 = AtomicU32::new(1);
+    INVOKE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Returns the wildcard address of the same family as `target`, to bind the socket to.
+fn bind_addr(target: SocketAddr) -> SocketAddr {
+    match target {
+        SocketAddr::V4(_) => SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+        SocketAddr::V6(_) => SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0),
+    }
+}
+
+/// Turns the answer to a route request into a result, [`Error::Timeout`] if there was none.
+fn route_result(response: Option<RouteResponse>) -> crate::Result<()> {
+    let response = response.ok_or(Error::Timeout)?;
+    if response.is_authentication_failure() {
+        return Err(Error::AuthenticationFailed(response.result()));
+    }
+    match response.result() {
+        AdsReturnCode::Ok => Ok(()),
+        code => Err(code.into()),
+    }
+}
@@ -0,0 +1,250 @@
+use super::{BROADCAST_ADDR, MAX_DATAGRAM, SOURCE, bind_addr, next_invoke_id, route_result};
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tcads_core::ams::AmsRoute;
+use tcads_core::udp::{
+    AddRouteRequest, DeleteRouteRequest, DiscoverRequest, DiscoverResponse, RouteResponse,
+    UdpMessage,
+};
+use tokio::net::{ToSocketAddrs, UdpSocket, lookup_host};
+use tokio::time::{Instant, timeout_at};
+
+/// Finds the TwinCAT systems answering at `addr` within `timeout`.
+///
+/// The async counterpart of [`blocking::discover`](super::blocking::discover).
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use tcads_client::udp::BROADCAST_ADDR;
+/// use tcads_client::udp::tokio::discover;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// for (addr, system) in discover(BROADCAST_ADDR, Duration::from_secs(1)).await? {
+///     println!("{} {} at {addr}, TwinCAT {}", system.host_name(), system.net_id(), system.tc_version());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn discover(
+    addr: impl ToSocketAddrs,
+    timeout: Duration,
+) -> crate::Result<Vec<(SocketAddr, DiscoverResponse)>> {
+    let request = DiscoverRequest::new(next_invoke_id(), SOURCE).to_message();
+    let mut systems: Vec<(SocketAddr, DiscoverResponse)> = Vec::new();
+
+    exchange(addr, &request, timeout, |from, message| {
+        if let Ok(system) = DiscoverResponse::try_from(&message)
+            && !systems.iter().any(|(_, s)| s.net_id() == system.net_id())
+        {
+            systems.push((from, system));
+        }
+        false
+    })
+    .await?;
+
+    Ok(systems)
+}
+
+/// Finds the TwinCAT systems on the local subnet, as with [`discover`] to
+/// [`BROADCAST_ADDR`].
+pub async fn discover_local(
+    timeout: Duration,
+) -> crate::Result<Vec<(SocketAddr, DiscoverResponse)>> {
+    discover(BROADCAST_ADDR, timeout).await
+}
+
+/// Adds `route` to the system answering at `addr`, authenticating as `user_name`.
+///
+/// The async counterpart of [`blocking::add_route`](super::blocking::add_route).
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use tcads_client::udp::tokio::add_route;
+/// use tcads_core::ams::AmsRoute;
+/// use tcads_core::udp::ADS_UDP_PORT;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let route = AmsRoute::new("provisioning", "192.168.0.10.1.1".parse()?, "192.168.0.10");
+/// let plc = ("192.168.0.20", ADS_UDP_PORT);
+/// add_route(plc, &route, "Administrator", "1", Duration::from_secs(5)).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn add_route(
+    addr: impl ToSocketAddrs,
+    route: &AmsRoute,
+    user_name: &str,
+    password: &str,
+    timeout: Duration,
+) -> crate::Result<()> {
+    let request = AddRouteRequest::new(next_invoke_id(), route, user_name, password);
+    let mut response = None;
+    exchange(addr, &request.to_message(), timeout, |_, message| {
+        response = RouteResponse::try_from(&message).ok();
+        response.is_some()
+    })
+    .await?;
+    route_result(response)
+}
+
+/// Deletes the route named `route_name` from the system answering at `addr`,
+/// authenticating as `user_name`.
+///
+/// The async counterpart of [`blocking::delete_route`](super::blocking::delete_route).
+pub async fn delete_route(
+    addr: impl ToSocketAddrs,
+    route_name: &str,
+    user_name: &str,
+    password: &str,
+    timeout: Duration,
+) -> crate::Result<()> {
+    let request =
+        DeleteRouteRequest::new(next_invoke_id(), SOURCE, route_name, user_name, password);
+    let mut response = None;
+    exchange(addr, &request.to_message(), timeout, |_, message| {
+        response = RouteResponse::try_from(&message).ok();
+        response.is_some()
+    })
+    .await?;
+    route_result(response)
+}
+
+/// Sends `request` to `addr`, passing each response to it to `on_response` until
+/// `on_response` returns `true` or `timeout` elapses.
+pub(crate) async fn exchange(
+    addr: impl ToSocketAddrs,
+    request: &UdpMessage,
+    timeout: Duration,
+    mut on_response: impl FnMut(SocketAddr, UdpMessage) -> bool,
+) -> crate::Result<()> {
+    let target = lookup_host(addr)
+        .await?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no address to send to"))?;
+    let socket = UdpSocket::bind(bind_addr(target)).await?;
+    socket.set_broadcast(true)?;
+    socket.send_to(&request.to_bytes(), target).await?;
+
+    let deadline = Instant::now() + timeout;
+    let mut buf = [0; MAX_DATAGRAM];
+    loop {
+        let (len, from) = match timeout_at(deadline, socket.recv_from(&mut buf)).await {
+            Ok(received) => received?,
+            Err(_) => return Ok(()),
+        };
+        let Ok(message) = UdpMessage::parse(&buf[..len]) else {
+            continue;
+        };
+        if message.is_response()
+            && message.service() == request.service()
+            && message.invoke_id() == request.invoke_id()
+            && on_response(from, message)
+        {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use tcads_core::udp::{OsVersion, TcVersion, UdpServiceId};
+    use tcads_core::{AdsReturnCode, AmsAddr, AmsNetId};
+
+    #[tokio::test]
+    async fn discovers_local_responders() {
+        // Two systems behind one address, as when a broadcast reaches several hosts.
+        let responder = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = responder.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0; MAX_DATAGRAM];
+            let (len, from) = responder.recv_from(&mut buf).await.unwrap();
+            let request =
+                DiscoverRequest::try_from(&UdpMessage::parse(&buf[..len]).unwrap()).unwrap();
+
+            for (i, name) in ["PLC-1", "PLC-2"].into_iter().enumerate() {
+                let system = AmsAddr::new(AmsNetId::new(5, 1, 2, i as u8, 1, 1), 10000);
+                let os_version = OsVersion {
+                    major: 13,
+                    minor: 2,
+                    build: 0,
+                    platform_id: 0,
+                    service_pack: "TC/BSD".into(),
+                };
+                let response = DiscoverResponse::new(
+                    request.invoke_id(),
+                    system,
+                    name,
+                    TcVersion::new(3, 1, 4024),
+                    os_version,
+                );
+                responder.send_to(&response.to_bytes(), from).await.unwrap();
+            }
+            // Answers to other requests are ignored.
+            let stale = DiscoverResponse::new(
+                request.invoke_id() + 1,
+                AmsAddr::new(AmsNetId::new(5, 1, 2, 9, 1, 1), 10000),
+                "PLC-9",
+                TcVersion::default(),
+                OsVersion::default(),
+            );
+            responder.send_to(&stale.to_bytes(), from).await.unwrap();
+        });
+
+        let systems = discover(addr, Duration::from_millis(300)).await.unwrap();
+        let names: Vec<_> = systems.iter().map(|(_, s)| s.host_name()).collect();
+        assert_eq!(names, ["PLC-1", "PLC-2"]);
+        assert_eq!(systems[1].1.os_version().to_string(), "13.2.0 TC/BSD");
+        assert_eq!(systems[1].1.fingerprint(), None);
+    }
+
+    #[tokio::test]
+    async fn deletes_route() {
+        let responder = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = responder.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0; MAX_DATAGRAM];
+            let (len, from) = responder.recv_from(&mut buf).await.unwrap();
+            let request =
+                DeleteRouteRequest::try_from(&UdpMessage::parse(&buf[..len]).unwrap()).unwrap();
+            assert_eq!(request.route_name(), "test");
+            let system = AmsAddr::new(AmsNetId::new(5, 1, 2, 3, 1, 1), 10000);
+            let response = RouteResponse::new(
+                request.invoke_id(),
+                UdpServiceId::DeleteRoute,
+                system,
+                AdsReturnCode::AdsErrDeviceNotFound,
+            );
+            responder.send_to(&response.to_bytes(), from).await.unwrap();
+        });
+
+        let result = delete_route(addr, "test", "Administrator", "1", Duration::from_secs(1)).await;
+        assert!(matches!(
+            result,
+            Err(Error::AdsReturnCode(AdsReturnCode::AdsErrDeviceNotFound))
+        ));
+    }
+
+    #[tokio::test]
+    async fn times_out_without_answer() {
+        let silent = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let route = AmsRoute::new("test", AmsNetId::new(127, 0, 0, 1, 1, 1), "127.0.0.1");
+        let result = add_route(
+            silent.local_addr().unwrap(),
+            &route,
+            "Administrator",
+            "1",
+            Duration::from_millis(100),
+        )
+        .await;
+        assert!(matches!(result, Err(Error::Timeout)));
+    }
+}
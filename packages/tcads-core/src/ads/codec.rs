@@ -0,0 +1,577 @@
+use super::error::AdsTypeError;
+use super::{AdsString, AdsWString, AdsWStringBuf, WindowsFileTime};
+
+/// Converts a Rust value into its PLC memory representation.
+///
+/// Values are written little-endian, exactly as they are laid out in the PLC.
+///
+/// # Example
+///
+/// ```
+/// use tcads_core::ads::AdsEncode;
+///
+/// assert_eq!(42i16.to_ads_bytes(), [42, 0]);
+/// assert_eq!(true.to_ads_bytes(), [1]);
+/// ```
+pub trait AdsEncode {
+    /// Returns the number of bytes [`encode`](Self::encode) appends.
+    fn encoded_len(&self) -> usize;
+
+    /// Appends the PLC representation of `self` to `buf`.
+    fn encode(&self, buf: &mut Vec<u8>);
+
+    /// Returns the PLC representation of `self`.
+    fn to_ads_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.encoded_len());
+        self.encode(&mut buf);
+        buf
+    }
+}
+
+/// Converts PLC memory into a Rust value.
+///
+/// Decoding reads from the start of `bytes`. Trailing bytes are ignored, so a value
+/// can be decoded from a larger buffer.
+///
+/// # Example
+///
+/// ```
+/// use tcads_core::ads::AdsDecode;
+///
+/// assert_eq!(u32::decode(&[0x2A, 0, 0, 0]).unwrap(), 42);
+/// assert!(u32::decode(&[0x2A]).is_err());
+/// ```
+pub trait AdsDecode: Sized {
+    /// Decodes a value from the start of `bytes`.
+    fn decode(bytes: &[u8]) -> Result<Self, AdsTypeError>;
+}
+
+/// A type with a fixed size and alignment in PLC memory.
+///
+/// Implemented for the primitive integers and floats, `bool`, [`AdsString<N>`],
+/// [`WindowsFileTime`] and arrays of other `AdsType`s. User structs and enums get an
+/// implementation, together with [`AdsEncode`] and [`AdsDecode`], from `#[derive(AdsType)]`.
+///
+/// # IEC 61131-3 types
+///
+/// | PLC type                                  | Rust type                 |
+/// |-------------------------------------------|---------------------------|
+/// | `BOOL`                                    | `bool`                    |
+/// | `BYTE`, `USINT`                           | `u8`                      |
+/// | `WORD`, `UINT`                            | `u16`                     |
+/// | `DWORD`, `UDINT`                          | `u32`                     |
+/// | `LWORD`, `ULINT`                          | `u64`                     |
+/// | `SINT`                                    | `i8`                      |
+/// | `INT`                                     | `i16`                     |
+/// | `DINT`                                    | `i32`                     |
+/// | `LINT`                                    | `i64`                     |
+/// | `REAL`                                    | `f32`                     |
+/// | `LREAL`                                   | `f64`                     |
+/// | `STRING(n)`                               | `AdsString<{n+1}>`        |
+/// | `WSTRING(n)`                              | `AdsWString<{n+1}>`       |
+/// | `T_FILETIME`                              | `WindowsFileTime`         |
+/// | `TIME`, `LTIME`, `DATE`, `TOD`, `DT`, ... | see [`time`](super::time) |
+/// | `ARRAY[0..n-1] OF T`                      | `[T; n]`                  |
+///
+/// Arrays whose length is only known at runtime can be read into a `Vec<T>`, which
+/// implements [`AdsEncode`] and [`AdsDecode`] but not `AdsType`.
+///
+/// # Deriving
+///
+/// * **Structs** (named, tuple or unit) lay their fields out in declaration order. Every
+///   field must itself be an `AdsType`, so nested structs, enums, arrays and strings work.
+/// * **Enums** must be field-less and declare their underlying type with `#[repr(..)]`,
+///   matching the base type of the PLC enum (`INT` when none is given in the PLC).
+///   Decoding a value that matches no variant fails with
+///   [`UnknownEnumValue`](AdsTypeError::UnknownEnumValue).
+///
+/// # Attributes
+///
+/// * `#[ads(pack = 1 | 2 | 4 | 8)]` - The maximum member alignment, matching
+///   `{attribute 'pack_mode' := '<n>'}` on the PLC type. Defaults to `8`, the TwinCAT 3
+///   default on x86 and x64 targets. Use `1` for TwinCAT 2 and `pack_mode` 1 structs.
+/// * `#[ads(crate = "path")]` - The path to this crate, for callers that only depend on
+///   it through a re-export such as `tcads::core`. Defaults to `::tcads_core`.
+///
+/// # Example
+///
+/// ```
+/// use tcads_core::ads::{AdsDecode, AdsEncode, AdsString, AdsType};
+///
+/// // TYPE E_Mode : (Idle := 0, Auto := 1, Manual := 2) UINT; END_TYPE
+/// #[derive(AdsType, Debug, PartialEq)]
+/// #[repr(u16)]
+/// enum Mode {
+///     Idle = 0,
+///     Auto = 1,
+///     Manual = 2,
+/// }
+///
+/// // TYPE ST_Axis : STRUCT
+/// //     bEnabled : BOOL;
+/// //     fPosition : LREAL;
+/// //     eMode : E_Mode;
+/// //     sName : STRING(15);
+/// //     aLimits : ARRAY[0..1] OF REAL;
+/// // END_STRUCT END_TYPE
+/// #[derive(AdsType, Debug, PartialEq)]
+/// struct Axis {
+///     enabled: bool,
+///     position: f64,
+///     mode: Mode,
+///     name: AdsString<16>,
+///     limits: [f32; 2],
+/// }
+///
+/// assert_eq!(Axis::SIZE, 48);
+///
+/// let axis = Axis {
+///     enabled: true,
+///     position: 12.5,
+///     mode: Mode::Auto,
+///     name: AdsString::try_from("X").unwrap(),
+///     limits: [-100.0, 100.0],
+/// };
+/// let bytes = axis.to_ads_bytes();
+///
+/// assert_eq!(bytes.len(), Axis::SIZE);
+/// assert_eq!(Axis::decode(&bytes).unwrap(), axis);
+/// ```
+pub trait AdsType: AdsEncode + AdsDecode {
+    /// The size of the type in PLC memory, including any trailing padding.
+    const SIZE: usize;
+
+    /// The alignment of the type in PLC memory when not limited by a pack mode.
+    const ALIGN: usize;
+}
+
+pub use tcads_derive::AdsType;
+
+/// Returns the alignment of a member with natural alignment `align` under `pack_mode` `pack`.
+#[doc(hidden)]
+pub const fn pack_align(align: usize, pack: usize) -> usize {
+    if align < pack { align } else { pack }
+}
+
+/// Rounds `offset` up to the alignment of a member with natural alignment `align` under
+/// `pack_mode` `pack`.
+#[doc(hidden)]
+pub const fn pack_offset(offset: usize, align: usize, pack: usize) -> usize {
+    let align = pack_align(align, pack);
+    if align <= 1 {
+        offset
+    } else {
+        offset.div_ceil(align) * align
+    }
+}
+
+/// Returns the first `N` bytes of `bytes`, or an error if it is too short.
+pub(crate) fn prefix<const N: usize>(bytes: &[u8]) -> Result<[u8; N], AdsTypeError> {
+    match bytes.get(..N) {
+        Some(prefix) => Ok(prefix.try_into().unwrap()),
+        None => Err(AdsTypeError::UnexpectedLength {
+            expected: N,
+            got: bytes.len(),
+        }),
+    }
+}
+
+macro_rules! impl_codec_for_primitive {
+    ($($ty:ty),*) => {
+        $(
+            impl AdsEncode for $ty {
+                fn encoded_len(&self) -> usize {
+                    size_of::<$ty>()
+                }
+
+                fn encode(&self, buf: &mut Vec<u8>) {
+                    buf.extend_from_slice(&self.to_le_bytes());
+                }
+            }
+
+            impl AdsDecode for $ty {
+                fn decode(bytes: &[u8]) -> Result<Self, AdsTypeError> {
+                    Ok(<$ty>::from_le_bytes(prefix(bytes)?))
+                }
+            }
+
+            impl AdsType for $ty {
+                const SIZE: usize = size_of::<$ty>();
+                const ALIGN: usize = size_of::<$ty>();
+            }
+        )*
+    };
+}
+
+impl_codec_for_primitive!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64);
+
+impl AdsEncode for bool {
+    fn encoded_len(&self) -> usize {
+        1
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.push(*self as u8);
+    }
+}
+
+impl AdsDecode for bool {
+    /// Any non-zero byte decodes as `true`, matching the PLC's `BOOL` semantics.
+    fn decode(bytes: &[u8]) -> Result<Self, AdsTypeError> {
+        Ok(prefix::<1>(bytes)?[0] != 0)
+    }
+}
+
+impl AdsType for bool {
+    const SIZE: usize = 1;
+    const ALIGN: usize = 1;
+}
+
+impl<const N: usize> AdsEncode for AdsString<N> {
+    fn encoded_len(&self) -> usize {
+        N
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl<const N: usize> AdsDecode for AdsString<N> {
+    fn decode(bytes: &[u8]) -> Result<Self, AdsTypeError> {
+        Ok(Self::from(prefix::<N>(bytes)?))
+    }
+}
+
+impl<const N: usize> AdsType for AdsString<N> {
+    const SIZE: usize = N;
+    const ALIGN: usize = 1;
+}
+
+impl<const N: usize> AdsEncode for AdsWString<N> {
+    fn encoded_len(&self) -> usize {
+        N * 2
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        for unit in self.as_units() {
+            buf.extend_from_slice(&unit.to_le_bytes());
+        }
+    }
+}
+
+impl<const N: usize> AdsDecode for AdsWString<N> {
+    fn decode(bytes: &[u8]) -> Result<Self, AdsTypeError> {
+        Ok(Self::from(<[u16; N]>::decode(bytes)?))
+    }
+}
+
+impl<const N: usize> AdsType for AdsWString<N> {
+    const SIZE: usize = N * 2;
+    const ALIGN: usize = 2;
+}
+
+/// Writes the code units followed by a null terminator.
+impl AdsEncode for AdsWStringBuf {
+    fn encoded_len(&self) -> usize {
+        self.byte_len()
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        for unit in self.as_units() {
+            buf.extend_from_slice(&unit.to_le_bytes());
+        }
+        buf.extend_from_slice(&[0, 0]);
+    }
+}
+
+/// Reads up to the first null terminator, or the whole buffer if there is none. An odd
+/// trailing byte is ignored.
+impl AdsDecode for AdsWStringBuf {
+    fn decode(bytes: &[u8]) -> Result<Self, AdsTypeError> {
+        Ok(Self::from(Vec::<u16>::decode(&bytes[..bytes.len() & !1])?))
+    }
+}
+
+/// Elements are laid out back to back, each taking [`T::SIZE`](AdsType::SIZE) bytes.
+impl<T: AdsType, const N: usize> AdsEncode for [T; N] {
+    fn encoded_len(&self) -> usize {
+        T::SIZE * N
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.as_slice().encode(buf);
+    }
+}
+
+impl<T: AdsType, const N: usize> AdsDecode for [T; N] {
+    fn decode(bytes: &[u8]) -> Result<Self, AdsTypeError> {
+        let len = T::SIZE * N;
+        if bytes.len() < len {
+            return Err(AdsTypeError::UnexpectedLength {
+                expected: len,
+                got: bytes.len(),
+            });
+        }
+
+        let mut items = Vec::with_capacity(N);
+        for i in 0..N {
+            items.push(T::decode(&bytes[i * T::SIZE..])?);
+        }
+
+        Ok(items.try_into().unwrap_or_else(|_| unreachable!()))
+    }
+}
+
+impl<T: AdsType, const N: usize> AdsType for [T; N] {
+    const SIZE: usize = T::SIZE * N;
+    const ALIGN: usize = T::ALIGN;
+}
+
+/// Elements are laid out back to back, as for `[T; N]`.
+impl<T: AdsType> AdsEncode for [T] {
+    fn encoded_len(&self) -> usize {
+        T::SIZE * self.len()
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        for item in self {
+            let start = buf.len();
+            item.encode(buf);
+            buf.resize(start + T::SIZE, 0);
+        }
+    }
+}
+
+impl<T: AdsType> AdsEncode for Vec<T> {
+    fn encoded_len(&self) -> usize {
+        self.as_slice().encoded_len()
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.as_slice().encode(buf);
+    }
+}
+
+/// Decodes one element per [`T::SIZE`](AdsType::SIZE) bytes. Unlike the fixed-size types, a
+/// `Vec` consumes the whole buffer, so read exactly `count * T::SIZE` bytes for `count`
+/// elements. A trailing partial element fails with
+/// [`UnexpectedLength`](AdsTypeError::UnexpectedLength).
+impl<T: AdsType> AdsDecode for Vec<T> {
+    fn decode(bytes: &[u8]) -> Result<Self, AdsTypeError> {
+        if T::SIZE == 0 {
+            return Ok(Vec::new());
+        }
+
+        let chunks = bytes.chunks_exact(T::SIZE);
+        if !chunks.remainder().is_empty() {
+            return Err(AdsTypeError::UnexpectedLength {
+                expected: bytes.len().next_multiple_of(T::SIZE),
+                got: bytes.len(),
+            });
+        }
+        chunks.map(T::decode).collect()
+    }
+}
+
+impl AdsEncode for WindowsFileTime {
+    fn encoded_len(&self) -> usize {
+        Self::LENGTH
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.to_bytes());
+    }
+}
+
+impl AdsDecode for WindowsFileTime {
+    fn decode(bytes: &[u8]) -> Result<Self, AdsTypeError> {
+        Ok(Self::from_bytes(prefix(bytes)?))
+    }
+}
+
+/// Matches `T_FILETIME`, a struct of two `DWORD`s, so it is 4-byte aligned.
+impl AdsType for WindowsFileTime {
+    const SIZE: usize = Self::LENGTH;
+    const ALIGN: usize = 4;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primitives_round_trip() {
+        assert_eq!(i64::decode(&(-5i64).to_ads_bytes()).unwrap(), -5);
+        assert_eq!(f32::decode(&1.5f32.to_ads_bytes()).unwrap(), 1.5);
+        assert_eq!(u16::decode(&[0x34, 0x12, 0xFF]).unwrap(), 0x1234);
+    }
+
+    #[test]
+    fn bool_decodes_any_non_zero_as_true() {
+        assert!(bool::decode(&[0x02]).unwrap());
+        assert!(!bool::decode(&[0x00]).unwrap());
+    }
+
+    #[test]
+    fn short_input_is_rejected() {
+        assert_eq!(
+            f64::decode(&[0; 4]),
+            Err(AdsTypeError::UnexpectedLength {
+                expected: 8,
+                got: 4
+            })
+        );
+    }
+
+    #[test]
+    fn arrays_and_strings_round_trip() {
+        let values = [1u16, 2, 3];
+        assert_eq!(values.to_ads_bytes(), [1, 0, 2, 0, 3, 0]);
+        assert_eq!(<[u16; 3]>::decode(&values.to_ads_bytes()).unwrap(), values);
+
+        let name = AdsString::<6>::try_from("axis").unwrap();
+        assert_eq!(name.to_ads_bytes(), *b"axis\0\0");
+        assert_eq!(AdsString::<6>::decode(b"axis\0\0").unwrap(), name);
+    }
+
+    #[test]
+    fn vec_decodes_whole_elements() {
+        let values = vec![1i32, -2, 3];
+        let bytes = values.to_ads_bytes();
+
+        assert_eq!(bytes.len(), 12);
+        assert_eq!(Vec::<i32>::decode(&bytes).unwrap(), values);
+        assert!(Vec::<i32>::decode(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn vec_rejects_partial_elements() {
+        let bytes = vec![1i32, -2, 3].to_ads_bytes();
+
+        assert_eq!(
+            Vec::<i32>::decode(&bytes[..10]),
+            Err(AdsTypeError::UnexpectedLength {
+                expected: 12,
+                got: 10
+            })
+        );
+        assert_eq!(
+            AdsWStringBuf::decode(&[b'a', 0, b'b', 0, b'c']).unwrap(),
+            AdsWStringBuf::decode(&[b'a', 0, b'b', 0]).unwrap()
+        );
+    }
+
+    #[test]
+    fn wide_strings_round_trip() {
+        let name = AdsWString::<4>::try_from("Añ").unwrap();
+        assert_eq!(name.to_ads_bytes(), [0x41, 0, 0xF1, 0, 0, 0, 0, 0]);
+        assert_eq!(AdsWString::<4>::decode(&name.to_ads_bytes()).unwrap(), name);
+
+        let buf = AdsWStringBuf::from("Añ");
+        assert_eq!(buf.to_ads_bytes(), [0x41, 0, 0xF1, 0, 0, 0]);
+        assert_eq!(
+            AdsWStringBuf::decode(&[0x41, 0, 0xF1, 0, 0, 0, 0x42, 0]).unwrap(),
+            buf
+        );
+    }
+
+    #[test]
+    fn filetime_round_trips() {
+        let time = WindowsFileTime::from_raw(0x01D9_0000_1234_5678);
+
+        assert_eq!(time.to_ads_bytes(), time.to_bytes());
+        assert_eq!(WindowsFileTime::decode(&time.to_bytes()).unwrap(), time);
+    }
+
+    #[derive(AdsType, Debug, PartialEq)]
+    struct Natural {
+        flag: bool,
+        value: u32,
+        wide: u64,
+        small: u8,
+    }
+
+    #[derive(AdsType, Debug, PartialEq)]
+    #[ads(pack = 1)]
+    struct Packed {
+        flag: bool,
+        value: u32,
+        wide: u64,
+        small: u8,
+    }
+
+    #[derive(AdsType, Debug, PartialEq)]
+    struct Nested {
+        inner: Packed,
+        values: [Natural; 2],
+        state: State,
+    }
+
+    #[derive(AdsType, Debug, PartialEq, Clone, Copy)]
+    #[repr(i16)]
+    enum State {
+        Stopped = -1,
+        Running = 1,
+    }
+
+    #[test]
+    fn derived_layout_follows_pack_mode() {
+        assert_eq!((Natural::SIZE, Natural::ALIGN), (24, 8));
+        assert_eq!((Packed::SIZE, Packed::ALIGN), (14, 1));
+        assert_eq!(Nested::SIZE, 14 + 2 + 48 + 2 + 6);
+
+        let natural = Natural {
+            flag: true,
+            value: 0x0102_0304,
+            wide: 5,
+            small: 6,
+        };
+        let bytes = natural.to_ads_bytes();
+
+        assert_eq!(bytes.len(), 24);
+        assert_eq!(bytes[0], 1);
+        assert_eq!(bytes[4..8], [4, 3, 2, 1]);
+        assert_eq!(bytes[8..16], 5u64.to_le_bytes());
+        assert_eq!(bytes[16], 6);
+        assert_eq!(Natural::decode(&bytes).unwrap(), natural);
+    }
+
+    #[test]
+    fn derived_nested_round_trip() {
+        let natural = |n| Natural {
+            flag: false,
+            value: n,
+            wide: n as u64,
+            small: n as u8,
+        };
+        let nested = Nested {
+            inner: Packed {
+                flag: true,
+                value: 7,
+                wide: 8,
+                small: 9,
+            },
+            values: [natural(1), natural(2)],
+            state: State::Stopped,
+        };
+        let bytes = nested.to_ads_bytes();
+
+        assert_eq!(bytes.len(), Nested::SIZE);
+        assert_eq!(bytes[64..66], (-1i16).to_le_bytes());
+        assert_eq!(Nested::decode(&bytes).unwrap(), nested);
+        assert!(Nested::decode(&bytes[..Nested::SIZE - 1]).is_err());
+    }
+
+    #[test]
+    fn derived_enum_rejects_unknown_values() {
+        assert_eq!(State::decode(&1i16.to_le_bytes()).unwrap(), State::Running);
+        assert_eq!(
+            State::decode(&5i16.to_le_bytes()),
+            Err(AdsTypeError::UnknownEnumValue {
+                type_name: "State",
+                value: 5
+            })
+        );
+    }
+}
@@ -34,6 +34,12 @@ pub enum AdsError {
     /// Invalid Windows file time format or content.
     #[error("Invalid Windows file time: {0}")]
     InvalidWindowsFileTime(#[from] WindowsFileTimeError),
+    /// Invalid PLC value format or content.
+    #[error("Invalid PLC value: {0}")]
+    InvalidAdsType(#[from] AdsTypeError),
+    /// Invalid PLC time or date literal or value.
+    #[error("Invalid PLC time: {0}")]
+    InvalidAdsTime(#[from] AdsTimeError),
     /// Invalid ADS data length format or content (not header or return code).
     #[error("Unexpected data length: expected {expected} bytes, got {got} bytes")]
     UnexpectedDataLength { expected: usize, got: usize },
@@ -112,3 +118,27 @@ pub enum WindowsFileTimeError {
     #[error("unexpected length: expected {expected}, got {got}")]
     UnexpectedLength { expected: usize, got: usize },
 }
+
+/// Error returned when decoding a value from PLC memory fails.
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+pub enum AdsTypeError {
+    #[error("unexpected length: expected {expected}, got {got}")]
+    UnexpectedLength { expected: usize, got: usize },
+    #[error("unknown value {value} for enum {type_name}")]
+    UnknownEnumValue {
+        type_name: &'static str,
+        value: i128,
+    },
+}
+
+/// Error returned when parsing or converting a PLC time or date fails.
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+pub enum AdsTimeError {
+    #[error("invalid {type_name} literal: {literal:?}")]
+    InvalidLiteral {
+        type_name: &'static str,
+        literal: String,
+    },
+    #[error("value out of range for {type_name}")]
+    OutOfRange { type_name: &'static str },
+}
@@ -0,0 +1,54 @@
+//! Well-known ADS index groups.
+//!
+//! These are the reserved index groups served by the TwinCAT runtime
+//! (typically port `851` for the PLC) for symbol access and sum commands.
+//! Use them as the `index_group` argument of `Read`, `Write` and `ReadWrite` requests.
+
+use super::IndexGroup;
+
+/// Gets a symbol handle by name.
+///
+/// `ReadWrite` with the null-terminated symbol name as write data; returns a 4-byte handle.
+pub const SYM_HNDBYNAME: IndexGroup = 0xF003;
+/// Reads or writes a symbol value by name.
+pub const SYM_VALBYNAME: IndexGroup = 0xF004;
+/// Reads or writes a symbol value by handle. The handle is passed as the index offset.
+pub const SYM_VALBYHND: IndexGroup = 0xF005;
+/// Releases a symbol handle. The handle is written as data with index offset `0`.
+pub const SYM_RELEASEHND: IndexGroup = 0xF006;
+/// Reads the symbol information by name.
+pub const SYM_INFOBYNAME: IndexGroup = 0xF007;
+/// Reads the symbol table version.
+pub const SYM_VERSION: IndexGroup = 0xF008;
+/// Reads the extended symbol information (an `AdsSymbolEntry`) by name.
+pub const SYM_INFOBYNAMEEX: IndexGroup = 0xF009;
+/// Downloads symbols.
+pub const SYM_DOWNLOAD: IndexGroup = 0xF00A;
+/// Uploads the symbol table.
+pub const SYM_UPLOAD: IndexGroup = 0xF00B;
+/// Reads the legacy symbol upload information (symbol count and length only).
+pub const SYM_UPLOADINFO: IndexGroup = 0xF00C;
+/// Uploads the data type table.
+pub const SYM_DT_UPLOAD: IndexGroup = 0xF00E;
+/// Reads the symbol and data type upload information.
+pub const SYM_UPLOADINFO2: IndexGroup = 0xF00F;
+/// Reads the extended data type information (an `AdsDatatypeEntry`) by name.
+pub const SYM_DT_INFOBYNAMEEX: IndexGroup = 0xF011;
+
+/// Sum command: multiple reads in one request.
+pub const SUMUP_READ: IndexGroup = 0xF080;
+/// Sum command: multiple writes in one request.
+pub const SUMUP_WRITE: IndexGroup = 0xF081;
+/// Sum command: multiple read/writes in one request.
+pub const SUMUP_READWRITE: IndexGroup = 0xF082;
+/// Sum command: multiple reads, returning the actual length read per item.
+pub const SUMUP_READEX: IndexGroup = 0xF083;
+/// Sum command: like [`SUMUP_READEX`], with the length returned before the data.
+pub const SUMUP_READEX2: IndexGroup = 0xF084;
+/// Sum command: multiple device notification registrations in one request.
+pub const SUMUP_ADDDEVNOTE: IndexGroup = 0xF085;
+/// Sum command: multiple device notification deletions in one request.
+pub const SUMUP_DELDEVNOTE: IndexGroup = 0xF086;
+
+/// Device data: ADS state and device state.
+pub const DEVICE_DATA: IndexGroup = 0xF100;
@@ -1,22 +1,27 @@
+pub mod codec;
 pub mod command;
 pub mod device_state;
 pub mod device_version;
 pub mod error;
 pub mod filetime;
 pub mod header;
+pub mod index_group;
 pub mod notification_handle;
 pub mod return_codes;
 pub mod state_flag;
 pub mod string;
+pub mod time;
 pub mod trans_mode;
+pub mod wstring;
 
+pub use codec::{AdsDecode, AdsEncode, AdsType, pack_align, pack_offset};
 pub use command::AdsCommand;
 pub use device_state::{AdsState, DeviceState};
 pub use device_version::AdsDeviceVersion;
 pub use error::{
     AdsCommandError, AdsDeviceVersionError, AdsError, AdsHeaderError, AdsNotificationHandleError,
-    AdsReturnCodeError, AdsStateError, AdsStringError, AdsTransModeError, StateFlagError,
-    WindowsFileTimeError,
+    AdsReturnCodeError, AdsStateError, AdsStringError, AdsTimeError, AdsTransModeError,
+    AdsTypeError, StateFlagError, WindowsFileTimeError,
 };
 pub use filetime::WindowsFileTime;
 pub use header::AdsHeader;
@@ -24,7 +29,11 @@ pub use notification_handle::NotificationHandle;
 pub use return_codes::AdsReturnCode;
 pub use state_flag::StateFlag;
 pub use string::AdsString;
+pub use time::{
+    AdsDate, AdsDateTime, AdsLDate, AdsLDateTime, AdsLTime, AdsLTimeOfDay, AdsTime, AdsTimeOfDay,
+};
 pub use trans_mode::AdsTransMode;
+pub use wstring::{AdsWString, AdsWStringBuf};
 
 pub type IndexGroup = u32;
 pub type IndexOffset = u32;
@@ -730,6 +730,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::useless_conversion)]
     fn test_from_ads_return_code() {
         assert_eq!(AdsReturnCode::from(AdsReturnCode::Ok), AdsReturnCode::Ok);
     }
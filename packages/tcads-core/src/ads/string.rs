@@ -201,6 +201,19 @@ impl<const N: usize> Default for AdsString<N> {
     }
 }
 
+impl<const N: usize> serde::Serialize for AdsString<N> {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&self.as_str())
+    }
+}
+
+impl<'de, const N: usize> serde::Deserialize<'de> for AdsString<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(d)?;
+        Self::try_from(s.as_str()).map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -267,4 +280,13 @@ mod test {
         let bytes: Vec<u8> = s.bytes().cloned().collect();
         assert_eq!(bytes, vec![b'H', b'i']);
     }
+
+    #[test]
+    fn test_serde() {
+        let s: AdsString<10> = AdsString::try_from("Hi €").unwrap();
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(json, "\"Hi €\"");
+        assert_eq!(serde_json::from_str::<AdsString<10>>(&json).unwrap(), s);
+        assert!(serde_json::from_str::<AdsString<3>>(&json).is_err());
+    }
 }
@@ -0,0 +1,810 @@
+//! IEC 61131-3 time and date types.
+//!
+//! Each type wraps the raw integer the PLC stores and converts to and from
+//! [`std::time::Duration`] or the matching `chrono` type. All of them parse and format IEC
+//! literals such as `T#1h2m3s` or `DT#2024-01-01-12:00:00`, and serialize as those literals.
+//!
+//! | PLC type | Rust type         | Raw value                          |
+//! |----------|-------------------|------------------------------------|
+//! | `TIME`   | [`AdsTime`]       | `u32` milliseconds                 |
+//! | `LTIME`  | [`AdsLTime`]      | `u64` nanoseconds                  |
+//! | `DATE`   | [`AdsDate`]       | `u32` seconds since 1970-01-01     |
+//! | `LDATE`  | [`AdsLDate`]      | `u64` nanoseconds since 1970-01-01 |
+//! | `TOD`    | [`AdsTimeOfDay`]  | `u32` milliseconds since midnight  |
+//! | `LTOD`   | [`AdsLTimeOfDay`] | `u64` nanoseconds since midnight   |
+//! | `DT`     | [`AdsDateTime`]   | `u32` seconds since 1970-01-01     |
+//! | `LDT`    | [`AdsLDateTime`]  | `u64` nanoseconds since 1970-01-01 |
+//!
+//! Dates and times of day carry no time zone. The PLC usually runs on local time, so they
+//! convert to `chrono`'s naive types.
+
+use super::codec::prefix;
+use super::error::AdsTimeError;
+use super::{AdsDecode, AdsEncode, AdsType, AdsTypeError};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+const NANOS_PER_MICRO: u128 = 1_000;
+const NANOS_PER_MILLI: u128 = 1_000_000;
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+const NANOS_PER_MIN: u128 = 60 * NANOS_PER_SEC;
+const NANOS_PER_HOUR: u128 = 60 * NANOS_PER_MIN;
+const NANOS_PER_DAY: u128 = 24 * NANOS_PER_HOUR;
+
+/// Duration units of `TIME` literals, largest first.
+const TIME_UNITS: &[(&str, u128)] = &[
+    ("d", NANOS_PER_DAY),
+    ("h", NANOS_PER_HOUR),
+    ("m", NANOS_PER_MIN),
+    ("s", NANOS_PER_SEC),
+    ("ms", NANOS_PER_MILLI),
+];
+
+/// Duration units of `LTIME` literals, largest first.
+const LTIME_UNITS: &[(&str, u128)] = &[
+    ("d", NANOS_PER_DAY),
+    ("h", NANOS_PER_HOUR),
+    ("m", NANOS_PER_MIN),
+    ("s", NANOS_PER_SEC),
+    ("ms", NANOS_PER_MILLI),
+    ("us", NANOS_PER_MICRO),
+    ("ns", 1),
+];
+
+macro_rules! iec_time_type {
+    ($(#[$meta:meta])* $name:ident($raw:ty), $plc:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name($raw);
+
+        impl $name {
+            /// Creates a value from its raw PLC representation.
+            pub const fn from_raw(raw: $raw) -> Self {
+                Self(raw)
+            }
+
+            /// Returns the raw PLC representation.
+            pub const fn as_raw(self) -> $raw {
+                self.0
+            }
+
+            /// The IEC name of the type, used in errors.
+            const TYPE_NAME: &'static str = $plc;
+
+            fn invalid(literal: &str) -> AdsTimeError {
+                AdsTimeError::InvalidLiteral {
+                    type_name: Self::TYPE_NAME,
+                    literal: literal.to_string(),
+                }
+            }
+        }
+
+        impl From<$raw> for $name {
+            fn from(value: $raw) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for $raw {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl AdsEncode for $name {
+            fn encoded_len(&self) -> usize {
+                size_of::<$raw>()
+            }
+
+            fn encode(&self, buf: &mut Vec<u8>) {
+                buf.extend_from_slice(&self.0.to_le_bytes());
+            }
+        }
+
+        impl AdsDecode for $name {
+            fn decode(bytes: &[u8]) -> Result<Self, AdsTypeError> {
+                Ok(Self(<$raw>::from_le_bytes(prefix(bytes)?)))
+            }
+        }
+
+        impl AdsType for $name {
+            const SIZE: usize = size_of::<$raw>();
+            const ALIGN: usize = size_of::<$raw>();
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                s.collect_str(self)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+                String::deserialize(d)?
+                    .parse()
+                    .map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+iec_time_type!(
+    /// A PLC `TIME`: a duration with millisecond resolution.
+    ///
+    /// Formats as `T#1h2m3s4ms`. Parsing accepts the `T#` and `TIME#` prefixes in any case,
+    /// the units `d`, `h`, `m`, `s`, `ms`, `us` and `ns`, a decimal fraction on any
+    /// component (`T#1.5s`) and `_` separators. Anything below a millisecond is truncated.
+    AdsTime(u32),
+    "TIME"
+);
+
+iec_time_type!(
+    /// A PLC `LTIME`: a duration with nanosecond resolution.
+    ///
+    /// Formats as `LTIME#1h2m3s4ms5us6ns`. Parsing accepts the `LTIME#` and `LT#` prefixes
+    /// and otherwise follows [`AdsTime`].
+    AdsLTime(u64),
+    "LTIME"
+);
+
+iec_time_type!(
+    /// A PLC `DATE`: a calendar date, stored as the seconds from 1970-01-01 to its midnight.
+    ///
+    /// Formats as `D#2024-01-01`. Parsing accepts the `D#` and `DATE#` prefixes.
+    AdsDate(u32),
+    "DATE"
+);
+
+iec_time_type!(
+    /// A PLC `LDATE`: a calendar date, stored as the nanoseconds from 1970-01-01 to its
+    /// midnight.
+    ///
+    /// Formats as `LDATE#2024-01-01`. Parsing accepts the `LDATE#` and `LD#` prefixes.
+    AdsLDate(u64),
+    "LDATE"
+);
+
+iec_time_type!(
+    /// A PLC `TIME_OF_DAY` (`TOD`): a time of day with millisecond resolution.
+    ///
+    /// Formats as `TOD#12:30:15.250`. Parsing accepts the `TOD#` and `TIME_OF_DAY#` prefixes.
+    /// Raw values of 24 hours or more wrap past midnight when converted.
+    AdsTimeOfDay(u32),
+    "TOD"
+);
+
+iec_time_type!(
+    /// A PLC `LTIME_OF_DAY` (`LTOD`): a time of day with nanosecond resolution.
+    ///
+    /// Formats as `LTOD#12:30:15.123456789`. Parsing accepts the `LTOD#` and
+    /// `LTIME_OF_DAY#` prefixes. Raw values of 24 hours or more wrap past midnight when
+    /// converted.
+    AdsLTimeOfDay(u64),
+    "LTOD"
+);
+
+iec_time_type!(
+    /// A PLC `DATE_AND_TIME` (`DT`): a date and time with second resolution.
+    ///
+    /// Formats as `DT#2024-01-01-12:00:00`. Parsing accepts the `DT#` and `DATE_AND_TIME#`
+    /// prefixes.
+    AdsDateTime(u32),
+    "DT"
+);
+
+iec_time_type!(
+    /// A PLC `LDATE_AND_TIME` (`LDT`): a date and time with nanosecond resolution.
+    ///
+    /// Formats as `LDT#2024-01-01-12:00:00.123456789`. Parsing accepts the `LDT#` and
+    /// `LDATE_AND_TIME#` prefixes.
+    AdsLDateTime(u64),
+    "LDT"
+);
+
+impl AdsTime {
+    /// Creates a `TIME` of `millis` milliseconds.
+    pub const fn from_millis(millis: u32) -> Self {
+        Self(millis)
+    }
+
+    /// Returns the duration in milliseconds.
+    pub const fn as_millis(self) -> u32 {
+        self.0
+    }
+
+    /// Converts to a [`Duration`].
+    pub const fn to_duration(self) -> Duration {
+        Duration::from_millis(self.0 as u64)
+    }
+}
+
+impl From<AdsTime> for Duration {
+    fn from(value: AdsTime) -> Self {
+        value.to_duration()
+    }
+}
+
+impl TryFrom<Duration> for AdsTime {
+    type Error = AdsTimeError;
+
+    /// Truncates to whole milliseconds. Fails if the duration exceeds `u32::MAX` ms.
+    fn try_from(value: Duration) -> Result<Self, Self::Error> {
+        u32::try_from(value.as_millis())
+            .map(Self)
+            .map_err(|_| out_of_range(Self::TYPE_NAME))
+    }
+}
+
+impl fmt::Display for AdsTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("T#")?;
+        format_duration(f, self.0 as u128 * NANOS_PER_MILLI, TIME_UNITS)
+    }
+}
+
+impl FromStr for AdsTime {
+    type Err = AdsTimeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let body = strip_prefix(s, &["TIME#", "T#"]).ok_or_else(|| Self::invalid(s))?;
+        let nanos = parse_duration(body).ok_or_else(|| Self::invalid(s))?;
+
+        u32::try_from(nanos / NANOS_PER_MILLI)
+            .map(Self)
+            .map_err(|_| out_of_range(Self::TYPE_NAME))
+    }
+}
+
+impl AdsLTime {
+    /// Creates an `LTIME` of `nanos` nanoseconds.
+    pub const fn from_nanos(nanos: u64) -> Self {
+        Self(nanos)
+    }
+
+    /// Returns the duration in nanoseconds.
+    pub const fn as_nanos(self) -> u64 {
+        self.0
+    }
+
+    /// Converts to a [`Duration`].
+    pub const fn to_duration(self) -> Duration {
+        Duration::from_nanos(self.0)
+    }
+}
+
+impl From<AdsLTime> for Duration {
+    fn from(value: AdsLTime) -> Self {
+        value.to_duration()
+    }
+}
+
+impl TryFrom<Duration> for AdsLTime {
+    type Error = AdsTimeError;
+
+    /// Fails if the duration exceeds `u64::MAX` ns, about 584 years.
+    fn try_from(value: Duration) -> Result<Self, Self::Error> {
+        u64::try_from(value.as_nanos())
+            .map(Self)
+            .map_err(|_| out_of_range(Self::TYPE_NAME))
+    }
+}
+
+impl fmt::Display for AdsLTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LTIME#")?;
+        format_duration(f, self.0 as u128, LTIME_UNITS)
+    }
+}
+
+impl FromStr for AdsLTime {
+    type Err = AdsTimeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let body = strip_prefix(s, &["LTIME#", "LT#"]).ok_or_else(|| Self::invalid(s))?;
+        let nanos = parse_duration(body).ok_or_else(|| Self::invalid(s))?;
+
+        u64::try_from(nanos)
+            .map(Self)
+            .map_err(|_| out_of_range(Self::TYPE_NAME))
+    }
+}
+
+impl AdsDate {
+    /// Converts to a [`NaiveDate`].
+    pub fn to_date(self) -> NaiveDate {
+        from_epoch_nanos(self.0 as u128 * NANOS_PER_SEC).date()
+    }
+
+    /// Converts from a [`NaiveDate`]. Fails for dates before 1970 or after 2106-02-07.
+    pub fn from_date(date: NaiveDate) -> Result<Self, AdsTimeError> {
+        let nanos =
+            to_epoch_nanos(date.and_time(NaiveTime::MIN)).ok_or(out_of_range(Self::TYPE_NAME))?;
+
+        u32::try_from(nanos / NANOS_PER_SEC)
+            .map(Self)
+            .map_err(|_| out_of_range(Self::TYPE_NAME))
+    }
+}
+
+impl From<AdsDate> for NaiveDate {
+    fn from(value: AdsDate) -> Self {
+        value.to_date()
+    }
+}
+
+impl TryFrom<NaiveDate> for AdsDate {
+    type Error = AdsTimeError;
+
+    fn try_from(value: NaiveDate) -> Result<Self, Self::Error> {
+        Self::from_date(value)
+    }
+}
+
+impl fmt::Display for AdsDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "D#{}", self.to_date().format("%Y-%m-%d"))
+    }
+}
+
+impl FromStr for AdsDate {
+    type Err = AdsTimeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let body = strip_prefix(s, &["DATE#", "D#"]).ok_or_else(|| Self::invalid(s))?;
+        let date = parse_date(body).ok_or_else(|| Self::invalid(s))?;
+
+        Self::from_date(date)
+    }
+}
+
+impl AdsLDate {
+    /// Converts to a [`NaiveDate`].
+    pub fn to_date(self) -> NaiveDate {
+        from_epoch_nanos(self.0 as u128).date()
+    }
+
+    /// Converts from a [`NaiveDate`]. Fails for dates before 1970 or after 2554-07-21.
+    pub fn from_date(date: NaiveDate) -> Result<Self, AdsTimeError> {
+        let nanos =
+            to_epoch_nanos(date.and_time(NaiveTime::MIN)).ok_or(out_of_range(Self::TYPE_NAME))?;
+
+        u64::try_from(nanos)
+            .map(Self)
+            .map_err(|_| out_of_range(Self::TYPE_NAME))
+    }
+}
+
+impl From<AdsLDate> for NaiveDate {
+    fn from(value: AdsLDate) -> Self {
+        value.to_date()
+    }
+}
+
+impl TryFrom<NaiveDate> for AdsLDate {
+    type Error = AdsTimeError;
+
+    fn try_from(value: NaiveDate) -> Result<Self, Self::Error> {
+        Self::from_date(value)
+    }
+}
+
+impl fmt::Display for AdsLDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LDATE#{}", self.to_date().format("%Y-%m-%d"))
+    }
+}
+
+impl FromStr for AdsLDate {
+    type Err = AdsTimeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let body = strip_prefix(s, &["LDATE#", "LD#"]).ok_or_else(|| Self::invalid(s))?;
+        let date = parse_date(body).ok_or_else(|| Self::invalid(s))?;
+
+        Self::from_date(date)
+    }
+}
+
+impl AdsTimeOfDay {
+    /// Converts to a [`NaiveTime`], truncated to milliseconds.
+    pub fn to_time(self) -> NaiveTime {
+        time_from_nanos(self.0 as u128 * NANOS_PER_MILLI)
+    }
+
+    /// Converts from a [`NaiveTime`], truncated to milliseconds.
+    pub fn from_time(time: NaiveTime) -> Self {
+        Self((nanos_from_time(time) / NANOS_PER_MILLI) as u32)
+    }
+}
+
+impl From<AdsTimeOfDay> for NaiveTime {
+    fn from(value: AdsTimeOfDay) -> Self {
+        value.to_time()
+    }
+}
+
+impl From<NaiveTime> for AdsTimeOfDay {
+    fn from(value: NaiveTime) -> Self {
+        Self::from_time(value)
+    }
+}
+
+impl fmt::Display for AdsTimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TOD#{}", self.to_time().format("%H:%M:%S%.f"))
+    }
+}
+
+impl FromStr for AdsTimeOfDay {
+    type Err = AdsTimeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let body = strip_prefix(s, &["TIME_OF_DAY#", "TOD#"]).ok_or_else(|| Self::invalid(s))?;
+        let time = parse_time(body).ok_or_else(|| Self::invalid(s))?;
+
+        Ok(Self::from_time(time))
+    }
+}
+
+impl AdsLTimeOfDay {
+    /// Converts to a [`NaiveTime`].
+    pub fn to_time(self) -> NaiveTime {
+        time_from_nanos(self.0 as u128)
+    }
+
+    /// Converts from a [`NaiveTime`].
+    pub fn from_time(time: NaiveTime) -> Self {
+        Self(nanos_from_time(time) as u64)
+    }
+}
+
+impl From<AdsLTimeOfDay> for NaiveTime {
+    fn from(value: AdsLTimeOfDay) -> Self {
+        value.to_time()
+    }
+}
+
+impl From<NaiveTime> for AdsLTimeOfDay {
+    fn from(value: NaiveTime) -> Self {
+        Self::from_time(value)
+    }
+}
+
+impl fmt::Display for AdsLTimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LTOD#{}", self.to_time().format("%H:%M:%S%.f"))
+    }
+}
+
+impl FromStr for AdsLTimeOfDay {
+    type Err = AdsTimeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let body = strip_prefix(s, &["LTIME_OF_DAY#", "LTOD#"]).ok_or_else(|| Self::invalid(s))?;
+        let time = parse_time(body).ok_or_else(|| Self::invalid(s))?;
+
+        Ok(Self::from_time(time))
+    }
+}
+
+impl AdsDateTime {
+    /// Converts to a [`NaiveDateTime`].
+    pub fn to_datetime(self) -> NaiveDateTime {
+        from_epoch_nanos(self.0 as u128 * NANOS_PER_SEC)
+    }
+
+    /// Converts from a [`NaiveDateTime`], truncated to seconds. Fails for datetimes before
+    /// 1970 or after 2106-02-07.
+    pub fn from_datetime(dt: NaiveDateTime) -> Result<Self, AdsTimeError> {
+        let nanos = to_epoch_nanos(dt).ok_or(out_of_range(Self::TYPE_NAME))?;
+
+        u32::try_from(nanos / NANOS_PER_SEC)
+            .map(Self)
+            .map_err(|_| out_of_range(Self::TYPE_NAME))
+    }
+}
+
+impl From<AdsDateTime> for NaiveDateTime {
+    fn from(value: AdsDateTime) -> Self {
+        value.to_datetime()
+    }
+}
+
+impl TryFrom<NaiveDateTime> for AdsDateTime {
+    type Error = AdsTimeError;
+
+    fn try_from(value: NaiveDateTime) -> Result<Self, Self::Error> {
+        Self::from_datetime(value)
+    }
+}
+
+impl fmt::Display for AdsDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DT#{}", self.to_datetime().format("%Y-%m-%d-%H:%M:%S"))
+    }
+}
+
+impl FromStr for AdsDateTime {
+    type Err = AdsTimeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let body = strip_prefix(s, &["DATE_AND_TIME#", "DT#"]).ok_or_else(|| Self::invalid(s))?;
+        let dt = parse_datetime(body).ok_or_else(|| Self::invalid(s))?;
+
+        Self::from_datetime(dt)
+    }
+}
+
+impl AdsLDateTime {
+    /// Converts to a [`NaiveDateTime`].
+    pub fn to_datetime(self) -> NaiveDateTime {
+        from_epoch_nanos(self.0 as u128)
+    }
+
+    /// Converts from a [`NaiveDateTime`]. Fails for datetimes before 1970 or after
+    /// 2554-07-21.
+    pub fn from_datetime(dt: NaiveDateTime) -> Result<Self, AdsTimeError> {
+        let nanos = to_epoch_nanos(dt).ok_or(out_of_range(Self::TYPE_NAME))?;
+
+        u64::try_from(nanos)
+            .map(Self)
+            .map_err(|_| out_of_range(Self::TYPE_NAME))
+    }
+}
+
+impl From<AdsLDateTime> for NaiveDateTime {
+    fn from(value: AdsLDateTime) -> Self {
+        value.to_datetime()
+    }
+}
+
+impl TryFrom<NaiveDateTime> for AdsLDateTime {
+    type Error = AdsTimeError;
+
+    fn try_from(value: NaiveDateTime) -> Result<Self, Self::Error> {
+        Self::from_datetime(value)
+    }
+}
+
+impl fmt::Display for AdsLDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "LDT#{}",
+            self.to_datetime().format("%Y-%m-%d-%H:%M:%S%.f")
+        )
+    }
+}
+
+impl FromStr for AdsLDateTime {
+    type Err = AdsTimeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let body = strip_prefix(s, &["LDATE_AND_TIME#", "LDT#"]).ok_or_else(|| Self::invalid(s))?;
+        let dt = parse_datetime(body).ok_or_else(|| Self::invalid(s))?;
+
+        Self::from_datetime(dt)
+    }
+}
+
+fn out_of_range(type_name: &'static str) -> AdsTimeError {
+    AdsTimeError::OutOfRange { type_name }
+}
+
+/// Strips the first matching literal prefix, ignoring ASCII case.
+fn strip_prefix<'a>(s: &'a str, prefixes: &[&str]) -> Option<&'a str> {
+    prefixes.iter().find_map(|p| {
+        let head = s.get(..p.len())?;
+        head.eq_ignore_ascii_case(p).then(|| &s[p.len()..])
+    })
+}
+
+/// Parses the body of a `TIME` or `LTIME` literal, such as `1h2m3.5s`, into nanoseconds.
+fn parse_duration(body: &str) -> Option<u128> {
+    let body = body.replace('_', "").to_ascii_lowercase();
+    let mut rest = body.as_str();
+    let mut total = 0u128;
+
+    if rest.is_empty() {
+        return None;
+    }
+
+    while !rest.is_empty() {
+        let end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(end);
+        // Two-letter units first, so `ms` is not read as minutes.
+        let (unit, scale) = LTIME_UNITS
+            .iter()
+            .filter(|(unit, _)| unit.len() == 2)
+            .chain(LTIME_UNITS.iter().filter(|(unit, _)| unit.len() == 1))
+            .find(|(unit, _)| tail.starts_with(unit))?;
+
+        total = total.checked_add(scale_number(number, *scale)?)?;
+        rest = &tail[unit.len()..];
+    }
+
+    Some(total)
+}
+
+/// Multiplies a decimal `number` such as `1.5` by `scale`, dropping anything below 1.
+fn scale_number(number: &str, scale: u128) -> Option<u128> {
+    let (int, frac) = number.split_once('.').unwrap_or((number, ""));
+    if int.is_empty() || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut value = int.parse::<u128>().ok()?.checked_mul(scale)?;
+    let mut unit = scale;
+    for digit in frac.bytes() {
+        unit /= 10;
+        value += (digit - b'0') as u128 * unit;
+    }
+
+    Some(value)
+}
+
+/// Writes every non-zero component of `nanos`, or `0` of the smallest unit.
+fn format_duration(f: &mut fmt::Formatter<'_>, nanos: u128, units: &[(&str, u128)]) -> fmt::Result {
+    let mut rest = nanos;
+
+    for (unit, scale) in units {
+        let count = rest / scale;
+        rest %= scale;
+        if count > 0 {
+            write!(f, "{count}{unit}")?;
+        }
+    }
+
+    if nanos == 0 {
+        let (unit, _) = units[units.len() - 1];
+        write!(f, "0{unit}")?;
+    }
+
+    Ok(())
+}
+
+fn parse_date(body: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(body, "%Y-%m-%d").ok()
+}
+
+fn parse_time(body: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(body, "%H:%M:%S%.f").ok()
+}
+
+/// Parses `2024-01-01-12:00:00`, where the third `-` separates the date from the time.
+fn parse_datetime(body: &str) -> Option<NaiveDateTime> {
+    let (split, _) = body.match_indices('-').nth(2)?;
+    let date = parse_date(&body[..split])?;
+    let time = parse_time(&body[split + 1..])?;
+
+    Some(date.and_time(time))
+}
+
+fn from_epoch_nanos(nanos: u128) -> NaiveDateTime {
+    let delta = TimeDelta::seconds((nanos / NANOS_PER_SEC) as i64)
+        + TimeDelta::nanoseconds((nanos % NANOS_PER_SEC) as i64);
+
+    DateTime::UNIX_EPOCH.naive_utc() + delta
+}
+
+/// Returns the nanoseconds since 1970-01-01, or `None` for earlier datetimes.
+fn to_epoch_nanos(dt: NaiveDateTime) -> Option<u128> {
+    let delta = dt - DateTime::UNIX_EPOCH.naive_utc();
+    let secs = u128::try_from(delta.num_seconds()).ok()?;
+
+    Some(secs * NANOS_PER_SEC + delta.subsec_nanos() as u128)
+}
+
+fn time_from_nanos(nanos: u128) -> NaiveTime {
+    let nanos = nanos % NANOS_PER_DAY;
+
+    NaiveTime::from_num_seconds_from_midnight_opt(
+        (nanos / NANOS_PER_SEC) as u32,
+        (nanos % NANOS_PER_SEC) as u32,
+    )
+    .unwrap_or(NaiveTime::MIN)
+}
+
+fn nanos_from_time(time: NaiveTime) -> u128 {
+    // A leap second is reported as nanosecond 1_000_000_000 or more, clamp it into the second.
+    let subsec = time.nanosecond().min(999_999_999);
+
+    time.num_seconds_from_midnight() as u128 * NANOS_PER_SEC + subsec as u128
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_literals_round_trip() {
+        let time: AdsTime = "T#1h2m3s4ms".parse().unwrap();
+        assert_eq!(time.as_millis(), 3_723_004);
+        assert_eq!(time.to_string(), "T#1h2m3s4ms");
+
+        assert_eq!("time#1.5S".parse::<AdsTime>().unwrap().as_millis(), 1_500);
+        assert_eq!("T#1_000ms".parse::<AdsTime>().unwrap().as_millis(), 1_000);
+        assert_eq!(AdsTime::from_millis(0).to_string(), "T#0ms");
+        assert_eq!(AdsTime::from_millis(90_000_000).to_string(), "T#1d1h");
+    }
+
+    #[test]
+    fn ltime_keeps_nanoseconds() {
+        let time: AdsLTime = "LTIME#1s2us3ns".parse().unwrap();
+        assert_eq!(time.as_nanos(), 1_000_002_003);
+        assert_eq!(time.to_string(), "LTIME#1s2us3ns");
+        assert_eq!(time.to_duration(), Duration::new(1, 2_003));
+    }
+
+    #[test]
+    fn invalid_literals_are_rejected() {
+        assert!("1h".parse::<AdsTime>().is_err());
+        assert!("T#".parse::<AdsTime>().is_err());
+        assert!("T#5x".parse::<AdsTime>().is_err());
+        assert!("T#1.2.3s".parse::<AdsTime>().is_err());
+        assert_eq!(
+            "T#50d".parse::<AdsTime>(),
+            Err(AdsTimeError::OutOfRange { type_name: "TIME" })
+        );
+    }
+
+    #[test]
+    fn durations_convert() {
+        let time = AdsTime::try_from(Duration::from_micros(1_500)).unwrap();
+        assert_eq!(time.as_millis(), 1);
+        assert_eq!(Duration::from(time), Duration::from_millis(1));
+        assert!(AdsTime::try_from(Duration::from_secs(u64::MAX)).is_err());
+    }
+
+    #[test]
+    fn dates_round_trip() {
+        let date: AdsDate = "D#2024-02-29".parse().unwrap();
+        assert_eq!(date.as_raw(), 1_709_164_800);
+        assert_eq!(date.to_string(), "D#2024-02-29");
+
+        let ldate: AdsLDate = "LDATE#2024-02-29".parse().unwrap();
+        assert_eq!(ldate.as_raw(), 1_709_164_800 * 1_000_000_000);
+        assert_eq!(ldate.to_date(), date.to_date());
+
+        assert!("D#1969-12-31".parse::<AdsDate>().is_err());
+    }
+
+    #[test]
+    fn times_of_day_round_trip() {
+        let tod: AdsTimeOfDay = "TOD#12:30:15.25".parse().unwrap();
+        assert_eq!(tod.as_raw(), 45_015_250);
+        assert_eq!(tod.to_string(), "TOD#12:30:15.250");
+        assert_eq!(AdsTimeOfDay::from_raw(0).to_string(), "TOD#00:00:00");
+
+        let ltod: AdsLTimeOfDay = "LTOD#00:00:01.000000001".parse().unwrap();
+        assert_eq!(ltod.as_raw(), 1_000_000_001);
+    }
+
+    #[test]
+    fn datetimes_round_trip() {
+        let dt: AdsDateTime = "DT#2024-01-01-12:00:00".parse().unwrap();
+        assert_eq!(dt.as_raw(), 1_704_110_400);
+        assert_eq!(dt.to_string(), "DT#2024-01-01-12:00:00");
+
+        let ldt: AdsLDateTime = "LDT#2024-01-01-12:00:00.5".parse().unwrap();
+        assert_eq!(ldt.as_raw(), 1_704_110_400_500_000_000);
+        assert_eq!(ldt.to_string(), "LDT#2024-01-01-12:00:00.500");
+        assert_eq!(AdsDateTime::from_datetime(ldt.to_datetime()).unwrap(), dt);
+    }
+
+    #[test]
+    fn codec_and_serde() {
+        let time = AdsTime::from_millis(1_000);
+        assert_eq!(time.to_ads_bytes(), [0xE8, 0x03, 0, 0]);
+        assert_eq!(AdsTime::decode(&[0xE8, 0x03, 0, 0]).unwrap(), time);
+        assert_eq!(AdsLDateTime::SIZE, 8);
+
+        let json = serde_json::to_string(&time).unwrap();
+        assert_eq!(json, "\"T#1s\"");
+        assert_eq!(serde_json::from_str::<AdsTime>(&json).unwrap(), time);
+    }
+}
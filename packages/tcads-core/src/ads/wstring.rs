@@ -0,0 +1,378 @@
+use super::error::AdsStringError;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// A fixed-length ADS wide string.
+///
+/// Maps to `WSTRING(K)` in the PLC, where `N = K + 1`.
+/// The generic parameter `N` represents the **total number of UTF-16 code units** in the
+/// buffer, including the null terminator. The buffer takes `2 * N` bytes in PLC memory.
+///
+/// # Example
+/// * PLC: `WSTRING(80)` (holds 80 code units + 1 null, 162 bytes)
+/// * Rust: `AdsWString<81>`
+///
+/// # Encoding
+/// Handles conversion between Rust UTF-8 and PLC UTF-16LE automatically. Characters outside
+/// the Basic Multilingual Plane take two code units (a surrogate pair) and are never split
+/// by [`truncate`](Self::truncate).
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AdsWString<const N: usize>([u16; N]);
+
+impl<const N: usize> AdsWString<N> {
+    /// Creates a new empty string (all zeros).
+    pub const fn new() -> Self {
+        Self([0; N])
+    }
+
+    /// Returns the raw code unit array.
+    pub fn as_units(&self) -> &[u16] {
+        &self.0
+    }
+
+    /// Returns the code units up to the first null terminator.
+    pub fn as_units_until_nul(&self) -> &[u16] {
+        until_nul(&self.0)
+    }
+
+    /// Iterates over the code units of the string.
+    pub fn units(&self) -> std::slice::Iter<'_, u16> {
+        self.as_units_until_nul().iter()
+    }
+
+    /// Decodes the string content into a Rust UTF-8 string (lossy).
+    ///
+    /// Unpaired surrogates are replaced with `U+FFFD`.
+    pub fn as_str(&self) -> String {
+        String::from_utf16_lossy(self.as_units_until_nul())
+    }
+
+    /// Returns the length of the string in code units (excluding null terminator).
+    pub fn len(&self) -> usize {
+        self.as_units_until_nul().len()
+    }
+
+    /// Returns `true` if the string is empty (contains no characters).
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the total capacity of the string buffer in code units (\[N\]).
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Appends a string slice to the end of this string.
+    ///
+    /// Returns an error if the string exceeds the capacity.
+    pub fn push_str(&mut self, s: &str) -> Result<(), AdsStringError> {
+        let current_len = self.len();
+        let available = N.saturating_sub(1 + current_len);
+        let needed = s.encode_utf16().count();
+
+        if needed > available {
+            return Err(AdsStringError::TooLong {
+                expected: available,
+                got: needed,
+            });
+        }
+
+        for (slot, unit) in self.0[current_len..].iter_mut().zip(s.encode_utf16()) {
+            *slot = unit;
+        }
+        self.0[current_len + needed] = 0;
+
+        Ok(())
+    }
+
+    /// Appends a single character to the end of this string.
+    pub fn push(&mut self, c: char) -> Result<(), AdsStringError> {
+        let mut buf = [0u8; 4];
+        let s = c.encode_utf8(&mut buf);
+        self.push_str(s)
+    }
+
+    /// Truncates the string to `new_len` code units.
+    ///
+    /// If `new_len` falls between the two halves of a surrogate pair, the whole pair is
+    /// removed. If `new_len` is greater than the current length, this does nothing.
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len < self.len() {
+            let new_len = if is_low_surrogate(self.0[new_len]) && new_len > 0 {
+                new_len - 1
+            } else {
+                new_len
+            };
+            self.0[new_len] = 0;
+        }
+    }
+
+    /// Clears the string (sets length to 0).
+    pub fn clear(&mut self) {
+        self.0[0] = 0;
+    }
+}
+
+impl<const N: usize> Index<usize> for AdsWString<N> {
+    type Output = u16;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for AdsWString<N> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.0[index]
+    }
+}
+
+impl<'a, const N: usize> IntoIterator for &'a AdsWString<N> {
+    type Item = &'a u16;
+    type IntoIter = std::slice::Iter<'a, u16>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.units()
+    }
+}
+
+impl<const N: usize> fmt::Write for AdsWString<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s).map_err(|_| fmt::Error)
+    }
+}
+
+impl<const N: usize> From<[u16; N]> for AdsWString<N> {
+    fn from(units: [u16; N]) -> Self {
+        Self(units)
+    }
+}
+
+impl<const N: usize> From<&[u16; N]> for AdsWString<N> {
+    fn from(units: &[u16; N]) -> Self {
+        Self(*units)
+    }
+}
+
+impl<const N: usize> TryFrom<&str> for AdsWString<N> {
+    type Error = AdsStringError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut s = Self::new();
+        s.push_str(value).map_err(|_| AdsStringError::TooLong {
+            expected: N.saturating_sub(1),
+            got: value.encode_utf16().count(),
+        })?;
+
+        Ok(s)
+    }
+}
+
+impl<const N: usize> AsRef<[u16]> for AdsWString<N> {
+    fn as_ref(&self) -> &[u16] {
+        self.as_units_until_nul()
+    }
+}
+
+impl<const N: usize> fmt::Display for AdsWString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.as_str())
+    }
+}
+
+impl<const N: usize> fmt::Debug for AdsWString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.as_str())
+    }
+}
+
+impl<const N: usize> Default for AdsWString<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> serde::Serialize for AdsWString<N> {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&self.as_str())
+    }
+}
+
+impl<'de, const N: usize> serde::Deserialize<'de> for AdsWString<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(d)?;
+        Self::try_from(s.as_str()).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A wide string of any length.
+///
+/// Used where the size of a `WSTRING` is only known at runtime, for example when reading a
+/// variable by name with `read_write`. Decoding stops at the first null code unit, or at
+/// the end of the buffer if there is none.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AdsWStringBuf(Vec<u16>);
+
+impl AdsWStringBuf {
+    /// Creates a new empty string.
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Returns the code units of the string, without a null terminator.
+    pub fn as_units(&self) -> &[u16] {
+        &self.0
+    }
+
+    /// Decodes the string content into a Rust UTF-8 string (lossy).
+    ///
+    /// Unpaired surrogates are replaced with `U+FFFD`.
+    pub fn as_str(&self) -> String {
+        String::from_utf16_lossy(&self.0)
+    }
+
+    /// Returns the length of the string in code units.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the string is empty (contains no characters).
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Appends a string slice to the end of this string.
+    pub fn push_str(&mut self, s: &str) {
+        self.0.extend(s.encode_utf16());
+    }
+
+    /// Returns the number of bytes the string takes in PLC memory, including the terminator.
+    pub fn byte_len(&self) -> usize {
+        (self.0.len() + 1) * 2
+    }
+}
+
+impl From<&str> for AdsWStringBuf {
+    fn from(value: &str) -> Self {
+        Self(value.encode_utf16().collect())
+    }
+}
+
+impl From<String> for AdsWStringBuf {
+    fn from(value: String) -> Self {
+        Self::from(value.as_str())
+    }
+}
+
+impl<const N: usize> From<&AdsWString<N>> for AdsWStringBuf {
+    fn from(value: &AdsWString<N>) -> Self {
+        Self(value.as_units_until_nul().to_vec())
+    }
+}
+
+impl From<Vec<u16>> for AdsWStringBuf {
+    /// Takes the code units up to the first null terminator.
+    fn from(mut units: Vec<u16>) -> Self {
+        let len = until_nul(&units).len();
+        units.truncate(len);
+        Self(units)
+    }
+}
+
+impl fmt::Display for AdsWStringBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.as_str())
+    }
+}
+
+impl fmt::Debug for AdsWStringBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.as_str())
+    }
+}
+
+impl serde::Serialize for AdsWStringBuf {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&self.as_str())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for AdsWStringBuf {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        Ok(Self::from(String::deserialize(d)?))
+    }
+}
+
+/// Returns the code units before the first null terminator.
+fn until_nul(units: &[u16]) -> &[u16] {
+    let end = units.iter().position(|&u| u == 0).unwrap_or(units.len());
+    &units[..end]
+}
+
+fn is_low_surrogate(unit: u16) -> bool {
+    (0xDC00..=0xDFFF).contains(&unit)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fmt::Write;
+
+    #[test]
+    fn test_encoding_roundtrip() {
+        let input = "Grüße, 世界";
+        let s: AdsWString<20> = AdsWString::try_from(input).expect("Should fit");
+
+        assert_eq!(s.as_str(), input);
+        assert_eq!(s.len(), input.encode_utf16().count());
+    }
+
+    #[test]
+    fn test_surrogate_pairs() {
+        // U+1F600 takes two code units
+        let s: AdsWString<4> = AdsWString::try_from("a😀").unwrap();
+        assert_eq!(s.len(), 3);
+        assert_eq!(s.as_str(), "a😀");
+
+        let s: Result<AdsWString<3>, _> = AdsWString::try_from("a😀");
+        assert!(matches!(s, Err(AdsStringError::TooLong { .. })));
+    }
+
+    #[test]
+    fn test_truncate_keeps_surrogate_pairs_whole() {
+        let mut s: AdsWString<10> = AdsWString::try_from("a😀b").unwrap();
+
+        s.truncate(2);
+        assert_eq!(s.as_str(), "a");
+    }
+
+    #[test]
+    fn test_stops_at_terminator() {
+        // 'A' is 0x0041, whose high byte is zero, so only a full null unit ends the string.
+        let s = AdsWString::from([0x41, 0x42, 0, 0x43]);
+        assert_eq!(s.as_str(), "AB");
+
+        let buf = AdsWStringBuf::from(vec![0x41, 0, 0x42]);
+        assert_eq!(buf.as_str(), "A");
+    }
+
+    #[test]
+    fn test_push_and_format() {
+        let mut s: AdsWString<10> = AdsWString::new();
+        write!(s, "Val: {}", 42).unwrap();
+        assert_eq!(s.as_str(), "Val: 42");
+
+        s.push('!').unwrap();
+        assert!(s.push_str("!!").is_err());
+        assert_eq!(s[0], 'V' as u16);
+    }
+
+    #[test]
+    fn test_serde() {
+        let s: AdsWString<10> = AdsWString::try_from("Hi").unwrap();
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(json, "\"Hi\"");
+        assert_eq!(serde_json::from_str::<AdsWString<10>>(&json).unwrap(), s);
+        assert!(serde_json::from_str::<AdsWString<2>>(&json).is_err());
+    }
+}
@@ -1,3 +1,10 @@
+#[cfg(feature = "config")]
+use super::net_id::AmsNetId;
+#[cfg(feature = "config")]
+use std::path::PathBuf;
+#[cfg(feature = "config")]
+use std::sync::Arc;
+
 /// Errors specific to AMS protocol
 #[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
 pub enum AmsError {
@@ -97,3 +104,34 @@ pub enum RouterStateError {
     #[error("Invalid buffer size: expected {} bytes, got {}", expected, got)]
     InvalidBufferSize { expected: usize, got: usize },
 }
+
+/// Errors when loading or saving a route table
+#[cfg(feature = "config")]
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum RouteError {
+    /// The file could not be read
+    #[error("Cannot read {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: Arc<std::io::Error>,
+    },
+    /// Invalid TOML
+    #[error("Invalid TOML: {0}")]
+    Toml(#[from] toml::de::Error),
+    /// The table could not be written as TOML
+    #[error("Cannot write TOML: {0}")]
+    TomlWrite(#[from] toml::ser::Error),
+    /// Invalid JSON, or the table could not be written as JSON
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] Arc<serde_json::Error>),
+    /// Two routes share a net ID
+    #[error("Duplicate route to {0}")]
+    DuplicateNetId(AmsNetId),
+}
+
+#[cfg(feature = "config")]
+impl From<serde_json::Error> for RouteError {
+    fn from(err: serde_json::Error) -> Self {
+        RouteError::Json(Arc::new(err))
+    }
+}
@@ -3,11 +3,15 @@ pub mod command;
 pub mod error;
 pub mod header;
 pub mod net_id;
+pub mod route;
 pub mod router_state;
 
 pub use addr::{AmsAddr, AmsPort};
 pub use command::AmsCommand;
+#[cfg(feature = "config")]
+pub use error::RouteError;
 pub use error::{AddrError, AmsError, AmsTcpHeaderError, NetIdError};
 pub use header::AmsTcpHeader;
 pub use net_id::{AMS_PORT_LEN, AmsNetId};
+pub use route::{AmsRoute, AmsRouteFlags, AmsRouteTable, AmsTransport};
 pub use router_state::RouterState;
@@ -162,7 +162,7 @@ impl serde::Serialize for AmsNetId {
 
 impl<'de> serde::Deserialize<'de> for AmsNetId {
     fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
-        let s = <&str>::deserialize(d)?;
+        let s = String::deserialize(d)?;
         s.parse().map_err(serde::de::Error::custom)
     }
 }
@@ -0,0 +1,425 @@
+#[cfg(feature = "config")]
+use super::error::RouteError;
+use super::net_id::AmsNetId;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+#[cfg(feature = "config")]
+use std::path::Path;
+
+/// Transport used to reach the router of a [route](AmsRoute).
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Default,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum AmsTransport {
+    /// Plain AMS/TCP.
+    #[default]
+    Tcp,
+    /// Secure ADS, AMS/TCP over TLS.
+    Tls,
+}
+
+impl AmsTransport {
+    /// Default port of AMS/TCP routers.
+    pub const TCP_PORT: u16 = 48898;
+    /// Default port of Secure ADS routers.
+    pub const TLS_PORT: u16 = 8016;
+
+    /// Returns the port routers listen on for this transport by default.
+    pub const fn default_port(self) -> u16 {
+        match self {
+            Self::Tcp => Self::TCP_PORT,
+            Self::Tls => Self::TLS_PORT,
+        }
+    }
+}
+
+impl fmt::Display for AmsTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp => write!(f, "TCP"),
+            Self::Tls => write!(f, "TLS"),
+        }
+    }
+}
+
+/// Flags of an [`AmsRoute`] (32-bit bitfield).
+#[derive(
+    serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default,
+)]
+#[serde(transparent)]
+pub struct AmsRouteFlags(pub u32);
+
+impl AmsRouteFlags {
+    /// The route only lasts until the router restarts.
+    pub const TEMPORARY: u32 = 0x0000_0001;
+    /// The route is configured but not used.
+    pub const DISABLED: u32 = 0x0000_0002;
+
+    /// Creates a new set of flags from a raw u32.
+    pub const fn new(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    /// True if all bits of `flag` are set.
+    pub const fn contains(&self, flag: u32) -> bool {
+        self.0 & flag == flag
+    }
+
+    /// True if the route only lasts until the router restarts.
+    pub const fn is_temporary(&self) -> bool {
+        self.contains(Self::TEMPORARY)
+    }
+
+    /// True if the route is configured but not used.
+    pub const fn is_disabled(&self) -> bool {
+        self.contains(Self::DISABLED)
+    }
+}
+
+impl fmt::Debug for AmsRouteFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AmsRouteFlags({:#010x})", self.0)
+    }
+}
+
+/// A route to a remote AMS router.
+///
+/// Maps an [`AmsNetId`] to the host its router is reached on. `host` is an IP address or
+/// host name, optionally followed by `:port`. Without a port, the
+/// [default port](AmsTransport::default_port) of the transport is used.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AmsRoute {
+    name: String,
+    net_id: AmsNetId,
+    host: String,
+    #[serde(default)]
+    transport: AmsTransport,
+    #[serde(default)]
+    flags: AmsRouteFlags,
+}
+
+impl AmsRoute {
+    /// Creates a plain AMS/TCP route named `name` to the router of `net_id` on `host`.
+    pub fn new(name: impl Into<String>, net_id: AmsNetId, host: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            net_id,
+            host: host.into(),
+            transport: AmsTransport::default(),
+            flags: AmsRouteFlags::default(),
+        }
+    }
+
+    /// Sets the transport.
+    pub fn with_transport(mut self, transport: AmsTransport) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Sets the flags.
+    pub fn with_flags(mut self, flags: AmsRouteFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Returns the name of the route.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the net ID reached through the route.
+    pub fn net_id(&self) -> AmsNetId {
+        self.net_id
+    }
+
+    /// Returns the host, as configured.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Returns the transport.
+    pub fn transport(&self) -> AmsTransport {
+        self.transport
+    }
+
+    /// Returns the flags.
+    pub fn flags(&self) -> AmsRouteFlags {
+        self.flags
+    }
+
+    /// True unless the route is [disabled](AmsRouteFlags::DISABLED).
+    pub fn is_enabled(&self) -> bool {
+        !self.flags.is_disabled()
+    }
+
+    /// Returns the `host:port` address to connect to, filling in the default port.
+    pub fn address(&self) -> String {
+        let port = self.transport.default_port();
+        if let Ok(ip) = self.host.parse::<IpAddr>() {
+            return SocketAddr::new(ip, port).to_string();
+        }
+        if self.host.parse::<SocketAddr>().is_ok() {
+            return self.host.clone();
+        }
+        match self.host.rsplit_once(':') {
+            Some((_, p)) if p.parse::<u16>().is_ok() => self.host.clone(),
+            _ => format!("{}:{port}", self.host),
+        }
+    }
+}
+
+/// A set of [routes](AmsRoute) with unique net IDs.
+///
+/// With the `config` feature, tables can be loaded from and saved to TOML or JSON. In
+/// TOML, each route is a `[[route]]` table:
+///
+/// ```toml
+/// [[route]]
+/// name = "Line 1"
+/// net_id = "192.168.0.20.1.1"
+/// host = "192.168.0.20"
+///
+/// [[route]]
+/// name = "Line 2"
+/// net_id = "192.168.0.21.1.1"
+/// host = "plc-line2.local:8016"
+/// transport = "tls"
+/// flags = 1
+/// ```
+///
+/// In JSON, the routes are an array under `"route"` (or `"routes"`).
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct AmsRouteTable {
+    #[serde(rename = "route", alias = "routes", default)]
+    routes: Vec<AmsRoute>,
+}
+
+impl AmsRouteTable {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a table from TOML.
+    #[cfg(feature = "config")]
+    pub fn from_toml_str(s: &str) -> Result<Self, RouteError> {
+        let table: Self = toml::from_str(s)?;
+        table.validate()
+    }
+
+    /// Parses a table from JSON.
+    #[cfg(feature = "config")]
+    pub fn from_json_str(s: &str) -> Result<Self, RouteError> {
+        let table: Self = serde_json::from_str(s)?;
+        table.validate()
+    }
+
+    /// Loads a table from a file, read as JSON if it has a `.json` extension and as TOML
+    /// otherwise.
+    #[cfg(feature = "config")]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RouteError> {
+        let path = path.as_ref();
+        let s = std::fs::read_to_string(path).map_err(|e| RouteError::Io {
+            path: path.to_path_buf(),
+            source: e.into(),
+        })?;
+        match path.extension().is_some_and(|ext| ext == "json") {
+            true => Self::from_json_str(&s),
+            false => Self::from_toml_str(&s),
+        }
+    }
+
+    /// Serializes the table to TOML.
+    #[cfg(feature = "config")]
+    pub fn to_toml_string(&self) -> Result<String, RouteError> {
+        Ok(toml::to_string(self)?)
+    }
+
+    /// Serializes the table to pretty-printed JSON.
+    #[cfg(feature = "config")]
+    pub fn to_json_string(&self) -> Result<String, RouteError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Adds `route`, returning the route it replaces for the same net ID.
+    pub fn insert(&mut self, route: AmsRoute) -> Option<AmsRoute> {
+        match self.routes.iter_mut().find(|r| r.net_id == route.net_id) {
+            Some(existing) => Some(std::mem::replace(existing, route)),
+            None => {
+                self.routes.push(route);
+                None
+            }
+        }
+    }
+
+    /// Removes and returns the route to `net_id`.
+    pub fn remove(&mut self, net_id: AmsNetId) -> Option<AmsRoute> {
+        let index = self.routes.iter().position(|r| r.net_id == net_id)?;
+        Some(self.routes.remove(index))
+    }
+
+    /// Returns the route to `net_id`, whether enabled or not.
+    pub fn get(&self, net_id: AmsNetId) -> Option<&AmsRoute> {
+        self.routes.iter().find(|r| r.net_id == net_id)
+    }
+
+    /// Returns the route to `net_id` if it is enabled.
+    pub fn find(&self, net_id: AmsNetId) -> Option<&AmsRoute> {
+        self.get(net_id).filter(|r| r.is_enabled())
+    }
+
+    /// Returns an iterator over the routes, in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &AmsRoute> {
+        self.routes.iter()
+    }
+
+    /// Returns the number of routes.
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    /// True if the table has no routes.
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    #[cfg(feature = "config")]
+    fn validate(self) -> Result<Self, RouteError> {
+        for (i, route) in self.routes.iter().enumerate() {
+            if self.routes[..i].iter().any(|r| r.net_id == route.net_id) {
+                return Err(RouteError::DuplicateNetId(route.net_id));
+            }
+        }
+        Ok(self)
+    }
+}
+
+impl FromIterator<AmsRoute> for AmsRouteTable {
+    fn from_iter<I: IntoIterator<Item = AmsRoute>>(iter: I) -> Self {
+        let mut table = Self::new();
+        for route in iter {
+            table.insert(route);
+        }
+        table
+    }
+}
+
+impl IntoIterator for AmsRouteTable {
+    type Item = AmsRoute;
+    type IntoIter = std::vec::IntoIter<AmsRoute>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.routes.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a AmsRouteTable {
+    type Item = &'a AmsRoute;
+    type IntoIter = std::slice::Iter<'a, AmsRoute>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.routes.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "config")]
+    const TOML: &str = r#"
+        [[route]]
+        name = "Line 1"
+        net_id = "192.168.0.20.1.1"
+        host = "192.168.0.20"
+
+        [[route]]
+        name = "Line 2"
+        net_id = "192.168.0.21.1.1"
+        host = "plc-line2.local:8016"
+        transport = "tls"
+        flags = 3
+    "#;
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn parses_toml() {
+        let table = AmsRouteTable::from_toml_str(TOML).unwrap();
+        assert_eq!(table.len(), 2);
+
+        let line1 = table.get(AmsNetId::new(192, 168, 0, 20, 1, 1)).unwrap();
+        assert_eq!(line1.name(), "Line 1");
+        assert_eq!(line1.transport(), AmsTransport::Tcp);
+        assert_eq!(line1.address(), "192.168.0.20:48898");
+
+        let line2 = table.get(AmsNetId::new(192, 168, 0, 21, 1, 1)).unwrap();
+        assert_eq!(line2.transport(), AmsTransport::Tls);
+        assert!(line2.flags().is_temporary());
+        assert_eq!(line2.address(), "plc-line2.local:8016");
+        assert!(table.find(line2.net_id()).is_none());
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn json_roundtrip() {
+        let table = AmsRouteTable::from_toml_str(TOML).unwrap();
+        let json = table.to_json_string().unwrap();
+        assert_eq!(AmsRouteTable::from_json_str(&json).unwrap(), table);
+
+        let toml = table.to_toml_string().unwrap();
+        assert_eq!(AmsRouteTable::from_toml_str(&toml).unwrap(), table);
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn rejects_duplicate_net_ids() {
+        let json = r#"{"routes": [
+            {"name": "a", "net_id": "10.0.0.1.1.1", "host": "10.0.0.1"},
+            {"name": "b", "net_id": "10.0.0.1.1.1", "host": "10.0.0.2"}
+        ]}"#;
+        assert!(matches!(
+            AmsRouteTable::from_json_str(json),
+            Err(RouteError::DuplicateNetId(net_id)) if net_id == AmsNetId::new(10, 0, 0, 1, 1, 1)
+        ));
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn errors_keep_their_source() {
+        use std::error::Error;
+        use std::sync::Arc;
+
+        let err = AmsRouteTable::load("missing/routes.toml").unwrap_err();
+        assert!(matches!(&err, RouteError::Io { path, .. } if path.ends_with("routes.toml")));
+        let source = err.source().unwrap().downcast_ref::<Arc<std::io::Error>>();
+        assert_eq!(source.unwrap().kind(), std::io::ErrorKind::NotFound);
+
+        let err = AmsRouteTable::from_toml_str("[[route]]\nname = 1").unwrap_err();
+        assert!(matches!(err, RouteError::Toml(_)));
+        assert!(err.source().is_some());
+
+        let err = AmsRouteTable::from_json_str("{").unwrap_err();
+        assert!(matches!(err, RouteError::Json(_)));
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn address_fills_in_default_port() {
+        let net_id = AmsNetId::new(10, 0, 0, 1, 1, 1);
+        let route = AmsRoute::new("v6", net_id, "::1").with_transport(AmsTransport::Tls);
+        assert_eq!(route.address(), "[::1]:8016");
+        let route = AmsRoute::new("named", net_id, "plc");
+        assert_eq!(route.address(), "plc:48898");
+    }
+}
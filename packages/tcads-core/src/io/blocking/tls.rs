@@ -0,0 +1,446 @@
+use super::reader::AmsReader;
+use super::stream::AmsStream;
+use super::writer::AmsWriter;
+use crate::io::tls::{TlsConfig, invalid_data};
+use rustls::ClientConnection;
+#[cfg(feature = "tls-psk")]
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Size of the chunks read from the socket, small enough that decrypting one never
+/// overflows the plaintext buffer of the connection.
+const TLS_CHUNK_LEN: usize = 4096;
+
+/// A TLS client stream whose clones share one connection.
+///
+/// Unlike [`rustls::StreamOwned`], a `TlsStream` can be read on one thread while it is
+/// written on another: reads wait on the socket without holding the TLS state, so
+/// [`AmsStream::try_split`] works as it does over plain TCP.
+#[derive(Debug, Clone)]
+pub struct TlsStream {
+    socket: Arc<TcpStream>,
+    conn: Arc<Mutex<Session>>,
+}
+
+/// The TLS state of a [`TlsStream`], which never touches the socket itself.
+#[derive(Debug)]
+enum Session {
+    Rustls(Box<ClientConnection>),
+    /// A [PSK](TlsConfig::psk) connection, which rustls does not support.
+    #[cfg(feature = "tls-psk")]
+    Psk(openssl::ssl::SslStream<PskBuffers>),
+}
+
+impl TlsStream {
+    /// Performs the TLS handshake over `socket`.
+    pub fn connect(socket: TcpStream, config: &TlsConfig) -> io::Result<Self> {
+        #[cfg(feature = "tls-psk")]
+        if let Some(ssl) = config.psk_session()? {
+            let conn = Self::connect_psk(&socket, ssl)?;
+            return Ok(Self::new(socket, conn));
+        }
+
+        let server_name = config.server_name(socket.peer_addr()?.ip());
+        let mut conn =
+            ClientConnection::new(config.client_config()?, server_name).map_err(invalid_data)?;
+        let mut socket_ref = &socket;
+        while conn.is_handshaking() {
+            conn.complete_io(&mut socket_ref)?;
+        }
+
+        Ok(Self::new(socket, Session::Rustls(Box::new(conn))))
+    }
+
+    /// Returns the underlying socket.
+    pub fn socket(&self) -> &TcpStream {
+        &self.socket
+    }
+
+    fn new(socket: TcpStream, conn: Session) -> Self {
+        Self {
+            socket: Arc::new(socket),
+            conn: Arc::new(Mutex::new(conn)),
+        }
+    }
+
+    #[cfg(feature = "tls-psk")]
+    fn connect_psk(socket: &TcpStream, ssl: openssl::ssl::Ssl) -> io::Result<Session> {
+        let mut stream =
+            openssl::ssl::SslStream::new(ssl, PskBuffers::default()).map_err(invalid_data)?;
+        let mut chunk = [0u8; TLS_CHUNK_LEN];
+        loop {
+            let result = stream.connect();
+            socket_write_all(socket, &mut stream.get_mut().outgoing)?;
+            match result {
+                Ok(()) => return Ok(Session::Psk(stream)),
+                Err(e) if e.code() == openssl::ssl::ErrorCode::WANT_READ => {
+                    let len = (&*socket).read(&mut chunk)?;
+                    if len == 0 {
+                        return Err(io::ErrorKind::UnexpectedEof.into());
+                    }
+                    stream.get_mut().incoming.extend(&chunk[..len]);
+                }
+                Err(e) => return Err(e.into_io_error().unwrap_or_else(invalid_data)),
+            }
+        }
+    }
+
+    fn lock(&self) -> io::Result<MutexGuard<'_, Session>> {
+        self.conn
+            .lock()
+            .map_err(|_| io::Error::other("TLS connection poisoned"))
+    }
+
+    fn write_tls(&self, conn: &mut Session) -> io::Result<()> {
+        match conn {
+            Session::Rustls(conn) => {
+                while conn.wants_write() {
+                    conn.write_tls(&mut &*self.socket)?;
+                }
+                Ok(())
+            }
+            #[cfg(feature = "tls-psk")]
+            Session::Psk(stream) => socket_write_all(&self.socket, &mut stream.get_mut().outgoing),
+        }
+    }
+}
+
+impl Session {
+    /// Reads decrypted data, failing with [`io::ErrorKind::WouldBlock`] until enough data
+    /// has been [received](Self::receive).
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Rustls(conn) => conn.reader().read(buf),
+            #[cfg(feature = "tls-psk")]
+            Self::Psk(stream) => stream.read(buf),
+        }
+    }
+
+    /// Processes data read from the socket, where an empty `data` marks its end.
+    fn receive(&mut self, mut data: &[u8]) -> io::Result<()> {
+        match self {
+            Self::Rustls(conn) => loop {
+                conn.read_tls(&mut data)?;
+                conn.process_new_packets().map_err(invalid_data)?;
+                if data.is_empty() {
+                    return Ok(());
+                }
+            },
+            #[cfg(feature = "tls-psk")]
+            Self::Psk(stream) => {
+                let buffers = stream.get_mut();
+                buffers.incoming.extend(data);
+                buffers.eof |= data.is_empty();
+                Ok(())
+            }
+        }
+    }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Rustls(conn) => conn.writer().write(buf),
+            #[cfg(feature = "tls-psk")]
+            Self::Psk(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Rustls(conn) => conn.writer().flush(),
+            #[cfg(feature = "tls-psk")]
+            Self::Psk(stream) => stream.flush(),
+        }
+    }
+
+    fn send_close_notify(&mut self) {
+        match self {
+            Self::Rustls(conn) => conn.send_close_notify(),
+            #[cfg(feature = "tls-psk")]
+            Self::Psk(stream) => {
+                // Reports that the router's close_notify is still outstanding
+                let _ = stream.shutdown();
+            }
+        }
+    }
+}
+
+/// The transport of an OpenSSL session: data read from the socket is queued in `incoming`
+/// and records to be written to it are collected in `outgoing`.
+#[cfg(feature = "tls-psk")]
+#[derive(Debug, Default)]
+struct PskBuffers {
+    incoming: VecDeque<u8>,
+    outgoing: Vec<u8>,
+    eof: bool,
+}
+
+#[cfg(feature = "tls-psk")]
+impl Read for PskBuffers {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.incoming.is_empty() && !self.eof {
+            true => Err(io::ErrorKind::WouldBlock.into()),
+            false => self.incoming.read(buf),
+        }
+    }
+}
+
+#[cfg(feature = "tls-psk")]
+impl Write for PskBuffers {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.outgoing.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "tls-psk")]
+fn socket_write_all(mut socket: &TcpStream, data: &mut Vec<u8>) -> io::Result<()> {
+    socket.write_all(data)?;
+    data.clear();
+    Ok(())
+}
+
+impl Read for TlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut chunk = [0u8; TLS_CHUNK_LEN];
+        loop {
+            {
+                let mut conn = self.lock()?;
+                match conn.read(buf) {
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                    result => return result,
+                }
+                // Alerts and key updates are answered before waiting for more data.
+                self.write_tls(&mut conn)?;
+            }
+
+            let len = (&*self.socket).read(&mut chunk)?;
+            self.lock()?.receive(&chunk[..len])?;
+        }
+    }
+}
+
+impl Write for TlsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut conn = self.lock()?;
+        let len = conn.write(buf)?;
+        self.write_tls(&mut conn)?;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut conn = self.lock()?;
+        conn.flush()?;
+        self.write_tls(&mut conn)?;
+        (&*self.socket).flush()
+    }
+}
+
+impl AmsStream<TlsStream> {
+    /// Connects to a Secure ADS router at the specified address.
+    ///
+    /// Establishes a [`TcpStream`] connection with Nagle's algorithm disabled, as
+    /// [`connect`](AmsStream::connect) does, then performs the TLS handshake described by
+    /// `config`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use tcads_core::io::TlsConfig;
+    /// use tcads_core::io::blocking::AmsStream;
+    ///
+    /// let config = TlsConfig::self_signed(std::fs::read("plc.pem")?)?;
+    /// let stream = AmsStream::connect_tls("192.168.0.20:8016", &config)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn connect_tls<A: ToSocketAddrs>(addr: A, config: &TlsConfig) -> io::Result<Self> {
+        let socket = TcpStream::connect(addr)?;
+        socket.set_nodelay(true)?;
+        Ok(Self::new(TlsStream::connect(socket, config)?))
+    }
+
+    /// Connects to a Secure ADS router at the specified address with a timeout.
+    ///
+    /// The timeout applies to establishing the TCP connection, as for
+    /// [`connect_timeout`](AmsStream::connect_timeout), and to each read of the handshake.
+    pub fn connect_tls_timeout(
+        addr: &SocketAddr,
+        config: &TlsConfig,
+        timeout: Duration,
+    ) -> io::Result<Self> {
+        let socket = TcpStream::connect_timeout(addr, timeout)?;
+        socket.set_nodelay(true)?;
+        socket.set_read_timeout(Some(timeout))?;
+        let stream = TlsStream::connect(socket, config)?;
+        stream.socket().set_read_timeout(None)?;
+        Ok(Self::new(stream))
+    }
+
+    /// Splits the `TlsStream` into a buffered Reader and buffered Writer sharing the
+    /// TLS connection.
+    pub fn try_split(self) -> io::Result<(AmsReader<TlsStream>, AmsWriter<TlsStream>)> {
+        let stream = self.into_inner();
+        Ok((AmsReader::new(stream.clone()), AmsWriter::new(stream)))
+    }
+
+    /// Returns the socket address of the remote peer of this connection.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.get_ref().socket().peer_addr()
+    }
+
+    /// Returns the socket address of the local half of this connection.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.get_ref().socket().local_addr()
+    }
+
+    /// Sends a TLS `close_notify` alert, then shuts down both halves of the connection.
+    pub fn shutdown(&self) -> io::Result<()> {
+        let stream = self.get_ref();
+        {
+            let mut conn = stream.lock()?;
+            conn.send_close_notify();
+            stream.write_tls(&mut conn)?;
+        }
+        stream.socket().shutdown(Shutdown::Both)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ams::AmsCommand;
+    use crate::io::frame::AmsFrame;
+    use crate::io::tls::tests::{
+        CLIENT_CERT, CLIENT_KEY, SERVER_CERT, client_config, server_config,
+    };
+    use rustls::{ServerConnection, StreamOwned};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Accepts one TLS connection and echoes every frame back until the client disconnects.
+    fn spawn_echo_server() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let (socket, _) = listener.accept().unwrap();
+            let conn = ServerConnection::new(server_config()).unwrap();
+            let mut stream = AmsStream::new(StreamOwned::new(conn, socket));
+            while let Ok(frame) = stream.read_frame() {
+                stream.write_frame(&frame).unwrap();
+            }
+        });
+
+        addr
+    }
+
+    #[test]
+    fn frames_round_trip_over_split_stream() {
+        let addr = spawn_echo_server();
+        let stream = AmsStream::connect_tls(addr, &client_config()).unwrap();
+        let (mut reader, mut writer) = stream.try_split().unwrap();
+
+        let writes = thread::spawn(move || {
+            for i in 0..50u8 {
+                let frame = AmsFrame::new(AmsCommand::AdsCommand, vec![i; 5000]);
+                writer.write_frame(&frame).unwrap();
+            }
+        });
+        for i in 0..50u8 {
+            let frame = reader.read_frame().unwrap();
+            assert_eq!(frame.payload(), vec![i; 5000].as_slice());
+        }
+        writes.join().unwrap();
+    }
+
+    #[test]
+    fn pinned_certificate_ignores_server_name() {
+        let addr = spawn_echo_server();
+        let config = TlsConfig::self_signed(SERVER_CERT)
+            .unwrap()
+            .with_client_cert(CLIENT_CERT, CLIENT_KEY)
+            .unwrap()
+            .with_server_name("plc.local")
+            .unwrap();
+        let mut stream = AmsStream::connect_tls(addr, &config).unwrap();
+
+        let frame = AmsFrame::new(AmsCommand::PortConnect, [0x00, 0x00]);
+        stream.write_frame(&frame).unwrap();
+        assert_eq!(stream.read_frame().unwrap().payload(), frame.payload());
+    }
+
+    /// Accepts one TLS-PSK connection and echoes every frame back until the client
+    /// disconnects.
+    #[cfg(feature = "tls-psk")]
+    fn spawn_psk_echo_server() -> SocketAddr {
+        use crate::io::tls::tests::psk_server_context;
+        use openssl::ssl::Ssl;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let (socket, _) = listener.accept().unwrap();
+            let Ok(stream) = Ssl::new(&psk_server_context()).unwrap().accept(socket) else {
+                return;
+            };
+            let mut stream = AmsStream::new(stream);
+            while let Ok(frame) = stream.read_frame() {
+                stream.write_frame(&frame).unwrap();
+            }
+        });
+
+        addr
+    }
+
+    #[test]
+    #[cfg(feature = "tls-psk")]
+    fn psk_frames_round_trip_over_split_stream() {
+        use crate::io::tls::tests::{PSK_IDENTITY, PSK_KEY};
+
+        let addr = spawn_psk_echo_server();
+        let config = TlsConfig::psk(PSK_IDENTITY, PSK_KEY);
+        let stream = AmsStream::connect_tls(addr, &config).unwrap();
+        let (mut reader, mut writer) = stream.try_split().unwrap();
+
+        let writes = thread::spawn(move || {
+            for i in 0..50u8 {
+                let frame = AmsFrame::new(AmsCommand::AdsCommand, vec![i; 5000]);
+                writer.write_frame(&frame).unwrap();
+            }
+        });
+        for i in 0..50u8 {
+            let frame = reader.read_frame().unwrap();
+            assert_eq!(frame.payload(), vec![i; 5000].as_slice());
+        }
+        writes.join().unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "tls-psk")]
+    fn psk_rejects_unknown_identity() {
+        let addr = spawn_psk_echo_server();
+        let config = TlsConfig::psk("other", crate::io::tls::tests::PSK_KEY);
+        assert!(AmsStream::connect_tls(addr, &config).is_err());
+    }
+
+    #[test]
+    fn rejects_certificate_for_other_name() {
+        let addr = spawn_echo_server();
+        let config = client_config().with_server_name("plc.local").unwrap();
+        assert!(AmsStream::connect_tls(addr, &config).is_err());
+
+        let addr = spawn_echo_server();
+        let config = client_config()
+            .with_server_name("plc.local")
+            .unwrap()
+            .ignore_server_name();
+        assert!(AmsStream::connect_tls(addr, &config).is_ok());
+    }
+}
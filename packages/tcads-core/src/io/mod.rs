@@ -1,5 +1,9 @@
 pub mod blocking;
 pub mod frame;
+#[cfg(feature = "tls")]
+pub mod tls;
 pub mod tokio;
 
 pub use frame::AmsFrame;
+#[cfg(feature = "tls")]
+pub use tls::TlsConfig;
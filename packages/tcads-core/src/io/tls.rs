@@ -0,0 +1,480 @@
+#[cfg(feature = "tls-psk")]
+use openssl::ssl::{Ssl, SslContext, SslMethod, SslVersion};
+use rustls::client::WebPkiServerVerifier;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{CryptoProvider, ring, verify_tls12_signature, verify_tls13_signature};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{
+    CertificateError, ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+use std::fmt;
+use std::io;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+/// Configuration of a Secure ADS connection, AMS/TCP over TLS.
+///
+/// TwinCAT routers accept three kinds of Secure ADS connections, each with its own
+/// constructor:
+///
+/// * [`shared_ca`](Self::shared_ca): both sides hold certificates issued by a common,
+///   typically self-signed, certificate authority.
+/// * [`self_signed`](Self::self_signed): the router presents a self-signed certificate,
+///   which is trusted as is.
+/// * [`psk`](Self::psk): both sides know a pre-shared key. TwinCAT negotiates these with
+///   the TLS 1.2 `ECDHE_PSK` cipher suites, which rustls does not implement, so they are
+///   made with OpenSSL and need the `tls-psk` feature.
+///
+/// Certificate-based connections can also present a client certificate with
+/// [`with_client_cert`](Self::with_client_cert), which routers require unless they
+/// allow anonymous Secure ADS clients.
+///
+/// # Example
+///
+/// ```no_run
+/// use tcads_core::io::TlsConfig;
+///
+/// let config = TlsConfig::shared_ca(std::fs::read("ca.pem")?)?
+///     .with_client_cert(std::fs::read("client.pem")?, std::fs::read("client.key")?)?
+///     .ignore_server_name();
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    trust: TlsTrust,
+    client_cert: Option<ClientCert>,
+    server_name: Option<ServerName<'static>>,
+    verify_server_name: bool,
+}
+
+#[derive(Clone)]
+enum TlsTrust {
+    SharedCa(Vec<CertificateDer<'static>>),
+    SelfSigned(Vec<CertificateDer<'static>>),
+    #[cfg(feature = "tls-psk")]
+    Psk {
+        identity: String,
+        key: Vec<u8>,
+    },
+}
+
+impl fmt::Debug for TlsTrust {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SharedCa(cas) => f.debug_tuple("SharedCa").field(&cas.len()).finish(),
+            Self::SelfSigned(certs) => f.debug_tuple("SelfSigned").field(&certs.len()).finish(),
+            #[cfg(feature = "tls-psk")]
+            Self::Psk { identity, .. } => {
+                f.debug_struct("Psk").field("identity", identity).finish()
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ClientCert {
+    chain: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+}
+
+impl Clone for ClientCert {
+    fn clone(&self) -> Self {
+        Self {
+            chain: self.chain.clone(),
+            key: self.key.clone_key(),
+        }
+    }
+}
+
+impl TlsConfig {
+    /// Trusts routers whose certificate is issued by one of the PEM encoded certificate
+    /// authorities in `ca_pem`.
+    pub fn shared_ca(ca_pem: impl AsRef<[u8]>) -> io::Result<Self> {
+        Ok(Self::new(TlsTrust::SharedCa(parse_certs(ca_pem.as_ref())?)))
+    }
+
+    /// Trusts routers presenting exactly one of the PEM encoded certificates in `cert_pem`.
+    ///
+    /// The certificates are pinned: they need not be issued by a known authority and the
+    /// server name is not checked.
+    pub fn self_signed(cert_pem: impl AsRef<[u8]>) -> io::Result<Self> {
+        let mut config = Self::new(TlsTrust::SelfSigned(parse_certs(cert_pem.as_ref())?));
+        config.verify_server_name = false;
+        Ok(config)
+    }
+
+    /// Authenticates both sides with the pre-shared `key` of `identity`, as configured on
+    /// the router.
+    ///
+    /// The connection is made with OpenSSL and restricted to TLS 1.2 PSK cipher suites, so
+    /// no certificates are exchanged and client certificates and server names are ignored.
+    #[cfg(feature = "tls-psk")]
+    pub fn psk(identity: impl Into<String>, key: impl Into<Vec<u8>>) -> Self {
+        Self::new(TlsTrust::Psk {
+            identity: identity.into(),
+            key: key.into(),
+        })
+    }
+
+    /// Presents the PEM encoded certificate chain `cert_pem`, signed with the PEM encoded
+    /// private key `key_pem`, to routers asking for a client certificate.
+    pub fn with_client_cert(
+        mut self,
+        cert_pem: impl AsRef<[u8]>,
+        key_pem: impl AsRef<[u8]>,
+    ) -> io::Result<Self> {
+        let chain = parse_certs(cert_pem.as_ref())?;
+        let key = PrivateKeyDer::from_pem_slice(key_pem.as_ref()).map_err(invalid_data)?;
+        self.client_cert = Some(ClientCert { chain, key });
+        Ok(self)
+    }
+
+    /// Sets the name the router's certificate is checked against.
+    ///
+    /// Defaults to the IP address connected to.
+    pub fn with_server_name(mut self, name: impl Into<String>) -> io::Result<Self> {
+        let name = ServerName::try_from(name.into()).map_err(invalid_input)?;
+        self.server_name = Some(name);
+        Ok(self)
+    }
+
+    /// Accepts router certificates issued for any name, like the `IgnoreCN` option of
+    /// TwinCAT routes.
+    ///
+    /// TwinCAT certificates are usually issued for a net ID or device name rather than the
+    /// address routers are reached at.
+    pub fn ignore_server_name(mut self) -> Self {
+        self.verify_server_name = false;
+        self
+    }
+
+    /// Returns the name the router's certificate is checked against when connecting to `ip`.
+    pub fn server_name(&self, ip: IpAddr) -> ServerName<'static> {
+        self.server_name
+            .clone()
+            .unwrap_or_else(|| ServerName::IpAddress(ip.into()))
+    }
+
+    /// Builds the rustls client configuration.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidInput`] for a [`psk`](Self::psk) configuration,
+    /// which is served by [`psk_session`](Self::psk_session) instead.
+    pub fn client_config(&self) -> io::Result<Arc<ClientConfig>> {
+        let provider = Arc::new(ring::default_provider());
+        let verifier: Arc<dyn ServerCertVerifier> = match &self.trust {
+            TlsTrust::SharedCa(cas) => {
+                let mut roots = RootCertStore::empty();
+                for ca in cas {
+                    roots.add(ca.clone()).map_err(invalid_data)?;
+                }
+                let verifier =
+                    WebPkiServerVerifier::builder_with_provider(roots.into(), provider.clone())
+                        .build()
+                        .map_err(invalid_data)?;
+                Arc::new(SharedCaVerifier {
+                    verifier,
+                    verify_server_name: self.verify_server_name,
+                })
+            }
+            TlsTrust::SelfSigned(certs) => Arc::new(PinnedVerifier {
+                certs: certs.clone(),
+                provider: provider.clone(),
+            }),
+            #[cfg(feature = "tls-psk")]
+            TlsTrust::Psk { .. } => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "PSK connections are made with OpenSSL",
+                ));
+            }
+        };
+
+        let builder = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(invalid_input)?
+            .dangerous()
+            .with_custom_certificate_verifier(verifier);
+        let config = match &self.client_cert {
+            Some(cert) => builder
+                .with_client_auth_cert(cert.chain.clone(), cert.key.clone_key())
+                .map_err(invalid_input)?,
+            None => builder.with_no_client_auth(),
+        };
+        Ok(Arc::new(config))
+    }
+
+    /// Builds the OpenSSL session of a [`psk`](Self::psk) configuration, or returns `None`
+    /// for a certificate-based one.
+    #[cfg(feature = "tls-psk")]
+    pub fn psk_session(&self) -> io::Result<Option<Ssl>> {
+        let TlsTrust::Psk { identity, key } = &self.trust else {
+            return Ok(None);
+        };
+
+        let mut builder = SslContext::builder(SslMethod::tls_client()).map_err(invalid_input)?;
+        // No certificates are verified, so only suites authenticated by the PSK alone are
+        // offered. TLS 1.3 could fall back to certificates if the router offered no PSK.
+        builder
+            .set_min_proto_version(Some(SslVersion::TLS1_2))
+            .map_err(invalid_input)?;
+        builder
+            .set_max_proto_version(Some(SslVersion::TLS1_2))
+            .map_err(invalid_input)?;
+        builder.set_cipher_list("aPSK").map_err(invalid_input)?;
+
+        let (identity, key) = (identity.clone(), key.clone());
+        builder.set_psk_client_callback(move |_, _, identity_out, key_out| {
+            // The identity is written as a NUL terminated string
+            if identity.len() >= identity_out.len() || key.len() > key_out.len() {
+                return Err(openssl::error::ErrorStack::get());
+            }
+            identity_out[..identity.len()].copy_from_slice(identity.as_bytes());
+            identity_out[identity.len()] = 0;
+            key_out[..key.len()].copy_from_slice(&key);
+            Ok(key.len())
+        });
+
+        Ssl::new(&builder.build()).map(Some).map_err(invalid_input)
+    }
+
+    fn new(trust: TlsTrust) -> Self {
+        Self {
+            trust,
+            client_cert: None,
+            server_name: None,
+            verify_server_name: true,
+        }
+    }
+}
+
+/// Verifies the chain against the shared CA, optionally ignoring the server name.
+#[derive(Debug)]
+struct SharedCaVerifier {
+    verifier: Arc<WebPkiServerVerifier>,
+    verify_server_name: bool,
+}
+
+impl ServerCertVerifier for SharedCaVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let result = self.verifier.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        );
+        match result {
+            Err(rustls::Error::InvalidCertificate(
+                CertificateError::NotValidForName | CertificateError::NotValidForNameContext { .. },
+            )) if !self.verify_server_name => Ok(ServerCertVerified::assertion()),
+            result => result,
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.verifier.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.verifier.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.verifier.supported_verify_schemes()
+    }
+}
+
+/// Accepts exactly the pinned certificates.
+struct PinnedVerifier {
+    certs: Vec<CertificateDer<'static>>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl fmt::Debug for PinnedVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PinnedVerifier")
+            .field("certs", &self.certs.len())
+            .finish()
+    }
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        match self.certs.iter().any(|cert| cert == end_entity) {
+            true => Ok(ServerCertVerified::assertion()),
+            false => Err(rustls::Error::InvalidCertificate(
+                CertificateError::UnknownIssuer,
+            )),
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        let algorithms = &self.provider.signature_verification_algorithms;
+        verify_tls12_signature(message, cert, dss, algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        let algorithms = &self.provider.signature_verification_algorithms;
+        verify_tls13_signature(message, cert, dss, algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+fn parse_certs(pem: &[u8]) -> io::Result<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_slice_iter(pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(invalid_data)?;
+    if certs.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "no certificates found in PEM data",
+        ));
+    }
+    Ok(certs)
+}
+
+pub(crate) fn invalid_data(error: impl std::error::Error + Send + Sync + 'static) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+fn invalid_input(error: impl std::error::Error + Send + Sync + 'static) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, error)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use rustls::ServerConfig;
+    use rustls::server::WebPkiClientVerifier;
+
+    pub(crate) const CA: &[u8] = include_bytes!("testdata/ca.pem");
+    pub(crate) const SERVER_CERT: &[u8] = include_bytes!("testdata/server.pem");
+    pub(crate) const SERVER_KEY: &[u8] = include_bytes!("testdata/server.key");
+    pub(crate) const CLIENT_CERT: &[u8] = include_bytes!("testdata/client.pem");
+    pub(crate) const CLIENT_KEY: &[u8] = include_bytes!("testdata/client.key");
+
+    /// A server configuration requiring clients to present a certificate issued by the test CA.
+    pub(crate) fn server_config() -> Arc<ServerConfig> {
+        let provider = Arc::new(ring::default_provider());
+        let mut roots = RootCertStore::empty();
+        roots.add_parsable_certificates(parse_certs(CA).unwrap());
+        let client_verifier =
+            WebPkiClientVerifier::builder_with_provider(roots.into(), provider.clone())
+                .build()
+                .unwrap();
+
+        let config = ServerConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_client_cert_verifier(client_verifier)
+            .with_single_cert(
+                parse_certs(SERVER_CERT).unwrap(),
+                PrivateKeyDer::from_pem_slice(SERVER_KEY).unwrap(),
+            )
+            .unwrap();
+        Arc::new(config)
+    }
+
+    #[cfg(feature = "tls-psk")]
+    pub(crate) const PSK_IDENTITY: &str = "plc";
+    #[cfg(feature = "tls-psk")]
+    pub(crate) const PSK_KEY: &[u8] = b"0123456789abcdef0123456789abcdef";
+
+    /// A server context negotiating `ECDHE_PSK` cipher suites with the PSK of
+    /// [`PSK_IDENTITY`], as TwinCAT routers do.
+    #[cfg(feature = "tls-psk")]
+    pub(crate) fn psk_server_context() -> SslContext {
+        let mut builder = SslContext::builder(SslMethod::tls_server()).unwrap();
+        builder
+            .set_max_proto_version(Some(SslVersion::TLS1_2))
+            .unwrap();
+        builder.set_cipher_list("kECDHEPSK").unwrap();
+        builder.set_psk_server_callback(|_, identity, key_out| {
+            if identity != Some(PSK_IDENTITY.as_bytes()) {
+                return Err(openssl::error::ErrorStack::get());
+            }
+            key_out[..PSK_KEY.len()].copy_from_slice(PSK_KEY);
+            Ok(PSK_KEY.len())
+        });
+        builder.build()
+    }
+
+    /// A client configuration trusting the test CA and presenting the test client certificate.
+    pub(crate) fn client_config() -> TlsConfig {
+        TlsConfig::shared_ca(CA)
+            .unwrap()
+            .with_client_cert(CLIENT_CERT, CLIENT_KEY)
+            .unwrap()
+    }
+
+    #[test]
+    fn rejects_pem_without_certificates() {
+        let err = TlsConfig::shared_ca(SERVER_KEY).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    #[cfg(feature = "tls-psk")]
+    fn psk_is_served_by_openssl() {
+        let config = TlsConfig::psk(PSK_IDENTITY, PSK_KEY);
+        assert!(!format!("{config:?}").contains("0123456789abcdef"));
+        assert_eq!(
+            config.client_config().unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+        assert!(config.psk_session().unwrap().is_some());
+        assert!(client_config().psk_session().unwrap().is_none());
+    }
+
+    #[test]
+    fn server_name_defaults_to_ip() {
+        let ip = IpAddr::from([127, 0, 0, 1]);
+        let config = client_config();
+        assert_eq!(config.server_name(ip), ServerName::IpAddress(ip.into()));
+        let config = config.with_server_name("plc.local").unwrap();
+        assert_eq!(
+            config.server_name(ip),
+            ServerName::try_from("plc.local").unwrap()
+        );
+    }
+}
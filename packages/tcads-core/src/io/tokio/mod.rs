@@ -1,8 +1,12 @@
 pub mod reader;
 pub mod stream;
+#[cfg(feature = "tls")]
+pub mod tls;
 mod traits;
 pub mod writer;
 
 pub use reader::AmsReader;
 pub use stream::AmsStream;
+#[cfg(feature = "tls")]
+pub use tls::TlsStream;
 pub use writer::AmsWriter;
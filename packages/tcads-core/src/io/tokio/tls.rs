@@ -0,0 +1,263 @@
+use super::reader::AmsReader;
+use super::stream::AmsStream;
+use super::writer::AmsWriter;
+use crate::io::tls::TlsConfig;
+#[cfg(feature = "tls-psk")]
+use crate::io::tls::invalid_data;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{self, AsyncRead, AsyncWrite, AsyncWriteExt, ReadBuf, ReadHalf, WriteHalf};
+use tokio::net::{self, TcpStream};
+use tokio::time::{self, Duration};
+use tokio_rustls::TlsConnector;
+
+/// A TLS client stream over a [`TcpStream`].
+#[derive(Debug)]
+pub struct TlsStream(Session);
+
+#[derive(Debug)]
+enum Session {
+    Rustls(Box<tokio_rustls::client::TlsStream<TcpStream>>),
+    /// A [PSK](TlsConfig::psk) connection, which rustls does not support.
+    #[cfg(feature = "tls-psk")]
+    Psk(tokio_openssl::SslStream<TcpStream>),
+}
+
+impl TlsStream {
+    /// Performs the TLS handshake over `socket`.
+    pub async fn connect(socket: TcpStream, config: &TlsConfig) -> io::Result<Self> {
+        #[cfg(feature = "tls-psk")]
+        if let Some(ssl) = config.psk_session()? {
+            let mut stream = tokio_openssl::SslStream::new(ssl, socket).map_err(invalid_data)?;
+            Pin::new(&mut stream)
+                .connect()
+                .await
+                .map_err(|e| e.into_io_error().unwrap_or_else(invalid_data))?;
+            return Ok(Self(Session::Psk(stream)));
+        }
+
+        let server_name = config.server_name(socket.peer_addr()?.ip());
+        let connector = TlsConnector::from(config.client_config()?);
+        let stream = connector.connect(server_name, socket).await?;
+        Ok(Self(Session::Rustls(Box::new(stream))))
+    }
+
+    /// Returns the underlying socket.
+    pub fn socket(&self) -> &TcpStream {
+        match &self.0 {
+            Session::Rustls(stream) => stream.get_ref().0,
+            #[cfg(feature = "tls-psk")]
+            Session::Psk(stream) => stream.get_ref(),
+        }
+    }
+}
+
+impl AsyncRead for TlsStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match &mut self.get_mut().0 {
+            Session::Rustls(stream) => Pin::new(stream.as_mut()).poll_read(cx, buf),
+            #[cfg(feature = "tls-psk")]
+            Session::Psk(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for TlsStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match &mut self.get_mut().0 {
+            Session::Rustls(stream) => Pin::new(stream.as_mut()).poll_write(cx, buf),
+            #[cfg(feature = "tls-psk")]
+            Session::Psk(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut self.get_mut().0 {
+            Session::Rustls(stream) => Pin::new(stream.as_mut()).poll_flush(cx),
+            #[cfg(feature = "tls-psk")]
+            Session::Psk(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut self.get_mut().0 {
+            Session::Rustls(stream) => Pin::new(stream.as_mut()).poll_shutdown(cx),
+            #[cfg(feature = "tls-psk")]
+            Session::Psk(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
+impl AmsStream<TlsStream> {
+    /// Connects to a Secure ADS router at the specified address.
+    ///
+    /// Establishes a [`TcpStream`] connection with Nagle's algorithm disabled, as
+    /// [`connect`](AmsStream::connect) does, then performs the TLS handshake described by
+    /// `config`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use tcads_core::io::TlsConfig;
+    /// use tcads_core::io::tokio::AmsStream;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = TlsConfig::self_signed(std::fs::read("plc.pem")?)?;
+    /// let stream = AmsStream::connect_tls("192.168.0.20:8016", &config).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn connect_tls<A: net::ToSocketAddrs>(
+        addr: A,
+        config: &TlsConfig,
+    ) -> io::Result<Self> {
+        let socket = TcpStream::connect(addr).await?;
+        socket.set_nodelay(true)?;
+        Ok(Self::new(TlsStream::connect(socket, config).await?))
+    }
+
+    /// Connects to a Secure ADS router at a specified address with a timeout.
+    ///
+    /// The timeout applies to the TCP connection and TLS handshake together.
+    pub async fn connect_tls_timeout(
+        addr: &SocketAddr,
+        config: &TlsConfig,
+        timeout: Duration,
+    ) -> io::Result<Self> {
+        if timeout.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot set a 0 duration timeout",
+            ));
+        }
+
+        time::timeout(timeout, Self::connect_tls(addr, config))
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "timeout"))?
+    }
+
+    /// Splits the `TlsStream` into a buffered Reader and buffered Writer.
+    ///
+    /// The TLS state cannot be split, so both halves share it through [`tokio::io::split`].
+    pub fn into_split(
+        self,
+    ) -> (
+        AmsReader<ReadHalf<TlsStream>>,
+        AmsWriter<WriteHalf<TlsStream>>,
+    ) {
+        self.split()
+    }
+
+    /// Returns the socket address of the remote peer of this connection.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.get_ref().socket().peer_addr()
+    }
+
+    /// Returns the socket address of the local half of this connection.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.get_ref().socket().local_addr()
+    }
+
+    /// Sends a TLS `close_notify` alert and shuts down the output stream.
+    pub async fn shutdown(&mut self) -> io::Result<()> {
+        self.get_mut().shutdown().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ams::AmsCommand;
+    use crate::io::frame::AmsFrame;
+    use crate::io::tls::tests::{client_config, server_config};
+    use tokio::net::TcpListener;
+    use tokio_rustls::TlsAcceptor;
+
+    #[tokio::test]
+    async fn frames_round_trip_over_split_stream() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let tls = TlsAcceptor::from(server_config())
+                .accept(socket)
+                .await
+                .unwrap();
+            let mut stream = AmsStream::new(tls);
+            while let Ok(frame) = stream.read_frame().await {
+                stream.write_frame(&frame).await.unwrap();
+            }
+        });
+
+        let stream = AmsStream::connect_tls(addr, &client_config())
+            .await
+            .unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+        let (mut reader, mut writer) = stream.into_split();
+        for i in 0..10u8 {
+            let frame = AmsFrame::new(AmsCommand::AdsCommand, vec![i; 5000]);
+            writer.write_frame(&frame).await.unwrap();
+            assert_eq!(
+                reader.read_frame().await.unwrap().payload(),
+                frame.payload()
+            );
+        }
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tls-psk")]
+    async fn psk_frames_round_trip_over_split_stream() {
+        use crate::io::tls::tests::{PSK_IDENTITY, PSK_KEY, psk_server_context};
+        use openssl::ssl::Ssl;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let ssl = Ssl::new(&psk_server_context()).unwrap();
+            let mut tls = tokio_openssl::SslStream::new(ssl, socket).unwrap();
+            Pin::new(&mut tls).accept().await.unwrap();
+            let mut stream = AmsStream::new(tls);
+            while let Ok(frame) = stream.read_frame().await {
+                stream.write_frame(&frame).await.unwrap();
+            }
+        });
+
+        let config = TlsConfig::psk(PSK_IDENTITY, PSK_KEY);
+        let stream = AmsStream::connect_tls(addr, &config).await.unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+        let (mut reader, mut writer) = stream.into_split();
+        for i in 0..10u8 {
+            let frame = AmsFrame::new(AmsCommand::AdsCommand, vec![i; 5000]);
+            writer.write_frame(&frame).await.unwrap();
+            assert_eq!(
+                reader.read_frame().await.unwrap().payload(),
+                frame.payload()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_untrusted_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let _ = TlsAcceptor::from(server_config()).accept(socket).await;
+        });
+
+        let config = TlsConfig::self_signed(crate::io::tls::tests::CA).unwrap();
+        let result = AmsStream::connect_tls(addr, &config).await;
+        assert!(result.is_err_and(|err| err.kind() == io::ErrorKind::InvalidData));
+    }
+}
@@ -110,8 +110,8 @@
 ///
 /// This module contains the fundamental enums and constants defined by the Beckhoff
 /// specification, including [`AdsState`], [`AdsReturnCode`], and [`AdsTransMode`].
-/// It also provides helper types for ADS-specific data like [`AdsString`](ads::AdsString) and
-/// [`WindowsFileTime`].
+/// It also provides helper types for ADS-specific data like [`AdsString`](ads::AdsString),
+/// [`AdsWString`](ads::AdsWString) and [`WindowsFileTime`].
 pub mod ads;
 
 /// AMS layer addressing and router management.
@@ -124,7 +124,8 @@ pub mod ams;
 /// Frame I/O and transport implementations.
 ///
 /// Defines the [`AmsFrame`] for all wire communication and provides concrete `AmsStream` implementations
-/// for both [blocking](io::blocking::AmsStream) and [tokio-based](io::tokio::AmsStream) async I/O.
+/// for both [blocking](io::blocking::AmsStream) and [tokio-based](io::tokio::AmsStream) async I/O,
+/// over plain TCP or, with the `tls` feature, Secure ADS.
 pub mod io;
 
 /// High-level, type-safe Request and Response definitions.
@@ -135,9 +136,27 @@ pub mod io;
 /// to allow for zero-copy parsing directly from the wire.
 pub mod protocol;
 
+/// Symbol and data type information.
+///
+/// Wire-format types for the PLC symbol table ([`AdsSymbolEntry`](symbol::AdsSymbolEntry))
+/// and data type table ([`AdsDatatypeEntry`](symbol::AdsDatatypeEntry)), as uploaded
+/// from the reserved [symbol index groups](ads::index_group).
+pub mod symbol;
+
+/// AMS/UDP messages, sent without an AMS route.
+///
+/// Wire-format types for [discovering](udp::DiscoverRequest) TwinCAT systems on a network
+/// by broadcasting to [`ADS_UDP_PORT`](udp::ADS_UDP_PORT), and for
+/// [adding](udp::AddRouteRequest) and [deleting](udp::DeleteRouteRequest) their routes.
+pub mod udp;
+
+// Lets `#[derive(AdsType)]` refer to `::tcads_core` from inside this crate.
+extern crate self as tcads_core;
+
 pub use ads::{
-    AdsCommand, AdsDeviceVersion, AdsError, AdsHeader, AdsReturnCode, AdsState, AdsTransMode,
-    DeviceState, IndexGroup, IndexOffset, InvokeId, NotificationHandle, WindowsFileTime,
+    AdsCommand, AdsDecode, AdsDeviceVersion, AdsEncode, AdsError, AdsHeader, AdsReturnCode,
+    AdsState, AdsTransMode, AdsType, DeviceState, IndexGroup, IndexOffset, InvokeId,
+    NotificationHandle, WindowsFileTime,
 };
 pub use ams::{AmsAddr, AmsCommand, AmsNetId, AmsPort, AmsTcpHeader, RouterState};
 pub use io::AmsFrame;
@@ -12,6 +12,7 @@ pub mod get_local_net_id;
 pub mod port_close;
 pub mod port_connect;
 pub mod router_notification;
+pub mod sum_command;
 pub mod utils;
 
 pub use ads_add_device_notification::{
@@ -39,4 +40,8 @@ pub use get_local_net_id::{GetLocalNetIdRequest, GetLocalNetIdResponse};
 pub use port_close::PortCloseRequest;
 pub use port_connect::{PortConnectRequest, PortConnectResponse};
 pub use router_notification::{RouterNotification, RouterState};
+pub use sum_command::{
+    SumAddNotificationItem, SumDeleteNotificationItem, SumReadItem, SumReadWriteItem, SumRequest,
+    SumResult, SumWriteItem,
+};
 pub use utils::{parse_ads_frame, validate_ads_command, validate_ams_command};
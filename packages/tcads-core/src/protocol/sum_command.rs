@@ -0,0 +1,867 @@
+//! ADS sum commands.
+//!
+//! A sum command bundles several reads, writes, read/writes or notification requests into a
+//! single [`AdsReadWriteRequest`](super::AdsReadWriteRequest). The sub-requests are concatenated
+//! into the write data, the Index Offset carries the number of sub-requests, and the
+//! response carries one return code per sub-request followed by the concatenated data.
+//!
+//! | Index Group                                           | Write Data                                          | Read Data                                         |
+//! |-------------------------------------------------------|-----------------------------------------------------|---------------------------------------------------|
+//! | [`SUMUP_READ`](index_group::SUMUP_READ)               | n × (group, offset, length)                         | n × result, then n × data (requested lengths)     |
+//! | [`SUMUP_WRITE`](index_group::SUMUP_WRITE)             | n × (group, offset, length), then n × data          | n × result                                        |
+//! | [`SUMUP_READWRITE`](index_group::SUMUP_READWRITE)     | n × (group, offset, read length, write length), then n × data | n × (result, length), then n × data (actual lengths) |
+//! | [`SUMUP_ADDDEVNOTE`](index_group::SUMUP_ADDDEVNOTE)   | n × add notification payload                        | n × (result, handle)                              |
+//! | [`SUMUP_DELDEVNOTE`](index_group::SUMUP_DELDEVNOTE)   | n × handle                                          | n × result                                        |
+//!
+//! Each item type offers both sides of the exchange: `encode_request` and `parse_response`
+//! for clients, `parse_request` and `encode_response` for servers.
+
+use super::{
+    AdsAddDeviceNotificationRequest, AdsAddDeviceNotificationResponse,
+    AdsDeleteDeviceNotificationRequest, AdsReadWriteRequestOwned, ProtocolError,
+};
+use crate::ads::{
+    AdsReturnCode, AdsTransMode, IndexGroup, IndexOffset, InvokeId, NotificationHandle, index_group,
+};
+use crate::ams::AmsAddr;
+
+/// The outcome of a single sub-request in a sum command.
+pub type SumResult = Result<Vec<u8>, AdsReturnCode>;
+
+/// The parameters of the [`AdsReadWriteRequest`](super::AdsReadWriteRequest) that carries
+/// a sum command.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SumRequest {
+    index_group: IndexGroup,
+    count: u32,
+    read_length: u32,
+    data: Vec<u8>,
+}
+
+impl SumRequest {
+    /// Returns the sum command index group.
+    pub fn index_group(&self) -> IndexGroup {
+        self.index_group
+    }
+
+    /// Returns the number of sub-requests, sent as the Index Offset.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Returns the number of bytes expected in the response.
+    pub fn read_length(&self) -> u32 {
+        self.read_length
+    }
+
+    /// Returns the concatenated sub-requests.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consumes the request and returns the concatenated sub-requests.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Wraps the sum command in a Read/Write Request.
+    pub fn into_request(
+        self,
+        target: AmsAddr,
+        source: AmsAddr,
+        invoke_id: InvokeId,
+    ) -> AdsReadWriteRequestOwned {
+        AdsReadWriteRequestOwned::new(
+            target,
+            source,
+            invoke_id,
+            self.index_group,
+            self.count,
+            self.read_length,
+            self.data,
+        )
+    }
+}
+
+/// A single read inside a [`SUMUP_READ`](index_group::SUMUP_READ) command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SumReadItem {
+    index_group: IndexGroup,
+    index_offset: IndexOffset,
+    length: u32,
+}
+
+impl SumReadItem {
+    /// The size of a sub-request header (Index Group + Index Offset + Length).
+    pub const HEADER_SIZE: usize = 12;
+
+    /// Creates a read of `length` bytes.
+    pub fn new(index_group: IndexGroup, index_offset: IndexOffset, length: u32) -> Self {
+        Self {
+            index_group,
+            index_offset,
+            length,
+        }
+    }
+
+    /// Returns the index group.
+    pub fn index_group(&self) -> IndexGroup {
+        self.index_group
+    }
+
+    /// Returns the index offset.
+    pub fn index_offset(&self) -> IndexOffset {
+        self.index_offset
+    }
+
+    /// Returns the number of bytes to read.
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+
+    /// Returns the number of bytes this item adds to the request.
+    pub fn request_len(&self) -> usize {
+        Self::HEADER_SIZE
+    }
+
+    /// Returns the number of bytes this item adds to the response.
+    pub fn response_len(&self) -> usize {
+        AdsReturnCode::LENGTH + self.length as usize
+    }
+
+    /// Encodes `items` into a sum read request.
+    pub fn encode_request(items: &[Self]) -> SumRequest {
+        let mut data = Vec::with_capacity(items.len() * Self::HEADER_SIZE);
+        for item in items {
+            put_u32(&mut data, item.index_group);
+            put_u32(&mut data, item.index_offset);
+            put_u32(&mut data, item.length);
+        }
+
+        SumRequest {
+            index_group: index_group::SUMUP_READ,
+            count: items.len() as u32,
+            read_length: items.iter().map(Self::response_len).sum::<usize>() as u32,
+            data,
+        }
+    }
+
+    /// Splits a sum read response into one result per item in `items`.
+    pub fn parse_response(items: &[Self], data: &[u8]) -> Result<Vec<SumResult>, ProtocolError> {
+        let (codes, mut data) = split_headers(data, items.len(), AdsReturnCode::LENGTH)?;
+
+        items
+            .iter()
+            .zip(codes.chunks_exact(AdsReturnCode::LENGTH))
+            .map(|(item, code)| {
+                let value = take(&mut data, item.length as usize)?;
+                Ok(match parse_code(code) {
+                    AdsReturnCode::Ok => Ok(value.to_vec()),
+                    code => Err(code),
+                })
+            })
+            .collect()
+    }
+
+    /// Parses the write data of a sum read request carrying `count` items.
+    pub fn parse_request(count: u32, data: &[u8]) -> Result<Vec<Self>, ProtocolError> {
+        let (headers, _) = split_headers(data, count as usize, Self::HEADER_SIZE)?;
+
+        Ok(headers
+            .chunks_exact(Self::HEADER_SIZE)
+            .map(|h| Self::new(read_u32(h, 0), read_u32(h, 4), read_u32(h, 8)))
+            .collect())
+    }
+
+    /// Encodes the response to a sum read request.
+    ///
+    /// Each successful value is truncated or zero-padded to the length requested by its
+    /// item; failed items occupy their full length as zeros.
+    pub fn encode_response(items: &[Self], results: &[SumResult]) -> Vec<u8> {
+        let len = items.iter().map(Self::response_len).sum();
+        let mut data = Vec::with_capacity(len);
+
+        for result in results {
+            put_code(&mut data, result);
+        }
+        for (item, result) in items.iter().zip(results) {
+            let start = data.len();
+            if let Ok(value) = result {
+                data.extend_from_slice(&value[..value.len().min(item.length as usize)]);
+            }
+            data.resize(start + item.length as usize, 0);
+        }
+
+        data
+    }
+}
+
+/// A single write inside a [`SUMUP_WRITE`](index_group::SUMUP_WRITE) command.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SumWriteItem {
+    index_group: IndexGroup,
+    index_offset: IndexOffset,
+    data: Vec<u8>,
+}
+
+impl SumWriteItem {
+    /// The size of a sub-request header (Index Group + Index Offset + Length).
+    pub const HEADER_SIZE: usize = 12;
+
+    /// Creates a write of `data`.
+    pub fn new(
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        data: impl Into<Vec<u8>>,
+    ) -> Self {
+        Self {
+            index_group,
+            index_offset,
+            data: data.into(),
+        }
+    }
+
+    /// Returns the index group.
+    pub fn index_group(&self) -> IndexGroup {
+        self.index_group
+    }
+
+    /// Returns the index offset.
+    pub fn index_offset(&self) -> IndexOffset {
+        self.index_offset
+    }
+
+    /// Returns the data to write.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the number of bytes this item adds to the request.
+    pub fn request_len(&self) -> usize {
+        Self::HEADER_SIZE + self.data.len()
+    }
+
+    /// Returns the number of bytes this item adds to the response.
+    pub fn response_len(&self) -> usize {
+        AdsReturnCode::LENGTH
+    }
+
+    /// Encodes `items` into a sum write request.
+    pub fn encode_request(items: &[Self]) -> SumRequest {
+        let mut data = Vec::with_capacity(items.iter().map(Self::request_len).sum());
+        for item in items {
+            put_u32(&mut data, item.index_group);
+            put_u32(&mut data, item.index_offset);
+            put_u32(&mut data, item.data.len() as u32);
+        }
+        for item in items {
+            data.extend_from_slice(&item.data);
+        }
+
+        SumRequest {
+            index_group: index_group::SUMUP_WRITE,
+            count: items.len() as u32,
+            read_length: (items.len() * AdsReturnCode::LENGTH) as u32,
+            data,
+        }
+    }
+
+    /// Splits a sum write response into one result per item in `items`.
+    ///
+    /// Successful writes yield an empty buffer.
+    pub fn parse_response(items: &[Self], data: &[u8]) -> Result<Vec<SumResult>, ProtocolError> {
+        let (codes, _) = split_headers(data, items.len(), AdsReturnCode::LENGTH)?;
+
+        Ok(codes
+            .chunks_exact(AdsReturnCode::LENGTH)
+            .map(|code| match parse_code(code) {
+                AdsReturnCode::Ok => Ok(Vec::new()),
+                code => Err(code),
+            })
+            .collect())
+    }
+
+    /// Parses the write data of a sum write request carrying `count` items.
+    pub fn parse_request(count: u32, data: &[u8]) -> Result<Vec<Self>, ProtocolError> {
+        let (headers, mut data) = split_headers(data, count as usize, Self::HEADER_SIZE)?;
+
+        headers
+            .chunks_exact(Self::HEADER_SIZE)
+            .map(|h| {
+                let value = take(&mut data, read_u32(h, 8) as usize)?;
+                Ok(Self::new(read_u32(h, 0), read_u32(h, 4), value))
+            })
+            .collect()
+    }
+
+    /// Encodes the response to a sum write request.
+    pub fn encode_response(results: &[Result<(), AdsReturnCode>]) -> Vec<u8> {
+        let mut data = Vec::with_capacity(results.len() * AdsReturnCode::LENGTH);
+        for result in results {
+            put_code(&mut data, result);
+        }
+        data
+    }
+}
+
+/// A single read/write inside a [`SUMUP_READWRITE`](index_group::SUMUP_READWRITE) command.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SumReadWriteItem {
+    index_group: IndexGroup,
+    index_offset: IndexOffset,
+    read_length: u32,
+    data: Vec<u8>,
+}
+
+impl SumReadWriteItem {
+    /// The size of a sub-request header (Index Group + Index Offset + Read Length + Write Length).
+    pub const HEADER_SIZE: usize = 16;
+
+    /// The size of a sub-response header (Result + Length).
+    pub const RESPONSE_HEADER_SIZE: usize = 8;
+
+    /// Creates a read/write that writes `data` and reads up to `read_length` bytes.
+    pub fn new(
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        read_length: u32,
+        data: impl Into<Vec<u8>>,
+    ) -> Self {
+        Self {
+            index_group,
+            index_offset,
+            read_length,
+            data: data.into(),
+        }
+    }
+
+    /// Returns the index group.
+    pub fn index_group(&self) -> IndexGroup {
+        self.index_group
+    }
+
+    /// Returns the index offset.
+    pub fn index_offset(&self) -> IndexOffset {
+        self.index_offset
+    }
+
+    /// Returns the maximum number of bytes to read.
+    pub fn read_length(&self) -> u32 {
+        self.read_length
+    }
+
+    /// Returns the data to write.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the number of bytes this item adds to the request.
+    pub fn request_len(&self) -> usize {
+        Self::HEADER_SIZE + self.data.len()
+    }
+
+    /// Returns the maximum number of bytes this item adds to the response.
+    pub fn response_len(&self) -> usize {
+        Self::RESPONSE_HEADER_SIZE + self.read_length as usize
+    }
+
+    /// Encodes `items` into a sum read/write request.
+    pub fn encode_request(items: &[Self]) -> SumRequest {
+        let mut data = Vec::with_capacity(items.iter().map(Self::request_len).sum());
+        for item in items {
+            put_u32(&mut data, item.index_group);
+            put_u32(&mut data, item.index_offset);
+            put_u32(&mut data, item.read_length);
+            put_u32(&mut data, item.data.len() as u32);
+        }
+        for item in items {
+            data.extend_from_slice(&item.data);
+        }
+
+        SumRequest {
+            index_group: index_group::SUMUP_READWRITE,
+            count: items.len() as u32,
+            read_length: items.iter().map(Self::response_len).sum::<usize>() as u32,
+            data,
+        }
+    }
+
+    /// Splits a sum read/write response into one result per item in `items`.
+    ///
+    /// Each value has the length reported by the device, which may be shorter than
+    /// the requested read length.
+    pub fn parse_response(items: &[Self], data: &[u8]) -> Result<Vec<SumResult>, ProtocolError> {
+        let (headers, mut data) = split_headers(data, items.len(), Self::RESPONSE_HEADER_SIZE)?;
+
+        headers
+            .chunks_exact(Self::RESPONSE_HEADER_SIZE)
+            .map(|h| {
+                let value = take(&mut data, read_u32(h, 4) as usize)?;
+                Ok(match parse_code(&h[..4]) {
+                    AdsReturnCode::Ok => Ok(value.to_vec()),
+                    code => Err(code),
+                })
+            })
+            .collect()
+    }
+
+    /// Parses the write data of a sum read/write request carrying `count` items.
+    pub fn parse_request(count: u32, data: &[u8]) -> Result<Vec<Self>, ProtocolError> {
+        let (headers, mut data) = split_headers(data, count as usize, Self::HEADER_SIZE)?;
+
+        headers
+            .chunks_exact(Self::HEADER_SIZE)
+            .map(|h| {
+                let value = take(&mut data, read_u32(h, 12) as usize)?;
+                Ok(Self::new(
+                    read_u32(h, 0),
+                    read_u32(h, 4),
+                    read_u32(h, 8),
+                    value,
+                ))
+            })
+            .collect()
+    }
+
+    /// Encodes the response to a sum read/write request.
+    ///
+    /// Each successful value is truncated to the read length requested by its item;
+    /// failed items carry no data.
+    pub fn encode_response(items: &[Self], results: &[SumResult]) -> Vec<u8> {
+        let values: Vec<&[u8]> = items
+            .iter()
+            .zip(results)
+            .map(|(item, result)| match result {
+                Ok(value) => &value[..value.len().min(item.read_length as usize)],
+                Err(_) => &[][..],
+            })
+            .collect();
+
+        let len = values
+            .iter()
+            .map(|v| Self::RESPONSE_HEADER_SIZE + v.len())
+            .sum();
+        let mut data = Vec::with_capacity(len);
+        for (result, value) in results.iter().zip(&values) {
+            put_code(&mut data, result);
+            put_u32(&mut data, value.len() as u32);
+        }
+        for value in values {
+            data.extend_from_slice(value);
+        }
+
+        data
+    }
+}
+
+/// A single notification inside a [`SUMUP_ADDDEVNOTE`](index_group::SUMUP_ADDDEVNOTE) command.
+///
+/// Each sub-request uses the payload layout of an [`AdsAddDeviceNotificationRequest`], and
+/// each sub-response the payload layout of an [`AdsAddDeviceNotificationResponse`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SumAddNotificationItem {
+    index_group: IndexGroup,
+    index_offset: IndexOffset,
+    length: u32,
+    trans_mode: AdsTransMode,
+    max_delay: u32,
+    cycle_time: u32,
+}
+
+impl SumAddNotificationItem {
+    /// The size of a sub-request.
+    pub const HEADER_SIZE: usize = AdsAddDeviceNotificationRequest::PAYLOAD_SIZE;
+
+    /// The size of a sub-response (Result + Handle).
+    pub const RESPONSE_SIZE: usize = AdsAddDeviceNotificationResponse::PAYLOAD_SIZE;
+
+    /// Creates a notification of `length` bytes.
+    ///
+    /// * `max_delay` - Maximum buffering delay in milliseconds.
+    /// * `cycle_time` - Cyclic check interval in milliseconds.
+    pub fn new(
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        length: u32,
+        trans_mode: AdsTransMode,
+        max_delay: u32,
+        cycle_time: u32,
+    ) -> Self {
+        Self {
+            index_group,
+            index_offset,
+            length,
+            trans_mode,
+            max_delay,
+            cycle_time,
+        }
+    }
+
+    /// Returns the index group.
+    pub fn index_group(&self) -> IndexGroup {
+        self.index_group
+    }
+
+    /// Returns the index offset.
+    pub fn index_offset(&self) -> IndexOffset {
+        self.index_offset
+    }
+
+    /// Returns the number of bytes sent with every notification.
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+
+    /// Returns the transmission mode.
+    pub fn trans_mode(&self) -> AdsTransMode {
+        self.trans_mode
+    }
+
+    /// Returns the maximum buffering delay in milliseconds.
+    pub fn max_delay(&self) -> u32 {
+        self.max_delay
+    }
+
+    /// Returns the cyclic check interval in milliseconds.
+    pub fn cycle_time(&self) -> u32 {
+        self.cycle_time
+    }
+
+    /// Encodes `items` into a sum add notification request.
+    pub fn encode_request(items: &[Self]) -> SumRequest {
+        let mut data = Vec::with_capacity(items.len() * Self::HEADER_SIZE);
+        for item in items {
+            put_u32(&mut data, item.index_group);
+            put_u32(&mut data, item.index_offset);
+            put_u32(&mut data, item.length);
+            data.extend_from_slice(&item.trans_mode.to_bytes());
+            put_u32(&mut data, item.max_delay);
+            put_u32(&mut data, item.cycle_time);
+            data.extend_from_slice(&[0; AdsAddDeviceNotificationRequest::RESERVED_SIZE]);
+        }
+
+        SumRequest {
+            index_group: index_group::SUMUP_ADDDEVNOTE,
+            count: items.len() as u32,
+            read_length: (items.len() * Self::RESPONSE_SIZE) as u32,
+            data,
+        }
+    }
+
+    /// Splits a sum add notification response into one handle per item.
+    pub fn parse_response(
+        count: usize,
+        data: &[u8],
+    ) -> Result<Vec<Result<NotificationHandle, AdsReturnCode>>, ProtocolError> {
+        let (responses, _) = split_headers(data, count, Self::RESPONSE_SIZE)?;
+
+        responses
+            .chunks_exact(Self::RESPONSE_SIZE)
+            .map(|r| {
+                let (code, handle) = AdsAddDeviceNotificationResponse::parse_payload(r)?;
+                Ok(match code {
+                    AdsReturnCode::Ok => Ok(handle),
+                    code => Err(code),
+                })
+            })
+            .collect()
+    }
+
+    /// Parses the write data of a sum add notification request carrying `count` items.
+    pub fn parse_request(count: u32, data: &[u8]) -> Result<Vec<Self>, ProtocolError> {
+        let (requests, _) = split_headers(data, count as usize, Self::HEADER_SIZE)?;
+
+        requests
+            .chunks_exact(Self::HEADER_SIZE)
+            .map(|r| {
+                let (group, offset, length, trans_mode, max_delay, cycle_time, _) =
+                    AdsAddDeviceNotificationRequest::parse_payload(r)?;
+                Ok(Self::new(
+                    group, offset, length, trans_mode, max_delay, cycle_time,
+                ))
+            })
+            .collect()
+    }
+
+    /// Encodes the response to a sum add notification request.
+    ///
+    /// Failed items carry a zero handle.
+    pub fn encode_response(results: &[Result<NotificationHandle, AdsReturnCode>]) -> Vec<u8> {
+        let mut data = Vec::with_capacity(results.len() * Self::RESPONSE_SIZE);
+        for result in results {
+            put_code(&mut data, result);
+            put_u32(&mut data, result.map_or(0, |handle| handle.as_u32()));
+        }
+        data
+    }
+}
+
+/// A single handle inside a [`SUMUP_DELDEVNOTE`](index_group::SUMUP_DELDEVNOTE) command.
+///
+/// Each sub-request uses the payload layout of an [`AdsDeleteDeviceNotificationRequest`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SumDeleteNotificationItem {
+    handle: NotificationHandle,
+}
+
+impl SumDeleteNotificationItem {
+    /// The size of a sub-request (Handle).
+    pub const HEADER_SIZE: usize = AdsDeleteDeviceNotificationRequest::PAYLOAD_SIZE;
+
+    /// Creates a deletion of `handle`.
+    pub fn new(handle: NotificationHandle) -> Self {
+        Self { handle }
+    }
+
+    /// Returns the notification handle.
+    pub fn handle(&self) -> NotificationHandle {
+        self.handle
+    }
+
+    /// Encodes `items` into a sum delete notification request.
+    pub fn encode_request(items: &[Self]) -> SumRequest {
+        let mut data = Vec::with_capacity(items.len() * Self::HEADER_SIZE);
+        for item in items {
+            data.extend_from_slice(&item.handle.to_bytes());
+        }
+
+        SumRequest {
+            index_group: index_group::SUMUP_DELDEVNOTE,
+            count: items.len() as u32,
+            read_length: (items.len() * AdsReturnCode::LENGTH) as u32,
+            data,
+        }
+    }
+
+    /// Splits a sum delete notification response into one result per item.
+    pub fn parse_response(
+        count: usize,
+        data: &[u8],
+    ) -> Result<Vec<Result<(), AdsReturnCode>>, ProtocolError> {
+        let (codes, _) = split_headers(data, count, AdsReturnCode::LENGTH)?;
+
+        Ok(codes
+            .chunks_exact(AdsReturnCode::LENGTH)
+            .map(|code| match parse_code(code) {
+                AdsReturnCode::Ok => Ok(()),
+                code => Err(code),
+            })
+            .collect())
+    }
+
+    /// Parses the write data of a sum delete notification request carrying `count` items.
+    pub fn parse_request(count: u32, data: &[u8]) -> Result<Vec<Self>, ProtocolError> {
+        let (handles, _) = split_headers(data, count as usize, Self::HEADER_SIZE)?;
+
+        handles
+            .chunks_exact(Self::HEADER_SIZE)
+            .map(|h| {
+                Ok(Self::new(
+                    AdsDeleteDeviceNotificationRequest::parse_payload(h)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Encodes the response to a sum delete notification request.
+    pub fn encode_response(results: &[Result<(), AdsReturnCode>]) -> Vec<u8> {
+        let mut data = Vec::with_capacity(results.len() * AdsReturnCode::LENGTH);
+        for result in results {
+            put_code(&mut data, result);
+        }
+        data
+    }
+}
+
+fn put_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_code<T, E: Copy + Into<u32>>(buf: &mut Vec<u8>, result: &Result<T, E>) {
+    let code = match result {
+        Ok(_) => AdsReturnCode::Ok.into(),
+        Err(code) => (*code).into(),
+    };
+    put_u32(buf, code);
+}
+
+fn read_u32(buf: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(buf[at..at + 4].try_into().unwrap())
+}
+
+fn parse_code(buf: &[u8]) -> AdsReturnCode {
+    AdsReturnCode::from(read_u32(buf, 0))
+}
+
+/// Splits off `count` fixed-size headers from the front of `data`.
+fn split_headers(data: &[u8], count: usize, size: usize) -> Result<(&[u8], &[u8]), ProtocolError> {
+    let len = count * size;
+    if data.len() < len {
+        return Err(ProtocolError::UnexpectedLength {
+            expected: len,
+            got: data.len(),
+        });
+    }
+    Ok(data.split_at(len))
+}
+
+/// Takes `len` bytes from the front of `data`.
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8], ProtocolError> {
+    if data.len() < len {
+        return Err(ProtocolError::UnexpectedLength {
+            expected: len,
+            got: data.len(),
+        });
+    }
+    let (value, rest) = data.split_at(len);
+    *data = rest;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sum_read_round_trip() {
+        let items = [
+            SumReadItem::new(0x4020, 0, 4),
+            SumReadItem::new(0x4020, 4, 2),
+        ];
+        let request = SumReadItem::encode_request(&items);
+
+        assert_eq!(request.index_group(), index_group::SUMUP_READ);
+        assert_eq!(request.count(), 2);
+        assert_eq!(request.read_length(), 2 * 4 + 4 + 2);
+        assert_eq!(
+            SumReadItem::parse_request(request.count(), request.data()).unwrap(),
+            items
+        );
+
+        let results = [
+            Ok(vec![1, 2, 3, 4]),
+            Err(AdsReturnCode::AdsErrDeviceSymbolNotFound),
+        ];
+        let response = SumReadItem::encode_response(&items, &results);
+
+        assert_eq!(response.len(), request.read_length() as usize);
+        assert_eq!(
+            SumReadItem::parse_response(&items, &response).unwrap(),
+            results
+        );
+    }
+
+    #[test]
+    fn test_sum_write_round_trip() {
+        let items = [
+            SumWriteItem::new(0x4020, 0, [1, 2, 3, 4]),
+            SumWriteItem::new(0x4020, 8, [5]),
+        ];
+        let request = SumWriteItem::encode_request(&items);
+
+        assert_eq!(request.index_group(), index_group::SUMUP_WRITE);
+        assert_eq!(request.data().len(), 2 * 12 + 5);
+        assert_eq!(
+            SumWriteItem::parse_request(request.count(), request.data()).unwrap(),
+            items
+        );
+
+        let response =
+            SumWriteItem::encode_response(&[Ok(()), Err(AdsReturnCode::AdsErrDeviceAccessDenied)]);
+        assert_eq!(
+            SumWriteItem::parse_response(&items, &response).unwrap(),
+            [Ok(vec![]), Err(AdsReturnCode::AdsErrDeviceAccessDenied)]
+        );
+    }
+
+    #[test]
+    fn test_sum_read_write_round_trip() {
+        let items = [
+            SumReadWriteItem::new(index_group::SYM_HNDBYNAME, 0, 4, *b"MAIN.a\0"),
+            SumReadWriteItem::new(index_group::SYM_HNDBYNAME, 0, 4, *b"MAIN.b\0"),
+        ];
+        let request = SumReadWriteItem::encode_request(&items);
+
+        assert_eq!(request.index_group(), index_group::SUMUP_READWRITE);
+        assert_eq!(request.read_length(), 2 * (8 + 4));
+        assert_eq!(
+            SumReadWriteItem::parse_request(request.count(), request.data()).unwrap(),
+            items
+        );
+
+        let results = [
+            Err(AdsReturnCode::AdsErrDeviceSymbolNotFound),
+            Ok(vec![7, 0, 0, 0]),
+        ];
+        let response = SumReadWriteItem::encode_response(&items, &results);
+
+        assert_eq!(response.len(), 2 * 8 + 4);
+        assert_eq!(
+            SumReadWriteItem::parse_response(&items, &response).unwrap(),
+            results
+        );
+    }
+
+    #[test]
+    fn test_sum_response_too_short() {
+        let items = [SumReadItem::new(0x4020, 0, 4)];
+        let err = SumReadItem::parse_response(&items, &[0, 0, 0, 0, 1]).unwrap_err();
+
+        assert!(matches!(
+            err,
+            ProtocolError::UnexpectedLength {
+                expected: 4,
+                got: 1
+            }
+        ));
+    }
+
+    #[test]
+    fn test_sum_add_notification_round_trip() {
+        let items = [
+            SumAddNotificationItem::new(0x4020, 0, 4, AdsTransMode::ServerOnChange, 0, 100),
+            SumAddNotificationItem::new(0x4020, 4, 2, AdsTransMode::ServerCycle, 10, 50),
+        ];
+        let request = SumAddNotificationItem::encode_request(&items);
+
+        assert_eq!(request.index_group(), index_group::SUMUP_ADDDEVNOTE);
+        assert_eq!(request.data().len(), 2 * 40);
+        assert_eq!(request.read_length(), 2 * 8);
+        assert_eq!(
+            SumAddNotificationItem::parse_request(request.count(), request.data()).unwrap(),
+            items
+        );
+
+        let results = [
+            Ok(NotificationHandle::new(11)),
+            Err(AdsReturnCode::AdsErrDeviceNotifyHndInvalid),
+        ];
+        let response = SumAddNotificationItem::encode_response(&results);
+        assert_eq!(
+            SumAddNotificationItem::parse_response(2, &response).unwrap(),
+            results
+        );
+    }
+
+    #[test]
+    fn test_sum_delete_notification_round_trip() {
+        let items = [
+            SumDeleteNotificationItem::new(NotificationHandle::new(11)),
+            SumDeleteNotificationItem::new(NotificationHandle::new(12)),
+        ];
+        let request = SumDeleteNotificationItem::encode_request(&items);
+
+        assert_eq!(request.index_group(), index_group::SUMUP_DELDEVNOTE);
+        assert_eq!(
+            SumDeleteNotificationItem::parse_request(request.count(), request.data()).unwrap(),
+            items
+        );
+
+        let results = [Err(AdsReturnCode::AdsErrDeviceNotifyHndInvalid), Ok(())];
+        let response = SumDeleteNotificationItem::encode_response(&results);
+        assert_eq!(
+            SumDeleteNotificationItem::parse_response(2, &response).unwrap(),
+            results
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use super::SymbolError;
+use super::codec::{ByteReader, encode_string, put_string};
+
+/// A PLC attribute (pragma) attached to a symbol or data type,
+/// e.g. `{attribute 'OPC.UA.DA' := '1'}`.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct AdsAttribute {
+    name: String,
+    value: String,
+}
+
+impl AdsAttribute {
+    /// Creates a new attribute.
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+
+    /// Returns the attribute name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the attribute value (empty for flag attributes).
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Parses an attribute block: a 2-byte count followed by the attributes.
+    pub(crate) fn read_list(reader: &mut ByteReader<'_>) -> Result<Vec<Self>, SymbolError> {
+        let count = reader.u16()?;
+        (0..count).map(|_| Self::read(reader)).collect()
+    }
+
+    /// Writes an attribute block: a 2-byte count followed by the attributes.
+    pub(crate) fn write_list(buf: &mut Vec<u8>, attributes: &[Self]) -> Result<(), SymbolError> {
+        let count = u16::try_from(attributes.len()).map_err(|_| SymbolError::StringTooLong {
+            max: u16::MAX as usize,
+            got: attributes.len(),
+        })?;
+        buf.extend_from_slice(&count.to_le_bytes());
+        for attribute in attributes {
+            attribute.write(buf)?;
+        }
+        Ok(())
+    }
+
+    fn read(reader: &mut ByteReader<'_>) -> Result<Self, SymbolError> {
+        let name_len = reader.u8()? as usize;
+        let value_len = reader.u8()? as usize;
+        let name = reader.string(name_len)?;
+        let value = reader.string(value_len)?;
+        Ok(Self { name, value })
+    }
+
+    fn write(&self, buf: &mut Vec<u8>) -> Result<(), SymbolError> {
+        let name = encode_string(&self.name, u8::MAX as usize)?;
+        let value = encode_string(&self.value, u8::MAX as usize)?;
+        buf.push(name.len() as u8);
+        buf.push(value.len() as u8);
+        put_string(buf, &name);
+        put_string(buf, &value);
+        Ok(())
+    }
+}
+
+/// The bounds of one array dimension.
+#[derive(
+    serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default,
+)]
+pub struct AdsArrayInfo {
+    lower_bound: i32,
+    elements: u32,
+}
+
+impl AdsArrayInfo {
+    /// The length of an array dimension in bytes.
+    pub const LENGTH: usize = 8;
+
+    /// Creates a new array dimension, e.g. `ARRAY[1..10]` is `AdsArrayInfo::new(1, 10)`.
+    pub fn new(lower_bound: i32, elements: u32) -> Self {
+        Self {
+            lower_bound,
+            elements,
+        }
+    }
+
+    /// Returns the lower bound of the dimension.
+    pub fn lower_bound(&self) -> i32 {
+        self.lower_bound
+    }
+
+    /// Returns the number of elements in the dimension.
+    pub fn elements(&self) -> u32 {
+        self.elements
+    }
+
+    /// Returns the upper bound of the dimension (inclusive).
+    pub fn upper_bound(&self) -> i32 {
+        self.lower_bound + self.elements as i32 - 1
+    }
+
+    pub(crate) fn read(reader: &mut ByteReader<'_>) -> Result<Self, SymbolError> {
+        Ok(Self {
+            lower_bound: reader.i32()?,
+            elements: reader.u32()?,
+        })
+    }
+
+    pub(crate) fn write(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.lower_bound.to_le_bytes());
+        buf.extend_from_slice(&self.elements.to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attribute_list_round_trips() {
+        let attributes = vec![
+            AdsAttribute::new("OPC.UA.DA", "1"),
+            AdsAttribute::new("hide", ""),
+        ];
+        let mut buf = Vec::new();
+        AdsAttribute::write_list(&mut buf, &attributes).unwrap();
+
+        let mut reader = ByteReader::new(&buf);
+        assert_eq!(AdsAttribute::read_list(&mut reader).unwrap(), attributes);
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    #[test]
+    fn array_upper_bound_is_inclusive() {
+        assert_eq!(AdsArrayInfo::new(1, 10).upper_bound(), 10);
+        assert_eq!(AdsArrayInfo::new(-2, 5).upper_bound(), 2);
+    }
+}
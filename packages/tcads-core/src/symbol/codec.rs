@@ -0,0 +1,92 @@
+//! Little-endian cursor helpers shared by the symbol and data type parsers.
+
+use super::SymbolError;
+use encoding_rs::WINDOWS_1252;
+
+/// A forward-only reader over a little-endian byte slice.
+pub(crate) struct ByteReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> Result<&'a [u8], SymbolError> {
+        if self.remaining() < len {
+            return Err(SymbolError::UnexpectedLength {
+                expected: self.pos + len,
+                got: self.buf.len(),
+            });
+        }
+        let bytes = &self.buf[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    pub(crate) fn array<const N: usize>(&mut self) -> Result<[u8; N], SymbolError> {
+        Ok(self.bytes(N)?.try_into().unwrap())
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, SymbolError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, SymbolError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, SymbolError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn i32(&mut self) -> Result<i32, SymbolError> {
+        Ok(i32::from_le_bytes(self.array()?))
+    }
+
+    /// Reads a Windows-1252 string of `len` bytes followed by a null terminator.
+    pub(crate) fn string(&mut self, len: usize) -> Result<String, SymbolError> {
+        let bytes = self.bytes(len + 1)?;
+        let (cow, _, _) = WINDOWS_1252.decode(&bytes[..len]);
+        Ok(cow.into_owned())
+    }
+}
+
+/// Encodes `s` as Windows-1252, checking it fits in `max` bytes.
+pub(crate) fn encode_string(s: &str, max: usize) -> Result<Vec<u8>, SymbolError> {
+    let (encoded, _, has_errors) = WINDOWS_1252.encode(s);
+    if has_errors {
+        return Err(SymbolError::EncodingError);
+    }
+    if encoded.len() > max {
+        return Err(SymbolError::StringTooLong {
+            max,
+            got: encoded.len(),
+        });
+    }
+    Ok(encoded.into_owned())
+}
+
+/// Writes a null-terminated string body.
+pub(crate) fn put_string(buf: &mut Vec<u8>, encoded: &[u8]) {
+    buf.extend_from_slice(encoded);
+    buf.push(0);
+}
+
+/// Encodes a symbol name as the null-terminated Windows-1252 string expected by the
+/// by-name index groups, e.g. [`SYM_HNDBYNAME`](crate::ads::index_group::SYM_HNDBYNAME).
+pub fn encode_symbol_name(name: &str) -> Result<Vec<u8>, SymbolError> {
+    let mut buf = encode_string(name, u16::MAX as usize)?;
+    buf.push(0);
+    Ok(buf)
+}
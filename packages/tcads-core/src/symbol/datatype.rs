@@ -0,0 +1,565 @@
+use super::codec::{ByteReader, encode_string, put_string};
+use super::{AdsArrayInfo, AdsAttribute, AdsDataTypeId, AdsDatatypeFlags, SymbolError};
+
+/// A named value of an enumeration data type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AdsEnumInfo {
+    name: String,
+    value: Vec<u8>,
+}
+
+impl AdsEnumInfo {
+    /// Creates a new enumeration value from its little-endian bytes.
+    ///
+    /// The value must have the size of the enumeration's base type.
+    pub fn new(name: impl Into<String>, value: impl Into<Vec<u8>>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+
+    /// Returns the name of the enumeration value.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the raw little-endian value.
+    pub fn raw_value(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// Returns the value as a sign-extended integer.
+    ///
+    /// Returns [`None`] if the value is wider than 8 bytes.
+    pub fn value(&self) -> Option<i64> {
+        let len = self.value.len();
+        if len == 0 || len > 8 {
+            return None;
+        }
+        let fill = if self.value[len - 1] & 0x80 != 0 {
+            0xFF
+        } else {
+            0
+        };
+        let mut buf = [fill; 8];
+        buf[..len].copy_from_slice(&self.value);
+        Some(i64::from_le_bytes(buf))
+    }
+}
+
+/// A data type of the PLC data type table (`ADS_DATATYPE_ENTRY`).
+///
+/// Returned by a data type upload ([`SYM_DT_UPLOAD`](crate::ads::index_group::SYM_DT_UPLOAD)),
+/// one entry per type. Structured types and function blocks carry their members as
+/// [sub items](Self::sub_items), which use the same layout with the
+/// [`DATAITEM`](AdsDatatypeFlags::DATAITEM) flag set.
+///
+/// # Layout
+/// * **Entry Length:** 4 bytes (including this field)
+/// * **Version:** 4 bytes
+/// * **Hash Value:** 4 bytes
+/// * **Type Hash Value:** 4 bytes
+/// * **Size:** 4 bytes
+/// * **Offset:** 4 bytes (of a sub item within its parent)
+/// * **Data Type:** 4 bytes ([`AdsDataTypeId`])
+/// * **Flags:** 4 bytes ([`AdsDatatypeFlags`])
+/// * **Name Length:** 2 bytes
+/// * **Type Length:** 2 bytes
+/// * **Comment Length:** 2 bytes
+/// * **Array Dimensions:** 2 bytes
+/// * **Sub Item Count:** 2 bytes
+/// * **Name, Type, Comment:** n bytes each, null-terminated (Windows-1252)
+/// * **Array Info:** 8 bytes per dimension ([`AdsArrayInfo`])
+/// * **Sub Items:** one nested entry each
+/// * **Type GUID:** 16 bytes, if [`TYPE_GUID`](AdsDatatypeFlags::TYPE_GUID) is set
+/// * **Copy Mask:** `size` bytes, if [`COPY_MASK`](AdsDatatypeFlags::COPY_MASK) is set
+/// * **Method Infos:** if [`METHOD_INFOS`](AdsDatatypeFlags::METHOD_INFOS) is set (skipped)
+/// * **Attributes:** if [`ATTRIBUTES`](AdsDatatypeFlags::ATTRIBUTES) is set ([`AdsAttribute`])
+/// * **Enum Infos:** if [`ENUM_INFOS`](AdsDatatypeFlags::ENUM_INFOS) is set ([`AdsEnumInfo`])
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AdsDatatypeEntry {
+    version: u32,
+    hash_value: u32,
+    type_hash_value: u32,
+    size: u32,
+    offset: u32,
+    data_type: AdsDataTypeId,
+    flags: AdsDatatypeFlags,
+    name: String,
+    type_name: String,
+    comment: String,
+    array_info: Vec<AdsArrayInfo>,
+    sub_items: Vec<AdsDatatypeEntry>,
+    type_guid: Option<[u8; 16]>,
+    copy_mask: Option<Vec<u8>>,
+    attributes: Vec<AdsAttribute>,
+    enum_infos: Vec<AdsEnumInfo>,
+}
+
+impl AdsDatatypeEntry {
+    /// Size of the fixed entry header (before the strings).
+    pub const HEADER_SIZE: usize = 42;
+
+    /// Creates a new data type entry.
+    ///
+    /// `name` is the name of the type (or of the member, for sub items) and
+    /// `type_name` is its underlying type, e.g. `INT` for an enumeration or
+    /// the element type for an array.
+    pub fn new(
+        name: impl Into<String>,
+        type_name: impl Into<String>,
+        data_type: AdsDataTypeId,
+        size: u32,
+    ) -> Self {
+        Self {
+            version: 1,
+            hash_value: 0,
+            type_hash_value: 0,
+            size,
+            offset: 0,
+            data_type,
+            flags: AdsDatatypeFlags::new(AdsDatatypeFlags::DATATYPE),
+            name: name.into(),
+            type_name: type_name.into(),
+            comment: String::new(),
+            array_info: Vec::new(),
+            sub_items: Vec::new(),
+            type_guid: None,
+            copy_mask: None,
+            attributes: Vec::new(),
+            enum_infos: Vec::new(),
+        }
+    }
+
+    /// Creates a new sub item (member) at `offset` bytes within its parent.
+    pub fn new_item(
+        name: impl Into<String>,
+        type_name: impl Into<String>,
+        data_type: AdsDataTypeId,
+        size: u32,
+        offset: u32,
+    ) -> Self {
+        Self {
+            offset,
+            flags: AdsDatatypeFlags::new(AdsDatatypeFlags::DATAITEM),
+            ..Self::new(name, type_name, data_type, size)
+        }
+    }
+
+    /// Sets the comment.
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = comment.into();
+        self
+    }
+
+    /// Sets the flags.
+    ///
+    /// The section bits ([`TYPE_GUID`](AdsDatatypeFlags::TYPE_GUID),
+    /// [`COPY_MASK`](AdsDatatypeFlags::COPY_MASK), [`METHOD_INFOS`](AdsDatatypeFlags::METHOD_INFOS),
+    /// [`ATTRIBUTES`](AdsDatatypeFlags::ATTRIBUTES) and [`ENUM_INFOS`](AdsDatatypeFlags::ENUM_INFOS))
+    /// are managed automatically when encoding.
+    pub fn with_flags(mut self, flags: AdsDatatypeFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Sets the array dimensions.
+    pub fn with_array_info(mut self, array_info: impl Into<Vec<AdsArrayInfo>>) -> Self {
+        self.array_info = array_info.into();
+        self
+    }
+
+    /// Sets the sub items (members).
+    pub fn with_sub_items(mut self, sub_items: impl Into<Vec<AdsDatatypeEntry>>) -> Self {
+        self.sub_items = sub_items.into();
+        self
+    }
+
+    /// Sets the attributes.
+    pub fn with_attributes(mut self, attributes: impl Into<Vec<AdsAttribute>>) -> Self {
+        self.attributes = attributes.into();
+        self
+    }
+
+    /// Sets the enumeration values.
+    pub fn with_enum_infos(mut self, enum_infos: impl Into<Vec<AdsEnumInfo>>) -> Self {
+        self.enum_infos = enum_infos.into();
+        self
+    }
+
+    /// Sets the type GUID.
+    pub fn with_type_guid(mut self, guid: [u8; 16]) -> Self {
+        self.type_guid = Some(guid);
+        self
+    }
+
+    /// Returns the entry version.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns the hash value of the entry.
+    pub fn hash_value(&self) -> u32 {
+        self.hash_value
+    }
+
+    /// Returns the hash value of the underlying type.
+    pub fn type_hash_value(&self) -> u32 {
+        self.type_hash_value
+    }
+
+    /// Returns the name of the type (or of the member, for sub items).
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the underlying type name.
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    /// Returns the comment.
+    pub fn comment(&self) -> &str {
+        &self.comment
+    }
+
+    /// Returns the size of the type in bytes (in bits for [bit values](AdsDatatypeFlags::is_bit_values)).
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns the offset of a sub item within its parent, in bytes
+    /// (in bits for [bit values](AdsDatatypeFlags::is_bit_values)).
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// Returns the base data type.
+    pub fn data_type(&self) -> AdsDataTypeId {
+        self.data_type
+    }
+
+    /// Returns the data type flags.
+    pub fn flags(&self) -> AdsDatatypeFlags {
+        self.flags
+    }
+
+    /// Returns the array dimensions (empty for non-array types).
+    pub fn array_info(&self) -> &[AdsArrayInfo] {
+        &self.array_info
+    }
+
+    /// Returns the sub items (members) of a structured type.
+    pub fn sub_items(&self) -> &[AdsDatatypeEntry] {
+        &self.sub_items
+    }
+
+    /// Returns the sub item `name` (case-insensitive), if present.
+    pub fn sub_item(&self, name: &str) -> Option<&AdsDatatypeEntry> {
+        self.sub_items
+            .iter()
+            .find(|item| item.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the type GUID, if present.
+    pub fn type_guid(&self) -> Option<&[u8; 16]> {
+        self.type_guid.as_ref()
+    }
+
+    /// Returns the copy mask, if present.
+    pub fn copy_mask(&self) -> Option<&[u8]> {
+        self.copy_mask.as_deref()
+    }
+
+    /// Returns the attributes attached to the type.
+    pub fn attributes(&self) -> &[AdsAttribute] {
+        &self.attributes
+    }
+
+    /// Returns the value of the attribute `name` (case-insensitive), if present.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.name().eq_ignore_ascii_case(name))
+            .map(AdsAttribute::value)
+    }
+
+    /// Returns the values of an enumeration type.
+    pub fn enum_infos(&self) -> &[AdsEnumInfo] {
+        &self.enum_infos
+    }
+
+    /// Parses one entry from the front of `bytes`.
+    ///
+    /// Returns the entry and the bytes following it (as given by the entry length).
+    pub fn parse_prefix(bytes: &[u8]) -> Result<(Self, &[u8]), SymbolError> {
+        let entry_length = ByteReader::new(bytes).u32()?;
+        let len = entry_length as usize;
+        if len < Self::HEADER_SIZE {
+            return Err(SymbolError::InvalidEntryLength(entry_length));
+        }
+        if len > bytes.len() {
+            return Err(SymbolError::UnexpectedLength {
+                expected: len,
+                got: bytes.len(),
+            });
+        }
+
+        let mut reader = ByteReader::new(&bytes[..len]);
+        reader.u32()?;
+
+        let version = reader.u32()?;
+        let hash_value = reader.u32()?;
+        let type_hash_value = reader.u32()?;
+        let size = reader.u32()?;
+        let offset = reader.u32()?;
+        let data_type = AdsDataTypeId::from(reader.u32()?);
+        let flags = AdsDatatypeFlags::from(reader.u32()?);
+        let name_len = reader.u16()? as usize;
+        let type_len = reader.u16()? as usize;
+        let comment_len = reader.u16()? as usize;
+        let array_dim = reader.u16()?;
+        let sub_item_count = reader.u16()?;
+
+        let name = reader.string(name_len)?;
+        let type_name = reader.string(type_len)?;
+        let comment = reader.string(comment_len)?;
+
+        let array_info = (0..array_dim)
+            .map(|_| AdsArrayInfo::read(&mut reader))
+            .collect::<Result<_, _>>()?;
+
+        let mut sub_items = Vec::with_capacity(sub_item_count as usize);
+        for _ in 0..sub_item_count {
+            let rest = &bytes[reader.position()..len];
+            let (item, after) = Self::parse_prefix(rest)?;
+            reader.bytes(rest.len() - after.len())?;
+            sub_items.push(item);
+        }
+
+        let type_guid = match flags.contains(AdsDatatypeFlags::TYPE_GUID) {
+            true => Some(reader.array()?),
+            false => None,
+        };
+
+        let copy_mask = match flags.contains(AdsDatatypeFlags::COPY_MASK) {
+            true => Some(reader.bytes(size as usize)?.to_vec()),
+            false => None,
+        };
+
+        if flags.contains(AdsDatatypeFlags::METHOD_INFOS) {
+            for _ in 0..reader.u16()? {
+                let method_len = ByteReader::new(&bytes[reader.position()..len]).u32()?;
+                reader.bytes(method_len as usize)?;
+            }
+        }
+
+        let attributes = match flags.contains(AdsDatatypeFlags::ATTRIBUTES) {
+            true => AdsAttribute::read_list(&mut reader)?,
+            false => Vec::new(),
+        };
+
+        let mut enum_infos = Vec::new();
+        if flags.contains(AdsDatatypeFlags::ENUM_INFOS) {
+            for _ in 0..reader.u16()? {
+                let name_len = reader.u8()? as usize;
+                let name = reader.string(name_len)?;
+                let value = reader.bytes(size as usize)?.to_vec();
+                enum_infos.push(AdsEnumInfo { name, value });
+            }
+        }
+
+        let entry = Self {
+            version,
+            hash_value,
+            type_hash_value,
+            size,
+            offset,
+            data_type,
+            flags,
+            name,
+            type_name,
+            comment,
+            array_info,
+            sub_items,
+            type_guid,
+            copy_mask,
+            attributes,
+            enum_infos,
+        };
+
+        Ok((entry, &bytes[len..]))
+    }
+
+    /// Parses a full data type table, as returned by a data type upload.
+    pub fn parse_table(mut bytes: &[u8]) -> Result<Vec<Self>, SymbolError> {
+        let mut entries = Vec::new();
+        while !bytes.is_empty() {
+            let (entry, rest) = Self::parse_prefix(bytes)?;
+            entries.push(entry);
+            bytes = rest;
+        }
+        Ok(entries)
+    }
+
+    /// Encodes the entry (including its sub items) into its wire format.
+    ///
+    /// Method information is not retained when parsing and is never encoded.
+    /// Fails if a string cannot be encoded as Windows-1252 or exceeds its length field.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SymbolError> {
+        let name = encode_string(&self.name, u16::MAX as usize)?;
+        let type_name = encode_string(&self.type_name, u16::MAX as usize)?;
+        let comment = encode_string(&self.comment, u16::MAX as usize)?;
+
+        let flags = self
+            .flags
+            .with(AdsDatatypeFlags::TYPE_GUID, self.type_guid.is_some())
+            .with(AdsDatatypeFlags::COPY_MASK, self.copy_mask.is_some())
+            .with(AdsDatatypeFlags::METHOD_INFOS, false)
+            .with(AdsDatatypeFlags::ATTRIBUTES, !self.attributes.is_empty())
+            .with(AdsDatatypeFlags::ENUM_INFOS, !self.enum_infos.is_empty());
+
+        let mut buf = Vec::with_capacity(Self::HEADER_SIZE + name.len() + type_name.len());
+        buf.extend_from_slice(&[0; 4]);
+        buf.extend_from_slice(&self.version.to_le_bytes());
+        buf.extend_from_slice(&self.hash_value.to_le_bytes());
+        buf.extend_from_slice(&self.type_hash_value.to_le_bytes());
+        buf.extend_from_slice(&self.size.to_le_bytes());
+        buf.extend_from_slice(&self.offset.to_le_bytes());
+        buf.extend_from_slice(&u32::from(self.data_type).to_le_bytes());
+        buf.extend_from_slice(&flags.0.to_le_bytes());
+        buf.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        buf.extend_from_slice(&(type_name.len() as u16).to_le_bytes());
+        buf.extend_from_slice(&(comment.len() as u16).to_le_bytes());
+        buf.extend_from_slice(&(self.array_info.len() as u16).to_le_bytes());
+        buf.extend_from_slice(&(self.sub_items.len() as u16).to_le_bytes());
+        put_string(&mut buf, &name);
+        put_string(&mut buf, &type_name);
+        put_string(&mut buf, &comment);
+
+        for info in &self.array_info {
+            info.write(&mut buf);
+        }
+        for item in &self.sub_items {
+            buf.extend(item.to_bytes()?);
+        }
+        if let Some(guid) = &self.type_guid {
+            buf.extend_from_slice(guid);
+        }
+        if let Some(mask) = &self.copy_mask {
+            buf.extend_from_slice(mask);
+        }
+        if !self.attributes.is_empty() {
+            AdsAttribute::write_list(&mut buf, &self.attributes)?;
+        }
+        if !self.enum_infos.is_empty() {
+            buf.extend_from_slice(&(self.enum_infos.len() as u16).to_le_bytes());
+            for info in &self.enum_infos {
+                let name = encode_string(&info.name, u8::MAX as usize)?;
+                buf.push(name.len() as u8);
+                put_string(&mut buf, &name);
+                buf.extend_from_slice(&info.value);
+            }
+        }
+
+        let len = buf.len() as u32;
+        buf[..4].copy_from_slice(&len.to_le_bytes());
+        Ok(buf)
+    }
+}
+
+impl TryFrom<&[u8]> for AdsDatatypeEntry {
+    type Error = SymbolError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse_prefix(bytes).map(|(entry, _)| entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn st_data() -> AdsDatatypeEntry {
+        AdsDatatypeEntry::new("ST_Data", "", AdsDataTypeId::BigType, 8)
+            .with_comment("Process data")
+            .with_sub_items([
+                AdsDatatypeEntry::new_item("nValue", "DINT", AdsDataTypeId::Int32, 4, 0),
+                AdsDatatypeEntry::new_item("fValue", "REAL", AdsDataTypeId::Real32, 4, 4),
+            ])
+    }
+
+    #[test]
+    fn round_trips_struct_with_sub_items() {
+        let entry = st_data();
+        let bytes = entry.to_bytes().unwrap();
+        let parsed = AdsDatatypeEntry::try_from(bytes.as_slice()).unwrap();
+
+        assert_eq!(parsed, entry);
+        assert_eq!(parsed.sub_item("FVALUE").unwrap().offset(), 4);
+    }
+
+    #[test]
+    fn round_trips_enum_infos() {
+        let entry = AdsDatatypeEntry::new("E_Mode", "INT", AdsDataTypeId::Int16, 2)
+            .with_enum_infos([
+                AdsEnumInfo::new("Idle", 0i16.to_le_bytes()),
+                AdsEnumInfo::new("Fault", (-1i16).to_le_bytes()),
+            ])
+            .with_attributes([AdsAttribute::new("qualified_only", "")]);
+        let bytes = entry.to_bytes().unwrap();
+        let parsed = AdsDatatypeEntry::try_from(bytes.as_slice()).unwrap();
+
+        assert!(parsed.flags().contains(AdsDatatypeFlags::ENUM_INFOS));
+        assert_eq!(parsed.enum_infos()[1].value(), Some(-1));
+        assert_eq!(parsed.attribute("qualified_only"), Some(""));
+    }
+
+    #[test]
+    fn skips_method_infos() {
+        let mut bytes = AdsDatatypeEntry::new("FB_Test", "", AdsDataTypeId::BigType, 16)
+            .to_bytes()
+            .unwrap();
+        let flags = AdsDatatypeFlags::DATATYPE | AdsDatatypeFlags::METHOD_INFOS;
+        bytes[28..32].copy_from_slice(&flags.to_le_bytes());
+
+        // One opaque method entry of 8 bytes
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&8u32.to_le_bytes());
+        bytes.extend_from_slice(&[0xAA; 4]);
+        let len = bytes.len() as u32;
+        bytes[..4].copy_from_slice(&len.to_le_bytes());
+
+        let parsed = AdsDatatypeEntry::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(parsed.name(), "FB_Test");
+    }
+
+    #[test]
+    fn parses_table_of_entries() {
+        let mut table = st_data().to_bytes().unwrap();
+        table.extend(
+            AdsDatatypeEntry::new("ARRAY [1..3] OF INT", "INT", AdsDataTypeId::Int16, 6)
+                .with_array_info([AdsArrayInfo::new(1, 3)])
+                .to_bytes()
+                .unwrap(),
+        );
+
+        let entries = AdsDatatypeEntry::parse_table(&table).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].array_info()[0].upper_bound(), 3);
+    }
+
+    #[test]
+    fn rejects_sub_item_overflowing_parent() {
+        let mut bytes = st_data().to_bytes().unwrap();
+        // Claim a sub item is longer than its parent
+        let first_item = AdsDatatypeEntry::HEADER_SIZE + "ST_Data".len() + 1 + 1 + 13;
+        bytes[first_item..first_item + 4].copy_from_slice(&500u32.to_le_bytes());
+
+        assert!(matches!(
+            AdsDatatypeEntry::try_from(bytes.as_slice()),
+            Err(SymbolError::UnexpectedLength { .. })
+        ));
+    }
+}
@@ -0,0 +1,405 @@
+use super::codec::{ByteReader, encode_string, put_string};
+use super::{AdsArrayInfo, AdsAttribute, AdsDataTypeId, AdsSymbolFlags, SymbolError};
+use crate::ads::{IndexGroup, IndexOffset};
+
+/// A symbol of the PLC symbol table (`ADS_SYMBOL_ENTRY`).
+///
+/// Returned by a symbol upload ([`SYM_UPLOAD`](crate::ads::index_group::SYM_UPLOAD)),
+/// one entry per symbol, and by a symbol info query
+/// ([`SYM_INFOBYNAMEEX`](crate::ads::index_group::SYM_INFOBYNAMEEX)).
+///
+/// # Layout
+/// * **Entry Length:** 4 bytes (including this field)
+/// * **Index Group:** 4 bytes
+/// * **Index Offset:** 4 bytes
+/// * **Size:** 4 bytes
+/// * **Data Type:** 4 bytes ([`AdsDataTypeId`])
+/// * **Flags:** 2 bytes ([`AdsSymbolFlags`])
+/// * **Array Dimensions:** 2 bytes
+/// * **Name Length:** 2 bytes
+/// * **Type Length:** 2 bytes
+/// * **Comment Length:** 2 bytes
+/// * **Name, Type, Comment:** n bytes each, null-terminated (Windows-1252)
+/// * **Array Info:** 8 bytes per dimension ([`AdsArrayInfo`])
+/// * **Type GUID:** 16 bytes, if [`TYPE_GUID`](AdsSymbolFlags::TYPE_GUID) is set
+/// * **Attributes:** if [`ATTRIBUTES`](AdsSymbolFlags::ATTRIBUTES) is set ([`AdsAttribute`])
+/// * **Extended Flags:** 4 bytes, if [`EXTENDED_FLAGS`](AdsSymbolFlags::EXTENDED_FLAGS) is set
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AdsSymbolEntry {
+    index_group: IndexGroup,
+    index_offset: IndexOffset,
+    size: u32,
+    data_type: AdsDataTypeId,
+    flags: AdsSymbolFlags,
+    name: String,
+    type_name: String,
+    comment: String,
+    array_info: Vec<AdsArrayInfo>,
+    type_guid: Option<[u8; 16]>,
+    attributes: Vec<AdsAttribute>,
+    extended_flags: Option<u32>,
+}
+
+impl AdsSymbolEntry {
+    /// Size of the fixed entry header (before the strings).
+    pub const HEADER_SIZE: usize = 30;
+
+    /// Creates a new symbol entry with no comment, array info, GUID or attributes.
+    pub fn new(
+        name: impl Into<String>,
+        type_name: impl Into<String>,
+        data_type: AdsDataTypeId,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        size: u32,
+    ) -> Self {
+        Self {
+            index_group,
+            index_offset,
+            size,
+            data_type,
+            flags: AdsSymbolFlags::default(),
+            name: name.into(),
+            type_name: type_name.into(),
+            comment: String::new(),
+            array_info: Vec::new(),
+            type_guid: None,
+            attributes: Vec::new(),
+            extended_flags: None,
+        }
+    }
+
+    /// Sets the comment.
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = comment.into();
+        self
+    }
+
+    /// Sets the flags.
+    ///
+    /// The [`TYPE_GUID`](AdsSymbolFlags::TYPE_GUID), [`ATTRIBUTES`](AdsSymbolFlags::ATTRIBUTES)
+    /// and [`EXTENDED_FLAGS`](AdsSymbolFlags::EXTENDED_FLAGS) bits are managed automatically
+    /// when encoding.
+    pub fn with_flags(mut self, flags: AdsSymbolFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Sets the array dimensions.
+    pub fn with_array_info(mut self, array_info: impl Into<Vec<AdsArrayInfo>>) -> Self {
+        self.array_info = array_info.into();
+        self
+    }
+
+    /// Sets the attributes.
+    pub fn with_attributes(mut self, attributes: impl Into<Vec<AdsAttribute>>) -> Self {
+        self.attributes = attributes.into();
+        self
+    }
+
+    /// Sets the type GUID.
+    pub fn with_type_guid(mut self, guid: [u8; 16]) -> Self {
+        self.type_guid = Some(guid);
+        self
+    }
+
+    /// Returns the full symbol name, e.g. `MAIN.nCount`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the PLC type name, e.g. `DINT` or `ST_Data`.
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    /// Returns the symbol comment.
+    pub fn comment(&self) -> &str {
+        &self.comment
+    }
+
+    /// Returns the index group of the symbol.
+    pub fn index_group(&self) -> IndexGroup {
+        self.index_group
+    }
+
+    /// Returns the index offset of the symbol.
+    pub fn index_offset(&self) -> IndexOffset {
+        self.index_offset
+    }
+
+    /// Returns the size of the symbol in bytes (in bits for [bit values](AdsSymbolFlags::is_bit_value)).
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns the base data type.
+    pub fn data_type(&self) -> AdsDataTypeId {
+        self.data_type
+    }
+
+    /// Returns the symbol flags.
+    pub fn flags(&self) -> AdsSymbolFlags {
+        self.flags
+    }
+
+    /// Returns the array dimensions (empty for non-array symbols).
+    pub fn array_info(&self) -> &[AdsArrayInfo] {
+        &self.array_info
+    }
+
+    /// Returns the type GUID, if present.
+    pub fn type_guid(&self) -> Option<&[u8; 16]> {
+        self.type_guid.as_ref()
+    }
+
+    /// Returns the attributes attached to the symbol.
+    pub fn attributes(&self) -> &[AdsAttribute] {
+        &self.attributes
+    }
+
+    /// Returns the value of the attribute `name` (case-insensitive), if present.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.name().eq_ignore_ascii_case(name))
+            .map(AdsAttribute::value)
+    }
+
+    /// Returns the extended flags, if present.
+    pub fn extended_flags(&self) -> Option<u32> {
+        self.extended_flags
+    }
+
+    /// Parses one entry from the front of `bytes`.
+    ///
+    /// Returns the entry and the bytes following it (as given by the entry length).
+    pub fn parse_prefix(bytes: &[u8]) -> Result<(Self, &[u8]), SymbolError> {
+        let mut reader = ByteReader::new(bytes);
+
+        let entry_length = reader.u32()?;
+        let len = entry_length as usize;
+        if len < Self::HEADER_SIZE {
+            return Err(SymbolError::InvalidEntryLength(entry_length));
+        }
+        if len > bytes.len() {
+            return Err(SymbolError::UnexpectedLength {
+                expected: len,
+                got: bytes.len(),
+            });
+        }
+
+        let mut reader = ByteReader::new(&bytes[..len]);
+        reader.u32()?;
+
+        let index_group = reader.u32()?;
+        let index_offset = reader.u32()?;
+        let size = reader.u32()?;
+        let data_type = AdsDataTypeId::from(reader.u32()?);
+        let flags = AdsSymbolFlags::from(reader.u16()?);
+        let array_dim = reader.u16()?;
+        let name_len = reader.u16()? as usize;
+        let type_len = reader.u16()? as usize;
+        let comment_len = reader.u16()? as usize;
+
+        let name = reader.string(name_len)?;
+        let type_name = reader.string(type_len)?;
+        let comment = reader.string(comment_len)?;
+
+        let array_info = (0..array_dim)
+            .map(|_| AdsArrayInfo::read(&mut reader))
+            .collect::<Result<_, _>>()?;
+
+        let type_guid = match flags.contains(AdsSymbolFlags::TYPE_GUID) {
+            true => Some(reader.array()?),
+            false => None,
+        };
+
+        let attributes = match flags.contains(AdsSymbolFlags::ATTRIBUTES) {
+            true => AdsAttribute::read_list(&mut reader)?,
+            false => Vec::new(),
+        };
+
+        let extended_flags = match flags.contains(AdsSymbolFlags::EXTENDED_FLAGS) {
+            true => Some(reader.u32()?),
+            false => None,
+        };
+
+        let entry = Self {
+            index_group,
+            index_offset,
+            size,
+            data_type,
+            flags,
+            name,
+            type_name,
+            comment,
+            array_info,
+            type_guid,
+            attributes,
+            extended_flags,
+        };
+
+        Ok((entry, &bytes[len..]))
+    }
+
+    /// Parses a full symbol table, as returned by a symbol upload.
+    pub fn parse_table(mut bytes: &[u8]) -> Result<Vec<Self>, SymbolError> {
+        let mut entries = Vec::new();
+        while !bytes.is_empty() {
+            let (entry, rest) = Self::parse_prefix(bytes)?;
+            entries.push(entry);
+            bytes = rest;
+        }
+        Ok(entries)
+    }
+
+    /// Encodes the entry into its wire format.
+    ///
+    /// Fails if a string cannot be encoded as Windows-1252 or exceeds its length field.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SymbolError> {
+        let name = encode_string(&self.name, u16::MAX as usize)?;
+        let type_name = encode_string(&self.type_name, u16::MAX as usize)?;
+        let comment = encode_string(&self.comment, u16::MAX as usize)?;
+
+        let flags = self
+            .flags
+            .with(AdsSymbolFlags::TYPE_GUID, self.type_guid.is_some())
+            .with(AdsSymbolFlags::ATTRIBUTES, !self.attributes.is_empty())
+            .with(
+                AdsSymbolFlags::EXTENDED_FLAGS,
+                self.extended_flags.is_some(),
+            );
+
+        let mut buf = Vec::with_capacity(Self::HEADER_SIZE + name.len() + type_name.len());
+        buf.extend_from_slice(&[0; 4]);
+        buf.extend_from_slice(&self.index_group.to_le_bytes());
+        buf.extend_from_slice(&self.index_offset.to_le_bytes());
+        buf.extend_from_slice(&self.size.to_le_bytes());
+        buf.extend_from_slice(&u32::from(self.data_type).to_le_bytes());
+        buf.extend_from_slice(&flags.0.to_le_bytes());
+        buf.extend_from_slice(&(self.array_info.len() as u16).to_le_bytes());
+        buf.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        buf.extend_from_slice(&(type_name.len() as u16).to_le_bytes());
+        buf.extend_from_slice(&(comment.len() as u16).to_le_bytes());
+        put_string(&mut buf, &name);
+        put_string(&mut buf, &type_name);
+        put_string(&mut buf, &comment);
+
+        for info in &self.array_info {
+            info.write(&mut buf);
+        }
+        if let Some(guid) = &self.type_guid {
+            buf.extend_from_slice(guid);
+        }
+        if !self.attributes.is_empty() {
+            AdsAttribute::write_list(&mut buf, &self.attributes)?;
+        }
+        if let Some(extended) = self.extended_flags {
+            buf.extend_from_slice(&extended.to_le_bytes());
+        }
+
+        let len = buf.len() as u32;
+        buf[..4].copy_from_slice(&len.to_le_bytes());
+        Ok(buf)
+    }
+}
+
+impl TryFrom<&[u8]> for AdsSymbolEntry {
+    type Error = SymbolError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse_prefix(bytes).map(|(entry, _)| entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> AdsSymbolEntry {
+        AdsSymbolEntry::new(
+            "MAIN.nCount",
+            "DINT",
+            AdsDataTypeId::Int32,
+            0x4040,
+            0x1F4,
+            4,
+        )
+        .with_comment("Cycle counter")
+    }
+
+    #[test]
+    fn round_trips_minimal_entry() {
+        let entry = sample();
+        let bytes = entry.to_bytes().unwrap();
+
+        assert_eq!(bytes.len(), AdsSymbolEntry::HEADER_SIZE + 12 + 5 + 14);
+        assert_eq!(AdsSymbolEntry::try_from(bytes.as_slice()).unwrap(), entry);
+    }
+
+    #[test]
+    fn round_trips_optional_sections() {
+        let entry = sample()
+            .with_flags(AdsSymbolFlags::new(AdsSymbolFlags::READ_ONLY))
+            .with_array_info([AdsArrayInfo::new(1, 10)])
+            .with_type_guid([7; 16])
+            .with_attributes([AdsAttribute::new("OPC.UA.DA", "1")]);
+        let bytes = entry.to_bytes().unwrap();
+        let parsed = AdsSymbolEntry::try_from(bytes.as_slice()).unwrap();
+
+        assert!(parsed.flags().is_read_only());
+        assert!(parsed.flags().contains(AdsSymbolFlags::TYPE_GUID));
+        assert_eq!(parsed.type_guid(), Some(&[7; 16]));
+        assert_eq!(parsed.attribute("opc.ua.da"), Some("1"));
+        assert_eq!(parsed.array_info(), &[AdsArrayInfo::new(1, 10)]);
+    }
+
+    #[test]
+    fn parses_table_of_entries() {
+        let mut table = sample().to_bytes().unwrap();
+        table.extend(
+            AdsSymbolEntry::new("GVL.bFlag", "BOOL", AdsDataTypeId::BigType, 0x4040, 0, 1)
+                .to_bytes()
+                .unwrap(),
+        );
+
+        let entries = AdsSymbolEntry::parse_table(&table).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].name(), "GVL.bFlag");
+    }
+
+    #[test]
+    fn skips_padding_after_entry() {
+        let mut bytes = sample().to_bytes().unwrap();
+        let padded = bytes.len() as u32 + 3;
+        bytes[..4].copy_from_slice(&padded.to_le_bytes());
+        bytes.extend_from_slice(&[0; 3]);
+
+        let entries = AdsSymbolEntry::parse_table(&bytes).unwrap();
+        assert_eq!(entries, vec![sample()]);
+    }
+
+    #[test]
+    fn rejects_invalid_entry_length() {
+        let mut bytes = sample().to_bytes().unwrap();
+        bytes[..4].copy_from_slice(&4u32.to_le_bytes());
+
+        assert_eq!(
+            AdsSymbolEntry::try_from(bytes.as_slice()),
+            Err(SymbolError::InvalidEntryLength(4))
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_strings() {
+        let mut bytes = sample().to_bytes().unwrap();
+        let name_len = 200u16;
+        bytes[24..26].copy_from_slice(&name_len.to_le_bytes());
+
+        assert!(matches!(
+            AdsSymbolEntry::try_from(bytes.as_slice()),
+            Err(SymbolError::UnexpectedLength { .. })
+        ));
+    }
+}
@@ -0,0 +1,12 @@
+/// Error returned when parsing or encoding symbol and data type information fails.
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+pub enum SymbolError {
+    #[error("Unexpected length: expected {expected} bytes, got {got}")]
+    UnexpectedLength { expected: usize, got: usize },
+    #[error("Invalid entry length: {0} bytes")]
+    InvalidEntryLength(u32),
+    #[error("String contains characters not supported by Windows-1252 encoding")]
+    EncodingError,
+    #[error("String too long: maximum {max} bytes, got {got}")]
+    StringTooLong { max: usize, got: usize },
+}
@@ -0,0 +1,231 @@
+use core::ops::{BitAnd, BitOr, BitOrAssign};
+use std::fmt;
+
+/// Flags of an [`AdsSymbolEntry`](super::AdsSymbolEntry) (`ADSSYMBOLFLAG_*`, 16-bit bitfield).
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct AdsSymbolFlags(pub u16);
+
+impl AdsSymbolFlags {
+    /// The symbol is persistent.
+    pub const PERSISTENT: u16 = 0x0001;
+    /// The symbol is a bit value (size and offset are given in bits).
+    pub const BIT_VALUE: u16 = 0x0002;
+    /// The symbol is a `REFERENCE TO`.
+    pub const REFERENCE_TO: u16 = 0x0004;
+    /// A 16-byte type GUID follows the comment.
+    pub const TYPE_GUID: u16 = 0x0008;
+    /// The symbol is a TwinCAT COM interface pointer.
+    pub const TC_COM_IFACE_PTR: u16 = 0x0010;
+    /// The symbol is read-only.
+    pub const READ_ONLY: u16 = 0x0020;
+    /// Interface method access.
+    pub const ITF_METHOD_ACCESS: u16 = 0x0040;
+    /// The symbol can be dereferenced by a method.
+    pub const METHOD_DEREF: u16 = 0x0080;
+    /// Task context mask (4 bits).
+    pub const CONTEXT_MASK: u16 = 0x0F00;
+    /// Attributes follow the type GUID.
+    pub const ATTRIBUTES: u16 = 0x1000;
+    /// The symbol is static.
+    pub const STATIC: u16 = 0x2000;
+    /// The symbol is initialised on reset.
+    pub const INIT_ON_RESET: u16 = 0x4000;
+    /// A 4-byte extended flags field follows the attributes.
+    pub const EXTENDED_FLAGS: u16 = 0x8000;
+
+    /// Creates a new set of flags from a raw u16.
+    pub const fn new(raw: u16) -> Self {
+        Self(raw)
+    }
+
+    /// True if all bits of `flag` are set.
+    pub const fn contains(&self, flag: u16) -> bool {
+        (self.0 & flag) == flag
+    }
+
+    /// Returns a copy with the bits of `flag` set or cleared.
+    pub const fn with(self, flag: u16, on: bool) -> Self {
+        if on {
+            Self(self.0 | flag)
+        } else {
+            Self(self.0 & !flag)
+        }
+    }
+
+    /// True if the symbol is read-only.
+    pub const fn is_read_only(&self) -> bool {
+        self.contains(Self::READ_ONLY)
+    }
+
+    /// True if the symbol is persistent.
+    pub const fn is_persistent(&self) -> bool {
+        self.contains(Self::PERSISTENT)
+    }
+
+    /// True if the symbol is a bit value.
+    pub const fn is_bit_value(&self) -> bool {
+        self.contains(Self::BIT_VALUE)
+    }
+
+    /// True if the symbol is a `REFERENCE TO`.
+    pub const fn is_reference(&self) -> bool {
+        self.contains(Self::REFERENCE_TO)
+    }
+
+    /// True if the symbol is static.
+    pub const fn is_static(&self) -> bool {
+        self.contains(Self::STATIC)
+    }
+
+    /// Returns the task context of the symbol.
+    pub const fn context(&self) -> u8 {
+        ((self.0 & Self::CONTEXT_MASK) >> 8) as u8
+    }
+}
+
+/// Flags of an [`AdsDatatypeEntry`](super::AdsDatatypeEntry) (`ADSDATATYPEFLAG_*`, 32-bit bitfield).
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct AdsDatatypeFlags(pub u32);
+
+impl AdsDatatypeFlags {
+    /// The entry describes a data type.
+    pub const DATATYPE: u32 = 0x0000_0001;
+    /// The entry describes a data item (a member of a data type).
+    pub const DATAITEM: u32 = 0x0000_0002;
+    /// The type is a `REFERENCE TO`.
+    pub const REFERENCE_TO: u32 = 0x0000_0004;
+    /// The type can be dereferenced by a method.
+    pub const METHOD_DEREF: u32 = 0x0000_0008;
+    /// Oversampling type.
+    pub const OVERSAMPLE: u32 = 0x0000_0010;
+    /// Size and offset are given in bits.
+    pub const BIT_VALUES: u32 = 0x0000_0020;
+    /// The item is a property.
+    pub const PROP_ITEM: u32 = 0x0000_0040;
+    /// A 16-byte type GUID follows the sub items.
+    pub const TYPE_GUID: u32 = 0x0000_0080;
+    /// The type is persistent.
+    pub const PERSISTENT: u32 = 0x0000_0100;
+    /// A copy mask of `size` bytes follows the type GUID.
+    pub const COPY_MASK: u32 = 0x0000_0200;
+    /// The type is a TwinCAT COM interface pointer.
+    pub const TC_COM_INTERFACE_PTR: u32 = 0x0000_0400;
+    /// Method information follows the copy mask.
+    pub const METHOD_INFOS: u32 = 0x0000_0800;
+    /// Attributes follow the method information.
+    pub const ATTRIBUTES: u32 = 0x0000_1000;
+    /// Enumeration values follow the attributes.
+    pub const ENUM_INFOS: u32 = 0x0000_2000;
+    /// The type is aligned.
+    pub const ALIGNED: u32 = 0x0001_0000;
+    /// The item is static.
+    pub const STATIC: u32 = 0x0002_0000;
+    /// The type contains `SP_LEVELS`.
+    pub const SP_LEVELS: u32 = 0x0004_0000;
+    /// Persistence is ignored.
+    pub const IGNORE_PERSIST: u32 = 0x0008_0000;
+    /// The type is an array of any size.
+    pub const ANY_SIZE_ARRAY: u32 = 0x0010_0000;
+    /// The type is a persistent data type.
+    pub const PERSIST_DT: u32 = 0x0020_0000;
+    /// The item is initialised on reset.
+    pub const INIT_ON_RESET: u32 = 0x0040_0000;
+
+    /// Creates a new set of flags from a raw u32.
+    pub const fn new(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    /// True if all bits of `flag` are set.
+    pub const fn contains(&self, flag: u32) -> bool {
+        (self.0 & flag) == flag
+    }
+
+    /// Returns a copy with the bits of `flag` set or cleared.
+    pub const fn with(self, flag: u32, on: bool) -> Self {
+        if on {
+            Self(self.0 | flag)
+        } else {
+            Self(self.0 & !flag)
+        }
+    }
+
+    /// True if size and offset are given in bits.
+    pub const fn is_bit_values(&self) -> bool {
+        self.contains(Self::BIT_VALUES)
+    }
+
+    /// True if the type is a `REFERENCE TO`.
+    pub const fn is_reference(&self) -> bool {
+        self.contains(Self::REFERENCE_TO)
+    }
+
+    /// True if the item is a property.
+    pub const fn is_property(&self) -> bool {
+        self.contains(Self::PROP_ITEM)
+    }
+}
+
+macro_rules! impl_flag_ops {
+    ($ty:ident, $raw:ty) => {
+        impl From<$raw> for $ty {
+            fn from(val: $raw) -> Self {
+                Self(val)
+            }
+        }
+
+        impl From<$ty> for $raw {
+            fn from(flags: $ty) -> Self {
+                flags.0
+            }
+        }
+
+        impl BitOr for $ty {
+            type Output = Self;
+            fn bitor(self, rhs: Self) -> Self {
+                Self(self.0 | rhs.0)
+            }
+        }
+
+        impl BitOrAssign for $ty {
+            fn bitor_assign(&mut self, rhs: Self) {
+                self.0 |= rhs.0;
+            }
+        }
+
+        impl BitAnd for $ty {
+            type Output = Self;
+            fn bitand(self, rhs: Self) -> Self {
+                Self(self.0 & rhs.0)
+            }
+        }
+
+        impl fmt::Debug for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}({:#X})", stringify!($ty), self.0)
+            }
+        }
+    };
+}
+
+impl_flag_ops!(AdsSymbolFlags, u16);
+impl_flag_ops!(AdsDatatypeFlags, u32);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symbol_flags_context_is_extracted() {
+        let flags = AdsSymbolFlags::new(0x0320);
+        assert!(flags.is_read_only());
+        assert_eq!(flags.context(), 3);
+    }
+
+    #[test]
+    fn with_sets_and_clears_bits() {
+        let flags = AdsDatatypeFlags::default().with(AdsDatatypeFlags::ATTRIBUTES, true);
+        assert!(flags.contains(AdsDatatypeFlags::ATTRIBUTES));
+        assert_eq!(flags.with(AdsDatatypeFlags::ATTRIBUTES, false).0, 0);
+    }
+}
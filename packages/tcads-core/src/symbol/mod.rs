@@ -0,0 +1,17 @@
+pub mod attribute;
+pub(crate) mod codec;
+pub mod datatype;
+pub mod entry;
+pub mod error;
+pub mod flags;
+pub mod type_id;
+pub mod upload_info;
+
+pub use attribute::{AdsArrayInfo, AdsAttribute};
+pub use codec::encode_symbol_name;
+pub use datatype::{AdsDatatypeEntry, AdsEnumInfo};
+pub use entry::AdsSymbolEntry;
+pub use error::SymbolError;
+pub use flags::{AdsDatatypeFlags, AdsSymbolFlags};
+pub use type_id::AdsDataTypeId;
+pub use upload_info::AdsSymbolUploadInfo;
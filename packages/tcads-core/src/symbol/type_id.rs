@@ -0,0 +1,108 @@
+/// The base data type identifier of a symbol or data type (`ADST_*`).
+#[derive(
+    serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub enum AdsDataTypeId {
+    /// `VOID`
+    Void,
+    /// `SINT`
+    Int8,
+    /// `USINT`, `BYTE`
+    UInt8,
+    /// `INT`
+    Int16,
+    /// `UINT`, `WORD`
+    UInt16,
+    /// `DINT`
+    Int32,
+    /// `UDINT`, `DWORD`, `TIME`, `DATE`, ...
+    UInt32,
+    /// `LINT`
+    Int64,
+    /// `ULINT`, `LWORD`, `LTIME`, ...
+    UInt64,
+    /// `REAL`
+    Real32,
+    /// `LREAL`
+    Real64,
+    /// 80-bit extended precision floating point.
+    Real80,
+    /// `STRING`
+    String,
+    /// `WSTRING`
+    WString,
+    /// `BIT`
+    Bit,
+    /// Structures, function blocks, arrays, enumerations and `BOOL`.
+    BigType,
+    /// Unknown/Custom type ID.
+    Unknown(u32),
+}
+
+impl AdsDataTypeId {
+    /// The length of the data type identifier in bytes.
+    pub const LENGTH: usize = 4;
+}
+
+impl From<u32> for AdsDataTypeId {
+    fn from(val: u32) -> Self {
+        match val {
+            0 => Self::Void,
+            2 => Self::Int16,
+            3 => Self::Int32,
+            4 => Self::Real32,
+            5 => Self::Real64,
+            16 => Self::Int8,
+            17 => Self::UInt8,
+            18 => Self::UInt16,
+            19 => Self::UInt32,
+            20 => Self::Int64,
+            21 => Self::UInt64,
+            30 => Self::String,
+            31 => Self::WString,
+            32 => Self::Real80,
+            33 => Self::Bit,
+            65 => Self::BigType,
+            n => Self::Unknown(n),
+        }
+    }
+}
+
+impl From<AdsDataTypeId> for u32 {
+    fn from(val: AdsDataTypeId) -> Self {
+        match val {
+            AdsDataTypeId::Void => 0,
+            AdsDataTypeId::Int16 => 2,
+            AdsDataTypeId::Int32 => 3,
+            AdsDataTypeId::Real32 => 4,
+            AdsDataTypeId::Real64 => 5,
+            AdsDataTypeId::Int8 => 16,
+            AdsDataTypeId::UInt8 => 17,
+            AdsDataTypeId::UInt16 => 18,
+            AdsDataTypeId::UInt32 => 19,
+            AdsDataTypeId::Int64 => 20,
+            AdsDataTypeId::UInt64 => 21,
+            AdsDataTypeId::String => 30,
+            AdsDataTypeId::WString => 31,
+            AdsDataTypeId::Real80 => 32,
+            AdsDataTypeId::Bit => 33,
+            AdsDataTypeId::BigType => 65,
+            AdsDataTypeId::Unknown(n) => n,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_known_and_unknown_ids() {
+        for raw in [
+            0, 2, 3, 4, 5, 16, 17, 18, 19, 20, 21, 30, 31, 32, 33, 65, 99,
+        ] {
+            assert_eq!(u32::from(AdsDataTypeId::from(raw)), raw);
+        }
+        assert_eq!(AdsDataTypeId::from(99), AdsDataTypeId::Unknown(99));
+    }
+}
@@ -0,0 +1,175 @@
+use super::SymbolError;
+
+/// Symbol and data type table sizes, read from
+/// [`SYM_UPLOADINFO2`](crate::ads::index_group::SYM_UPLOADINFO2).
+///
+/// Use [`symbol_length`](Self::symbol_length) and [`datatype_length`](Self::datatype_length)
+/// as the read lengths when uploading the tables from
+/// [`SYM_UPLOAD`](crate::ads::index_group::SYM_UPLOAD) and
+/// [`SYM_DT_UPLOAD`](crate::ads::index_group::SYM_DT_UPLOAD).
+///
+/// # Layout
+/// * **Symbol Count:** 4 bytes
+/// * **Symbol Table Length:** 4 bytes
+/// * **Data Type Count:** 4 bytes
+/// * **Data Type Table Length:** 4 bytes
+/// * **Max Dynamic Symbols:** 4 bytes
+/// * **Used Dynamic Symbols:** 4 bytes
+#[derive(
+    serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default,
+)]
+pub struct AdsSymbolUploadInfo {
+    symbol_count: u32,
+    symbol_length: u32,
+    datatype_count: u32,
+    datatype_length: u32,
+    max_dynamic_symbols: u32,
+    used_dynamic_symbols: u32,
+}
+
+impl AdsSymbolUploadInfo {
+    /// The length of the upload info in bytes.
+    ///
+    /// Newer runtimes may return a longer structure; the extra bytes are ignored.
+    pub const LENGTH: usize = 24;
+
+    /// Creates new upload info.
+    pub fn new(
+        symbol_count: u32,
+        symbol_length: u32,
+        datatype_count: u32,
+        datatype_length: u32,
+    ) -> Self {
+        Self {
+            symbol_count,
+            symbol_length,
+            datatype_count,
+            datatype_length,
+            max_dynamic_symbols: 0,
+            used_dynamic_symbols: 0,
+        }
+    }
+
+    /// Creates upload info from a 24-byte array.
+    pub fn from_bytes(bytes: [u8; Self::LENGTH]) -> Self {
+        Self::from(bytes)
+    }
+
+    /// Converts the upload info into a 24-byte array.
+    pub fn to_bytes(&self) -> [u8; Self::LENGTH] {
+        (*self).into()
+    }
+
+    /// Tries to parse upload info from a byte slice.
+    pub fn try_from_slice(bytes: &[u8]) -> Result<Self, SymbolError> {
+        Self::try_from(bytes)
+    }
+
+    /// Returns the number of symbols.
+    pub fn symbol_count(&self) -> u32 {
+        self.symbol_count
+    }
+
+    /// Returns the length of the symbol table in bytes.
+    pub fn symbol_length(&self) -> u32 {
+        self.symbol_length
+    }
+
+    /// Returns the number of data types.
+    pub fn datatype_count(&self) -> u32 {
+        self.datatype_count
+    }
+
+    /// Returns the length of the data type table in bytes.
+    pub fn datatype_length(&self) -> u32 {
+        self.datatype_length
+    }
+
+    /// Returns the maximum number of dynamic symbols.
+    pub fn max_dynamic_symbols(&self) -> u32 {
+        self.max_dynamic_symbols
+    }
+
+    /// Returns the number of dynamic symbols in use.
+    pub fn used_dynamic_symbols(&self) -> u32 {
+        self.used_dynamic_symbols
+    }
+}
+
+impl From<[u8; Self::LENGTH]> for AdsSymbolUploadInfo {
+    fn from(bytes: [u8; Self::LENGTH]) -> Self {
+        let field = |i: usize| u32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap());
+        Self {
+            symbol_count: field(0),
+            symbol_length: field(1),
+            datatype_count: field(2),
+            datatype_length: field(3),
+            max_dynamic_symbols: field(4),
+            used_dynamic_symbols: field(5),
+        }
+    }
+}
+
+impl From<AdsSymbolUploadInfo> for [u8; AdsSymbolUploadInfo::LENGTH] {
+    fn from(info: AdsSymbolUploadInfo) -> Self {
+        let mut buf = [0u8; AdsSymbolUploadInfo::LENGTH];
+        let fields = [
+            info.symbol_count,
+            info.symbol_length,
+            info.datatype_count,
+            info.datatype_length,
+            info.max_dynamic_symbols,
+            info.used_dynamic_symbols,
+        ];
+        for (chunk, field) in buf.chunks_exact_mut(4).zip(fields) {
+            chunk.copy_from_slice(&field.to_le_bytes());
+        }
+        buf
+    }
+}
+
+impl TryFrom<&[u8]> for AdsSymbolUploadInfo {
+    type Error = SymbolError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() < Self::LENGTH {
+            return Err(SymbolError::UnexpectedLength {
+                expected: Self::LENGTH,
+                got: bytes.len(),
+            });
+        }
+        Ok(Self::from(
+            <[u8; Self::LENGTH]>::try_from(&bytes[..Self::LENGTH]).unwrap(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_bytes() {
+        let info = AdsSymbolUploadInfo::new(12, 3400, 5, 8000);
+        assert_eq!(AdsSymbolUploadInfo::from_bytes(info.to_bytes()), info);
+    }
+
+    #[test]
+    fn ignores_trailing_bytes() {
+        let mut bytes = AdsSymbolUploadInfo::new(1, 2, 3, 4).to_bytes().to_vec();
+        bytes.extend_from_slice(&[0xFF; 40]);
+        let info = AdsSymbolUploadInfo::try_from_slice(&bytes).unwrap();
+        assert_eq!(info.datatype_length(), 4);
+    }
+
+    #[test]
+    fn rejects_short_input() {
+        assert_eq!(
+            AdsSymbolUploadInfo::try_from_slice(&[0; 8]),
+            Err(SymbolError::UnexpectedLength {
+                expected: 24,
+                got: 8
+            })
+        );
+    }
+}
@@ -0,0 +1,325 @@
+use super::error::UdpError;
+use super::message::{UdpMessage, UdpServiceId, UdpTag, tag};
+use crate::ams::{AmsAddr, AmsNetId};
+use std::fmt;
+
+/// TwinCAT version reported by [discovery](DiscoverResponse).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct TcVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub build: u16,
+}
+
+impl TcVersion {
+    /// Length of the encoded version in bytes.
+    pub const LENGTH: usize = 4;
+
+    /// Creates a new version.
+    pub const fn new(major: u8, minor: u8, build: u16) -> Self {
+        Self {
+            major,
+            minor,
+            build,
+        }
+    }
+
+    /// Converts the version into a byte array.
+    pub fn to_bytes(&self) -> [u8; Self::LENGTH] {
+        let [b0, b1] = self.build.to_le_bytes();
+        [self.major, self.minor, b0, b1]
+    }
+}
+
+impl fmt::Display for TcVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.build)
+    }
+}
+
+/// Operating system version reported by [discovery](DiscoverResponse).
+///
+/// Encoded like the Windows `OSVERSIONINFO` structure: five little-endian `u32`s (structure
+/// size, major, minor, build and platform) followed by an optional UTF-16 service pack
+/// string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct OsVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub build: u32,
+    pub platform_id: u32,
+    pub service_pack: String,
+}
+
+impl OsVersion {
+    /// Length of the fixed part of the encoded version in bytes.
+    pub const MIN_LENGTH: usize = 20;
+
+    /// Encodes the version.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let service_pack = self.service_pack.encode_utf16().chain([0]);
+        let service_pack: Vec<u8> = service_pack.flat_map(u16::to_le_bytes).collect();
+        let size = (Self::MIN_LENGTH + service_pack.len()) as u32;
+
+        let mut buf = Vec::with_capacity(size as usize);
+        for value in [size, self.major, self.minor, self.build, self.platform_id] {
+            buf.extend_from_slice(&value.to_le_bytes());
+        }
+        buf.extend_from_slice(&service_pack);
+        buf
+    }
+
+    fn parse(tag: &UdpTag) -> Result<Self, UdpError> {
+        let data = tag.data();
+        if data.len() < Self::MIN_LENGTH {
+            return Err(tag.invalid());
+        }
+        let u32_at = |i: usize| u32::from_le_bytes(data[i..i + 4].try_into().unwrap());
+        let service_pack: Vec<u16> = data[Self::MIN_LENGTH..]
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .take_while(|&c| c != 0)
+            .collect();
+
+        Ok(Self {
+            major: u32_at(4),
+            minor: u32_at(8),
+            build: u32_at(12),
+            platform_id: u32_at(16),
+            service_pack: String::from_utf16_lossy(&service_pack),
+        })
+    }
+}
+
+impl fmt::Display for OsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.build)?;
+        if !self.service_pack.is_empty() {
+            write!(f, " {}", self.service_pack)?;
+        }
+        Ok(())
+    }
+}
+
+/// Represents an AMS/UDP discovery request ([`UdpServiceId::Discover`]).
+///
+/// Broadcast to port [`ADS_UDP_PORT`](super::ADS_UDP_PORT) to find TwinCAT systems. Every
+/// system receiving it answers with a [`DiscoverResponse`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct DiscoverRequest {
+    invoke_id: u32,
+    source: AmsAddr,
+}
+
+impl DiscoverRequest {
+    /// Creates a new discovery request sent from `source`.
+    pub fn new(invoke_id: u32, source: AmsAddr) -> Self {
+        Self { invoke_id, source }
+    }
+
+    /// Returns the invoke ID.
+    pub fn invoke_id(&self) -> u32 {
+        self.invoke_id
+    }
+
+    /// Returns the address of the sender.
+    pub fn source(&self) -> &AmsAddr {
+        &self.source
+    }
+
+    /// Converts the request into an [`UdpMessage`].
+    pub fn to_message(&self) -> UdpMessage {
+        UdpMessage::request(UdpServiceId::Discover, self.invoke_id, self.source)
+    }
+
+    /// Encodes the request.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_message().to_bytes()
+    }
+}
+
+impl TryFrom<&UdpMessage> for DiscoverRequest {
+    type Error = UdpError;
+
+    fn try_from(value: &UdpMessage) -> Result<Self, Self::Error> {
+        let expected = UdpServiceId::Discover.into();
+        if value.is_response() || value.service() != UdpServiceId::Discover {
+            return Err(UdpError::UnexpectedService {
+                expected,
+                got: value.service().into(),
+            });
+        }
+        Ok(Self::new(value.invoke_id(), *value.addr()))
+    }
+}
+
+/// Represents an AMS/UDP discovery response ([`UdpServiceId::Discover`]).
+///
+/// Identifies a TwinCAT system: its AMS Net ID, host name, TwinCAT and operating system
+/// versions, and, from TwinCAT 3.1.4024 on, its hardware fingerprint.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DiscoverResponse {
+    invoke_id: u32,
+    addr: AmsAddr,
+    host_name: String,
+    tc_version: TcVersion,
+    os_version: OsVersion,
+    fingerprint: Option<String>,
+}
+
+impl DiscoverResponse {
+    /// Creates a new discovery response for the system at `addr`.
+    pub fn new(
+        invoke_id: u32,
+        addr: AmsAddr,
+        host_name: impl Into<String>,
+        tc_version: TcVersion,
+        os_version: OsVersion,
+    ) -> Self {
+        Self {
+            invoke_id,
+            addr,
+            host_name: host_name.into(),
+            tc_version,
+            os_version,
+            fingerprint: None,
+        }
+    }
+
+    /// Sets the hardware fingerprint.
+    pub fn with_fingerprint(mut self, fingerprint: impl Into<String>) -> Self {
+        self.fingerprint = Some(fingerprint.into());
+        self
+    }
+
+    /// Parses a response from a datagram.
+    pub fn parse(bytes: &[u8]) -> Result<Self, UdpError> {
+        Self::try_from(&UdpMessage::parse(bytes)?)
+    }
+
+    /// Returns the invoke ID of the request answered.
+    pub fn invoke_id(&self) -> u32 {
+        self.invoke_id
+    }
+
+    /// Returns the AMS address the system answered from.
+    pub fn addr(&self) -> &AmsAddr {
+        &self.addr
+    }
+
+    /// Returns the AMS Net ID of the system.
+    pub fn net_id(&self) -> AmsNetId {
+        self.addr.net_id()
+    }
+
+    /// Returns the host name of the system.
+    pub fn host_name(&self) -> &str {
+        &self.host_name
+    }
+
+    /// Returns the TwinCAT version.
+    pub fn tc_version(&self) -> TcVersion {
+        self.tc_version
+    }
+
+    /// Returns the operating system version.
+    pub fn os_version(&self) -> &OsVersion {
+        &self.os_version
+    }
+
+    /// Returns the hardware fingerprint, if reported.
+    pub fn fingerprint(&self) -> Option<&str> {
+        self.fingerprint.as_deref()
+    }
+
+    /// Converts the response into an [`UdpMessage`].
+    pub fn to_message(&self) -> UdpMessage {
+        let message = UdpMessage::response(UdpServiceId::Discover, self.invoke_id, self.addr)
+            .with_tag(UdpTag::string(tag::HOST, &self.host_name))
+            .with_tag(UdpTag::new(tag::TC_VERSION, self.tc_version.to_bytes()))
+            .with_tag(UdpTag::new(tag::OS_VERSION, self.os_version.to_bytes()));
+        match &self.fingerprint {
+            Some(fingerprint) => message.with_tag(UdpTag::string(tag::FINGERPRINT, fingerprint)),
+            None => message,
+        }
+    }
+
+    /// Encodes the response.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_message().to_bytes()
+    }
+}
+
+impl TryFrom<&UdpMessage> for DiscoverResponse {
+    type Error = UdpError;
+
+    fn try_from(value: &UdpMessage) -> Result<Self, Self::Error> {
+        value.expect_response(UdpServiceId::Discover)?;
+
+        let version = value.require_tag(tag::TC_VERSION)?;
+        let tc_version = match version.data() {
+            [major, minor, b0, b1, ..] => {
+                TcVersion::new(*major, *minor, u16::from_le_bytes([*b0, *b1]))
+            }
+            _ => return Err(version.invalid()),
+        };
+
+        Ok(Self {
+            invoke_id: value.invoke_id(),
+            addr: *value.addr(),
+            host_name: value.require_tag(tag::HOST)?.as_string(),
+            tc_version,
+            os_version: OsVersion::parse(value.require_tag(tag::OS_VERSION)?)?,
+            fingerprint: value.tag(tag::FINGERPRINT).map(UdpTag::as_string),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_bytes() {
+        let source = AmsAddr::new(AmsNetId::new(192, 168, 0, 10, 1, 1), 10000);
+        let bytes = DiscoverRequest::new(0, source).to_bytes();
+        assert_eq!(
+            bytes,
+            [
+                0x03, 0x66, 0x14, 0x71, 0, 0, 0, 0, 1, 0, 0, 0, 192, 168, 0, 10, 1, 1, 0x10, 0x27,
+                0, 0, 0, 0
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_response() {
+        let addr = AmsAddr::new(AmsNetId::new(5, 80, 12, 34, 1, 1), 10000);
+        let os_version = OsVersion {
+            major: 10,
+            minor: 0,
+            build: 19045,
+            platform_id: 2,
+            service_pack: "LTSC".into(),
+        };
+        let response =
+            DiscoverResponse::new(3, addr, "CX-0A1B2C", TcVersion::new(3, 1, 4024), os_version)
+                .with_fingerprint("8c2c1a0f");
+
+        let parsed = DiscoverResponse::parse(&response.to_bytes()).unwrap();
+        assert_eq!(parsed, response);
+        assert_eq!(parsed.net_id(), addr.net_id());
+        assert_eq!(parsed.tc_version().to_string(), "3.1.4024");
+        assert_eq!(parsed.os_version().to_string(), "10.0.19045 LTSC");
+    }
+
+    #[test]
+    fn rejects_requests() {
+        let source = AmsAddr::new(AmsNetId::new(192, 168, 0, 10, 1, 1), 10000);
+        let bytes = DiscoverRequest::new(0, source).to_bytes();
+        assert!(matches!(
+            DiscoverResponse::parse(&bytes),
+            Err(UdpError::UnexpectedService { .. })
+        ));
+    }
+}
@@ -0,0 +1,14 @@
+/// Error returned when parsing an AMS/UDP message fails.
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+pub enum UdpError {
+    #[error("Unexpected length: expected {expected} bytes, got {got}")]
+    UnexpectedLength { expected: usize, got: usize },
+    #[error("Invalid cookie: {0:#010x}")]
+    InvalidCookie(u32),
+    #[error("Unexpected service: expected {expected:#010x}, got {got:#010x}")]
+    UnexpectedService { expected: u32, got: u32 },
+    #[error("Missing tag {0:#06x}")]
+    MissingTag(u16),
+    #[error("Invalid tag {tag:#06x} of {len} bytes")]
+    InvalidTag { tag: u16, len: usize },
+}
@@ -0,0 +1,355 @@
+use super::error::UdpError;
+use crate::ams::{AmsAddr, AmsNetId};
+
+/// Services of the AMS/UDP protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum UdpServiceId {
+    /// Asks for the identity of a system (1).
+    Discover,
+    /// Adds a route to the remote router (6).
+    AddRoute,
+    /// Deletes a route from the remote router (7).
+    DeleteRoute,
+    /// Unknown service.
+    Unknown(u32),
+}
+
+impl UdpServiceId {
+    /// Bit set in the service ID of responses.
+    pub const RESPONSE: u32 = 0x8000_0000;
+}
+
+impl From<u32> for UdpServiceId {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => Self::Discover,
+            6 => Self::AddRoute,
+            7 => Self::DeleteRoute,
+            n => Self::Unknown(n),
+        }
+    }
+}
+
+impl From<UdpServiceId> for u32 {
+    fn from(value: UdpServiceId) -> Self {
+        match value {
+            UdpServiceId::Discover => 1,
+            UdpServiceId::AddRoute => 6,
+            UdpServiceId::DeleteRoute => 7,
+            UdpServiceId::Unknown(n) => n,
+        }
+    }
+}
+
+/// Tag IDs of the AMS/UDP protocol.
+pub mod tag {
+    /// ADS return code of a request (`u32`).
+    pub const STATUS: u16 = 0x0001;
+    /// Password of the user adding a route (string).
+    pub const PASSWORD: u16 = 0x0002;
+    /// TwinCAT version (major `u8`, minor `u8`, build `u16`).
+    pub const TC_VERSION: u16 = 0x0003;
+    /// Operating system version.
+    pub const OS_VERSION: u16 = 0x0004;
+    /// Host name, or the address a route points at (string).
+    pub const HOST: u16 = 0x0005;
+    /// AMS Net ID of a route (6 bytes).
+    pub const NET_ID: u16 = 0x0007;
+    /// Route options (`u32`).
+    pub const OPTIONS: u16 = 0x0009;
+    /// Name of a route (string).
+    pub const ROUTE_NAME: u16 = 0x000C;
+    /// User name to authenticate with (string).
+    pub const USER_NAME: u16 = 0x000D;
+    /// Hardware fingerprint (string).
+    pub const FINGERPRINT: u16 = 0x0012;
+}
+
+/// A tag of an [`UdpMessage`]: an ID followed by a length-prefixed value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UdpTag {
+    id: u16,
+    data: Vec<u8>,
+}
+
+impl UdpTag {
+    /// Creates a tag with a raw value.
+    pub fn new(id: u16, data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            id,
+            data: data.into(),
+        }
+    }
+
+    /// Creates a tag holding a NUL-terminated string.
+    pub fn string(id: u16, value: &str) -> Self {
+        let mut data = value.as_bytes().to_vec();
+        data.push(0);
+        Self::new(id, data)
+    }
+
+    /// Creates a tag holding an [`AmsNetId`].
+    pub fn net_id(id: u16, value: AmsNetId) -> Self {
+        Self::new(id, value.to_bytes())
+    }
+
+    /// Creates a tag holding a little-endian `u32`.
+    pub fn u32(id: u16, value: u32) -> Self {
+        Self::new(id, value.to_le_bytes())
+    }
+
+    /// Returns the tag ID.
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// Returns the raw value.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Decodes the value as a NUL-terminated Windows-1252 string.
+    pub fn as_string(&self) -> String {
+        let len = self
+            .data
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(self.data.len());
+        let (cow, _, _) = encoding_rs::WINDOWS_1252.decode(&self.data[..len]);
+        cow.into_owned()
+    }
+
+    /// Decodes the value as a little-endian `u32`.
+    pub fn as_u32(&self) -> Result<u32, UdpError> {
+        let bytes = self
+            .data
+            .as_slice()
+            .try_into()
+            .map_err(|_| self.invalid())?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    /// Decodes the value as an [`AmsNetId`].
+    pub fn as_net_id(&self) -> Result<AmsNetId, UdpError> {
+        AmsNetId::try_from(self.data.as_slice()).map_err(|_| self.invalid())
+    }
+
+    pub(crate) fn invalid(&self) -> UdpError {
+        UdpError::InvalidTag {
+            tag: self.id,
+            len: self.data.len(),
+        }
+    }
+}
+
+/// A message of the AMS/UDP protocol, sent to port [`ADS_UDP_PORT`](super::ADS_UDP_PORT).
+///
+/// Used for discovering systems and managing their routes without an AMS route.
+///
+/// # Protocol Details
+/// * **Bytes 0-3:** [Cookie](Self::COOKIE) (Little Endian)
+/// * **Bytes 4-7:** Invoke ID (Little Endian)
+/// * **Bytes 8-11:** [Service ID](UdpServiceId), with [`RESPONSE`](UdpServiceId::RESPONSE)
+///   set in responses (Little Endian)
+/// * **Bytes 12-19:** [`AmsAddr`] of the sender
+/// * **Bytes 20-23:** Number of tags (Little Endian)
+/// * **Bytes 24..:** Tags, each a `u16` ID and `u16` length followed by the value
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UdpMessage {
+    invoke_id: u32,
+    service: u32,
+    addr: AmsAddr,
+    tags: Vec<UdpTag>,
+}
+
+impl UdpMessage {
+    /// Magic number starting every message.
+    pub const COOKIE: u32 = 0x7114_6603;
+    /// Length of the fixed part of a message in bytes.
+    pub const HEADER_LENGTH: usize = 24;
+
+    /// Creates a request for `service` sent from `addr`.
+    pub fn request(service: UdpServiceId, invoke_id: u32, addr: AmsAddr) -> Self {
+        Self {
+            invoke_id,
+            service: service.into(),
+            addr,
+            tags: Vec::new(),
+        }
+    }
+
+    /// Creates a response to `service` sent from `addr`.
+    pub fn response(service: UdpServiceId, invoke_id: u32, addr: AmsAddr) -> Self {
+        Self {
+            invoke_id,
+            service: u32::from(service) | UdpServiceId::RESPONSE,
+            addr,
+            tags: Vec::new(),
+        }
+    }
+
+    /// Appends `tag`.
+    pub fn with_tag(mut self, tag: UdpTag) -> Self {
+        self.tags.push(tag);
+        self
+    }
+
+    /// Returns the invoke ID.
+    pub fn invoke_id(&self) -> u32 {
+        self.invoke_id
+    }
+
+    /// Returns the service, without the response bit.
+    pub fn service(&self) -> UdpServiceId {
+        (self.service & !UdpServiceId::RESPONSE).into()
+    }
+
+    /// True if the message is a response.
+    pub fn is_response(&self) -> bool {
+        self.service & UdpServiceId::RESPONSE != 0
+    }
+
+    /// Returns the address of the sender.
+    pub fn addr(&self) -> &AmsAddr {
+        &self.addr
+    }
+
+    /// Returns the tags, in order.
+    pub fn tags(&self) -> &[UdpTag] {
+        &self.tags
+    }
+
+    /// Returns the first tag with `id`.
+    pub fn tag(&self, id: u16) -> Option<&UdpTag> {
+        self.tags.iter().find(|t| t.id == id)
+    }
+
+    /// Returns the first tag with `id`, or [`UdpError::MissingTag`].
+    pub fn require_tag(&self, id: u16) -> Result<&UdpTag, UdpError> {
+        self.tag(id).ok_or(UdpError::MissingTag(id))
+    }
+
+    /// Checks that the message is a response to `service`.
+    pub fn expect_response(&self, service: UdpServiceId) -> Result<(), UdpError> {
+        let expected = u32::from(service) | UdpServiceId::RESPONSE;
+        match self.service == expected {
+            true => Ok(()),
+            false => Err(UdpError::UnexpectedService {
+                expected,
+                got: self.service,
+            }),
+        }
+    }
+
+    /// Encodes the message.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let len = self.tags.iter().map(|t| 4 + t.data.len()).sum::<usize>();
+        let mut buf = Vec::with_capacity(Self::HEADER_LENGTH + len);
+        buf.extend_from_slice(&Self::COOKIE.to_le_bytes());
+        buf.extend_from_slice(&self.invoke_id.to_le_bytes());
+        buf.extend_from_slice(&self.service.to_le_bytes());
+        buf.extend_from_slice(&self.addr.to_bytes());
+        buf.extend_from_slice(&(self.tags.len() as u32).to_le_bytes());
+        for tag in &self.tags {
+            buf.extend_from_slice(&tag.id.to_le_bytes());
+            buf.extend_from_slice(&(tag.data.len() as u16).to_le_bytes());
+            buf.extend_from_slice(&tag.data);
+        }
+        buf
+    }
+
+    /// Parses a message from a datagram.
+    pub fn parse(bytes: &[u8]) -> Result<Self, UdpError> {
+        if bytes.len() < Self::HEADER_LENGTH {
+            return Err(UdpError::UnexpectedLength {
+                expected: Self::HEADER_LENGTH,
+                got: bytes.len(),
+            });
+        }
+        let u32_at = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+
+        let cookie = u32_at(0);
+        if cookie != Self::COOKIE {
+            return Err(UdpError::InvalidCookie(cookie));
+        }
+        let addr = AmsAddr::try_from(&bytes[12..20]).map_err(|_| UdpError::UnexpectedLength {
+            expected: Self::HEADER_LENGTH,
+            got: bytes.len(),
+        })?;
+
+        let count = u32_at(20) as usize;
+        let mut tags = Vec::with_capacity(count.min(64));
+        let mut rest = &bytes[Self::HEADER_LENGTH..];
+        for _ in 0..count {
+            if rest.len() < 4 {
+                return Err(UdpError::UnexpectedLength {
+                    expected: bytes.len() - rest.len() + 4,
+                    got: bytes.len(),
+                });
+            }
+            let id = u16::from_le_bytes([rest[0], rest[1]]);
+            let len = u16::from_le_bytes([rest[2], rest[3]]) as usize;
+            if rest.len() < 4 + len {
+                return Err(UdpError::UnexpectedLength {
+                    expected: bytes.len() - rest.len() + 4 + len,
+                    got: bytes.len(),
+                });
+            }
+            tags.push(UdpTag::new(id, &rest[4..4 + len]));
+            rest = &rest[4 + len..];
+        }
+
+        Ok(Self {
+            invoke_id: u32_at(4),
+            service: u32_at(8),
+            addr,
+            tags,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let addr = AmsAddr::new(AmsNetId::new(10, 0, 0, 1, 1, 1), 10000);
+        let message = UdpMessage::response(UdpServiceId::AddRoute, 9, addr)
+            .with_tag(UdpTag::u32(tag::STATUS, 0))
+            .with_tag(UdpTag::string(tag::HOST, "plc"));
+
+        let bytes = message.to_bytes();
+        assert_eq!(bytes[..4], [0x03, 0x66, 0x14, 0x71]);
+        assert_eq!(bytes[8..12], [0x06, 0x00, 0x00, 0x80]);
+
+        let parsed = UdpMessage::parse(&bytes).unwrap();
+        assert_eq!(parsed, message);
+        assert!(parsed.is_response());
+        assert_eq!(parsed.service(), UdpServiceId::AddRoute);
+        assert_eq!(parsed.require_tag(tag::HOST).unwrap().as_string(), "plc");
+        assert_eq!(
+            parsed.require_tag(tag::NET_ID),
+            Err(UdpError::MissingTag(tag::NET_ID))
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_tags() {
+        let addr = AmsAddr::new(AmsNetId::new(10, 0, 0, 1, 1, 1), 10000);
+        let bytes = UdpMessage::request(UdpServiceId::Discover, 0, addr)
+            .with_tag(UdpTag::string(tag::HOST, "plc"))
+            .to_bytes();
+        assert!(matches!(
+            UdpMessage::parse(&bytes[..bytes.len() - 1]),
+            Err(UdpError::UnexpectedLength { .. })
+        ));
+
+        let mut bytes = bytes;
+        bytes[0] = 0;
+        assert!(matches!(
+            UdpMessage::parse(&bytes),
+            Err(UdpError::InvalidCookie(_))
+        ));
+    }
+}
@@ -0,0 +1,17 @@
+pub mod discover;
+pub mod error;
+pub mod message;
+pub mod route;
+
+pub use discover::{DiscoverRequest, DiscoverResponse, OsVersion, TcVersion};
+pub use error::UdpError;
+pub use message::{UdpMessage, UdpServiceId, UdpTag, tag};
+pub use route::{AddRouteRequest, DeleteRouteRequest, RouteResponse};
+
+use crate::ams::AmsPort;
+
+/// UDP port TwinCAT systems answer AMS/UDP messages on.
+pub const ADS_UDP_PORT: u16 = 48899;
+
+/// AMS port of the system service, which AMS/UDP messages are sent from and to.
+pub const SYSTEM_SERVICE_PORT: AmsPort = 10000;
@@ -0,0 +1,372 @@
+use super::SYSTEM_SERVICE_PORT;
+use super::error::UdpError;
+use super::message::{UdpMessage, UdpServiceId, UdpTag, tag};
+use crate::ads::AdsReturnCode;
+use crate::ams::{AmsAddr, AmsNetId, AmsRoute};
+
+/// Bit of the [options tag](tag::OPTIONS) marking a route as temporary.
+pub const OPTION_TEMPORARY: u32 = 0x0000_0001;
+
+/// Represents an AMS/UDP Add Route request ([`UdpServiceId::AddRoute`]).
+///
+/// Asks the system receiving it to add a route back to the sender, authenticated with the
+/// credentials of a user on that system. The route is named and addressed as given by an
+/// [`AmsRoute`], and is temporary if the route has the
+/// [`TEMPORARY`](crate::ams::AmsRouteFlags::TEMPORARY) flag.
+///
+/// # Protocol Details
+/// * **Tags:** [`ROUTE_NAME`](tag::ROUTE_NAME), [`NET_ID`](tag::NET_ID),
+///   [`USER_NAME`](tag::USER_NAME), [`PASSWORD`](tag::PASSWORD), [`HOST`](tag::HOST), and
+///   [`OPTIONS`](tag::OPTIONS) for temporary routes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AddRouteRequest {
+    invoke_id: u32,
+    route_name: String,
+    net_id: AmsNetId,
+    host: String,
+    user_name: String,
+    password: String,
+    temporary: bool,
+}
+
+impl AddRouteRequest {
+    /// Creates a request to add `route`, authenticating as `user_name`.
+    pub fn new(
+        invoke_id: u32,
+        route: &AmsRoute,
+        user_name: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        Self {
+            invoke_id,
+            route_name: route.name().to_string(),
+            net_id: route.net_id(),
+            host: route.host().to_string(),
+            user_name: user_name.into(),
+            password: password.into(),
+            temporary: route.flags().is_temporary(),
+        }
+    }
+
+    /// Returns the invoke ID.
+    pub fn invoke_id(&self) -> u32 {
+        self.invoke_id
+    }
+
+    /// Returns the name of the route.
+    pub fn route_name(&self) -> &str {
+        &self.route_name
+    }
+
+    /// Returns the net ID the route leads to.
+    pub fn net_id(&self) -> AmsNetId {
+        self.net_id
+    }
+
+    /// Returns the host name or IP address the route leads to.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Returns the user name to authenticate as.
+    pub fn user_name(&self) -> &str {
+        &self.user_name
+    }
+
+    /// Returns the password to authenticate with.
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+
+    /// True if the route only lasts until the remote router restarts.
+    pub fn is_temporary(&self) -> bool {
+        self.temporary
+    }
+
+    /// Converts the request into an [`UdpMessage`], sent from the system service of the
+    /// route's net ID.
+    pub fn to_message(&self) -> UdpMessage {
+        let source = AmsAddr::new(self.net_id, SYSTEM_SERVICE_PORT);
+        let message = UdpMessage::request(UdpServiceId::AddRoute, self.invoke_id, source)
+            .with_tag(UdpTag::string(tag::ROUTE_NAME, &self.route_name))
+            .with_tag(UdpTag::net_id(tag::NET_ID, self.net_id))
+            .with_tag(UdpTag::string(tag::USER_NAME, &self.user_name))
+            .with_tag(UdpTag::string(tag::PASSWORD, &self.password))
+            .with_tag(UdpTag::string(tag::HOST, &self.host));
+        match self.temporary {
+            true => message.with_tag(UdpTag::u32(tag::OPTIONS, OPTION_TEMPORARY)),
+            false => message,
+        }
+    }
+
+    /// Encodes the request.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_message().to_bytes()
+    }
+}
+
+impl TryFrom<&UdpMessage> for AddRouteRequest {
+    type Error = UdpError;
+
+    fn try_from(value: &UdpMessage) -> Result<Self, Self::Error> {
+        expect_request(value, UdpServiceId::AddRoute)?;
+        let options = match value.tag(tag::OPTIONS) {
+            Some(options) => options.as_u32()?,
+            None => 0,
+        };
+
+        Ok(Self {
+            invoke_id: value.invoke_id(),
+            route_name: value.require_tag(tag::ROUTE_NAME)?.as_string(),
+            net_id: value.require_tag(tag::NET_ID)?.as_net_id()?,
+            host: value.require_tag(tag::HOST)?.as_string(),
+            user_name: value.require_tag(tag::USER_NAME)?.as_string(),
+            password: value.require_tag(tag::PASSWORD)?.as_string(),
+            temporary: options & OPTION_TEMPORARY != 0,
+        })
+    }
+}
+
+/// Represents an AMS/UDP Delete Route request ([`UdpServiceId::DeleteRoute`]).
+///
+/// Asks the system receiving it to delete the route named `route_name`, authenticated with
+/// the credentials of a user on that system.
+///
+/// # Protocol Details
+/// * **Tags:** [`ROUTE_NAME`](tag::ROUTE_NAME), [`USER_NAME`](tag::USER_NAME) and
+///   [`PASSWORD`](tag::PASSWORD).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeleteRouteRequest {
+    invoke_id: u32,
+    source: AmsAddr,
+    route_name: String,
+    user_name: String,
+    password: String,
+}
+
+impl DeleteRouteRequest {
+    /// Creates a request to delete the route named `route_name`, sent from `source`.
+    pub fn new(
+        invoke_id: u32,
+        source: AmsAddr,
+        route_name: impl Into<String>,
+        user_name: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        Self {
+            invoke_id,
+            source,
+            route_name: route_name.into(),
+            user_name: user_name.into(),
+            password: password.into(),
+        }
+    }
+
+    /// Returns the invoke ID.
+    pub fn invoke_id(&self) -> u32 {
+        self.invoke_id
+    }
+
+    /// Returns the address of the sender.
+    pub fn source(&self) -> &AmsAddr {
+        &self.source
+    }
+
+    /// Returns the name of the route to delete.
+    pub fn route_name(&self) -> &str {
+        &self.route_name
+    }
+
+    /// Returns the user name to authenticate as.
+    pub fn user_name(&self) -> &str {
+        &self.user_name
+    }
+
+    /// Returns the password to authenticate with.
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+
+    /// Converts the request into an [`UdpMessage`].
+    pub fn to_message(&self) -> UdpMessage {
+        UdpMessage::request(UdpServiceId::DeleteRoute, self.invoke_id, self.source)
+            .with_tag(UdpTag::string(tag::ROUTE_NAME, &self.route_name))
+            .with_tag(UdpTag::string(tag::USER_NAME, &self.user_name))
+            .with_tag(UdpTag::string(tag::PASSWORD, &self.password))
+    }
+
+    /// Encodes the request.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_message().to_bytes()
+    }
+}
+
+impl TryFrom<&UdpMessage> for DeleteRouteRequest {
+    type Error = UdpError;
+
+    fn try_from(value: &UdpMessage) -> Result<Self, Self::Error> {
+        expect_request(value, UdpServiceId::DeleteRoute)?;
+
+        Ok(Self {
+            invoke_id: value.invoke_id(),
+            source: *value.addr(),
+            route_name: value.require_tag(tag::ROUTE_NAME)?.as_string(),
+            user_name: value.require_tag(tag::USER_NAME)?.as_string(),
+            password: value.require_tag(tag::PASSWORD)?.as_string(),
+        })
+    }
+}
+
+/// Represents the response to an [`AddRouteRequest`] or [`DeleteRouteRequest`].
+///
+/// # Protocol Details
+/// * **Tags:** [`STATUS`](tag::STATUS), the ADS return code of the request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RouteResponse {
+    invoke_id: u32,
+    service: UdpServiceId,
+    addr: AmsAddr,
+    result: AdsReturnCode,
+}
+
+impl RouteResponse {
+    /// Creates a response to `service` from the system at `addr`.
+    pub fn new(
+        invoke_id: u32,
+        service: UdpServiceId,
+        addr: AmsAddr,
+        result: AdsReturnCode,
+    ) -> Self {
+        Self {
+            invoke_id,
+            service,
+            addr,
+            result,
+        }
+    }
+
+    /// Parses a response from a datagram.
+    pub fn parse(bytes: &[u8]) -> Result<Self, UdpError> {
+        Self::try_from(&UdpMessage::parse(bytes)?)
+    }
+
+    /// Returns the invoke ID of the request answered.
+    pub fn invoke_id(&self) -> u32 {
+        self.invoke_id
+    }
+
+    /// Returns the service of the request answered.
+    pub fn service(&self) -> UdpServiceId {
+        self.service
+    }
+
+    /// Returns the AMS address the system answered from.
+    pub fn addr(&self) -> &AmsAddr {
+        &self.addr
+    }
+
+    /// Returns the ADS return code of the request.
+    pub fn result(&self) -> AdsReturnCode {
+        self.result
+    }
+
+    /// True if the request was rejected because of its credentials.
+    pub fn is_authentication_failure(&self) -> bool {
+        matches!(
+            self.result,
+            AdsReturnCode::AdsErrDeviceInvalidAccess | AdsReturnCode::AdsErrDeviceAccessDenied
+        )
+    }
+
+    /// Converts the response into an [`UdpMessage`].
+    pub fn to_message(&self) -> UdpMessage {
+        UdpMessage::response(self.service, self.invoke_id, self.addr)
+            .with_tag(UdpTag::u32(tag::STATUS, self.result.into()))
+    }
+
+    /// Encodes the response.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_message().to_bytes()
+    }
+}
+
+impl TryFrom<&UdpMessage> for RouteResponse {
+    type Error = UdpError;
+
+    fn try_from(value: &UdpMessage) -> Result<Self, Self::Error> {
+        let service = match value.service() {
+            service @ (UdpServiceId::AddRoute | UdpServiceId::DeleteRoute) => service,
+            _ => UdpServiceId::AddRoute,
+        };
+        value.expect_response(service)?;
+
+        Ok(Self {
+            invoke_id: value.invoke_id(),
+            service,
+            addr: *value.addr(),
+            result: value.require_tag(tag::STATUS)?.as_u32()?.into(),
+        })
+    }
+}
+
+fn expect_request(value: &UdpMessage, service: UdpServiceId) -> Result<(), UdpError> {
+    if value.is_response() || value.service() != service {
+        return Err(UdpError::UnexpectedService {
+            expected: service.into(),
+            got: value.service().into(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ams::AmsRouteFlags;
+
+    #[test]
+    fn add_route_roundtrip() {
+        let net_id = AmsNetId::new(192, 168, 0, 10, 1, 1);
+        let route = AmsRoute::new("provisioning", net_id, "192.168.0.10")
+            .with_flags(AmsRouteFlags::new(AmsRouteFlags::TEMPORARY));
+        let request = AddRouteRequest::new(4, &route, "Administrator", "1");
+
+        let message = UdpMessage::parse(&request.to_bytes()).unwrap();
+        assert_eq!(message.addr().port(), SYSTEM_SERVICE_PORT);
+        assert_eq!(message.tag(tag::PASSWORD).unwrap().data(), b"1\0");
+        let parsed = AddRouteRequest::try_from(&message).unwrap();
+        assert_eq!(parsed, request);
+        assert!(parsed.is_temporary());
+
+        let static_route = AmsRoute::new("provisioning", net_id, "192.168.0.10");
+        let message = AddRouteRequest::new(4, &static_route, "Administrator", "1").to_message();
+        assert!(message.tag(tag::OPTIONS).is_none());
+    }
+
+    #[test]
+    fn delete_route_roundtrip() {
+        let source = AmsAddr::new(AmsNetId::new(192, 168, 0, 10, 1, 1), SYSTEM_SERVICE_PORT);
+        let request = DeleteRouteRequest::new(5, source, "provisioning", "Administrator", "1");
+        let message = UdpMessage::parse(&request.to_bytes()).unwrap();
+        assert_eq!(DeleteRouteRequest::try_from(&message).unwrap(), request);
+        assert!(AddRouteRequest::try_from(&message).is_err());
+    }
+
+    #[test]
+    fn parses_responses() {
+        let addr = AmsAddr::new(AmsNetId::new(5, 1, 2, 3, 1, 1), SYSTEM_SERVICE_PORT);
+        let response = RouteResponse::new(
+            4,
+            UdpServiceId::AddRoute,
+            addr,
+            AdsReturnCode::AdsErrDeviceInvalidAccess,
+        );
+        let parsed = RouteResponse::parse(&response.to_bytes()).unwrap();
+        assert_eq!(parsed, response);
+        assert!(parsed.is_authentication_failure());
+
+        let discover = UdpMessage::response(UdpServiceId::Discover, 4, addr)
+            .with_tag(UdpTag::u32(tag::STATUS, 0));
+        assert!(RouteResponse::try_from(&discover).is_err());
+    }
+}
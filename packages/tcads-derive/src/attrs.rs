@@ -0,0 +1,67 @@
+use syn::{Attribute, Ident, LitInt, LitStr, Path};
+
+/// The `pack_mode` TwinCAT 3 uses when no attribute is given.
+const DEFAULT_PACK: usize = 8;
+
+/// Options given through `#[ads(...)]` on the deriving type.
+pub(crate) struct ContainerAttrs {
+    /// The maximum member alignment, matching `{attribute 'pack_mode' := '<pack>'}`.
+    pub(crate) pack: usize,
+    /// The path under which `tcads-core` is reachable.
+    pub(crate) krate: Path,
+}
+
+impl ContainerAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut pack = DEFAULT_PACK;
+        let mut krate: Path = syn::parse_quote!(::tcads_core);
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("ads")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("pack") {
+                    let lit: LitInt = meta.value()?.parse()?;
+                    pack = lit.base10_parse()?;
+                    if !matches!(pack, 1 | 2 | 4 | 8) {
+                        return Err(syn::Error::new_spanned(lit, "pack must be 1, 2, 4 or 8"));
+                    }
+                    Ok(())
+                } else if meta.path.is_ident("crate") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    krate = lit.parse()?;
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown ads attribute, expected `pack` or `crate`"))
+                }
+            })?;
+        }
+
+        Ok(Self { pack, krate })
+    }
+}
+
+/// Returns the integer type named by `#[repr(..)]`, if any.
+pub(crate) fn repr(attrs: &[Attribute]) -> syn::Result<Option<Ident>> {
+    let mut repr = None;
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if let Some(ident) = meta.path.get_ident()
+                && matches!(
+                    ident.to_string().as_str(),
+                    "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "u64" | "i64"
+                )
+            {
+                repr = Some(ident.clone());
+            }
+            // Skip arguments such as `align(4)`.
+            if meta.input.peek(syn::token::Paren) {
+                let args;
+                syn::parenthesized!(args in meta.input);
+                args.parse::<proc_macro2::TokenStream>()?;
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(repr)
+}
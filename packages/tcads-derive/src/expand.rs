@@ -0,0 +1,279 @@
+use crate::attrs::{ContainerAttrs, repr};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DataEnum, DataStruct, DeriveInput, Fields, Index, Path};
+
+pub(crate) fn derive(input: &DeriveInput) -> syn::Result<TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "AdsType cannot be derived for generic types",
+        ));
+    }
+
+    let attrs = ContainerAttrs::parse(&input.attrs)?;
+
+    match &input.data {
+        Data::Struct(data) => Ok(derive_struct(input, data, &attrs)),
+        Data::Enum(data) => derive_enum(input, data, &attrs),
+        Data::Union(_) => Err(syn::Error::new_spanned(
+            &input.ident,
+            "AdsType cannot be derived for unions",
+        )),
+    }
+}
+
+/// Lays the fields out in declaration order, each aligned to the smaller of its own
+/// alignment and the pack mode, and pads the struct to a multiple of its alignment.
+fn derive_struct(input: &DeriveInput, data: &DataStruct, attrs: &ContainerAttrs) -> TokenStream {
+    let ident = &input.ident;
+    let krate = &attrs.krate;
+    let pack = attrs.pack;
+
+    let types: Vec<_> = data.fields.iter().map(|field| &field.ty).collect();
+    let count = types.len();
+    let indices: Vec<_> = (0..count).collect();
+
+    let members: Vec<TokenStream> = data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| match &field.ident {
+            Some(name) => quote!(#name),
+            None => {
+                let index = Index::from(i);
+                quote!(#index)
+            }
+        })
+        .collect();
+
+    let decode_fields = quote! {
+        #(#members: <#types as #krate::ads::AdsDecode>::decode(&bytes[OFFSETS[#indices]..])?,)*
+    };
+    let construct = match &data.fields {
+        Fields::Named(_) => quote!(Self { #decode_fields }),
+        Fields::Unnamed(_) => {
+            let values = types.iter().zip(&indices).map(
+                |(ty, i)| quote!(<#ty as #krate::ads::AdsDecode>::decode(&bytes[OFFSETS[#i]..])?),
+            );
+            quote!(Self(#(#values),*))
+        }
+        Fields::Unit => quote!(Self),
+    };
+
+    let layout = layout(krate, pack, &types);
+
+    quote! {
+        const _: () = {
+            #layout
+
+            impl #krate::ads::AdsType for #ident {
+                const SIZE: usize = SIZE;
+                const ALIGN: usize = ALIGN;
+            }
+
+            impl #krate::ads::AdsEncode for #ident {
+                fn encoded_len(&self) -> usize {
+                    SIZE
+                }
+
+                fn encode(&self, buf: &mut ::std::vec::Vec<u8>) {
+                    let start = buf.len();
+                    buf.reserve(SIZE);
+                    #(
+                        buf.resize(start + OFFSETS[#indices], 0);
+                        #krate::ads::AdsEncode::encode(&self.#members, buf);
+                    )*
+                    buf.resize(start + SIZE, 0);
+                }
+            }
+
+            impl #krate::ads::AdsDecode for #ident {
+                fn decode(bytes: &[u8]) -> ::std::result::Result<Self, #krate::ads::AdsTypeError> {
+                    if bytes.len() < SIZE {
+                        return Err(#krate::ads::AdsTypeError::UnexpectedLength {
+                            expected: SIZE,
+                            got: bytes.len(),
+                        });
+                    }
+
+                    Ok(#construct)
+                }
+            }
+        };
+    }
+}
+
+/// Emits the `OFFSETS`, `ALIGN` and `SIZE` constants for a struct with `types` as fields.
+fn layout(krate: &Path, pack: usize, types: &[&syn::Type]) -> TokenStream {
+    let count = types.len();
+    let steps = types.iter().enumerate().map(|(i, ty)| {
+        quote! {
+            offsets[#i] = #krate::ads::pack_offset(end, <#ty as #krate::ads::AdsType>::ALIGN, #pack);
+            end = offsets[#i] + <#ty as #krate::ads::AdsType>::SIZE;
+        }
+    });
+    let aligns = types.iter().map(|ty| {
+        quote! {
+            let field = #krate::ads::pack_align(<#ty as #krate::ads::AdsType>::ALIGN, #pack);
+            if field > align {
+                align = field;
+            }
+        }
+    });
+
+    quote! {
+        #[allow(unused_mut)]
+        const LAYOUT: ([usize; #count], usize) = {
+            let mut offsets = [0usize; #count];
+            let mut end = 0usize;
+            #(#steps)*
+            (offsets, end)
+        };
+
+        #[allow(unused_mut)]
+        const ALIGN: usize = {
+            let mut align = 1usize;
+            #(#aligns)*
+            align
+        };
+
+        #[allow(dead_code)]
+        const OFFSETS: [usize; #count] = LAYOUT.0;
+        const SIZE: usize = #krate::ads::pack_offset(LAYOUT.1, ALIGN, #pack);
+    }
+}
+
+/// Encodes the enum as its `#[repr]` integer and rejects unknown values on decode.
+fn derive_enum(
+    input: &DeriveInput,
+    data: &DataEnum,
+    attrs: &ContainerAttrs,
+) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let krate = &attrs.krate;
+
+    let Some(repr) = repr(&input.attrs)? else {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "AdsType enums need an explicit integer representation, e.g. `#[repr(u16)]`",
+        ));
+    };
+
+    if let Some(variant) = data.variants.iter().find(|v| !v.fields.is_empty()) {
+        return Err(syn::Error::new_spanned(
+            variant,
+            "AdsType enums cannot have variants with fields",
+        ));
+    }
+
+    let variants: Vec<_> = data.variants.iter().map(|v| &v.ident).collect();
+    let name = ident.to_string();
+
+    Ok(quote! {
+        const _: () = {
+            impl #krate::ads::AdsType for #ident {
+                const SIZE: usize = <#repr as #krate::ads::AdsType>::SIZE;
+                const ALIGN: usize = <#repr as #krate::ads::AdsType>::ALIGN;
+            }
+
+            impl #krate::ads::AdsEncode for #ident {
+                fn encoded_len(&self) -> usize {
+                    <#repr as #krate::ads::AdsType>::SIZE
+                }
+
+                fn encode(&self, buf: &mut ::std::vec::Vec<u8>) {
+                    let value: #repr = match self {
+                        #(Self::#variants => Self::#variants as #repr,)*
+                    };
+                    #krate::ads::AdsEncode::encode(&value, buf);
+                }
+            }
+
+            impl #krate::ads::AdsDecode for #ident {
+                fn decode(bytes: &[u8]) -> ::std::result::Result<Self, #krate::ads::AdsTypeError> {
+                    let value = <#repr as #krate::ads::AdsDecode>::decode(bytes)?;
+                    #(
+                        if value == Self::#variants as #repr {
+                            return Ok(Self::#variants);
+                        }
+                    )*
+                    Err(#krate::ads::AdsTypeError::UnknownEnumValue {
+                        type_name: #name,
+                        value: value as i128,
+                    })
+                }
+            }
+        };
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Derives for `source` and returns the error message with the source text it points at.
+    fn error(source: &str) -> (String, String) {
+        let input: DeriveInput = syn::parse_str(source).unwrap();
+        let err = derive(&input).unwrap_err();
+        (err.to_string(), err.span().source_text().unwrap())
+    }
+
+    #[test]
+    fn rejects_generic_types() {
+        let (message, span) = error("struct Pair<T> { a: T, b: T }");
+        assert_eq!(message, "AdsType cannot be derived for generic types");
+        assert_eq!(span, "<T>");
+    }
+
+    #[test]
+    fn rejects_unions() {
+        let (message, span) = error("union Word { raw: u16, bytes: [u8; 2] }");
+        assert_eq!(message, "AdsType cannot be derived for unions");
+        assert_eq!(span, "Word");
+    }
+
+    #[test]
+    fn rejects_enums_without_integer_repr() {
+        let expected = "AdsType enums need an explicit integer representation, e.g. `#[repr(u16)]`";
+        for source in [
+            "enum Mode { Auto, Manual }",
+            "#[repr(C)] enum Mode { Auto, Manual }",
+            "#[repr(u128)] enum Mode { Auto, Manual }",
+        ] {
+            assert_eq!(error(source), (expected.into(), "Mode".into()), "{source}");
+        }
+    }
+
+    #[test]
+    fn rejects_enum_variants_with_fields() {
+        let (message, span) = error("#[repr(u8)] enum Mode { Auto, Manual(u8) }");
+        assert_eq!(message, "AdsType enums cannot have variants with fields");
+        assert_eq!(span, "Manual(u8)");
+    }
+
+    #[test]
+    fn accepts_integer_repr_next_to_other_arguments() {
+        let input: DeriveInput =
+            syn::parse_str("#[repr(align(4), u16)] enum Mode { Auto, Manual }").unwrap();
+        assert!(derive(&input).is_ok());
+    }
+
+    #[test]
+    fn rejects_bad_pack_values() {
+        let (message, span) = error("#[ads(pack = 3)] struct Sample { value: u8 }");
+        assert_eq!(message, "pack must be 1, 2, 4 or 8");
+        assert_eq!(span, "3");
+
+        let (message, span) = error(r#"#[ads(pack = "4")] struct Sample { value: u8 }"#);
+        assert_eq!(message, "expected integer literal");
+        assert_eq!(span, r#""4""#);
+    }
+
+    #[test]
+    fn rejects_unknown_attributes() {
+        let (message, span) = error("#[ads(align = 4)] struct Sample { value: u8 }");
+        assert_eq!(message, "unknown ads attribute, expected `pack` or `crate`");
+        assert_eq!(span, "align");
+    }
+}
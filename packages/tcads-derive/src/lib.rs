@@ -0,0 +1,25 @@
+//! # TwinCAT ADS Derive
+//!
+//! Derive macros for mapping Rust types onto TwinCAT PLC memory layouts.
+//!
+//! This crate is an implementation detail of `tcads-core`. Use the macros through the
+//! `tcads_core::ads::AdsType` re-export, where they are documented alongside the traits
+//! they implement.
+
+mod attrs;
+mod expand;
+
+use proc_macro::TokenStream;
+use syn::{DeriveInput, parse_macro_input};
+
+/// Derives `AdsType`, `AdsEncode` and `AdsDecode` for a struct or a field-less enum.
+///
+/// See `tcads_core::ads::AdsType` for the supported attributes and layout rules.
+#[proc_macro_derive(AdsType, attributes(ads))]
+pub fn derive_ads_type(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand::derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
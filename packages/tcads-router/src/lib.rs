@@ -0,0 +1,22 @@
+//! A pure-Rust AMS router.
+//!
+//! [`Router`] stands in for the TwinCAT router on hosts without Beckhoff software. It
+//! listens on the AMS/TCP port (`48898`) and:
+//!
+//! * registers local ADS clients and servers with
+//!   [`PortConnect`](tcads_core::protocol::PortConnectRequest), assigning dynamic ports on
+//!   request, and unregisters them on
+//!   [`PortClose`](tcads_core::protocol::PortCloseRequest) or disconnect;
+//! * answers [`GetLocalNetId`](tcads_core::protocol::GetLocalNetIdRequest);
+//! * routes ADS frames by the target of their [`AdsHeader`](tcads_core::AdsHeader), to a
+//!   local port or to a remote router over a static route;
+//! * broadcasts [`RouterNotification`](tcads_core::protocol::RouterNotification)s to local
+//!   clients when it stops or loses a remote router.
+//!
+//! The `tcads-router` binary runs a router from the command line.
+
+pub mod error;
+pub mod router;
+
+pub use error::{Error, Result};
+pub use router::{Router, RouterHandle};
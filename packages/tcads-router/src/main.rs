@@ -0,0 +1,104 @@
+use std::process::ExitCode;
+use tcads_core::AmsNetId;
+use tcads_core::ams::{AmsRoute, AmsRouteTable};
+use tcads_router::Router;
+
+const USAGE: &str = "\
+Usage: tcads-router [OPTIONS]
+
+Options:
+  --net-id <NET_ID>         AMS Net ID of the router [default: 127.0.0.1.1.1]
+  --listen <ADDR>           Address to accept AMS/TCP connections on [default: 0.0.0.0:48898]
+  --route <NET_ID>=<HOST>   Static route to a remote router, e.g. 192.168.0.20.1.1=192.168.0.20
+                            (port 48898 unless given), may be repeated
+  --routes <FILE>           Load static routes from a TOML file, or JSON with a .json extension
+  -h, --help                Print help";
+
+struct Args {
+    net_id: AmsNetId,
+    listen: String,
+    routes: AmsRouteTable,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        net_id: AmsNetId::new(127, 0, 0, 1, 1, 1),
+        listen: "0.0.0.0:48898".to_string(),
+        routes: AmsRouteTable::new(),
+    };
+
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or(format!("{arg} requires a value"));
+        match arg.as_str() {
+            "--net-id" => {
+                let value = value()?;
+                args.net_id = value
+                    .parse()
+                    .map_err(|err| format!("invalid net ID {value}: {err}"))?;
+            }
+            "--listen" => args.listen = value()?,
+            "--route" => {
+                args.routes.insert(parse_route(&value()?)?);
+            }
+            "--routes" => {
+                let value = value()?;
+                let routes = AmsRouteTable::load(&value)
+                    .map_err(|err| format!("cannot load routes from {value}: {err}"))?;
+                for route in routes {
+                    args.routes.insert(route);
+                }
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ => return Err(format!("unknown argument {arg}\n\n{USAGE}")),
+        }
+    }
+
+    Ok(args)
+}
+
+fn parse_route(route: &str) -> Result<AmsRoute, String> {
+    let (net_id, host) = route
+        .split_once('=')
+        .ok_or(format!("invalid route {route}, expected <NET_ID>=<HOST>"))?;
+    let net_id: AmsNetId = net_id
+        .parse()
+        .map_err(|err| format!("invalid net ID {net_id}: {err}"))?;
+
+    Ok(AmsRoute::new(net_id.to_string(), net_id, host))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{message}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let router = match Router::bind(&args.listen, args.net_id).await {
+        Ok(router) => router,
+        Err(err) => {
+            eprintln!("cannot listen on {}: {err}", args.listen);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let handle = router.handle();
+    handle.add_routes(args.routes);
+    tokio::spawn(async move {
+        tokio::signal::ctrl_c().await.ok();
+        handle.shutdown();
+    });
+
+    println!("Routing as {} on {}", args.net_id, args.listen);
+    match router.run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
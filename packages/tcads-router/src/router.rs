@@ -0,0 +1,796 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tcads_core::ads::StateFlag;
+use tcads_core::ams::{AmsRoute, AmsRouteTable, AmsTransport};
+use tcads_core::io::tokio::AmsStream;
+use tcads_core::protocol::{
+    GetLocalNetIdRequest, GetLocalNetIdResponse, PortCloseRequest, PortConnectRequest,
+    PortConnectResponse, RouterNotification,
+};
+use tcads_core::{
+    AdsHeader, AdsReturnCode, AmsAddr, AmsCommand, AmsFrame, AmsNetId, AmsPort, RouterState,
+};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::{mpsc, watch};
+
+/// An AMS router serving local ports and forwarding to remote routers.
+///
+/// A connection that sends [`PortConnect`](PortConnectRequest) is a local client and
+/// receives the frames addressed to its port. A connection that sends ADS frames without
+/// registering is treated as a remote router: frames for the net ID it sends from are
+/// routed back over it, unless that net ID has a [static route](RouterHandle::add_route).
+/// Frames for net IDs with a static route are always forwarded over it, connecting on
+/// first use. Only enabled [TCP](AmsTransport::Tcp) routes are used, the net IDs of
+/// disabled and Secure ADS routes are unreachable.
+///
+/// Requests that cannot be delivered are answered by the router with an empty response
+/// carrying [`ErrTargetPortNotFound`](AdsReturnCode::ErrTargetPortNotFound) or
+/// [`ErrTargetMachineNotFound`](AdsReturnCode::ErrTargetMachineNotFound) in the ADS header.
+///
+/// # Example
+///
+/// ```no_run
+/// use tcads_core::AmsNetId;
+/// use tcads_core::ams::{AmsRoute, AmsRouteTable};
+/// use tcads_router::Router;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let router = Router::bind("0.0.0.0:48898", AmsNetId::new(192, 168, 0, 10, 1, 1)).await?;
+/// let handle = router.handle();
+/// handle.add_routes(AmsRouteTable::load("routes.toml")?);
+/// handle.add_route(AmsRoute::new("PLC", AmsNetId::new(192, 168, 0, 20, 1, 1), "192.168.0.20"));
+///
+/// tokio::spawn(async move {
+///     tokio::signal::ctrl_c().await.ok();
+///     handle.shutdown();
+/// });
+///
+/// router.run().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Router {
+    listener: TcpListener,
+    shared: Arc<Shared>,
+}
+
+/// A cloneable handle to a [`Router`], for managing routes and stopping it.
+#[derive(Clone)]
+pub struct RouterHandle {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    net_id: AmsNetId,
+    table: Mutex<Table>,
+    shutdown: watch::Sender<bool>,
+}
+
+#[derive(Default)]
+struct Table {
+    last_link: u64,
+    last_port: AmsPort,
+    /// Registered local ports.
+    ports: HashMap<AmsPort, Link>,
+    /// Connections to remote routers over static routes.
+    peers: HashMap<AmsNetId, Link>,
+    /// Connections from remote routers, for net IDs without a static route.
+    learned: HashMap<AmsNetId, Link>,
+    /// Remote routers to connect to on demand.
+    routes: AmsRouteTable,
+}
+
+/// The sending side of a connection.
+#[derive(Clone)]
+struct Link {
+    id: u64,
+    tx: mpsc::UnboundedSender<AmsFrame>,
+}
+
+impl Router {
+    /// The first port assigned to clients that register port `0`.
+    pub const FIRST_DYNAMIC_PORT: AmsPort = 32768;
+
+    /// Listens for AMS/TCP connections on `addr`, routing as `net_id`.
+    pub async fn bind(addr: impl ToSocketAddrs, net_id: AmsNetId) -> crate::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let (shutdown, _) = watch::channel(false);
+
+        Ok(Self {
+            listener,
+            shared: Arc::new(Shared {
+                net_id,
+                table: Mutex::new(Table::default()),
+                shutdown,
+            }),
+        })
+    }
+
+    /// Returns the net ID of the router.
+    pub fn net_id(&self) -> AmsNetId {
+        self.shared.net_id
+    }
+
+    /// Returns the address the router listens on.
+    pub fn local_addr(&self) -> crate::Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Returns a handle for managing the router while it runs.
+    pub fn handle(&self) -> RouterHandle {
+        RouterHandle {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+
+    /// How long to pause accepting after an error, such as running out of file descriptors.
+    const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+    /// Accepts and serves connections until [`RouterHandle::shutdown`] is called.
+    ///
+    /// Errors accepting a connection only affect that connection: the router pauses
+    /// briefly and keeps accepting.
+    pub async fn run(self) -> crate::Result<()> {
+        let mut shutdown = self.shared.shutdown.subscribe();
+
+        loop {
+            let accepted = tokio::select! {
+                accepted = self.listener.accept() => accepted,
+                _ = shutdown.wait_for(|stopped| *stopped) => return Ok(()),
+            };
+            let Ok((socket, _)) = accepted else {
+                tokio::time::sleep(Self::ACCEPT_BACKOFF).await;
+                continue;
+            };
+            socket.set_nodelay(true).ok();
+            let (link, rx) = self.shared.table().new_link();
+            tokio::spawn(Arc::clone(&self.shared).serve(AmsStream::new(socket), link, rx));
+        }
+    }
+}
+
+impl RouterHandle {
+    /// Returns the net ID of the router.
+    pub fn net_id(&self) -> AmsNetId {
+        self.shared.net_id
+    }
+
+    /// Adds a static route, forwarding frames for its net ID to the router on its host.
+    ///
+    /// Replaces any route to the same net ID. An open connection to the old host is kept
+    /// until it closes.
+    pub fn add_route(&self, route: AmsRoute) {
+        self.shared.table().routes.insert(route);
+    }
+
+    /// Adds every route of `routes`, as with [`add_route`](Self::add_route).
+    pub fn add_routes(&self, routes: impl IntoIterator<Item = AmsRoute>) {
+        let mut table = self.shared.table();
+        for route in routes {
+            table.routes.insert(route);
+        }
+    }
+
+    /// Removes the static route to `net_id` and closes the connection to it.
+    pub fn remove_route(&self, net_id: AmsNetId) {
+        let mut table = self.shared.table();
+        table.routes.remove(net_id);
+        table.peers.remove(&net_id);
+    }
+
+    /// Returns the static routes.
+    pub fn routes(&self) -> AmsRouteTable {
+        self.shared.table().routes.clone()
+    }
+
+    /// Returns the registered local ports.
+    pub fn ports(&self) -> Vec<AmsPort> {
+        self.shared.table().ports.keys().copied().collect()
+    }
+
+    /// Notifies local clients that the router stopped, then closes every connection and
+    /// makes [`Router::run`] return.
+    pub fn shutdown(&self) {
+        self.shared.broadcast(RouterState::Stop);
+        self.shared.shutdown.send_replace(true);
+    }
+}
+
+impl Shared {
+    fn table(&self) -> MutexGuard<'_, Table> {
+        // The table is left consistent between statements, so a panic elsewhere cannot
+        // corrupt it.
+        self.table.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Serves one connection until it closes or the router shuts down.
+    async fn serve(
+        self: Arc<Self>,
+        stream: AmsStream<TcpStream>,
+        link: Link,
+        mut rx: mpsc::UnboundedReceiver<AmsFrame>,
+    ) {
+        let (mut reader, mut writer) = stream.into_split();
+        let shared = Arc::clone(&self);
+        tokio::spawn(async move {
+            while let Some(frame) = rx.recv().await {
+                if writer.write_frame(&frame).await.is_err() {
+                    // Nothing more can be written, so return what is left to the senders.
+                    rx.close();
+                    shared.bounce_undelivered(&frame);
+                    while let Some(frame) = rx.recv().await {
+                        shared.bounce_undelivered(&frame);
+                    }
+                }
+            }
+        });
+
+        let mut shutdown = self.shutdown.subscribe();
+        loop {
+            let frame = tokio::select! {
+                frame = reader.read_frame() => match frame {
+                    Ok(frame) => frame,
+                    Err(_) => break,
+                },
+                _ = shutdown.wait_for(|stopped| *stopped) => break,
+            };
+            if !self.handle(frame, &link) {
+                break;
+            }
+        }
+
+        self.disconnect(link.id);
+    }
+
+    /// Handles a frame received on `link`, returning `false` to close the connection.
+    fn handle(self: &Arc<Self>, frame: AmsFrame, link: &Link) -> bool {
+        match frame.header().command() {
+            AmsCommand::AdsCommand => {
+                let Ok((header, _)) = AdsHeader::parse_prefix(frame.payload()) else {
+                    return true;
+                };
+                let source = header.source().net_id();
+                if source != self.net_id {
+                    let mut table = self.table();
+                    // Remote routers cannot take over net IDs with a static route.
+                    if table.routes.get(source).is_none() {
+                        table.learned.entry(source).or_insert_with(|| link.clone());
+                    }
+                }
+                self.route(frame);
+                true
+            }
+            AmsCommand::PortConnect => {
+                let Ok(request) = PortConnectRequest::try_from(&frame) else {
+                    return true;
+                };
+                let Some(port) = self.table().register(request.desired_port(), link) else {
+                    // The port is taken, and the handshake has no way to report errors.
+                    return false;
+                };
+                let response = PortConnectResponse::new(AmsAddr::new(self.net_id, port));
+                link.tx.send(response.into_frame()).is_ok()
+            }
+            AmsCommand::PortClose => {
+                if let Ok(request) = PortCloseRequest::try_from(&frame) {
+                    let mut table = self.table();
+                    if table
+                        .ports
+                        .get(&request.port())
+                        .is_some_and(|l| l.id == link.id)
+                    {
+                        table.ports.remove(&request.port());
+                    }
+                }
+                false
+            }
+            AmsCommand::GetLocalNetId => {
+                if GetLocalNetIdRequest::try_from(&frame).is_err() {
+                    return true;
+                }
+                let response = GetLocalNetIdResponse::new(self.net_id);
+                link.tx.send(response.into_frame()).is_ok()
+            }
+            _ => true,
+        }
+    }
+
+    /// Delivers an ADS frame to the port or router its target is reached through.
+    fn route(self: &Arc<Self>, frame: AmsFrame) {
+        let Ok((header, _)) = AdsHeader::parse_prefix(frame.payload()) else {
+            return;
+        };
+        let target = *header.target();
+
+        let (link, code) = {
+            let mut table = self.table();
+            if target.net_id() == self.net_id {
+                let link = table.ports.get(&target.port()).cloned();
+                (link, AdsReturnCode::ErrTargetPortNotFound)
+            } else {
+                let net_id = target.net_id();
+                let route = table.routes.get(net_id).map(|route| {
+                    (route.is_enabled() && route.transport() == AmsTransport::Tcp)
+                        .then(|| route.address())
+                });
+                let link = match route {
+                    Some(Some(addr)) => match table.peers.get(&net_id) {
+                        Some(link) => Some(link.clone()),
+                        None => Some(self.dial(&mut table, net_id, addr)),
+                    },
+                    Some(None) => None,
+                    None => table.learned.get(&net_id).cloned(),
+                };
+                (link, AdsReturnCode::ErrTargetMachineNotFound)
+            }
+        };
+
+        let undelivered = match link {
+            Some(link) => link.tx.send(frame).err().map(|err| err.0),
+            None => Some(frame),
+        };
+        if undelivered.is_some() {
+            self.bounce(&header, code);
+        }
+    }
+
+    /// Returns an undeliverable frame to its sender with `code` in the ADS header.
+    ///
+    /// Frames that already carry an error are dropped, so bounces cannot loop.
+    fn bounce(self: &Arc<Self>, header: &AdsHeader, code: AdsReturnCode) {
+        if header.error_code() != AdsReturnCode::Ok {
+            return;
+        }
+        let reply = AdsHeader::new(
+            *header.source(),
+            *header.target(),
+            header.command_id(),
+            StateFlag::tcp_ads_response(),
+            0,
+            code,
+            header.invoke_id(),
+        );
+        self.route(AmsFrame::new(AmsCommand::AdsCommand, reply.to_bytes()));
+    }
+
+    /// Returns an ADS frame that could not be written to its connection to its sender.
+    fn bounce_undelivered(self: &Arc<Self>, frame: &AmsFrame) {
+        if frame.header().command() != AmsCommand::AdsCommand {
+            return;
+        }
+        if let Ok((header, _)) = AdsHeader::parse_prefix(frame.payload()) {
+            let code = match header.target().net_id() == self.net_id {
+                true => AdsReturnCode::ErrTargetPortNotFound,
+                false => AdsReturnCode::ErrTargetMachineNotFound,
+            };
+            self.bounce(&header, code);
+        }
+    }
+
+    /// Opens a connection to the remote router at `addr`, queueing frames until it is up.
+    fn dial(self: &Arc<Self>, table: &mut Table, net_id: AmsNetId, addr: String) -> Link {
+        let (link, mut rx) = table.new_link();
+        table.peers.insert(net_id, link.clone());
+
+        let shared = Arc::clone(self);
+        let connection = link.clone();
+        tokio::spawn(async move {
+            match TcpStream::connect(addr).await {
+                Ok(socket) => {
+                    socket.set_nodelay(true).ok();
+                    shared.serve(AmsStream::new(socket), connection, rx).await;
+                }
+                Err(_) => {
+                    shared.disconnect(connection.id);
+                    drop(connection);
+                    rx.close();
+                    while let Some(frame) = rx.recv().await {
+                        shared.bounce_undelivered(&frame);
+                    }
+                }
+            }
+        });
+
+        link
+    }
+
+    /// Unregisters everything reached through the connection `id`.
+    ///
+    /// Local clients are not notified when a remote router is lost: they treat
+    /// [`Removed`](RouterState::Removed) as the loss of their own router. Requests to it
+    /// bounce instead.
+    fn disconnect(&self, id: u64) {
+        let mut table = self.table();
+        table.ports.retain(|_, link| link.id != id);
+        table.peers.retain(|_, link| link.id != id);
+        table.learned.retain(|_, link| link.id != id);
+    }
+
+    /// Sends a router notification to every local client.
+    fn broadcast(&self, state: RouterState) {
+        let table = self.table();
+        let mut notified = Vec::new();
+        for link in table.ports.values() {
+            if !notified.contains(&link.id) {
+                notified.push(link.id);
+                link.tx
+                    .send(RouterNotification::new(state).into_frame())
+                    .ok();
+            }
+        }
+    }
+}
+
+impl Table {
+    fn new_link(&mut self) -> (Link, mpsc::UnboundedReceiver<AmsFrame>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.last_link += 1;
+        (
+            Link {
+                id: self.last_link,
+                tx,
+            },
+            rx,
+        )
+    }
+
+    /// Registers `port` for `link`, or a free dynamic port if `port` is `0`.
+    fn register(&mut self, port: AmsPort, link: &Link) -> Option<AmsPort> {
+        let port = match port {
+            0 => {
+                let dynamic = Router::FIRST_DYNAMIC_PORT..AmsPort::MAX;
+                let count = dynamic.len();
+                (0..count).find_map(|_| {
+                    self.last_port = match self.last_port {
+                        p if dynamic.contains(&(p + 1)) => p + 1,
+                        _ => dynamic.start,
+                    };
+                    (!self.ports.contains_key(&self.last_port)).then_some(self.last_port)
+                })?
+            }
+            port if self.ports.contains_key(&port) => return None,
+            port => port,
+        };
+        self.ports.insert(port, link.clone());
+        Some(port)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tcads_client::devices::tokio::AdsDevice;
+    use tcads_core::ams::AmsRouteFlags;
+    use tcads_core::protocol::{AdsReadRequest, AdsReadResponse, AdsReadResponseOwned};
+    use tcads_core::symbol::AdsDataTypeId;
+    use tcads_server::SymbolServer;
+    use tcads_server::port::tokio::AdsPort;
+
+    async fn start(net_id: AmsNetId) -> (SocketAddr, RouterHandle) {
+        let router = Router::bind("127.0.0.1:0", net_id).await.unwrap();
+        let addr = router.local_addr().unwrap();
+        let handle = router.handle();
+        tokio::spawn(router.run());
+        (addr, handle)
+    }
+
+    async fn register(router: SocketAddr, port: AmsPort) -> (AmsStream<TcpStream>, AmsAddr) {
+        let mut stream = AmsStream::connect(router).await.unwrap();
+        stream
+            .write_frame(&PortConnectRequest::new(port).into_frame())
+            .await
+            .unwrap();
+        let frame = stream.read_frame().await.unwrap();
+        let addr = *PortConnectResponse::try_from(&frame).unwrap().addr();
+        (stream, addr)
+    }
+
+    #[tokio::test]
+    async fn routes_between_local_ports() {
+        let net_id = AmsNetId::new(10, 0, 0, 1, 1, 1);
+        let (router, handle) = start(net_id).await;
+
+        let (mut client, client_addr) = register(router, 0).await;
+        let (mut server, server_addr) = register(router, 851).await;
+        assert_eq!(
+            client_addr,
+            AmsAddr::new(net_id, Router::FIRST_DYNAMIC_PORT)
+        );
+        assert_eq!(server_addr, AmsAddr::new(net_id, 851));
+
+        client
+            .write_frame(&GetLocalNetIdRequest::into_frame())
+            .await
+            .unwrap();
+        let frame = client.read_frame().await.unwrap();
+        assert_eq!(
+            GetLocalNetIdResponse::try_from(&frame).unwrap().net_id(),
+            net_id
+        );
+
+        let request = AdsReadRequest::new(server_addr, client_addr, 1, 0x4020, 0, 2);
+        client.write_frame(&request.into_frame()).await.unwrap();
+        let frame = server.read_frame().await.unwrap();
+        let request = AdsReadRequest::try_from(&frame).unwrap();
+        assert_eq!(*request.header().source(), client_addr);
+
+        let response =
+            AdsReadResponseOwned::new(client_addr, server_addr, 1, AdsReturnCode::Ok, [1, 2]);
+        server.write_frame(&response.into_frame()).await.unwrap();
+        let frame = client.read_frame().await.unwrap();
+        assert_eq!(frame.payload()[AdsHeader::LENGTH + 8..], [1, 2]);
+
+        // Requests to unregistered ports bounce.
+        let missing = AmsAddr::new(net_id, 999);
+        let request = AdsReadRequest::new(missing, client_addr, 2, 0x4020, 0, 2);
+        client.write_frame(&request.into_frame()).await.unwrap();
+        let frame = client.read_frame().await.unwrap();
+        let (header, _) = AdsHeader::parse_prefix(frame.payload()).unwrap();
+        assert_eq!(*header.source(), missing);
+        assert_eq!(header.invoke_id(), 2);
+        assert_eq!(header.error_code(), AdsReturnCode::ErrTargetPortNotFound);
+
+        // Closing the port unregisters it, and shutting down notifies the rest.
+        server
+            .write_frame(&PortCloseRequest::new(851).into_frame())
+            .await
+            .unwrap();
+        assert!(server.read_frame().await.is_err());
+        assert_eq!(handle.ports(), [Router::FIRST_DYNAMIC_PORT]);
+
+        handle.shutdown();
+        let frame = client.read_frame().await.unwrap();
+        assert_eq!(
+            RouterNotification::try_from(&frame).unwrap().state(),
+            RouterState::Stop
+        );
+    }
+
+    /// Reads the next ADS header received on `stream`.
+    async fn read_header(stream: &mut AmsStream<TcpStream>) -> AdsHeader {
+        let frame = stream.read_frame().await.unwrap();
+        AdsHeader::parse_prefix(frame.payload()).unwrap().0
+    }
+
+    #[tokio::test]
+    async fn forwards_between_local_clients_and_remote_routers() {
+        let local = AmsNetId::new(10, 0, 0, 1, 1, 1);
+        let remote = AmsAddr::new(AmsNetId::new(10, 0, 0, 2, 1, 1), 851);
+        let (router, handle) = start(local).await;
+
+        let (mut client, client_addr) = register(router, 0).await;
+        let mut peer = AmsStream::connect(router).await.unwrap();
+
+        // A connection sending ADS frames without registering is a remote router.
+        let request = AdsReadRequest::new(client_addr, remote, 1, 0x4020, 0, 2);
+        peer.write_frame(&request.into_frame()).await.unwrap();
+        let frame = client.read_frame().await.unwrap();
+        let request = AdsReadRequest::try_from(&frame).unwrap();
+        assert_eq!(*request.header().source(), remote);
+        assert_eq!(request.header().invoke_id(), 1);
+
+        // Frames for its net ID go back over it.
+        let response = AdsReadResponseOwned::new(remote, client_addr, 1, AdsReturnCode::Ok, [3]);
+        client.write_frame(&response.into_frame()).await.unwrap();
+        let frame = peer.read_frame().await.unwrap();
+        let response = AdsReadResponse::try_from(&frame).unwrap();
+        assert_eq!(*response.header().target(), remote);
+        assert_eq!(response.data(), [3]);
+        assert!(handle.ports().contains(&client_addr.port()));
+    }
+
+    #[tokio::test]
+    async fn bounces_frames_for_unknown_net_ids() {
+        let net_id = AmsNetId::new(10, 0, 0, 1, 1, 1);
+        let (router, _) = start(net_id).await;
+        let (mut client, client_addr) = register(router, 0).await;
+
+        let unknown = AmsAddr::new(AmsNetId::new(10, 0, 0, 9, 1, 1), 851);
+        let request = AdsReadRequest::new(unknown, client_addr, 7, 0x4020, 0, 2);
+        client.write_frame(&request.into_frame()).await.unwrap();
+        let header = read_header(&mut client).await;
+        assert_eq!(*header.source(), unknown);
+        assert_eq!(*header.target(), client_addr);
+        assert_eq!(header.invoke_id(), 7);
+        assert_eq!(header.state_flags(), StateFlag::tcp_ads_response());
+        assert_eq!(header.length(), 0);
+        assert_eq!(header.error_code(), AdsReturnCode::ErrTargetMachineNotFound);
+
+        // Frames already carrying an error are dropped rather than bounced back.
+        let error = AdsHeader::new(
+            unknown,
+            client_addr,
+            header.command_id(),
+            StateFlag::tcp_ads_response(),
+            0,
+            AdsReturnCode::ErrTargetMachineNotFound,
+            8,
+        );
+        let frame = AmsFrame::new(AmsCommand::AdsCommand, error.to_bytes());
+        client.write_frame(&frame).await.unwrap();
+        let request = AdsReadRequest::new(unknown, client_addr, 9, 0x4020, 0, 2);
+        client.write_frame(&request.into_frame()).await.unwrap();
+        assert_eq!(read_header(&mut client).await.invoke_id(), 9);
+    }
+
+    #[tokio::test]
+    async fn allocates_dynamic_ports_and_releases_them_on_disconnect() {
+        let net_id = AmsNetId::new(10, 0, 0, 1, 1, 1);
+        let (router, handle) = start(net_id).await;
+
+        let (first, first_addr) = register(router, 0).await;
+        let (mut second, second_addr) = register(router, 0).await;
+        let (_fixed, fixed_addr) = register(router, 851).await;
+        assert_eq!(first_addr.port(), Router::FIRST_DYNAMIC_PORT);
+        assert_eq!(second_addr.port(), Router::FIRST_DYNAMIC_PORT + 1);
+        assert_eq!(fixed_addr.port(), 851);
+
+        // Taken ports are refused by closing the connection.
+        let mut taken = AmsStream::connect(router).await.unwrap();
+        taken
+            .write_frame(&PortConnectRequest::new(851).into_frame())
+            .await
+            .unwrap();
+        assert!(taken.read_frame().await.is_err());
+
+        drop(first);
+        while handle.ports().contains(&first_addr.port()) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let mut ports = handle.ports();
+        ports.sort();
+        assert_eq!(ports, [851, second_addr.port()]);
+
+        // The released port no longer receives frames.
+        let request = AdsReadRequest::new(first_addr, second_addr, 1, 0x4020, 0, 2);
+        second.write_frame(&request.into_frame()).await.unwrap();
+        let header = read_header(&mut second).await;
+        assert_eq!(header.error_code(), AdsReturnCode::ErrTargetPortNotFound);
+
+        // Dynamic ports continue after the last one assigned.
+        let (_third, third_addr) = register(router, 0).await;
+        assert_eq!(third_addr.port(), Router::FIRST_DYNAMIC_PORT + 2);
+    }
+
+    #[tokio::test]
+    async fn static_routes_take_precedence_over_learned_peers() {
+        let local = AmsNetId::new(10, 0, 0, 1, 1, 1);
+        let routed = AmsAddr::new(AmsNetId::new(10, 0, 0, 2, 1, 1), 851);
+        let learned = AmsAddr::new(AmsNetId::new(10, 0, 0, 3, 1, 1), 851);
+        let (router, handle) = start(local).await;
+
+        let remote = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let remote_addr = remote.local_addr().unwrap();
+        handle.add_route(AmsRoute::new(
+            "Remote",
+            routed.net_id(),
+            remote_addr.to_string(),
+        ));
+
+        let (mut client, client_addr) = register(router, 0).await;
+        let mut peer = AmsStream::connect(router).await.unwrap();
+
+        // The peer claims both net IDs, but only learns the one without a static route.
+        for (source, invoke_id) in [(routed, 1), (learned, 2)] {
+            let request = AdsReadRequest::new(client_addr, source, invoke_id, 0x4020, 0, 2);
+            peer.write_frame(&request.into_frame()).await.unwrap();
+            assert_eq!(read_header(&mut client).await.invoke_id(), invoke_id);
+        }
+
+        let request = AdsReadRequest::new(routed, client_addr, 3, 0x4020, 0, 2);
+        client.write_frame(&request.into_frame()).await.unwrap();
+        let (socket, _) = remote.accept().await.unwrap();
+        let mut remote = AmsStream::new(socket);
+        assert_eq!(read_header(&mut remote).await.invoke_id(), 3);
+
+        let request = AdsReadRequest::new(learned, client_addr, 4, 0x4020, 0, 2);
+        client.write_frame(&request.into_frame()).await.unwrap();
+        assert_eq!(read_header(&mut peer).await.invoke_id(), 4);
+
+        // Disabling the static route makes its net ID unreachable, not learnable.
+        handle.remove_route(routed.net_id());
+        let disabled = AmsRoute::new("Remote", routed.net_id(), remote_addr.to_string())
+            .with_flags(AmsRouteFlags::new(AmsRouteFlags::DISABLED));
+        handle.add_route(disabled);
+        let request = AdsReadRequest::new(client_addr, routed, 5, 0x4020, 0, 2);
+        peer.write_frame(&request.into_frame()).await.unwrap();
+        assert_eq!(read_header(&mut client).await.invoke_id(), 5);
+
+        let request = AdsReadRequest::new(routed, client_addr, 6, 0x4020, 0, 2);
+        client.write_frame(&request.into_frame()).await.unwrap();
+        let header = read_header(&mut client).await;
+        assert_eq!(header.invoke_id(), 6);
+        assert_eq!(header.error_code(), AdsReturnCode::ErrTargetMachineNotFound);
+    }
+
+    #[tokio::test]
+    async fn local_clients_survive_unreachable_static_routes() {
+        let local = AmsNetId::new(10, 0, 0, 1, 1, 1);
+        let unreachable = AmsNetId::new(10, 0, 0, 2, 1, 1);
+        let (router, handle) = start(local).await;
+
+        // Nothing listens on the address of the route once the listener is dropped.
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let closed_addr = closed.local_addr().unwrap();
+        drop(closed);
+        handle.add_route(AmsRoute::new("Down", unreachable, closed_addr.to_string()));
+
+        let mut server = SymbolServer::new("Local");
+        server
+            .add_symbol("MAIN.nCount", "DINT", AdsDataTypeId::Int32, 4, "")
+            .unwrap();
+        server.set("MAIN.nCount", &7i32).unwrap();
+        let port = AdsPort::connect(router, 851).await.unwrap();
+        tokio::spawn(async move { port.serve(&server).await });
+
+        let timeout = Some(Duration::from_secs(5));
+        let device = AdsDevice::connect_to(router, timeout).await.unwrap();
+        let mut router_states = device.subscribe_router().unwrap();
+        let unreachable = AmsAddr::new(unreachable, 851);
+        assert!(device.read_state(unreachable).await.is_err());
+        assert!(device.read_state(unreachable).await.is_err());
+
+        let target = AmsAddr::new(local, 851);
+        let entry = device
+            .read_symbol_info(target, "MAIN.nCount")
+            .await
+            .unwrap();
+        let data = device
+            .read(target, entry.index_group(), entry.index_offset(), 4)
+            .await
+            .unwrap();
+        assert_eq!(data, 7i32.to_le_bytes());
+        assert!(!device.is_closed());
+        assert!(router_states.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn serves_clients_across_static_routes() {
+        let local = AmsNetId::new(10, 0, 0, 1, 1, 1);
+        let remote = AmsNetId::new(10, 0, 0, 2, 1, 1);
+        let (local_router, local_handle) = start(local).await;
+        let (remote_router, _) = start(remote).await;
+        local_handle.add_route(AmsRoute::new("Remote", remote, remote_router.to_string()));
+
+        let mut server = SymbolServer::new("Remote");
+        server
+            .add_symbol("MAIN.nCount", "DINT", AdsDataTypeId::Int32, 4, "")
+            .unwrap();
+        server.set("MAIN.nCount", &1234i32).unwrap();
+        let port = AdsPort::connect(remote_router, 851).await.unwrap();
+        tokio::spawn(async move { port.serve(&server).await });
+
+        let timeout = Some(Duration::from_secs(5));
+        let device = AdsDevice::connect_to(local_router, timeout).await.unwrap();
+        let target = AmsAddr::new(remote, 851);
+        let entry = device
+            .read_symbol_info(target, "MAIN.nCount")
+            .await
+            .unwrap();
+        let data = device
+            .read(
+                target,
+                entry.index_group(),
+                entry.index_offset(),
+                entry.size(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(data, 1234i32.to_le_bytes());
+
+        // Net IDs without a route are unreachable.
+        let unknown = AmsAddr::new(AmsNetId::new(10, 0, 0, 3, 1, 1), 851);
+        assert!(device.read_state(unknown).await.is_err());
+
+        // So are those behind disabled routes, even with an open connection.
+        local_handle.remove_route(remote);
+        let disabled = AmsRoute::new("Remote", remote, remote_router.to_string())
+            .with_flags(AmsRouteFlags::new(AmsRouteFlags::DISABLED));
+        local_handle.add_route(disabled);
+        assert!(device.read_state(target).await.is_err());
+        assert_eq!(local_handle.routes().len(), 1);
+    }
+}
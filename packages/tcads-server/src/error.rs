@@ -0,0 +1,42 @@
+use std::io;
+use std::sync::{Arc, PoisonError};
+use tcads_core::ads::{AdsReturnCode, AdsTypeError};
+use tcads_core::protocol::ProtocolError;
+use tcads_core::symbol::SymbolError;
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Error {
+    #[error("I/O error: {0}")]
+    Io(#[from] Arc<io::Error>),
+    #[error(transparent)]
+    Protocol(#[from] ProtocolError),
+    #[error(transparent)]
+    AdsReturnCode(#[from] AdsReturnCode),
+    #[error("Symbol error: {0}")]
+    Symbol(#[from] SymbolError),
+    #[error("Value error: {0}")]
+    Type(#[from] AdsTypeError),
+    #[error("Symbol {0} already exists")]
+    DuplicateSymbol(String),
+    #[error("Disconnected")]
+    Disconnected,
+    #[error("Poisoned lock")]
+    PoisonedLock,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl<T> From<PoisonError<T>> for Error {
+    fn from(_: PoisonError<T>) -> Self {
+        Error::PoisonedLock
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof => Error::Disconnected,
+            _ => Error::Io(Arc::new(err)),
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use tcads_core::ads::{AdsCommand, AdsHeader, StateFlag};
+use tcads_core::protocol::{
+    AdsAddDeviceNotificationRequest, AdsAddDeviceNotificationResponse,
+    AdsDeleteDeviceNotificationRequest, AdsDeleteDeviceNotificationResponse,
+    AdsReadDeviceInfoRequest, AdsReadDeviceInfoResponse, AdsReadRequest, AdsReadResponseOwned,
+    AdsReadStateRequest, AdsReadStateResponse, AdsReadWriteRequest, AdsReadWriteResponseOwned,
+    AdsWriteControlRequest, AdsWriteControlResponse, AdsWriteRequest, AdsWriteResponse,
+};
+use tcads_core::{
+    AdsDeviceVersion, AdsReturnCode, AdsState, AmsAddr, AmsCommand, AmsFrame, DeviceState,
+    NotificationHandle,
+};
+
+/// An ADS device implemented in Rust.
+///
+/// Each method handles one ADS command and receives the decoded request, whose
+/// [`header`](AdsReadRequest::header) carries the address of the caller. Returning an
+/// [`AdsReturnCode`] sends an error response carrying that code instead of data.
+///
+/// Every method defaults to [`AdsErrDeviceSrvNotSupp`](AdsReturnCode::AdsErrDeviceSrvNotSupp),
+/// so an implementation only needs to provide the commands it supports. Handlers take
+/// `&self` and may be called from the connection's reader thread or task, so shared state
+/// needs interior mutability.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Mutex;
+/// use tcads_core::protocol::{AdsReadRequest, AdsWriteRequest};
+/// use tcads_core::AdsReturnCode;
+/// use tcads_server::AdsServer;
+///
+/// /// A device exposing 256 bytes of memory in index group `0x4020`.
+/// struct Memory(Mutex<[u8; 256]>);
+///
+/// impl AdsServer for Memory {
+///     fn read(&self, request: &AdsReadRequest) -> Result<Vec<u8>, AdsReturnCode> {
+///         let memory = self.0.lock().unwrap();
+///         let start = request.index_offset() as usize;
+///         let end = start + request.length() as usize;
+///         memory
+///             .get(start..end)
+///             .map(<[u8]>::to_vec)
+///             .ok_or(AdsReturnCode::AdsErrDeviceInvalidOffset)
+///     }
+///
+///     fn write(&self, request: &AdsWriteRequest) -> Result<(), AdsReturnCode> {
+///         let mut memory = self.0.lock().unwrap();
+///         let start = request.index_offset() as usize;
+///         let end = start + request.data().len();
+///         memory
+///             .get_mut(start..end)
+///             .ok_or(AdsReturnCode::AdsErrDeviceInvalidOffset)?
+///             .copy_from_slice(request.data());
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait AdsServer: Send + Sync {
+    /// Handles [`AdsReadDeviceInfo`](AdsCommand::AdsReadDeviceInfo), returning the version and
+    /// name (at most 15 characters) of the device.
+    fn read_device_info(
+        &self,
+        request: &AdsReadDeviceInfoRequest,
+    ) -> Result<(AdsDeviceVersion, String), AdsReturnCode> {
+        let _ = request;
+        Err(AdsReturnCode::AdsErrDeviceSrvNotSupp)
+    }
+
+    /// Handles [`AdsRead`](AdsCommand::AdsRead), returning at most
+    /// [`length`](AdsReadRequest::length) bytes.
+    fn read(&self, request: &AdsReadRequest) -> Result<Vec<u8>, AdsReturnCode> {
+        let _ = request;
+        Err(AdsReturnCode::AdsErrDeviceSrvNotSupp)
+    }
+
+    /// Handles [`AdsWrite`](AdsCommand::AdsWrite).
+    fn write(&self, request: &AdsWriteRequest) -> Result<(), AdsReturnCode> {
+        let _ = request;
+        Err(AdsReturnCode::AdsErrDeviceSrvNotSupp)
+    }
+
+    /// Handles [`AdsReadWrite`](AdsCommand::AdsReadWrite), returning at most
+    /// [`read_length`](AdsReadWriteRequest::read_length) bytes.
+    fn read_write(&self, request: &AdsReadWriteRequest) -> Result<Vec<u8>, AdsReturnCode> {
+        let _ = request;
+        Err(AdsReturnCode::AdsErrDeviceSrvNotSupp)
+    }
+
+    /// Handles [`AdsReadState`](AdsCommand::AdsReadState).
+    fn read_state(
+        &self,
+        request: &AdsReadStateRequest,
+    ) -> Result<(AdsState, DeviceState), AdsReturnCode> {
+        let _ = request;
+        Err(AdsReturnCode::AdsErrDeviceSrvNotSupp)
+    }
+
+    /// Handles [`AdsWriteControl`](AdsCommand::AdsWriteControl).
+    fn write_control(&self, request: &AdsWriteControlRequest) -> Result<(), AdsReturnCode> {
+        let _ = request;
+        Err(AdsReturnCode::AdsErrDeviceSrvNotSupp)
+    }
+
+    /// Handles [`AdsAddDeviceNotification`](AdsCommand::AdsAddDeviceNotification), returning
+    /// the handle of the new subscription.
+    fn add_device_notification(
+        &self,
+        request: &AdsAddDeviceNotificationRequest,
+    ) -> Result<NotificationHandle, AdsReturnCode> {
+        let _ = request;
+        Err(AdsReturnCode::AdsErrDeviceSrvNotSupp)
+    }
+
+    /// Handles [`AdsDeleteDeviceNotification`](AdsCommand::AdsDeleteDeviceNotification).
+    fn delete_device_notification(
+        &self,
+        request: &AdsDeleteDeviceNotificationRequest,
+    ) -> Result<(), AdsReturnCode> {
+        let _ = request;
+        Err(AdsReturnCode::AdsErrDeviceSrvNotSupp)
+    }
+
+    /// Called when the router returns a device notification because `client` can no longer
+    /// be reached, so its subscriptions can be dropped.
+    fn client_disconnected(&self, client: AmsAddr) {
+        let _ = client;
+    }
+}
+
+/// Serves one incoming frame with `server`, returning the response to send back.
+///
+/// Returns `None` for frames that need no reply: anything other than an ADS request, and
+/// device notifications. A device notification returned by the router with an error is
+/// reported to [`AdsServer::client_disconnected`]. Requests that fail to decode are answered with
+/// [`AdsErrDeviceInvalidParm`](AdsReturnCode::AdsErrDeviceInvalidParm), and unknown commands
+/// with [`AdsErrDeviceSrvNotSupp`](AdsReturnCode::AdsErrDeviceSrvNotSupp).
+///
+/// This is the I/O-free core of the [`port`](crate::port) runtimes, exposed for custom
+/// transports.
+pub fn respond<S: AdsServer + ?Sized>(server: &S, frame: &AmsFrame) -> Option<AmsFrame> {
+    if frame.header().command() != AmsCommand::AdsCommand {
+        return None;
+    }
+    let (header, _) = AdsHeader::parse_prefix(frame.payload()).ok()?;
+    if header.command_id() == AdsCommand::AdsDeviceNotification {
+        // The router returns notifications it cannot deliver with the addresses swapped.
+        if header.error_code() != AdsReturnCode::Ok {
+            server.client_disconnected(*header.source());
+        }
+        return None;
+    }
+    if !header.state_flags().is_request() {
+        return None;
+    }
+
+    // Replies go back to the caller, from the address it called.
+    let target = *header.source();
+    let source = *header.target();
+    let invoke_id = header.invoke_id();
+    let invalid = AdsReturnCode::AdsErrDeviceInvalidParm;
+
+    Some(match header.command_id() {
+        AdsCommand::AdsReadDeviceInfo => {
+            let (result, version, name) = match AdsReadDeviceInfoRequest::try_from(frame) {
+                Ok(request) => match server.read_device_info(&request) {
+                    Ok((version, name)) => (AdsReturnCode::Ok, version, name),
+                    Err(code) => (code, AdsDeviceVersion::default(), String::new()),
+                },
+                Err(_) => (invalid, AdsDeviceVersion::default(), String::new()),
+            };
+            AdsReadDeviceInfoResponse::try_new(target, source, invoke_id, result, version, name)
+                .or_else(|_| {
+                    AdsReadDeviceInfoResponse::try_new(
+                        target,
+                        source,
+                        invoke_id,
+                        invalid,
+                        AdsDeviceVersion::default(),
+                        "",
+                    )
+                })
+                .ok()?
+                .into_frame()
+        }
+        AdsCommand::AdsRead => {
+            let (result, data) = split(
+                AdsReadRequest::try_from(frame)
+                    .map_err(|_| invalid)
+                    .and_then(|request| {
+                        let data = server.read(&request)?;
+                        match data.len() <= request.length() as usize {
+                            true => Ok(data),
+                            false => Err(AdsReturnCode::AdsErrDeviceInvalidSize),
+                        }
+                    }),
+                Vec::new(),
+            );
+            AdsReadResponseOwned::new(target, source, invoke_id, result, data).into_frame()
+        }
+        AdsCommand::AdsWrite => {
+            let result = AdsWriteRequest::try_from(frame)
+                .map_err(|_| invalid)
+                .and_then(|request| server.write(&request));
+            AdsWriteResponse::new(target, source, invoke_id, code(result)).into_frame()
+        }
+        AdsCommand::AdsReadWrite => {
+            let (result, data) = split(
+                AdsReadWriteRequest::try_from(frame)
+                    .map_err(|_| invalid)
+                    .and_then(|request| {
+                        let data = server.read_write(&request)?;
+                        match data.len() <= request.read_length() as usize {
+                            true => Ok(data),
+                            false => Err(AdsReturnCode::AdsErrDeviceInvalidSize),
+                        }
+                    }),
+                Vec::new(),
+            );
+            AdsReadWriteResponseOwned::new(target, source, invoke_id, result, data).into_frame()
+        }
+        AdsCommand::AdsReadState => {
+            let (result, (ads_state, device_state)) = split(
+                AdsReadStateRequest::try_from(frame)
+                    .map_err(|_| invalid)
+                    .and_then(|request| server.read_state(&request)),
+                (AdsState::Invalid, 0),
+            );
+            AdsReadStateResponse::new(target, source, invoke_id, result, ads_state, device_state)
+                .into_frame()
+        }
+        AdsCommand::AdsWriteControl => {
+            let result = AdsWriteControlRequest::try_from(frame)
+                .map_err(|_| invalid)
+                .and_then(|request| server.write_control(&request));
+            AdsWriteControlResponse::new(target, source, invoke_id, code(result)).into_frame()
+        }
+        AdsCommand::AdsAddDeviceNotification => {
+            let (result, handle) = split(
+                AdsAddDeviceNotificationRequest::try_from(frame)
+                    .map_err(|_| invalid)
+                    .and_then(|request| server.add_device_notification(&request)),
+                NotificationHandle::new(0),
+            );
+            AdsAddDeviceNotificationResponse::new(target, source, invoke_id, result, handle)
+                .into_frame()
+        }
+        AdsCommand::AdsDeleteDeviceNotification => {
+            let result = AdsDeleteDeviceNotificationRequest::try_from(frame)
+                .map_err(|_| invalid)
+                .and_then(|request| server.delete_device_notification(&request));
+            AdsDeleteDeviceNotificationResponse::new(target, source, invoke_id, code(result))
+                .into_frame()
+        }
+        command => {
+            // Unknown commands are answered with a bare result code.
+            let result = AdsReturnCode::AdsErrDeviceSrvNotSupp;
+            let header = AdsHeader::new(
+                target,
+                source,
+                command,
+                StateFlag::tcp_ads_response(),
+                AdsReturnCode::LENGTH as u32,
+                result,
+                invoke_id,
+            );
+            let mut payload = header.to_bytes().to_vec();
+            payload.extend_from_slice(&result.to_bytes());
+            AmsFrame::new(AmsCommand::AdsCommand, payload)
+        }
+    })
+}
+
+/// Splits a handler result into the response code and the data to send with it, using
+/// `fallback` as the data of an error response.
+fn split<T>(result: Result<T, AdsReturnCode>, fallback: T) -> (AdsReturnCode, T) {
+    match result {
+        Ok(value) => (AdsReturnCode::Ok, value),
+        Err(code) => (code, fallback),
+    }
+}
+
+fn code(result: Result<(), AdsReturnCode>) -> AdsReturnCode {
+    result.err().unwrap_or(AdsReturnCode::Ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tcads_core::AmsNetId;
+    use tcads_core::protocol::{AdsReadWriteRequestOwned, AdsReadWriteResponse};
+
+    struct Echo;
+
+    impl AdsServer for Echo {
+        fn read_write(&self, request: &AdsReadWriteRequest) -> Result<Vec<u8>, AdsReturnCode> {
+            Ok(request.data().to_vec())
+        }
+    }
+
+    fn addrs() -> (AmsAddr, AmsAddr) {
+        (
+            AmsAddr::new(AmsNetId::new(10, 0, 0, 1, 1, 1), 25000),
+            AmsAddr::new(AmsNetId::new(10, 0, 0, 2, 1, 1), 32768),
+        )
+    }
+
+    #[test]
+    fn replies_to_the_caller() {
+        let (served, client) = addrs();
+        let request = AdsReadWriteRequestOwned::new(served, client, 3, 0xF003, 0, 4, [1, 2, 3]);
+
+        let frame = respond(&Echo, &request.into_frame()).unwrap();
+        let response = AdsReadWriteResponse::try_from(&frame).unwrap();
+        assert_eq!(*response.header().source(), served);
+        assert_eq!(*response.header().target(), client);
+        assert_eq!(response.data(), [1, 2, 3]);
+
+        // Returning more than the caller asked for is an error
+        let request = AdsReadWriteRequestOwned::new(served, client, 4, 0xF003, 0, 2, [1, 2, 3]);
+        let frame = respond(&Echo, &request.into_frame()).unwrap();
+        let response = AdsReadWriteResponse::try_from(&frame).unwrap();
+        assert_eq!(response.result(), AdsReturnCode::AdsErrDeviceInvalidSize);
+    }
+
+    #[test]
+    fn ignores_responses() {
+        let (served, client) = addrs();
+        let response = AdsWriteResponse::new(served, client, 1, AdsReturnCode::Ok);
+
+        assert!(respond(&Echo, &response.into_frame()).is_none());
+    }
+}
@@ -1,14 +1,20 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
-}
+//! Framework for building custom ADS devices in Rust.
+//!
+//! Implement [`AdsServer`] with one handler per ADS command, then host it on an AMS port
+//! with [`port::blocking::AdsPort`] or [`port::tokio::AdsPort`]. Requests are decoded with
+//! the [`tcads_core::protocol`] request types and answered with the matching responses.
+//!
+//! Device notifications are served by a [`NotificationEngine`], which the server's
+//! notification handlers delegate to. A [`SymbolServer`] serves Rust variables as PLC
+//! symbols that ADS clients can browse and access by name.
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub mod error;
+pub mod handler;
+pub mod notifications;
+pub mod port;
+pub mod symbols;
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub use error::{Error, Result};
+pub use handler::{AdsServer, respond};
+pub use notifications::NotificationEngine;
+pub use symbols::SymbolServer;
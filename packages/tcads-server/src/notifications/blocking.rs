@@ -0,0 +1,111 @@
+use super::NotificationEngine;
+use crate::handler::AdsServer;
+use crate::port::blocking::PortHandle;
+use std::time::Instant;
+
+/// Sends the notifications of `engine` through `port` until [`shutdown`] is called.
+///
+/// Blocks the calling thread, sleeping until the next sample or batch is due. If sending
+/// fails, all subscriptions are dropped, since their clients can no longer be reached, and
+/// the error is returned.
+///
+/// [`shutdown`]: NotificationEngine::shutdown
+pub fn run<S: AdsServer + ?Sized>(
+    engine: &NotificationEngine,
+    server: &S,
+    port: &PortHandle,
+) -> crate::Result<()> {
+    loop {
+        for frame in engine.poll(server, Instant::now()) {
+            if let Err(err) = port.send(&frame) {
+                engine.shutdown();
+                return Err(err);
+            }
+        }
+
+        // The schedule is read under the lock the engine notifies on, so a subscription
+        // added after the poll is not missed.
+        let state = engine.state.lock()?;
+        if state.closed {
+            return Ok(());
+        }
+        match state.next_deadline() {
+            Some(deadline) => {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                drop(engine.changed.wait_timeout(state, timeout)?);
+            }
+            None => drop(engine.changed.wait(state)?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::port::blocking::AdsPort;
+    use std::net::TcpListener;
+    use std::sync::Mutex;
+    use std::thread;
+    use tcads_core::io::blocking::AmsStream;
+    use tcads_core::protocol::{
+        AdsAddDeviceNotificationRequest, AdsDeviceNotification, AdsReadRequest,
+    };
+    use tcads_core::{AdsReturnCode, AdsTransMode, AmsAddr, AmsNetId};
+
+    struct Counter(Mutex<u8>);
+
+    impl AdsServer for Counter {
+        fn read(&self, _: &AdsReadRequest) -> Result<Vec<u8>, AdsReturnCode> {
+            let mut count = self.0.lock().unwrap();
+            *count += 1;
+            Ok(vec![*count])
+        }
+    }
+
+    #[test]
+    fn sends_cyclic_notifications() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let router = listener.local_addr().unwrap();
+        let served = AmsAddr::new(AmsNetId::new(10, 0, 0, 1, 1, 1), 25000);
+        let client = AmsAddr::new(AmsNetId::new(10, 0, 0, 2, 1, 1), 32768);
+
+        let engine = NotificationEngine::new();
+        let counter = Counter(Mutex::new(0));
+        let request = AdsAddDeviceNotificationRequest::new(
+            served,
+            client,
+            1,
+            0x4020,
+            0,
+            1,
+            AdsTransMode::ServerCycle,
+            0,
+            5,
+        );
+        engine.add(&request).unwrap();
+
+        let mock = thread::spawn(move || {
+            let (socket, _) = listener.accept().unwrap();
+            let mut stream = AmsStream::new(socket);
+            let mut values = Vec::new();
+            while values.len() < 3 {
+                let frame = stream.read_frame().unwrap();
+                let notification = AdsDeviceNotification::try_from(&frame).unwrap();
+                assert_eq!(*notification.header().target(), client);
+                values.extend(notification.iter_samples().map(|(_, s)| s.data()[0]));
+            }
+            values
+        });
+
+        let stream = AmsStream::connect(router).unwrap();
+        let port = AdsPort::new(stream, served).unwrap();
+        let handle = port.handle();
+
+        thread::scope(|scope| {
+            let driver = scope.spawn(|| run(&engine, &counter, &handle));
+            assert_eq!(mock.join().unwrap(), [1, 2, 3]);
+            engine.shutdown();
+            driver.join().unwrap().unwrap();
+        });
+    }
+}
@@ -0,0 +1,450 @@
+//! Server-side device notifications.
+//!
+//! [`NotificationEngine`] keeps the subscriptions made with `AddDeviceNotification`, samples
+//! the subscribed memory through [`AdsServer::read`] on each subscription's schedule, and
+//! batches the samples into [`AdsDeviceNotificationOwned`] frames. An [`AdsServer`] forwards
+//! its notification handlers to the engine, and a driver from [`blocking`] or [`tokio`]
+//! sends the frames through the port the server is hosted on.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::sync::{Arc, Mutex};
+//! use std::thread;
+//! use tcads_core::protocol::{
+//!     AdsAddDeviceNotificationRequest, AdsDeleteDeviceNotificationRequest, AdsReadRequest,
+//! };
+//! use tcads_core::{AdsReturnCode, AmsAddr, NotificationHandle};
+//! use tcads_server::AdsServer;
+//! use tcads_server::notifications::{self, NotificationEngine};
+//! use tcads_server::port::blocking::AdsPort;
+//!
+//! struct Device {
+//!     memory: Mutex<[u8; 64]>,
+//!     notifications: NotificationEngine,
+//! }
+//!
+//! impl AdsServer for Device {
+//!     fn read(&self, request: &AdsReadRequest) -> Result<Vec<u8>, AdsReturnCode> {
+//!         let start = request.index_offset() as usize;
+//!         let end = start + request.length() as usize;
+//!         let memory = self.memory.lock().unwrap();
+//!         memory
+//!             .get(start..end)
+//!             .map(<[u8]>::to_vec)
+//!             .ok_or(AdsReturnCode::AdsErrDeviceInvalidOffset)
+//!     }
+//!
+//!     fn add_device_notification(
+//!         &self,
+//!         request: &AdsAddDeviceNotificationRequest,
+//!     ) -> Result<NotificationHandle, AdsReturnCode> {
+//!         self.notifications.add(request)
+//!     }
+//!
+//!     fn delete_device_notification(
+//!         &self,
+//!         request: &AdsDeleteDeviceNotificationRequest,
+//!     ) -> Result<(), AdsReturnCode> {
+//!         self.notifications.delete(request)
+//!     }
+//!
+//!     fn client_disconnected(&self, client: AmsAddr) {
+//!         self.notifications.remove_client(client);
+//!     }
+//! }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let device = Arc::new(Device {
+//!     memory: Mutex::new([0; 64]),
+//!     notifications: NotificationEngine::new(),
+//! });
+//!
+//! let port = AdsPort::connect("127.0.0.1:48898", 25000)?;
+//! let handle = port.handle();
+//! let driver = thread::spawn({
+//!     let device = Arc::clone(&device);
+//!     move || notifications::blocking::run(&device.notifications, &*device, &handle)
+//! });
+//!
+//! port.serve(&*device)?;
+//! device.notifications.shutdown();
+//! driver.join().unwrap()?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod blocking;
+pub mod tokio;
+
+use crate::handler::AdsServer;
+use std::collections::HashMap;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tcads_core::protocol::{
+    AdsAddDeviceNotificationRequest, AdsDeleteDeviceNotificationRequest,
+    AdsDeviceNotificationOwned, AdsNotificationSampleOwned, AdsReadRequest, AdsStampHeaderOwned,
+};
+use tcads_core::{
+    AdsReturnCode, AdsTransMode, AmsAddr, AmsFrame, IndexGroup, IndexOffset, NotificationHandle,
+    WindowsFileTime,
+};
+
+/// Keeps device notification subscriptions and produces the notifications due for them.
+///
+/// Cyclic subscriptions ([`ServerCycle`](AdsTransMode::ServerCycle)) send a sample every
+/// `cycle_time`. On-change subscriptions ([`ServerOnChange`](AdsTransMode::ServerOnChange))
+/// sample every `cycle_time` too, but only send when the data differs from the last sample
+/// sent, starting with an initial sample. The client variants of both modes are treated
+/// the same. Samples are held for up to `max_delay` and sent together, one frame per client.
+///
+/// Handles are unique across all clients, and a subscription can only be deleted by the
+/// client that created it.
+#[derive(Default)]
+pub struct NotificationEngine {
+    state: Mutex<State>,
+    /// Wakes the blocking driver when the schedule changes.
+    changed: Condvar,
+    /// Wakes the `tokio` driver when the schedule changes.
+    notify: ::tokio::sync::Notify,
+}
+
+#[derive(Default)]
+struct State {
+    last_handle: u32,
+    subscriptions: HashMap<(AmsAddr, NotificationHandle), Subscription>,
+    /// Samples waiting to be sent, by client and serving address.
+    batches: HashMap<(AmsAddr, AmsAddr), Batch>,
+    closed: bool,
+}
+
+struct Subscription {
+    /// The address the subscription was made on, which sends the notifications.
+    source: AmsAddr,
+    index_group: IndexGroup,
+    index_offset: IndexOffset,
+    length: u32,
+    on_change: bool,
+    cycle_time: Duration,
+    max_delay: Duration,
+    last: Option<Vec<u8>>,
+    next_due: Instant,
+}
+
+struct Batch {
+    stamps: Vec<AdsStampHeaderOwned>,
+    deadline: Instant,
+}
+
+impl NotificationEngine {
+    /// Creates an engine with no subscriptions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the subscription requested by `request` and returns its handle.
+    ///
+    /// The first sample is taken on the next [`poll`](Self::poll).
+    pub fn add(
+        &self,
+        request: &AdsAddDeviceNotificationRequest,
+    ) -> Result<NotificationHandle, AdsReturnCode> {
+        let on_change = match request.trans_mode() {
+            AdsTransMode::ServerCycle | AdsTransMode::ClientCycle => false,
+            AdsTransMode::ServerOnChange | AdsTransMode::ClientOnChange => true,
+            _ => return Err(AdsReturnCode::AdsErrDeviceInvalidParm),
+        };
+        if request.length() == 0 {
+            return Err(AdsReturnCode::AdsErrDeviceInvalidSize);
+        }
+
+        let client = *request.header().source();
+        let mut state = self.lock()?;
+        if state.closed {
+            return Err(AdsReturnCode::AdsErrDeviceInvalidState);
+        }
+        let handle = state.allocate(client);
+        state.subscriptions.insert(
+            (client, handle),
+            Subscription {
+                source: *request.header().target(),
+                index_group: request.index_group(),
+                index_offset: request.index_offset(),
+                length: request.length(),
+                on_change,
+                cycle_time: Duration::from_millis(request.cycle_time().max(1) as u64),
+                max_delay: Duration::from_millis(request.max_delay() as u64),
+                last: None,
+                next_due: Instant::now(),
+            },
+        );
+        drop(state);
+
+        self.wake();
+        Ok(handle)
+    }
+
+    /// Removes the subscription named by `request`.
+    pub fn delete(
+        &self,
+        request: &AdsDeleteDeviceNotificationRequest,
+    ) -> Result<(), AdsReturnCode> {
+        let client = *request.header().source();
+        self.lock()?
+            .subscriptions
+            .remove(&(client, request.handle()))
+            .map(|_| ())
+            .ok_or(AdsReturnCode::AdsErrDeviceNotifyHndInvalid)
+    }
+
+    /// Removes every subscription of `client` and drops its unsent samples.
+    pub fn remove_client(&self, client: AmsAddr) {
+        if let Ok(mut state) = self.lock() {
+            state.subscriptions.retain(|(owner, _), _| *owner != client);
+            state.batches.retain(|(owner, _), _| *owner != client);
+        }
+    }
+
+    /// Returns the number of active subscriptions.
+    pub fn len(&self) -> usize {
+        self.lock()
+            .map(|state| state.subscriptions.len())
+            .unwrap_or(0)
+    }
+
+    /// Returns `true` if there are no active subscriptions.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all subscriptions and stops the drivers.
+    ///
+    /// Call this once the port the engine serves has closed. Later subscriptions are refused
+    /// with [`AdsErrDeviceInvalidState`](AdsReturnCode::AdsErrDeviceInvalidState).
+    pub fn shutdown(&self) {
+        if let Ok(mut state) = self.lock() {
+            state.subscriptions.clear();
+            state.batches.clear();
+            state.closed = true;
+        }
+        self.wake();
+    }
+
+    /// Samples every subscription due at `now` through `server` and returns the notification
+    /// frames ready to be sent.
+    ///
+    /// Subscriptions whose memory can no longer be read are skipped until it can.
+    pub fn poll<S: AdsServer + ?Sized>(&self, server: &S, now: Instant) -> Vec<AmsFrame> {
+        let Ok(mut state) = self.lock() else {
+            return Vec::new();
+        };
+        let State {
+            subscriptions,
+            batches,
+            ..
+        } = &mut *state;
+
+        let timestamp = WindowsFileTime::now();
+        let mut sampled: HashMap<(AmsAddr, AmsAddr), (Vec<_>, Instant)> = HashMap::new();
+
+        for ((client, handle), sub) in subscriptions.iter_mut() {
+            if sub.next_due > now {
+                continue;
+            }
+            sub.next_due += sub.cycle_time;
+            if sub.next_due <= now {
+                sub.next_due = now + sub.cycle_time;
+            }
+
+            let request = AdsReadRequest::new(
+                sub.source,
+                *client,
+                0,
+                sub.index_group,
+                sub.index_offset,
+                sub.length,
+            );
+            let Ok(data) = server.read(&request) else {
+                continue;
+            };
+            if sub.on_change && sub.last.as_deref() == Some(data.as_slice()) {
+                continue;
+            }
+
+            let (samples, deadline) = sampled
+                .entry((*client, sub.source))
+                .or_insert_with(|| (Vec::new(), now + sub.max_delay));
+            *deadline = (*deadline).min(now + sub.max_delay);
+            samples.push(AdsNotificationSampleOwned::new(*handle, data.clone()));
+            sub.last = Some(data);
+        }
+
+        for (key, (samples, deadline)) in sampled {
+            let batch = batches.entry(key).or_insert_with(|| Batch {
+                stamps: Vec::new(),
+                deadline,
+            });
+            batch.deadline = batch.deadline.min(deadline);
+            batch
+                .stamps
+                .push(AdsStampHeaderOwned::new(timestamp, samples));
+        }
+
+        let due: Vec<_> = batches
+            .iter()
+            .filter(|(_, batch)| batch.deadline <= now)
+            .map(|(key, _)| *key)
+            .collect();
+
+        due.into_iter()
+            .filter_map(|key| batches.remove(&key).map(|batch| (key, batch)))
+            .map(|((client, source), batch)| {
+                AdsDeviceNotificationOwned::new(client, source, batch.stamps).into_frame()
+            })
+            .collect()
+    }
+
+    /// Returns when [`poll`](Self::poll) next has work to do, or `None` if nothing is
+    /// scheduled.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.lock().ok()?.next_deadline()
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, State>, AdsReturnCode> {
+        self.state
+            .lock()
+            .map_err(|_| AdsReturnCode::AdsErrDeviceError)
+    }
+
+    fn wake(&self) {
+        self.changed.notify_all();
+        self.notify.notify_one();
+    }
+}
+
+impl State {
+    /// Returns the next free handle for `client`, skipping `0` and handles in use.
+    fn allocate(&mut self, client: AmsAddr) -> NotificationHandle {
+        loop {
+            self.last_handle = self.last_handle.wrapping_add(1).max(1);
+            let handle = NotificationHandle::new(self.last_handle);
+            if !self.subscriptions.contains_key(&(client, handle)) {
+                return handle;
+            }
+        }
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        let sampling = self.subscriptions.values().map(|sub| sub.next_due);
+        let sending = self.batches.values().map(|batch| batch.deadline);
+        sampling.chain(sending).min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tcads_core::AmsNetId;
+    use tcads_core::protocol::AdsDeviceNotification;
+
+    struct Memory(Mutex<Vec<u8>>);
+
+    impl AdsServer for Memory {
+        fn read(&self, request: &AdsReadRequest) -> Result<Vec<u8>, AdsReturnCode> {
+            let start = request.index_offset() as usize;
+            let end = start + request.length() as usize;
+            Ok(self.0.lock().unwrap()[start..end].to_vec())
+        }
+    }
+
+    fn addrs() -> (AmsAddr, AmsAddr) {
+        (
+            AmsAddr::new(AmsNetId::new(10, 0, 0, 1, 1, 1), 25000),
+            AmsAddr::new(AmsNetId::new(10, 0, 0, 2, 1, 1), 32768),
+        )
+    }
+
+    fn subscribe(
+        engine: &NotificationEngine,
+        mode: AdsTransMode,
+        max_delay: u32,
+    ) -> NotificationHandle {
+        let (served, client) = addrs();
+        let request = AdsAddDeviceNotificationRequest::new(
+            served, client, 1, 0x4020, 0, 2, mode, max_delay, 10,
+        );
+        engine.add(&request).unwrap()
+    }
+
+    fn samples(frames: &[AmsFrame]) -> Vec<Vec<u8>> {
+        frames
+            .iter()
+            .flat_map(|frame| {
+                let notification = AdsDeviceNotification::try_from(frame).unwrap();
+                notification
+                    .iter_samples()
+                    .map(|(_, sample)| sample.data().to_vec())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn on_change_sends_only_changes() {
+        let engine = NotificationEngine::new();
+        let memory = Memory(Mutex::new(vec![1, 2, 3, 4]));
+        subscribe(&engine, AdsTransMode::ServerOnChange, 0);
+
+        let start = Instant::now();
+        assert_eq!(samples(&engine.poll(&memory, start)), [vec![1, 2]]);
+
+        let later = start + Duration::from_millis(10);
+        assert!(engine.poll(&memory, later).is_empty());
+
+        memory.0.lock().unwrap()[0] = 9;
+        let later = later + Duration::from_millis(10);
+        assert_eq!(samples(&engine.poll(&memory, later)), [vec![9, 2]]);
+    }
+
+    #[test]
+    fn cyclic_samples_are_batched_until_max_delay() {
+        let engine = NotificationEngine::new();
+        let memory = Memory(Mutex::new(vec![5, 6]));
+        let handle = subscribe(&engine, AdsTransMode::ServerCycle, 25);
+
+        let start = Instant::now();
+        assert!(engine.poll(&memory, start).is_empty());
+        assert!(
+            engine
+                .poll(&memory, start + Duration::from_millis(10))
+                .is_empty()
+        );
+
+        let frames = engine.poll(&memory, start + Duration::from_millis(30));
+        assert_eq!(frames.len(), 1);
+        let notification = AdsDeviceNotification::try_from(&frames[0]).unwrap();
+        assert_eq!(notification.stamps().len(), 3);
+        assert_eq!(*notification.header().target(), addrs().1);
+
+        let (served, client) = addrs();
+        let request = AdsDeleteDeviceNotificationRequest::new(served, client, 2, handle);
+        engine.delete(&request).unwrap();
+        assert!(engine.is_empty());
+        assert_eq!(
+            engine.delete(&request),
+            Err(AdsReturnCode::AdsErrDeviceNotifyHndInvalid)
+        );
+    }
+
+    #[test]
+    fn removes_disconnected_clients() {
+        let engine = NotificationEngine::new();
+        subscribe(&engine, AdsTransMode::ServerOnChange, 0);
+        subscribe(&engine, AdsTransMode::ServerCycle, 0);
+        assert_eq!(engine.len(), 2);
+
+        engine.remove_client(addrs().1);
+        assert!(engine.is_empty());
+        assert_eq!(engine.next_deadline(), None);
+    }
+}
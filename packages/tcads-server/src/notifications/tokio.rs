@@ -0,0 +1,114 @@
+use super::NotificationEngine;
+use crate::handler::AdsServer;
+use crate::port::tokio::PortHandle;
+use std::time::Instant;
+
+/// Sends the notifications of `engine` through `port` until [`shutdown`] is called.
+///
+/// Sleeps until the next sample or batch is due. Sampling calls [`AdsServer::read`]
+/// directly on the task, so reads should not block for long. If sending fails, all
+/// subscriptions are dropped, since their clients can no longer be reached, and the error
+/// is returned.
+///
+/// [`shutdown`]: NotificationEngine::shutdown
+pub async fn run<S: AdsServer + ?Sized>(
+    engine: &NotificationEngine,
+    server: &S,
+    port: &PortHandle,
+) -> crate::Result<()> {
+    loop {
+        for frame in engine.poll(server, Instant::now()) {
+            if let Err(err) = port.send(&frame).await {
+                engine.shutdown();
+                return Err(err);
+            }
+        }
+
+        // A change made after the poll leaves a permit, so this returns at once.
+        let notified = engine.notify.notified();
+        let deadline = {
+            let state = engine.state.lock()?;
+            if state.closed {
+                return Ok(());
+            }
+            state.next_deadline()
+        };
+        match deadline {
+            Some(deadline) => {
+                tokio::select! {
+                    _ = notified => {}
+                    _ = tokio::time::sleep_until(deadline.into()) => {}
+                }
+            }
+            None => notified.await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::port::tokio::AdsPort;
+    use std::sync::Mutex;
+    use tcads_core::io::tokio::AmsStream;
+    use tcads_core::protocol::{
+        AdsAddDeviceNotificationRequest, AdsDeviceNotification, AdsReadRequest,
+    };
+    use tcads_core::{AdsReturnCode, AdsTransMode, AmsAddr, AmsNetId};
+    use tokio::net::TcpListener;
+
+    struct Memory(Mutex<u8>);
+
+    impl AdsServer for Memory {
+        fn read(&self, _: &AdsReadRequest) -> Result<Vec<u8>, AdsReturnCode> {
+            Ok(vec![*self.0.lock().unwrap()])
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_changes() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let router = listener.local_addr().unwrap();
+        let served = AmsAddr::new(AmsNetId::new(10, 0, 0, 1, 1, 1), 25000);
+        let client = AmsAddr::new(AmsNetId::new(10, 0, 0, 2, 1, 1), 32768);
+
+        let engine = NotificationEngine::new();
+        let memory = Memory(Mutex::new(1));
+
+        let accept = async { AmsStream::new(listener.accept().await.unwrap().0) };
+        let (stream, mut mock) =
+            tokio::join!(async { AmsStream::connect(router).await.unwrap() }, accept);
+        let port = AdsPort::new(stream, served);
+        let handle = port.handle();
+
+        let driver = run(&engine, &memory, &handle);
+        let check = async {
+            let request = AdsAddDeviceNotificationRequest::new(
+                served,
+                client,
+                1,
+                0x4020,
+                0,
+                1,
+                AdsTransMode::ServerOnChange,
+                0,
+                1,
+            );
+            engine.add(&request).unwrap();
+
+            let frame = mock.read_frame().await.unwrap();
+            let notification = AdsDeviceNotification::try_from(&frame).unwrap();
+            assert_eq!(notification.iter_samples().next().unwrap().1.data(), [1]);
+
+            *memory.0.lock().unwrap() = 2;
+            let frame = mock.read_frame().await.unwrap();
+            let notification = AdsDeviceNotification::try_from(&frame).unwrap();
+            assert_eq!(notification.iter_samples().next().unwrap().1.data(), [2]);
+
+            engine.shutdown();
+        };
+
+        let (result, ()) = tokio::join!(driver, check);
+        result.unwrap();
+    }
+}
@@ -0,0 +1,177 @@
+use crate::handler::{AdsServer, respond};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use tcads_core::io::blocking::{AmsReader, AmsStream, AmsWriter};
+use tcads_core::protocol::{PortCloseRequest, PortConnectRequest, PortConnectResponse};
+use tcads_core::{AmsAddr, AmsCommand, AmsFrame, AmsPort};
+
+/// A blocking AMS port serving an [`AdsServer`].
+///
+/// # Example
+///
+/// ```no_run
+/// use tcads_server::AdsServer;
+/// use tcads_server::port::blocking::AdsPort;
+///
+/// struct Device;
+///
+/// impl AdsServer for Device {}
+///
+/// let port = AdsPort::connect("127.0.0.1:48898", 25000)?;
+/// println!("Serving on {}", port.addr());
+///
+/// // Blocks until the router closes the connection or `PortHandle::close` is called.
+/// port.serve(&Device)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct AdsPort {
+    reader: AmsReader,
+    handle: PortHandle,
+}
+
+/// A cloneable handle to a running [`AdsPort`], for sending frames and closing the port
+/// from other threads.
+#[derive(Clone)]
+pub struct PortHandle {
+    addr: AmsAddr,
+    writer: Arc<Mutex<AmsWriter>>,
+}
+
+impl AdsPort {
+    /// Connects to the AMS router at `router` and registers `port`.
+    ///
+    /// Performs a [`PortConnect`](PortConnectRequest) handshake. Pass `0` to let the router
+    /// assign a free port.
+    pub fn connect(router: impl ToSocketAddrs, port: AmsPort) -> crate::Result<Self> {
+        let mut stream = AmsStream::connect(router)?;
+        stream.write_frame(&PortConnectRequest::new(port).into_frame())?;
+
+        // Skip router notifications until the handshake is answered.
+        let addr = loop {
+            let frame = stream.read_frame()?;
+            if frame.header().command() == AmsCommand::PortConnect {
+                break *PortConnectResponse::try_from(&frame)?.addr();
+            }
+        };
+
+        Self::new(stream, addr)
+    }
+
+    /// Creates an [`AdsPort`] from an existing [`AmsStream`] that already serves `addr`,
+    /// skipping the [`PortConnect`](PortConnectRequest) handshake.
+    pub fn new(stream: AmsStream<TcpStream>, addr: AmsAddr) -> crate::Result<Self> {
+        let (reader, writer) = stream.try_split()?;
+        Ok(Self {
+            reader,
+            handle: PortHandle {
+                addr,
+                writer: Arc::new(Mutex::new(writer)),
+            },
+        })
+    }
+
+    /// Returns the address requests are served on.
+    pub fn addr(&self) -> AmsAddr {
+        self.handle.addr
+    }
+
+    /// Returns a handle for sending frames from this port.
+    pub fn handle(&self) -> PortHandle {
+        self.handle.clone()
+    }
+
+    /// Serves requests with `server` until the connection closes.
+    ///
+    /// Requests are handled one at a time, in the order they arrive. Returns `Ok(())` when the
+    /// router closes the connection, for example after [`PortHandle::close`].
+    pub fn serve<S: AdsServer + ?Sized>(mut self, server: &S) -> crate::Result<()> {
+        loop {
+            let frame = match self.reader.read_frame() {
+                Ok(frame) => frame,
+                Err(err) => {
+                    return match crate::Error::from(err) {
+                        crate::Error::Disconnected => Ok(()),
+                        err => Err(err),
+                    };
+                }
+            };
+
+            if let Some(response) = respond(server, &frame) {
+                self.handle.send(&response)?;
+            }
+        }
+    }
+}
+
+impl PortHandle {
+    /// Returns the address of the port.
+    pub fn addr(&self) -> AmsAddr {
+        self.addr
+    }
+
+    /// Sends a frame to the router.
+    pub fn send(&self, frame: &AmsFrame) -> crate::Result<()> {
+        Ok(self.writer.lock()?.write_frame(frame)?)
+    }
+
+    /// Unregisters the port with a [`PortClose`](PortCloseRequest), after which the router
+    /// closes the connection and [`AdsPort::serve`] returns.
+    pub fn close(&self) -> crate::Result<()> {
+        self.send(&PortCloseRequest::new(self.addr.port()).into_frame())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+    use tcads_core::protocol::{AdsReadRequest, AdsReadResponse};
+    use tcads_core::{AdsReturnCode, AmsNetId};
+
+    struct Counter;
+
+    impl AdsServer for Counter {
+        fn read(&self, request: &AdsReadRequest) -> Result<Vec<u8>, AdsReturnCode> {
+            Ok(vec![7; request.length() as usize])
+        }
+    }
+
+    #[test]
+    fn registers_port_and_serves_requests() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let router = listener.local_addr().unwrap();
+        let served = AmsAddr::new(AmsNetId::new(10, 0, 0, 1, 1, 1), 25000);
+        let client = AmsAddr::new(AmsNetId::new(10, 0, 0, 2, 1, 1), 32768);
+
+        // Plays the router: answers the handshake, routes one read, then closes.
+        let mock = thread::spawn(move || {
+            let (socket, _) = listener.accept().unwrap();
+            let mut stream = AmsStream::new(socket);
+
+            let frame = stream.read_frame().unwrap();
+            assert_eq!(
+                PortConnectRequest::try_from(&frame).unwrap().desired_port(),
+                25000
+            );
+            stream
+                .write_frame(&PortConnectResponse::new(served).into_frame())
+                .unwrap();
+
+            let request = AdsReadRequest::new(served, client, 42, 0x4020, 0, 3);
+            stream.write_frame(&request.into_frame()).unwrap();
+
+            let frame = stream.read_frame().unwrap();
+            let response = AdsReadResponse::try_from(&frame).unwrap();
+            assert_eq!(*response.header().target(), client);
+            assert_eq!(response.header().invoke_id(), 42);
+            assert_eq!(response.data(), [7, 7, 7]);
+        });
+
+        let port = AdsPort::connect(router, 25000).unwrap();
+        assert_eq!(port.addr(), served);
+
+        port.serve(&Counter).unwrap();
+        mock.join().unwrap();
+    }
+}
@@ -0,0 +1,8 @@
+//! Hosting an [`AdsServer`](crate::AdsServer) on an AMS port.
+//!
+//! An `AdsPort` connects to an AMS router, registers a port with a
+//! [`PortConnect`](tcads_core::protocol::PortConnectRequest) handshake, and then serves
+//! every ADS request routed to that port. Both a blocking and a `tokio` flavour are provided.
+
+pub mod blocking;
+pub mod tokio;
@@ -0,0 +1,182 @@
+use crate::handler::{AdsServer, respond};
+use std::sync::Arc;
+use tcads_core::io::tokio::{AmsReader, AmsStream, AmsWriter};
+use tcads_core::protocol::{PortCloseRequest, PortConnectRequest, PortConnectResponse};
+use tcads_core::{AmsAddr, AmsCommand, AmsFrame, AmsPort};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio::sync::Mutex;
+
+/// An asynchronous AMS port serving an [`AdsServer`].
+///
+/// Handlers are called directly on the task running [`serve`](Self::serve), so they
+/// should not block for long.
+///
+/// # Example
+///
+/// ```no_run
+/// use tcads_server::AdsServer;
+/// use tcads_server::port::tokio::AdsPort;
+///
+/// struct Device;
+///
+/// impl AdsServer for Device {}
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let port = AdsPort::connect("127.0.0.1:48898", 25000).await?;
+/// println!("Serving on {}", port.addr());
+///
+/// port.serve(&Device).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AdsPort {
+    reader: AmsReader<OwnedReadHalf>,
+    handle: PortHandle,
+}
+
+/// A cloneable handle to a running [`AdsPort`], for sending frames and closing the port
+/// from other tasks.
+#[derive(Clone)]
+pub struct PortHandle {
+    addr: AmsAddr,
+    writer: Arc<Mutex<AmsWriter<OwnedWriteHalf>>>,
+}
+
+impl AdsPort {
+    /// Connects to the AMS router at `router` and registers `port`.
+    ///
+    /// Performs a [`PortConnect`](PortConnectRequest) handshake. Pass `0` to let the router
+    /// assign a free port.
+    pub async fn connect(router: impl ToSocketAddrs, port: AmsPort) -> crate::Result<Self> {
+        let mut stream = AmsStream::connect(router).await?;
+        stream
+            .write_frame(&PortConnectRequest::new(port).into_frame())
+            .await?;
+
+        // Skip router notifications until the handshake is answered.
+        let addr = loop {
+            let frame = stream.read_frame().await?;
+            if frame.header().command() == AmsCommand::PortConnect {
+                break *PortConnectResponse::try_from(&frame)?.addr();
+            }
+        };
+
+        Ok(Self::new(stream, addr))
+    }
+
+    /// Creates an [`AdsPort`] from an existing [`AmsStream`] that already serves `addr`,
+    /// skipping the [`PortConnect`](PortConnectRequest) handshake.
+    pub fn new(stream: AmsStream<TcpStream>, addr: AmsAddr) -> Self {
+        let (reader, writer) = stream.into_split();
+        Self {
+            reader,
+            handle: PortHandle {
+                addr,
+                writer: Arc::new(Mutex::new(writer)),
+            },
+        }
+    }
+
+    /// Returns the address requests are served on.
+    pub fn addr(&self) -> AmsAddr {
+        self.handle.addr
+    }
+
+    /// Returns a handle for sending frames from this port.
+    pub fn handle(&self) -> PortHandle {
+        self.handle.clone()
+    }
+
+    /// Serves requests with `server` until the connection closes.
+    ///
+    /// Requests are handled one at a time, in the order they arrive. Returns `Ok(())` when the
+    /// router closes the connection, for example after [`PortHandle::close`].
+    pub async fn serve<S: AdsServer + ?Sized>(mut self, server: &S) -> crate::Result<()> {
+        loop {
+            let frame = match self.reader.read_frame().await {
+                Ok(frame) => frame,
+                Err(err) => {
+                    return match crate::Error::from(err) {
+                        crate::Error::Disconnected => Ok(()),
+                        err => Err(err),
+                    };
+                }
+            };
+
+            if let Some(response) = respond(server, &frame) {
+                self.handle.send(&response).await?;
+            }
+        }
+    }
+}
+
+impl PortHandle {
+    /// Returns the address of the port.
+    pub fn addr(&self) -> AmsAddr {
+        self.addr
+    }
+
+    /// Sends a frame to the router.
+    pub async fn send(&self, frame: &AmsFrame) -> crate::Result<()> {
+        Ok(self.writer.lock().await.write_frame(frame).await?)
+    }
+
+    /// Unregisters the port with a [`PortClose`](PortCloseRequest), after which the router
+    /// closes the connection and [`AdsPort::serve`] returns.
+    pub async fn close(&self) -> crate::Result<()> {
+        self.send(&PortCloseRequest::new(self.addr.port()).into_frame())
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tcads_core::protocol::{AdsWriteRequestOwned, AdsWriteResponse};
+    use tcads_core::{AdsReturnCode, AmsNetId};
+    use tokio::net::TcpListener;
+
+    struct ReadOnly;
+
+    impl AdsServer for ReadOnly {}
+
+    #[tokio::test]
+    async fn answers_unsupported_commands() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let router = listener.local_addr().unwrap();
+        let served = AmsAddr::new(AmsNetId::new(10, 0, 0, 1, 1, 1), 25000);
+        let client = AmsAddr::new(AmsNetId::new(10, 0, 0, 2, 1, 1), 32768);
+
+        // Plays the router: answers the handshake, routes one write, then closes.
+        let mock = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut stream = AmsStream::new(socket);
+
+            let frame = stream.read_frame().await.unwrap();
+            assert_eq!(
+                PortConnectRequest::try_from(&frame).unwrap().desired_port(),
+                0
+            );
+            stream
+                .write_frame(&PortConnectResponse::new(served).into_frame())
+                .await
+                .unwrap();
+
+            let request = AdsWriteRequestOwned::new(served, client, 7, 0x4020, 0, [1, 2]);
+            stream.write_frame(&request.into_frame()).await.unwrap();
+
+            let frame = stream.read_frame().await.unwrap();
+            let response = AdsWriteResponse::try_from(&frame).unwrap();
+            assert_eq!(response.header().invoke_id(), 7);
+            assert_eq!(response.result(), AdsReturnCode::AdsErrDeviceSrvNotSupp);
+        });
+
+        let port = AdsPort::connect(router, 0).await.unwrap();
+        assert_eq!(port.addr(), served);
+
+        port.serve(&ReadOnly).await.unwrap();
+        mock.await.unwrap();
+    }
+}
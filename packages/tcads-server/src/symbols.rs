@@ -0,0 +1,635 @@
+//! Rust variables served as browsable ADS symbols.
+//!
+//! A [`SymbolServer`] owns a block of memory holding the variables declared with
+//! [`add_symbol`](SymbolServer::add_symbol), and answers the symbol index groups a PLC
+//! answers, so ADS clients can browse it, acquire handles and read and write values by
+//! name. The Rust side accesses the same variables with [`get`](SymbolServer::get) and
+//! [`set`](SymbolServer::set).
+//!
+//! | Index Group | Command | Served |
+//! |---|---|---|
+//! | [`INDEX_GROUP`](SymbolServer::INDEX_GROUP) | `Read`, `Write` | Variable memory, by offset |
+//! | [`SYM_HNDBYNAME`] | `ReadWrite` | A handle for the named symbol |
+//! | [`SYM_VALBYNAME`] | `ReadWrite` | The value of the named symbol |
+//! | [`SYM_VALBYHND`] | `Read`, `Write` | The value of the symbol with the handle in the Index Offset |
+//! | [`SYM_RELEASEHND`] | `Write` | Releases the handle in the write data |
+//! | [`SYM_INFOBYNAMEEX`] | `ReadWrite` | The [`AdsSymbolEntry`] of the named symbol |
+//! | [`SYM_UPLOAD`] | `Read` | The symbol table |
+//! | [`SYM_DT_UPLOAD`] | `Read` | The data type table |
+//! | [`SYM_UPLOADINFO2`] | `Read` | The [`AdsSymbolUploadInfo`] |
+//! | [`SUMUP_READ`], [`SUMUP_WRITE`], [`SUMUP_READWRITE`] | `ReadWrite` | Sum commands over the above |
+//!
+//! Device notifications on any readable index group are served by the server's
+//! [`NotificationEngine`].
+
+use crate::handler::AdsServer;
+use crate::notifications::NotificationEngine;
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use tcads_core::ads::index_group::{
+    SUMUP_READ, SUMUP_READWRITE, SUMUP_WRITE, SYM_DT_UPLOAD, SYM_HNDBYNAME, SYM_INFOBYNAMEEX,
+    SYM_RELEASEHND, SYM_UPLOAD, SYM_UPLOADINFO2, SYM_VALBYHND, SYM_VALBYNAME,
+};
+use tcads_core::protocol::{
+    AdsAddDeviceNotificationRequest, AdsDeleteDeviceNotificationRequest, AdsReadDeviceInfoRequest,
+    AdsReadRequest, AdsReadStateRequest, AdsReadWriteRequest, AdsWriteRequest, SumReadItem,
+    SumReadWriteItem, SumWriteItem,
+};
+use tcads_core::symbol::{
+    AdsDataTypeId, AdsDatatypeEntry, AdsSymbolEntry, AdsSymbolUploadInfo, encode_symbol_name,
+};
+use tcads_core::{
+    AdsDecode, AdsDeviceVersion, AdsEncode, AdsReturnCode, AdsState, AmsAddr, DeviceState,
+    IndexGroup, IndexOffset, NotificationHandle,
+};
+
+/// An [`AdsServer`] exposing Rust variables as PLC symbols.
+///
+/// Symbol names are matched case-insensitively, like on a PLC. Handles are shared by all
+/// clients, and released when the client that acquired them disconnects.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use std::thread;
+/// use tcads_core::symbol::AdsDataTypeId;
+/// use tcads_server::notifications;
+/// use tcads_server::port::blocking::AdsPort;
+/// use tcads_server::symbols::SymbolServer;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut server = SymbolServer::new("RustPlc");
+/// server.add_symbol("MAIN.nCount", "DINT", AdsDataTypeId::Int32, 4, "Cycle counter")?;
+/// server.add_symbol("MAIN.fSetpoint", "LREAL", AdsDataTypeId::Real64, 8, "")?;
+/// server.set("MAIN.fSetpoint", &21.5f64)?;
+/// let server = Arc::new(server);
+///
+/// let port = AdsPort::connect("127.0.0.1:48898", 851)?;
+/// let handle = port.handle();
+/// thread::spawn({
+///     let server = Arc::clone(&server);
+///     move || notifications::blocking::run(server.notifications(), &*server, &handle)
+/// });
+///
+/// port.serve(&*server)?;
+/// server.notifications().shutdown();
+/// # Ok(())
+/// # }
+/// ```
+pub struct SymbolServer {
+    device_name: String,
+    symbols: Vec<AdsSymbolEntry>,
+    /// Symbol indices by lowercased, encoded name.
+    by_name: HashMap<Vec<u8>, usize>,
+    datatypes: Vec<AdsDatatypeEntry>,
+    memory: RwLock<Vec<u8>>,
+    handles: Mutex<Handles>,
+    notifications: NotificationEngine,
+}
+
+#[derive(Default)]
+struct Handles {
+    last: u32,
+    /// The owner and symbol index of each handle.
+    symbols: HashMap<u32, (AmsAddr, usize)>,
+}
+
+impl SymbolServer {
+    /// The index group the variable memory is served in.
+    pub const INDEX_GROUP: IndexGroup = 0x4040;
+
+    /// Creates a server with no symbols, reporting `device_name` (at most 15 characters)
+    /// as its device name.
+    pub fn new(device_name: impl Into<String>) -> Self {
+        Self {
+            device_name: device_name.into(),
+            symbols: Vec::new(),
+            by_name: HashMap::new(),
+            datatypes: Vec::new(),
+            memory: RwLock::new(Vec::new()),
+            handles: Mutex::new(Handles::default()),
+            notifications: NotificationEngine::new(),
+        }
+    }
+
+    /// Declares a zero-initialised variable of `size` bytes and returns its offset in
+    /// [`INDEX_GROUP`](Self::INDEX_GROUP).
+    ///
+    /// `type_name` is the PLC type clients see, e.g. `DINT` or `ARRAY [1..10] OF INT`.
+    /// Types other than the IEC 61131-3 elementary types must be described with
+    /// [`add_datatype`](Self::add_datatype). The variable is aligned to its size, up to 8
+    /// bytes.
+    pub fn add_symbol(
+        &mut self,
+        name: impl Into<String>,
+        type_name: impl Into<String>,
+        data_type: AdsDataTypeId,
+        size: u32,
+        comment: impl Into<String>,
+    ) -> crate::Result<IndexOffset> {
+        let name = name.into();
+        let key = lookup_key(&encode_symbol_name(&name)?);
+        if self.by_name.contains_key(&key) {
+            return Err(crate::Error::DuplicateSymbol(name));
+        }
+
+        let memory = self.memory.get_mut()?;
+        let align = (size as usize).next_power_of_two().min(8);
+        let offset = memory.len().next_multiple_of(align);
+
+        let entry = AdsSymbolEntry::new(
+            name,
+            type_name,
+            data_type,
+            Self::INDEX_GROUP,
+            offset as IndexOffset,
+            size,
+        )
+        .with_comment(comment);
+        // Rejects names and comments the symbol table cannot carry.
+        entry.to_bytes()?;
+
+        memory.resize(offset + size as usize, 0);
+        self.by_name.insert(key, self.symbols.len());
+        self.symbols.push(entry);
+        Ok(offset as IndexOffset)
+    }
+
+    /// Adds a data type to the data type table, describing the layout of symbols of that
+    /// type to clients.
+    pub fn add_datatype(&mut self, datatype: AdsDatatypeEntry) -> crate::Result<()> {
+        datatype.to_bytes()?;
+        self.datatypes.push(datatype);
+        Ok(())
+    }
+
+    /// Returns the symbol table.
+    pub fn symbols(&self) -> &[AdsSymbolEntry] {
+        &self.symbols
+    }
+
+    /// Returns the data type table.
+    pub fn datatypes(&self) -> &[AdsDatatypeEntry] {
+        &self.datatypes
+    }
+
+    /// Returns the notification engine serving subscriptions to this server.
+    ///
+    /// Run one of its drivers to send the notifications.
+    pub fn notifications(&self) -> &NotificationEngine {
+        &self.notifications
+    }
+
+    /// Reads the raw value of the symbol `name`.
+    pub fn read_symbol(&self, name: &str) -> crate::Result<Vec<u8>> {
+        let entry = self.symbol(&encode_symbol_name(name)?)?;
+        Ok(self.read_entry(entry, entry.size())?)
+    }
+
+    /// Writes the raw value of the symbol `name`, which must be exactly the symbol size.
+    pub fn write_symbol(&self, name: &str, data: &[u8]) -> crate::Result<()> {
+        let entry = self.symbol(&encode_symbol_name(name)?)?;
+        Ok(self.write_entry(entry, data)?)
+    }
+
+    /// Reads the symbol `name` as a `T`.
+    pub fn get<T: AdsDecode>(&self, name: &str) -> crate::Result<T> {
+        Ok(T::decode(&self.read_symbol(name)?)?)
+    }
+
+    /// Writes `value` to the symbol `name`.
+    pub fn set<T: AdsEncode + ?Sized>(&self, name: &str, value: &T) -> crate::Result<()> {
+        self.write_symbol(name, &value.to_ads_bytes())
+    }
+
+    /// Looks up a symbol by its encoded name, which may be null-terminated.
+    fn symbol(&self, name: &[u8]) -> Result<&AdsSymbolEntry, AdsReturnCode> {
+        self.by_name
+            .get(&lookup_key(name))
+            .map(|&index| &self.symbols[index])
+            .ok_or(AdsReturnCode::AdsErrDeviceSymbolNotFound)
+    }
+
+    fn handle_symbol(&self, handle: u32) -> Result<&AdsSymbolEntry, AdsReturnCode> {
+        let handles = self
+            .handles
+            .lock()
+            .map_err(|_| AdsReturnCode::AdsErrDeviceError)?;
+        handles
+            .symbols
+            .get(&handle)
+            .map(|&(_, index)| &self.symbols[index])
+            .ok_or(AdsReturnCode::AdsErrDeviceSymbolNotFound)
+    }
+
+    /// Reads up to `length` bytes of a symbol.
+    fn read_entry(&self, entry: &AdsSymbolEntry, length: u32) -> Result<Vec<u8>, AdsReturnCode> {
+        self.read_memory(entry.index_offset(), length.min(entry.size()))
+    }
+
+    fn write_entry(&self, entry: &AdsSymbolEntry, data: &[u8]) -> Result<(), AdsReturnCode> {
+        if data.len() != entry.size() as usize {
+            return Err(AdsReturnCode::AdsErrDeviceInvalidSize);
+        }
+        self.write_memory(entry.index_offset(), data)
+    }
+
+    fn read_memory(&self, offset: IndexOffset, length: u32) -> Result<Vec<u8>, AdsReturnCode> {
+        let memory = self
+            .memory
+            .read()
+            .map_err(|_| AdsReturnCode::AdsErrDeviceError)?;
+        let start = offset as usize;
+        memory
+            .get(start..start + length as usize)
+            .map(<[u8]>::to_vec)
+            .ok_or(AdsReturnCode::AdsErrDeviceInvalidOffset)
+    }
+
+    fn write_memory(&self, offset: IndexOffset, data: &[u8]) -> Result<(), AdsReturnCode> {
+        let mut memory = self
+            .memory
+            .write()
+            .map_err(|_| AdsReturnCode::AdsErrDeviceError)?;
+        let start = offset as usize;
+        memory
+            .get_mut(start..start + data.len())
+            .ok_or(AdsReturnCode::AdsErrDeviceInvalidOffset)?
+            .copy_from_slice(data);
+        Ok(())
+    }
+
+    fn read_at(
+        &self,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        length: u32,
+    ) -> Result<Vec<u8>, AdsReturnCode> {
+        match index_group {
+            Self::INDEX_GROUP => self.read_memory(index_offset, length),
+            SYM_VALBYHND => self.read_entry(self.handle_symbol(index_offset)?, length),
+            SYM_UPLOADINFO2 => {
+                let symbols = self.symbol_table()?;
+                let datatypes = self.datatype_table()?;
+                let info = AdsSymbolUploadInfo::new(
+                    self.symbols.len() as u32,
+                    symbols.len() as u32,
+                    self.datatypes.len() as u32,
+                    datatypes.len() as u32,
+                );
+                Ok(info.to_bytes().to_vec())
+            }
+            SYM_UPLOAD => self.symbol_table(),
+            SYM_DT_UPLOAD => self.datatype_table(),
+            _ => Err(AdsReturnCode::AdsErrDeviceInvalidGrp),
+        }
+    }
+
+    fn write_at(
+        &self,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        data: &[u8],
+    ) -> Result<(), AdsReturnCode> {
+        match index_group {
+            Self::INDEX_GROUP => self.write_memory(index_offset, data),
+            SYM_VALBYHND => self.write_entry(self.handle_symbol(index_offset)?, data),
+            SYM_RELEASEHND => {
+                let handle =
+                    u32::decode(data).map_err(|_| AdsReturnCode::AdsErrDeviceInvalidSize)?;
+                let mut handles = self
+                    .handles
+                    .lock()
+                    .map_err(|_| AdsReturnCode::AdsErrDeviceError)?;
+                handles
+                    .symbols
+                    .remove(&handle)
+                    .map(|_| ())
+                    .ok_or(AdsReturnCode::AdsErrDeviceSymbolNotFound)
+            }
+            _ => Err(AdsReturnCode::AdsErrDeviceInvalidGrp),
+        }
+    }
+
+    fn read_write_at(
+        &self,
+        client: AmsAddr,
+        index_group: IndexGroup,
+        index_offset: IndexOffset,
+        read_length: u32,
+        data: &[u8],
+    ) -> Result<Vec<u8>, AdsReturnCode> {
+        let invalid = |_| AdsReturnCode::AdsErrDeviceInvalidParm;
+        match index_group {
+            SYM_HNDBYNAME => {
+                let index = self
+                    .by_name
+                    .get(&lookup_key(data))
+                    .copied()
+                    .ok_or(AdsReturnCode::AdsErrDeviceSymbolNotFound)?;
+                let mut handles = self
+                    .handles
+                    .lock()
+                    .map_err(|_| AdsReturnCode::AdsErrDeviceError)?;
+                let handle = handles.allocate();
+                handles.symbols.insert(handle, (client, index));
+                Ok(handle.to_le_bytes().to_vec())
+            }
+            SYM_VALBYNAME => self.read_entry(self.symbol(data)?, read_length),
+            SYM_INFOBYNAMEEX => self
+                .symbol(data)?
+                .to_bytes()
+                .map_err(|_| AdsReturnCode::AdsErrDeviceError),
+            SUMUP_READ => {
+                let items = SumReadItem::parse_request(index_offset, data).map_err(invalid)?;
+                let results: Vec<_> = items
+                    .iter()
+                    .map(|item| {
+                        self.read_at(item.index_group(), item.index_offset(), item.length())
+                    })
+                    .collect();
+                Ok(SumReadItem::encode_response(&items, &results))
+            }
+            SUMUP_WRITE => {
+                let items = SumWriteItem::parse_request(index_offset, data).map_err(invalid)?;
+                let results: Vec<_> = items
+                    .iter()
+                    .map(|item| self.write_at(item.index_group(), item.index_offset(), item.data()))
+                    .collect();
+                Ok(SumWriteItem::encode_response(&results))
+            }
+            SUMUP_READWRITE => {
+                let items = SumReadWriteItem::parse_request(index_offset, data).map_err(invalid)?;
+                let results: Vec<_> = items
+                    .iter()
+                    .map(|item| {
+                        self.read_write_at(
+                            client,
+                            item.index_group(),
+                            item.index_offset(),
+                            item.read_length(),
+                            item.data(),
+                        )
+                    })
+                    .collect();
+                Ok(SumReadWriteItem::encode_response(&items, &results))
+            }
+            _ => Err(AdsReturnCode::AdsErrDeviceInvalidGrp),
+        }
+    }
+
+    fn symbol_table(&self) -> Result<Vec<u8>, AdsReturnCode> {
+        let mut table = Vec::new();
+        for entry in &self.symbols {
+            table.extend(
+                entry
+                    .to_bytes()
+                    .map_err(|_| AdsReturnCode::AdsErrDeviceError)?,
+            );
+        }
+        Ok(table)
+    }
+
+    fn datatype_table(&self) -> Result<Vec<u8>, AdsReturnCode> {
+        let mut table = Vec::new();
+        for datatype in &self.datatypes {
+            table.extend(
+                datatype
+                    .to_bytes()
+                    .map_err(|_| AdsReturnCode::AdsErrDeviceError)?,
+            );
+        }
+        Ok(table)
+    }
+}
+
+impl AdsServer for SymbolServer {
+    fn read_device_info(
+        &self,
+        _: &AdsReadDeviceInfoRequest,
+    ) -> Result<(AdsDeviceVersion, String), AdsReturnCode> {
+        Ok((AdsDeviceVersion::default(), self.device_name.clone()))
+    }
+
+    fn read(&self, request: &AdsReadRequest) -> Result<Vec<u8>, AdsReturnCode> {
+        self.read_at(
+            request.index_group(),
+            request.index_offset(),
+            request.length(),
+        )
+    }
+
+    fn write(&self, request: &AdsWriteRequest) -> Result<(), AdsReturnCode> {
+        self.write_at(
+            request.index_group(),
+            request.index_offset(),
+            request.data(),
+        )
+    }
+
+    fn read_write(&self, request: &AdsReadWriteRequest) -> Result<Vec<u8>, AdsReturnCode> {
+        self.read_write_at(
+            *request.header().source(),
+            request.index_group(),
+            request.index_offset(),
+            request.read_length(),
+            request.data(),
+        )
+    }
+
+    fn read_state(
+        &self,
+        _: &AdsReadStateRequest,
+    ) -> Result<(AdsState, DeviceState), AdsReturnCode> {
+        Ok((AdsState::Run, 0))
+    }
+
+    fn add_device_notification(
+        &self,
+        request: &AdsAddDeviceNotificationRequest,
+    ) -> Result<NotificationHandle, AdsReturnCode> {
+        self.notifications.add(request)
+    }
+
+    fn delete_device_notification(
+        &self,
+        request: &AdsDeleteDeviceNotificationRequest,
+    ) -> Result<(), AdsReturnCode> {
+        self.notifications.delete(request)
+    }
+
+    fn client_disconnected(&self, client: AmsAddr) {
+        self.notifications.remove_client(client);
+        if let Ok(mut handles) = self.handles.lock() {
+            handles.symbols.retain(|_, (owner, _)| *owner != client);
+        }
+    }
+}
+
+impl Handles {
+    /// Returns the next free handle, skipping `0` and handles in use.
+    fn allocate(&mut self) -> u32 {
+        loop {
+            self.last = self.last.wrapping_add(1).max(1);
+            if !self.symbols.contains_key(&self.last) {
+                return self.last;
+            }
+        }
+    }
+}
+
+/// Returns the key a symbol is looked up by: its encoded name up to the first null,
+/// lowercased.
+fn lookup_key(name: &[u8]) -> Vec<u8> {
+    let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+    name[..end].to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handler::respond;
+    use tcads_core::protocol::{
+        AdsReadRequest, AdsReadResponse, AdsReadWriteRequestOwned, AdsReadWriteResponse,
+        AdsWriteRequestOwned, AdsWriteResponse,
+    };
+    use tcads_core::{AmsFrame, AmsNetId};
+
+    fn addrs() -> (AmsAddr, AmsAddr) {
+        (
+            AmsAddr::new(AmsNetId::new(10, 0, 0, 1, 1, 1), 851),
+            AmsAddr::new(AmsNetId::new(10, 0, 0, 2, 1, 1), 32768),
+        )
+    }
+
+    fn server() -> SymbolServer {
+        let mut server = SymbolServer::new("RustPlc");
+        server
+            .add_symbol("MAIN.bEnable", "BOOL", AdsDataTypeId::BigType, 1, "")
+            .unwrap();
+        server
+            .add_symbol("MAIN.nCount", "DINT", AdsDataTypeId::Int32, 4, "Counter")
+            .unwrap();
+        server
+    }
+
+    fn read(server: &SymbolServer, ig: IndexGroup, io: IndexOffset, len: u32) -> AmsFrame {
+        let (served, client) = addrs();
+        let request = AdsReadRequest::new(served, client, 1, ig, io, len);
+        respond(server, &request.into_frame()).unwrap()
+    }
+
+    fn read_write(server: &SymbolServer, ig: IndexGroup, len: u32, data: &[u8]) -> AmsFrame {
+        let (served, client) = addrs();
+        let request = AdsReadWriteRequestOwned::new(served, client, 1, ig, 0, len, data);
+        respond(server, &request.into_frame()).unwrap()
+    }
+
+    #[test]
+    fn declares_aligned_symbols() {
+        let mut server = server();
+        assert_eq!(server.symbols()[1].index_offset(), 4);
+        assert!(matches!(
+            server.add_symbol("main.NCOUNT", "DINT", AdsDataTypeId::Int32, 4, ""),
+            Err(crate::Error::DuplicateSymbol(_))
+        ));
+
+        server.set("MAIN.nCount", &-5i32).unwrap();
+        assert_eq!(server.get::<i32>("Main.nCount").unwrap(), -5);
+        assert!(matches!(
+            server.get::<i32>("MAIN.nMissing"),
+            Err(crate::Error::AdsReturnCode(
+                AdsReturnCode::AdsErrDeviceSymbolNotFound
+            ))
+        ));
+    }
+
+    #[test]
+    fn serves_symbol_upload() {
+        let server = server();
+
+        let frame = read(
+            &server,
+            SYM_UPLOADINFO2,
+            0,
+            AdsSymbolUploadInfo::LENGTH as u32,
+        );
+        let response = AdsReadResponse::try_from(&frame).unwrap();
+        let info = AdsSymbolUploadInfo::try_from_slice(response.data()).unwrap();
+        assert_eq!(info.symbol_count(), 2);
+
+        let frame = read(&server, SYM_UPLOAD, 0, info.symbol_length());
+        let response = AdsReadResponse::try_from(&frame).unwrap();
+        let symbols = AdsSymbolEntry::parse_table(response.data()).unwrap();
+        assert_eq!(symbols, server.symbols());
+
+        let name = encode_symbol_name("MAIN.nCount").unwrap();
+        let frame = read_write(&server, SYM_INFOBYNAMEEX, u16::MAX as u32, &name);
+        let response = AdsReadWriteResponse::try_from(&frame).unwrap();
+        let entry = AdsSymbolEntry::try_from(response.data()).unwrap();
+        assert_eq!(entry.comment(), "Counter");
+    }
+
+    #[test]
+    fn serves_values_by_handle() {
+        let server = server();
+        server.set("MAIN.nCount", &42i32).unwrap();
+
+        let name = encode_symbol_name("main.ncount").unwrap();
+        let frame = read_write(&server, SYM_HNDBYNAME, 4, &name);
+        let response = AdsReadWriteResponse::try_from(&frame).unwrap();
+        let handle = u32::decode(response.data()).unwrap();
+
+        let frame = read(&server, SYM_VALBYHND, handle, 4);
+        let response = AdsReadResponse::try_from(&frame).unwrap();
+        assert_eq!(response.data(), 42i32.to_le_bytes());
+
+        let (served, client) = addrs();
+        let request =
+            AdsWriteRequestOwned::new(served, client, 2, SYM_VALBYHND, handle, 7i32.to_le_bytes());
+        respond(&server, &request.into_frame()).unwrap();
+        assert_eq!(server.get::<i32>("MAIN.nCount").unwrap(), 7);
+
+        // Released handles, and handles of disconnected clients, stop working.
+        let request =
+            AdsWriteRequestOwned::new(served, client, 3, SYM_RELEASEHND, 0, handle.to_le_bytes());
+        let frame = respond(&server, &request.into_frame()).unwrap();
+        assert_eq!(
+            AdsWriteResponse::try_from(&frame).unwrap().result(),
+            AdsReturnCode::Ok
+        );
+
+        let frame = read(&server, SYM_VALBYHND, handle, 4);
+        let response = AdsReadResponse::try_from(&frame).unwrap();
+        assert_eq!(response.result(), AdsReturnCode::AdsErrDeviceSymbolNotFound);
+
+        let frame = read_write(&server, SYM_HNDBYNAME, 4, &name);
+        let handle = u32::decode(AdsReadWriteResponse::try_from(&frame).unwrap().data()).unwrap();
+        server.client_disconnected(client);
+        let frame = read(&server, SYM_VALBYHND, handle, 4);
+        let response = AdsReadResponse::try_from(&frame).unwrap();
+        assert_eq!(response.result(), AdsReturnCode::AdsErrDeviceSymbolNotFound);
+    }
+
+    #[test]
+    fn serves_sum_reads() {
+        let server = server();
+        server.set("MAIN.bEnable", &true).unwrap();
+        server.set("MAIN.nCount", &9i32).unwrap();
+
+        let items = [
+            SumReadItem::new(SymbolServer::INDEX_GROUP, 0, 1),
+            SumReadItem::new(SymbolServer::INDEX_GROUP, 4, 4),
+            SumReadItem::new(0x1234, 0, 2),
+        ];
+        let sum = SumReadItem::encode_request(&items);
+        let (served, client) = addrs();
+        let frame = respond(&server, &sum.into_request(served, client, 1).into_frame()).unwrap();
+        let response = AdsReadWriteResponse::try_from(&frame).unwrap();
+        let results = SumReadItem::parse_response(&items, response.data()).unwrap();
+        assert_eq!(results[0], Ok(vec![1]));
+        assert_eq!(results[1], Ok(9i32.to_le_bytes().to_vec()));
+        assert_eq!(results[2], Err(AdsReturnCode::AdsErrDeviceInvalidGrp));
+    }
+}
@@ -11,7 +11,9 @@
 //! - [`core`] - Protocol primitives, serialization, and frame I/O
 //! - [`client`] - High-level connection and request management for ADS devices.
 //! - [`server`] - Framework for building custom ADS servers/devices in Rust.
+//! - [`router`] - A pure-Rust AMS router for hosts without a TwinCAT router.
 
 pub use tcads_client as client;
 pub use tcads_core as core;
+pub use tcads_router as router;
 pub use tcads_server as server;